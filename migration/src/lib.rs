@@ -1,5 +1,18 @@
 pub use sea_orm_migration::prelude::*;
 mod m20251229_063323_create_users;
+mod m20251230_000001_create_audit_logs;
+mod m20251231_000001_add_users_search_indexes;
+mod m20260101_000001_add_users_ban_fields;
+mod m20260102_000001_add_users_avatar_url;
+mod m20260103_000001_create_username_history;
+mod m20260104_000001_add_users_settings;
+mod m20260105_000001_create_access_logs;
+mod m20260106_000001_create_security_events;
+mod m20260107_000001_create_login_history;
+mod m20260108_000001_create_feature_flags;
+mod m20260109_000001_create_delegations;
+mod m20260110_000001_create_consents;
+mod m20260111_000001_add_users_public_id;
 
 
 pub struct Migrator;
@@ -9,6 +22,19 @@ impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
         vec![
             Box::new(m20251229_063323_create_users::Migration),
+            Box::new(m20251230_000001_create_audit_logs::Migration),
+            Box::new(m20251231_000001_add_users_search_indexes::Migration),
+            Box::new(m20260101_000001_add_users_ban_fields::Migration),
+            Box::new(m20260102_000001_add_users_avatar_url::Migration),
+            Box::new(m20260103_000001_create_username_history::Migration),
+            Box::new(m20260104_000001_add_users_settings::Migration),
+            Box::new(m20260105_000001_create_access_logs::Migration),
+            Box::new(m20260106_000001_create_security_events::Migration),
+            Box::new(m20260107_000001_create_login_history::Migration),
+            Box::new(m20260108_000001_create_feature_flags::Migration),
+            Box::new(m20260109_000001_create_delegations::Migration),
+            Box::new(m20260110_000001_create_consents::Migration),
+            Box::new(m20260111_000001_add_users_public_id::Migration),
         ]
     }
 }
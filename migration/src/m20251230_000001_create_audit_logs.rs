@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 1. 创建审计日志表
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLogs::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuditLogs::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    // 操作者：系统操作时可以为空
+                    .col(ColumnDef::new(AuditLogs::ActorId).uuid())
+                    .col(ColumnDef::new(AuditLogs::Action).string().not_null())
+                    // 操作对象：如被注册/封禁的用户
+                    .col(ColumnDef::new(AuditLogs::TargetId).uuid())
+                    .col(ColumnDef::new(AuditLogs::Ip).string())
+                    // 变更内容：JSONB 格式的字段差异
+                    .col(ColumnDef::new(AuditLogs::Diff).json_binary())
+                    .col(
+                        ColumnDef::new(AuditLogs::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 2. 创建索引：按时间倒序分页，以及按操作对象查询
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_logs_created_at")
+                    .table(AuditLogs::Table)
+                    .col(AuditLogs::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_audit_logs_target_id")
+                    .table(AuditLogs::Table)
+                    .col(AuditLogs::TargetId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuditLogs::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuditLogs {
+    Table,
+    Id,
+    ActorId,
+    Action,
+    TargetId,
+    Ip,
+    Diff,
+    CreatedAt,
+}
@@ -0,0 +1,39 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // 1. 启用 pg_trgm 扩展：提供三元组相似度匹配，使 ILIKE '%keyword%' 也能走索引
+        db.execute_unprepared("CREATE EXTENSION IF NOT EXISTS pg_trgm;")
+            .await?;
+
+        // 2. 为用户名和手机号创建 GIN 三元组索引
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_users_username_trgm
+             ON users USING gin (username gin_trgm_ops);",
+        )
+        .await?;
+
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_users_phone_trgm
+             ON users USING gin (phone gin_trgm_ops);",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 只删除索引，保留扩展（其他表可能也在使用）
+        let db = manager.get_connection();
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_users_phone_trgm;").await?;
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_users_username_trgm;").await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 1. 创建用户名变更历史表：记录用户曾经使用过的用户名
+        manager
+            .create_table(
+                Table::create()
+                    .table(UsernameHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UsernameHistory::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(UsernameHistory::UserId).uuid().not_null())
+                    .col(ColumnDef::new(UsernameHistory::OldUsername).string().not_null())
+                    .col(ColumnDef::new(UsernameHistory::NewUsername).string().not_null())
+                    .col(
+                        ColumnDef::new(UsernameHistory::ChangedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    // 用户被删除时一并删除其历史记录
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_username_history_user_id")
+                            .from(UsernameHistory::Table, UsernameHistory::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 2. 创建索引：按用户查询变更历史
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_username_history_user_id")
+                    .table(UsernameHistory::Table)
+                    .col(UsernameHistory::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UsernameHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UsernameHistory {
+    Table,
+    Id,
+    UserId,
+    OldUsername,
+    NewUsername,
+    ChangedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
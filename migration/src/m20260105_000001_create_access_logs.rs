@@ -0,0 +1,81 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 1. 创建访问日志表：记录HTTP请求的访问记录，用于安全审查和访问分析。
+        // user_id 不设外键：用户删除后访问记录仍需保留。
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessLogs::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessLogs::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(AccessLogs::Method).string_len(16).not_null())
+                    .col(ColumnDef::new(AccessLogs::Path).string().not_null())
+                    .col(ColumnDef::new(AccessLogs::Status).small_integer().not_null())
+                    .col(ColumnDef::new(AccessLogs::LatencyMs).big_integer().not_null())
+                    .col(ColumnDef::new(AccessLogs::UserId).uuid().null())
+                    .col(ColumnDef::new(AccessLogs::RequestId).string().null())
+                    .col(ColumnDef::new(AccessLogs::Ip).string().null())
+                    .col(
+                        ColumnDef::new(AccessLogs::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 2. 创建索引：按时间范围查询和清理、按用户查询访问记录
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_access_logs_created_at")
+                    .table(AccessLogs::Table)
+                    .col(AccessLogs::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_access_logs_user_id")
+                    .table(AccessLogs::Table)
+                    .col(AccessLogs::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccessLogs::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AccessLogs {
+    Table,
+    Id,
+    Method,
+    Path,
+    Status,
+    LatencyMs,
+    UserId,
+    RequestId,
+    Ip,
+    CreatedAt,
+}
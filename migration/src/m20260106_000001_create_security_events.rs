@@ -0,0 +1,90 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 1. 创建安全事件表：登录失败、刷新令牌重复使用、已吊销令牌的访问、越权访问等认证异常。
+        // user_id 不设外键：用户删除后安全事件仍需保留。
+        manager
+            .create_table(
+                Table::create()
+                    .table(SecurityEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SecurityEvents::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(SecurityEvents::Kind).string().not_null())
+                    .col(ColumnDef::new(SecurityEvents::UserId).uuid().null())
+                    // 事件涉及的账号：登录失败时为提交的用户名或手机号，账号可能并不存在
+                    .col(ColumnDef::new(SecurityEvents::Subject).string().null())
+                    .col(ColumnDef::new(SecurityEvents::Ip).string().null())
+                    .col(ColumnDef::new(SecurityEvents::RequestId).string().null())
+                    .col(ColumnDef::new(SecurityEvents::Detail).string().null())
+                    .col(
+                        ColumnDef::new(SecurityEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 2. 创建索引：按时间倒序分页，以及按事件类型、用户查询
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_security_events_created_at")
+                    .table(SecurityEvents::Table)
+                    .col(SecurityEvents::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_security_events_kind")
+                    .table(SecurityEvents::Table)
+                    .col(SecurityEvents::Kind)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_security_events_user_id")
+                    .table(SecurityEvents::Table)
+                    .col(SecurityEvents::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SecurityEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SecurityEvents {
+    Table,
+    Id,
+    Kind,
+    UserId,
+    Subject,
+    Ip,
+    RequestId,
+    Detail,
+    CreatedAt,
+}
@@ -0,0 +1,79 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 1. 创建登录历史表：记录每次成功登录的时间、来源IP、客户端和登录方式，供用户发现异常登录
+        manager
+            .create_table(
+                Table::create()
+                    .table(LoginHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LoginHistory::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(LoginHistory::UserId).uuid().not_null())
+                    .col(ColumnDef::new(LoginHistory::Method).string().not_null())
+                    .col(ColumnDef::new(LoginHistory::Ip).string().null())
+                    .col(ColumnDef::new(LoginHistory::UserAgent).string().null())
+                    .col(
+                        ColumnDef::new(LoginHistory::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    // 用户被删除时一并删除其登录历史
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_login_history_user_id")
+                            .from(LoginHistory::Table, LoginHistory::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 2. 创建索引：按用户分页查询最近的登录记录
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_login_history_user_id_created_at")
+                    .table(LoginHistory::Table)
+                    .col(LoginHistory::UserId)
+                    .col(LoginHistory::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LoginHistory::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LoginHistory {
+    Table,
+    Id,
+    UserId,
+    Method,
+    Ip,
+    UserAgent,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
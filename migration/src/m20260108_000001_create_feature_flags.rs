@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 创建功能开关表：每个开关按键名唯一，支持按比例灰度发布以及按用户/租户定向开启
+        manager
+            .create_table(
+                Table::create()
+                    .table(FeatureFlags::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(FeatureFlags::Key).string().not_null().primary_key())
+                    .col(ColumnDef::new(FeatureFlags::Description).string().null())
+                    .col(ColumnDef::new(FeatureFlags::Enabled).boolean().not_null().default(false))
+                    // 灰度比例（0-100）：未被定向命中的用户按稳定哈希分桶，桶号小于该值时开启
+                    .col(
+                        ColumnDef::new(FeatureFlags::RolloutPercentage)
+                            .small_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(FeatureFlags::TargetUsers)
+                            .json_binary()
+                            .not_null()
+                            .default(Expr::cust("'[]'::jsonb")),
+                    )
+                    .col(
+                        ColumnDef::new(FeatureFlags::TargetTenants)
+                            .json_binary()
+                            .not_null()
+                            .default(Expr::cust("'[]'::jsonb")),
+                    )
+                    .col(
+                        ColumnDef::new(FeatureFlags::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(FeatureFlags::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum FeatureFlags {
+    Table,
+    Key,
+    Description,
+    Enabled,
+    RolloutPercentage,
+    TargetUsers,
+    TargetTenants,
+    UpdatedAt,
+}
@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 1. 创建委托授权表：授权人（grantor）允许被授权人（grantee）在有效期内以其身份执行限定范围的操作
+        manager
+            .create_table(
+                Table::create()
+                    .table(Delegations::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Delegations::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(Delegations::GrantorId).uuid().not_null())
+                    .col(ColumnDef::new(Delegations::GranteeId).uuid().not_null())
+                    .col(ColumnDef::new(Delegations::Scopes).json_binary().not_null())
+                    .col(ColumnDef::new(Delegations::ExpiresAt).timestamp_with_time_zone().not_null())
+                    .col(ColumnDef::new(Delegations::RevokedAt).timestamp_with_time_zone().null())
+                    .col(
+                        ColumnDef::new(Delegations::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    // 任一方用户被删除时一并删除委托记录
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_delegations_grantor_id")
+                            .from(Delegations::Table, Delegations::GrantorId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_delegations_grantee_id")
+                            .from(Delegations::Table, Delegations::GranteeId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 2. 创建索引：分别按授权人和被授权人查询各自的委托
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_delegations_grantor_id")
+                    .table(Delegations::Table)
+                    .col(Delegations::GrantorId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_delegations_grantee_id")
+                    .table(Delegations::Table)
+                    .col(Delegations::GranteeId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Delegations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Delegations {
+    Table,
+    Id,
+    GrantorId,
+    GranteeId,
+    Scopes,
+    ExpiresAt,
+    RevokedAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
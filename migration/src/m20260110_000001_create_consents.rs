@@ -0,0 +1,83 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 1. 创建同意记录表：只追加不修改，每次变更写入一条记录（目的、是否同意、隐私政策版本、时间、来源IP），
+        //    每个目的的最新一条记录即当前状态，完整的记录用于证明用户在何时同意了哪个版本的政策
+        manager
+            .create_table(
+                Table::create()
+                    .table(Consents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Consents::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .default(Expr::cust("gen_random_uuid()")),
+                    )
+                    .col(ColumnDef::new(Consents::UserId).uuid().not_null())
+                    .col(ColumnDef::new(Consents::Purpose).string().not_null())
+                    .col(ColumnDef::new(Consents::Granted).boolean().not_null())
+                    .col(ColumnDef::new(Consents::PolicyVersion).string().not_null())
+                    .col(ColumnDef::new(Consents::Ip).string().null())
+                    .col(
+                        ColumnDef::new(Consents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    // 用户被删除时一并删除其同意记录
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_consents_user_id")
+                            .from(Consents::Table, Consents::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 2. 创建索引：按用户和目的查询最新的同意记录
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_consents_user_id_purpose_created_at")
+                    .table(Consents::Table)
+                    .col(Consents::UserId)
+                    .col(Consents::Purpose)
+                    .col(Consents::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Consents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Consents {
+    Table,
+    Id,
+    UserId,
+    Purpose,
+    Granted,
+    PolicyVersion,
+    Ip,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // 1. 公开短ID：用于对外的URL和响应，避免暴露内部UUID。应用在创建用户时生成（nanoid），
+        //    数据库默认值只用于为已有用户回填以及遗漏生成的插入路径
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Users::PublicId)
+                            .string_len(21)
+                            .not_null()
+                            .default(Expr::cust("substr(replace(gen_random_uuid()::text, '-', ''), 1, 12)")),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // 2. 唯一索引：按公开短ID解析用户
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_users_public_id")
+                    .table(Users::Table)
+                    .col(Users::PublicId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_users_public_id").table(Users::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::PublicId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    PublicId,
+}
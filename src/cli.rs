@@ -0,0 +1,292 @@
+// src/cli.rs
+use std::{future::Future, net::IpAddr, path::PathBuf, time::Duration};
+
+use clap::{Args, Parser, Subcommand};
+use migration::{Migrator, MigratorTrait};
+use redis::aio::ConnectionManager;
+use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use secrecy::ExposeSecret;
+use validator::Validate;
+
+use crate::{
+    core::{
+        backup,
+        config::{self, Config, ConfigOverrides},
+        enums::UserRole,
+    },
+    dtos::auth::RegisterRequest,
+    mock,
+    services::auth as AuthService,
+    start,
+};
+
+/// 命令行入口。不带子命令启动时等同于 `serve`。
+#[derive(Debug, Parser)]
+#[command(version, about = "Axum best practices API server")]
+pub struct Cli {
+    /// 配置文件目录，替代默认的 `config/`
+    #[arg(long, global = true, value_name = "DIR")]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// 运维子命令，运维任务与服务器使用同一个二进制，不需要单独构建。
+///
+/// 导出 OpenAPI 文档的 `openapi` 子命令暂未提供：它需要先为处理器和 DTO 生成接口描述（如引入 utoipa），
+/// 项目目前没有这部分基础设施，作为单独的后续工作实现。
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// 启动HTTP服务器（默认）
+    Serve(ServeArgs),
+    /// 执行数据库迁移，默认应用全部未执行的迁移
+    Migrate {
+        #[command(subcommand)]
+        action: Option<MigrateAction>,
+    },
+    /// 创建管理员账号，用于初始化部署（注册接口本身需要管理员权限）
+    CreateAdmin(CreateAdminArgs),
+    /// 把 Redis 中的认证状态（刷新令牌、黑名单、吊销记录等）导出到文件
+    BackupAuth { file: PathBuf },
+    /// 从备份文件恢复认证状态，用于 Redis 重建后避免所有用户被强制下线
+    RestoreAuth { file: PathBuf },
+    /// 部署前自检：输出生效的配置（敏感字段已隐藏），检查 Postgres、Redis 连通性和迁移状态，任一检查失败时以非零状态码退出
+    Doctor,
+}
+
+#[derive(Debug, Default, Args)]
+struct ServeArgs {
+    /// 监听地址，覆盖 HOST
+    #[arg(long)]
+    host: Option<IpAddr>,
+    /// 监听端口，覆盖 PORT
+    #[arg(long)]
+    port: Option<u16>,
+    /// 以模拟模式启动，返回示例响应，不需要数据库和Redis
+    #[arg(long)]
+    mock: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum MigrateAction {
+    /// 应用全部未执行的迁移
+    Up,
+    /// 回滚最近的迁移
+    Down {
+        /// 回滚的迁移数量
+        #[arg(short = 'n', long, default_value_t = 1)]
+        steps: u32,
+    },
+    /// 列出各迁移的执行状态
+    Status,
+}
+
+#[derive(Debug, Args)]
+struct CreateAdminArgs {
+    #[arg(long)]
+    username: String,
+    /// 初始密码，建议通过环境变量传入，避免留在 shell 历史中
+    #[arg(long, env = "ADMIN_PASSWORD", hide_env_values = true)]
+    password: String,
+    #[arg(long)]
+    phone: Option<String>,
+    /// 创建超级管理员（可以管理角色）
+    #[arg(long)]
+    super_admin: bool,
+}
+
+/// 解析命令行参数并执行对应的命令。
+pub async fn run() {
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Serve(ServeArgs::default()));
+
+    // 命令行参数的覆盖在第一次加载配置之前设置
+    let (host, port) = match &command {
+        Command::Serve(args) => (args.host, args.port),
+        _ => (None, None),
+    };
+    config::set_overrides(ConfigOverrides { config_dir: cli.config, host, port });
+
+    match command {
+        Command::Serve(ServeArgs { mock: true, .. }) => mock::run().await,
+        Command::Serve(_) => start::run().await,
+        Command::Migrate { action } => migrate(action.unwrap_or(MigrateAction::Up)).await,
+        Command::CreateAdmin(args) => create_admin(args).await,
+        Command::BackupAuth { file } => {
+            let result = backup::snapshot(&connect_redis().await, &file).await;
+            report(result.map(|summary| format!("Backed up {} keys ({} expired keys skipped)", summary.keys, summary.expired)));
+        }
+        Command::RestoreAuth { file } => {
+            let result = backup::restore(&connect_redis().await, &file).await;
+            report(result.map(|summary| format!(
+                "Restored {} keys ({} expired keys skipped, {} keys kept because Redis has newer state)",
+                summary.keys, summary.expired, summary.skipped
+            )));
+        }
+        Command::Doctor => doctor().await,
+    }
+}
+
+/// 执行数据库迁移。只连接数据库，不需要 Redis。
+async fn migrate(action: MigrateAction) {
+    let db = connect_database().await;
+    let result = match action {
+        MigrateAction::Up => Migrator::up(&db, None).await.map(|_| "Migrations applied".to_string()),
+        MigrateAction::Down { steps } => Migrator::down(&db, Some(steps))
+            .await
+            .map(|_| format!("Rolled back {} migration(s)", steps)),
+        // 状态由迁移框架逐条打印
+        MigrateAction::Status => Migrator::status(&db).await.map(|_| "Migration status listed".to_string()),
+    };
+    report(result.map_err(|e| e.to_string()));
+}
+
+/// 创建管理员账号。用户名、密码和手机号按注册接口的规则校验。
+async fn create_admin(args: CreateAdminArgs) {
+    let request = RegisterRequest {
+        username: args.username,
+        password: args.password,
+        phone: args.phone,
+    };
+    if let Err(e) = request.validate() {
+        report::<String>(Err(e.to_string()));
+        return;
+    }
+
+    let role = if args.super_admin { UserRole::SuperAdmin } else { UserRole::Admin };
+    let db = connect_database().await;
+    let result = AuthService::create_user(&db, request, role.clone()).await;
+    report(result.map(|user| format!("Created {} {} ({})", role, user.username, user.id)).map_err(|e| e.to_string()));
+}
+
+/// 部署前自检，适合作为 CI/CD 的发布前检查。依次检查配置、Postgres、Redis 和迁移状态，
+/// 前一项失败不影响后续检查，最后输出汇总；有失败项时以非零状态码退出。未执行的迁移只作为警告。
+async fn doctor() {
+    // 第一步：加载并校验配置，配置无效时无法继续检查连接
+    let config = match Config::try_new().await {
+        Ok(config) => config,
+        Err(problems) => {
+            eprintln!("❌ Configuration: {} problem(s)", problems.len());
+            for problem in &problems {
+                eprintln!("   - {}", problem);
+            }
+            eprintln!("❌ Doctor found 1 failed check (configuration), skipped connectivity checks");
+            std::process::exit(1);
+        }
+    };
+    println!("📋 Configuration ({}):", config.app_env);
+    for entry in config.describe() {
+        println!("   {} = {} [{}]", entry.env, entry.value, entry.source);
+    }
+    println!("✅ Configuration: valid");
+
+    let mut failed = Vec::new();
+    let mut warnings = 0;
+    let timeout = config.database_connect_timeout;
+
+    // 第二步：Postgres 连通性
+    let mut opt = ConnectOptions::new(config.database_url.expose_secret());
+    opt.max_connections(1)
+        .connect_timeout(timeout)
+        .acquire_timeout(timeout)
+        .sqlx_logging(false);
+    let db = match with_timeout(timeout, async {
+        let db = Database::connect(opt).await?;
+        db.ping().await.map(|_| db)
+    })
+    .await
+    {
+        Ok(db) => {
+            println!("✅ Postgres: connected");
+            Some(db)
+        }
+        Err(e) => {
+            println!("❌ Postgres: {}", e);
+            failed.push("postgres");
+            None
+        }
+    };
+
+    // 第三步：Redis 连通性
+    let redis = with_timeout(timeout, async {
+        let mut conn = redis::Client::open(config.redis_url.expose_secret())?.get_connection_manager().await?;
+        redis::cmd("PING").query_async::<String>(&mut conn).await
+    })
+    .await;
+    match redis {
+        Ok(_) => println!("✅ Redis: connected"),
+        Err(e) => {
+            println!("❌ Redis: {}", e);
+            failed.push("redis");
+        }
+    }
+
+    // 第四步：迁移状态，需要数据库可用
+    match &db {
+        Some(db) => match (Migrator::get_applied_migrations(db).await, Migrator::get_pending_migrations(db).await) {
+            (Ok(applied), Ok(pending)) if pending.is_empty() => {
+                println!("✅ Migrations: {} applied, none pending", applied.len());
+            }
+            (Ok(applied), Ok(pending)) => {
+                let names: Vec<&str> = pending.iter().map(|migration| migration.name()).collect();
+                println!("⚠️ Migrations: {} applied, {} pending ({})", applied.len(), pending.len(), names.join(", "));
+                warnings += 1;
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                println!("❌ Migrations: {}", e);
+                failed.push("migrations");
+            }
+        },
+        None => {
+            println!("⏭️ Migrations: skipped, database is unavailable");
+            failed.push("migrations");
+        }
+    }
+
+    // 第五步：汇总
+    if failed.is_empty() {
+        println!("✅ Doctor passed all checks ({} warning(s))", warnings);
+    } else {
+        eprintln!("❌ Doctor found {} failed check(s): {}", failed.len(), failed.join(", "));
+        std::process::exit(1);
+    }
+}
+
+/// 为自检中的连接操作加上超时，避免目标不可达时命令长时间挂起。
+async fn with_timeout<T, E: std::fmt::Display>(
+    timeout: Duration,
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, String> {
+    match tokio::time::timeout(timeout, future).await {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(_) => Err(format!("timed out after {}s", timeout.as_secs())),
+    }
+}
+
+/// 输出命令的执行结果，失败时以非零状态码退出。
+fn report<E: std::fmt::Display>(result: Result<String, E>) {
+    match result {
+        Ok(message) => println!("✅ {}", message),
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn connect_database() -> DatabaseConnection {
+    let config = Config::new().await;
+    Database::connect(config.database_url.expose_secret())
+        .await
+        .expect("❌ Failed to connect to Database")
+}
+
+async fn connect_redis() -> ConnectionManager {
+    let config = Config::new().await;
+    let client = redis::Client::open(config.redis_url.expose_secret()).expect("❌ Invalid Redis URL");
+    client
+        .get_connection_manager()
+        .await
+        .expect("❌ Failed to connect to Redis")
+}
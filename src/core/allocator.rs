@@ -0,0 +1,121 @@
+// src/core/allocator.rs
+use serde::Serialize;
+
+use crate::core::error::AppError;
+
+// 可选的全局内存分配器，通过 Cargo feature 在编译时选择：
+// - `jemalloc`：使用 jemalloc，可以查看分配器统计，排查长时间运行后的内存增长（如缓存占用）
+// - `heap-profiling`：在 jemalloc 的基础上启用堆采样，可以导出堆分析文件（用 jeprof 分析）。
+//   采样需要在启动时通过环境变量开启：`_RJEM_MALLOC_CONF=prof:true`（可以用 `lg_prof_sample` 调整采样间隔），
+//   需要时通过管理端接口导出
+// - `mimalloc`：使用 mimalloc，只替换分配器，不提供统计
+// 都不启用时使用系统分配器。
+
+#[cfg(all(feature = "jemalloc", feature = "mimalloc"))]
+compile_error!("features `jemalloc` and `mimalloc` are mutually exclusive");
+
+#[cfg(feature = "jemalloc")]
+#[global_allocator]
+static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+#[cfg(feature = "mimalloc")]
+#[global_allocator]
+static GLOBAL: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+/// 当前使用的分配器名称
+pub const NAME: &str = if cfg!(feature = "jemalloc") {
+    "jemalloc"
+} else if cfg!(feature = "mimalloc") {
+    "mimalloc"
+} else {
+    "system"
+};
+
+/// 分配器统计（字节）。只有 jemalloc 提供统计，其他分配器的统计字段为空。
+#[derive(Debug, Default, Serialize)]
+pub struct AllocatorStats {
+    pub allocator: &'static str,
+    /// 应用程序已分配的字节数
+    pub allocated: Option<usize>,
+    /// 活跃页面占用的字节数（包含分配器内部碎片）
+    pub active: Option<usize>,
+    /// 分配器元数据占用的字节数
+    pub metadata: Option<usize>,
+    /// 实际驻留在物理内存中的字节数
+    pub resident: Option<usize>,
+    /// 映射的字节数
+    pub mapped: Option<usize>,
+    /// 已归还但仍保留映射的字节数
+    pub retained: Option<usize>,
+    /// 进程是否已开启堆采样，开启后可以导出堆分析文件
+    pub heap_profiling: bool,
+}
+
+/// 读取分配器统计。jemalloc 的统计按 epoch 缓存，读取前先推进 epoch 以获得最新数据。
+pub fn stats() -> AllocatorStats {
+    #[cfg(feature = "jemalloc")]
+    {
+        use tikv_jemalloc_ctl::{epoch, stats};
+
+        if let Err(e) = epoch::advance() {
+            tracing::warn!(target: crate::core::log::target::SYSTEM, "⚠️ jemalloc epoch advance failed: {}", e);
+        }
+        AllocatorStats {
+            allocator: NAME,
+            allocated: stats::allocated::read().ok(),
+            active: stats::active::read().ok(),
+            metadata: stats::metadata::read().ok(),
+            resident: stats::resident::read().ok(),
+            mapped: stats::mapped::read().ok(),
+            retained: stats::retained::read().ok(),
+            heap_profiling: heap_profiling_active(),
+        }
+    }
+
+    #[cfg(not(feature = "jemalloc"))]
+    AllocatorStats { allocator: NAME, ..Default::default() }
+}
+
+#[cfg(feature = "jemalloc")]
+fn heap_profiling_active() -> bool {
+    #[cfg(feature = "heap-profiling")]
+    return tikv_jemalloc_ctl::profiling::prof::read().unwrap_or(false);
+
+    #[cfg(not(feature = "heap-profiling"))]
+    false
+}
+
+/// 导出一份堆分析文件（jemalloc heap profile 格式，使用 `jeprof` 分析）。
+/// 导出会写入临时文件并同步读取，调用方应在阻塞线程池中执行。
+///
+/// # 返回值
+/// - `Ok(Vec<u8>)`: 堆分析文件内容
+/// - `Err(AppError)`: 构建时未启用 `heap-profiling`、进程未开启堆采样或导出失败
+pub fn dump_heap_profile() -> Result<Vec<u8>, AppError> {
+    #[cfg(feature = "heap-profiling")]
+    {
+        use std::ffi::{c_char, CString};
+
+        if !heap_profiling_active() {
+            return Err(AppError::BadRequest(
+                "Heap profiling is not active, start the process with _RJEM_MALLOC_CONF=prof:true".to_string(),
+            ));
+        }
+        let path = std::env::temp_dir().join(format!("heap-{}-{}.heap", std::process::id(), uuid::Uuid::new_v4()));
+        let c_path = CString::new(path.to_string_lossy().into_owned())
+            .map_err(|e| AppError::InternalServerError(format!("Invalid profile path: {}", e)))?;
+        // SAFETY: prof.dump 的值类型为以 NUL 结尾的文件路径，c_path 在调用期间保持有效
+        unsafe { tikv_jemalloc_ctl::raw::write(b"prof.dump\0", c_path.as_ptr() as *const c_char) }
+            .map_err(|e| AppError::InternalServerError(format!("Heap profile dump failed: {}", e)))?;
+
+        let profile = std::fs::read(&path)
+            .map_err(|e| AppError::InternalServerError(format!("Read heap profile failed: {}", e)));
+        let _ = std::fs::remove_file(&path);
+        profile
+    }
+
+    #[cfg(not(feature = "heap-profiling"))]
+    Err(AppError::BadRequest(
+        "Heap profiling is not enabled in this build, rebuild with --features heap-profiling".to_string(),
+    ))
+}
@@ -0,0 +1,282 @@
+// src/core/backup.rs
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use redis::{aio::ConnectionManager, AsyncCommands, Script};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+
+use crate::core::{
+    constants::{
+        REDIS_PREFIX_BLACKLIST, REDIS_PREFIX_DELEGATION_REVOKED, REDIS_PREFIX_REFRESH, REDIS_PREFIX_REFRESH_ROTATED,
+        REDIS_PREFIX_TOKEN_VERSION, REDIS_PREFIX_USED, REDIS_PREFIX_USER_REVOKED, REDIS_PREFIX_USER_SESSIONS,
+    },
+    error::AppError,
+};
+
+// Redis 中认证状态的备份与恢复：刷新令牌、令牌黑名单、用户级吊销和令牌版本等只保存在 Redis 中，
+// Redis 数据丢失后所有用户都会被强制下线，已吊销的令牌也会重新生效。
+// 备份文件每行一个键（JSON），值为 DUMP 的序列化结果，恢复时按原过期时间点计算剩余有效期。
+// 恢复只补回缺失的状态，不覆盖 Redis 中现有的键：备份之后发生的登出、吊销、令牌轮换都必须保留。
+
+/// 恢复时写入的临时键后缀，用于读取备份中的值后再决定如何合并
+const RESTORE_SUFFIX: &str = ":restoring";
+
+/// 需要备份的键空间前缀
+const KEYSPACES: &[&str] = &[
+    REDIS_PREFIX_REFRESH,
+    REDIS_PREFIX_REFRESH_ROTATED,
+    REDIS_PREFIX_USER_SESSIONS,
+    REDIS_PREFIX_BLACKLIST,
+    REDIS_PREFIX_USER_REVOKED,
+    REDIS_PREFIX_TOKEN_VERSION,
+    REDIS_PREFIX_DELEGATION_REVOKED,
+];
+
+/// 备份文件中的一行
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    key: String,
+    /// 过期时间点（Unix 毫秒时间戳），没有过期时间的键为空
+    expires_at_ms: Option<i64>,
+    /// DUMP 返回的序列化值（Base64）
+    value: String,
+}
+
+/// 备份或恢复的结果统计
+#[derive(Debug, Default)]
+pub struct BackupSummary {
+    /// 写入备份文件或恢复到 Redis 的键数量
+    pub keys: usize,
+    /// 备份/恢复期间已经过期而跳过的键数量
+    pub expired: usize,
+    /// 恢复时因 Redis 中已有更新的状态而跳过的键数量
+    pub skipped: usize,
+}
+
+/// 恢复一个键的结果
+enum Restored {
+    Written,
+    Skipped,
+}
+
+fn io_error(e: std::io::Error) -> AppError {
+    AppError::InternalServerError(format!("Backup file error: {}", e))
+}
+
+/// 把认证相关的键空间导出到文件。导出期间 Redis 继续正常服务，导出的是逐个键的时间点快照。
+///
+/// # 参数
+/// - `redis`: Redis 连接。
+/// - `path`: 备份文件路径，已存在时覆盖。文件包含刷新令牌，只允许当前用户读写（0600）。
+///
+/// # 返回值
+/// - `Ok(BackupSummary)`: 导出的键数量。
+/// - `Err(AppError)`: Redis 操作或文件写入失败。
+pub async fn snapshot(redis: &ConnectionManager, path: &Path) -> Result<BackupSummary, AppError> {
+    let mut scan_conn = redis.clone();
+    let mut conn = redis.clone();
+    let file = create_private(path).await.map_err(io_error)?;
+    let mut writer = BufWriter::new(file);
+    let mut summary = BackupSummary::default();
+
+    for prefix in KEYSPACES {
+        // 第一步：收集该键空间下的全部键
+        let mut keys: Vec<String> = Vec::new();
+        {
+            let mut iter = scan_conn.scan_match::<_, String>(format!("{}*", prefix)).await?;
+            while let Some(key) = iter.next_item().await {
+                keys.push(key?);
+            }
+        }
+
+        // 第二步：逐个导出值和剩余有效期。扫描之后才过期的键 DUMP 结果为空，直接跳过
+        for key in keys {
+            let (value, pttl): (Option<Vec<u8>>, i64) = redis::pipe()
+                .cmd("DUMP")
+                .arg(&key)
+                .cmd("PTTL")
+                .arg(&key)
+                .query_async(&mut conn)
+                .await?;
+            let Some(value) = value else {
+                summary.expired += 1;
+                continue;
+            };
+
+            let entry = Entry {
+                expires_at_ms: (pttl > 0).then(|| Utc::now().timestamp_millis() + pttl),
+                key,
+                value: STANDARD.encode(value),
+            };
+            let line = serde_json::to_string(&entry)
+                .map_err(|e| AppError::InternalServerError(format!("Serialize backup entry failed: {}", e)))?;
+            writer.write_all(line.as_bytes()).await.map_err(io_error)?;
+            writer.write_all(b"\n").await.map_err(io_error)?;
+            summary.keys += 1;
+        }
+    }
+
+    writer.flush().await.map_err(io_error)?;
+    Ok(summary)
+}
+
+/// 从备份文件恢复认证相关的键。备份之后已经过期的键被跳过，Redis 中已存在的键保持不变：
+/// - 令牌版本取备份值与当前值中较大的一个，版本号不会回退
+/// - 刷新令牌只在该用户的会话状态整体丢失时恢复（没有会话索引，也没有吊销记录），
+///   避免把已登出、已吊销的会话找回来；已存在的令牌（包括 "USED:" 标记）不会被重置
+/// - 其余键（黑名单、吊销记录等）只补回缺失的键
+///
+/// # 参数
+/// - `redis`: Redis 连接。
+/// - `path`: `snapshot` 生成的备份文件路径。
+///
+/// # 返回值
+/// - `Ok(BackupSummary)`: 恢复的键数量，以及因过期或已有更新状态而跳过的键数量。
+/// - `Err(AppError)`: 文件格式错误、Redis 操作失败或文件读取失败。
+pub async fn restore(redis: &ConnectionManager, path: &Path) -> Result<BackupSummary, AppError> {
+    let mut conn = redis.clone();
+    let file = tokio::fs::File::open(path).await.map_err(io_error)?;
+    let mut lines = BufReader::new(file).lines();
+    let mut summary = BackupSummary::default();
+
+    while let Some(line) = lines.next_line().await.map_err(io_error)? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: Entry = serde_json::from_str(&line)
+            .map_err(|e| AppError::BadRequest(format!("Invalid backup entry: {}", e)))?;
+        let value = STANDARD
+            .decode(&entry.value)
+            .map_err(|e| AppError::BadRequest(format!("Invalid backup value for {}: {}", entry.key, e)))?;
+
+        // RESTORE 的 TTL 为 0 表示不过期
+        let ttl_ms = match entry.expires_at_ms {
+            Some(expires_at_ms) => {
+                let remaining = expires_at_ms - Utc::now().timestamp_millis();
+                if remaining <= 0 {
+                    summary.expired += 1;
+                    continue;
+                }
+                remaining
+            }
+            None => 0,
+        };
+
+        let restored = if entry.key.starts_with(REDIS_PREFIX_TOKEN_VERSION) {
+            restore_version(&mut conn, &entry.key, ttl_ms, value).await?
+        } else if entry.key.starts_with(REDIS_PREFIX_REFRESH) {
+            restore_session(&mut conn, &entry.key, ttl_ms, value).await?
+        } else {
+            restore_missing(&mut conn, &entry.key, ttl_ms, value).await?
+        };
+        match restored {
+            Restored::Written => summary.keys += 1,
+            Restored::Skipped => summary.skipped += 1,
+        }
+    }
+
+    Ok(summary)
+}
+
+/// 键不存在时才写入。RESTORE 不带 REPLACE 时，键已存在会返回 BUSYKEY 错误
+async fn restore_missing(
+    conn: &mut ConnectionManager,
+    key: &str,
+    ttl_ms: i64,
+    value: Vec<u8>,
+) -> Result<Restored, AppError> {
+    let result: redis::RedisResult<()> =
+        redis::cmd("RESTORE").arg(key).arg(ttl_ms).arg(value).query_async(conn).await;
+    match result {
+        Ok(()) => Ok(Restored::Written),
+        Err(e) if e.code() == Some("BUSYKEY") => Ok(Restored::Skipped),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// 把备份值写入临时键，供读取后合并
+async fn restore_temporary(conn: &mut ConnectionManager, key: &str, ttl_ms: i64, value: Vec<u8>) -> Result<String, AppError> {
+    let temporary = format!("{}{}", key, RESTORE_SUFFIX);
+    redis::cmd("RESTORE")
+        .arg(&temporary)
+        .arg(ttl_ms)
+        .arg(value)
+        .arg("REPLACE")
+        .query_async::<()>(conn)
+        .await?;
+    Ok(temporary)
+}
+
+/// 令牌版本取较大值：备份之后递增过的版本保留当前值，丢失或较小时恢复为备份值
+async fn restore_version(
+    conn: &mut ConnectionManager,
+    key: &str,
+    ttl_ms: i64,
+    value: Vec<u8>,
+) -> Result<Restored, AppError> {
+    let temporary = restore_temporary(conn, key, ttl_ms, value).await?;
+    let script = Script::new(r#"
+        local restored = tonumber(redis.call("GET", KEYS[2]) or "0")
+        local current = tonumber(redis.call("GET", KEYS[1]) or "0")
+        redis.call("DEL", KEYS[2])
+        if restored > current then
+            redis.call("SET", KEYS[1], restored)
+            return 1
+        end
+        return 0
+    "#);
+    let written: i64 = script.key(key).key(&temporary).invoke_async(conn).await?;
+    Ok(if written == 1 { Restored::Written } else { Restored::Skipped })
+}
+
+/// 刷新令牌：用户仍有会话索引（会话状态没有丢失，缺少的令牌是被登出或轮换掉的）
+/// 或有吊销记录时跳过，否则在令牌不存在时写入
+async fn restore_session(
+    conn: &mut ConnectionManager,
+    key: &str,
+    ttl_ms: i64,
+    value: Vec<u8>,
+) -> Result<Restored, AppError> {
+    let temporary = restore_temporary(conn, key, ttl_ms, value).await?;
+    let session: Option<String> = conn.get(&temporary).await?;
+    let user_id = session
+        .as_deref()
+        .map(|value| value.trim_start_matches(REDIS_PREFIX_USED))
+        .and_then(|value| value.split('@').next())
+        .unwrap_or_default()
+        .to_string();
+
+    let (has_sessions, revoked): (bool, bool) = redis::pipe()
+        .exists(format!("{}{}", REDIS_PREFIX_USER_SESSIONS, user_id))
+        .exists(format!("{}{}", REDIS_PREFIX_USER_REVOKED, user_id))
+        .query_async(conn)
+        .await?;
+    let renamed: bool = if user_id.is_empty() || has_sessions || revoked {
+        false
+    } else {
+        conn.rename_nx(&temporary, key).await?
+    };
+    if !renamed {
+        let _: () = conn.del(&temporary).await?;
+    }
+    Ok(if renamed { Restored::Written } else { Restored::Skipped })
+}
+
+/// 创建（或截断）只允许当前用户读写的文件
+async fn create_private(path: &Path) -> std::io::Result<tokio::fs::File> {
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let file = options.open(path).await?;
+
+    // 文件已存在时 mode 不生效，单独收紧权限
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600)).await?;
+    }
+    Ok(file)
+}
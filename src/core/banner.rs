@@ -0,0 +1,117 @@
+// src/core/banner.rs
+use std::net::SocketAddr;
+
+use redis::aio::ConnectionManager;
+use sea_orm::{ConnectionTrait, DatabaseConnection, Statement};
+
+use crate::core::log::target;
+use crate::{core::config::ConfigSource, state::AppState};
+
+/// 打印启动摘要：应用版本、运行环境、启用的功能、依赖服务版本、迁移版本和监听地址。
+/// 排查 "这台机器跑的到底是什么" 之类的部署不一致问题时，只需要看启动日志的这一段。
+///
+/// 依赖服务的版本查询失败时显示 "unknown"，不影响启动。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接、Redis客户端和配置。
+/// - `addr`: HTTP服务器监听地址。
+pub async fn print(state: &AppState, addr: &SocketAddr) {
+    let config = &state.config;
+    let build = if cfg!(debug_assertions) { "debug" } else { "release" };
+
+    let postgres = postgres_version(&state.db).await;
+    let redis = redis_version(&state.redis).await;
+    let migration = migration_level(&state.db).await;
+
+    // 配置来源统计：多少项来自系统环境变量、.env 文件和默认值
+    let entries = config.describe();
+    let count = |source: ConfigSource| entries.iter().filter(|entry| entry.source == source).count();
+
+    let transports = config
+        .refresh_transports()
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+
+    tracing::info!(target: target::SYSTEM, "==================== 🚀 Startup summary ====================");
+    tracing::info!(target: target::SYSTEM, "📦 {} v{} ({} build)", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), build);
+    tracing::info!(
+        target: target::SYSTEM,
+        "🌍 Environment: {}, region: {} (config: {} env, {} .env, {} default)",
+        config.app_env,
+        config.region,
+        count(ConfigSource::Env),
+        count(ConfigSource::Dotenv),
+        count(ConfigSource::Default),
+    );
+    tracing::info!(
+        target: target::SYSTEM,
+        "🧩 Features: json_case={}, refresh_transports={}, static_claims={}, storage=local:{}",
+        config.json_case,
+        transports,
+        if config.jwt_static_claims.is_some() { "on" } else { "off" },
+        config.storage_local_dir,
+    );
+    tracing::info!(target: target::SYSTEM, "🐘 PostgreSQL: {}", postgres);
+    tracing::info!(target: target::SYSTEM, "🧱 Migration level: {}", migration);
+    tracing::info!(target: target::SYSTEM, "⚡️ Redis: {}", redis);
+    tracing::info!(target: target::SYSTEM, "👂 Listening on: http://{}", addr);
+    tracing::info!(target: target::SYSTEM, "============================================================");
+}
+
+async fn postgres_version(db: &DatabaseConnection) -> String {
+    let result = db
+        .query_one(Statement::from_string(db.get_database_backend(), "SHOW server_version"))
+        .await;
+
+    match result {
+        Ok(Some(row)) => row
+            .try_get::<String>("", "server_version")
+            .unwrap_or_else(|_| "unknown".to_string()),
+        Ok(None) => "unknown".to_string(),
+        Err(e) => {
+            tracing::warn!(target: target::SYSTEM, "⚠️ Failed to query PostgreSQL version: {}", e);
+            "unknown".to_string()
+        }
+    }
+}
+
+/// 最新已执行的迁移名称及已执行的迁移数量（读取 SeaORM 的迁移记录表）
+async fn migration_level(db: &DatabaseConnection) -> String {
+    let sql = "SELECT version, COUNT(*) OVER () AS applied FROM seaql_migrations ORDER BY version DESC LIMIT 1";
+    let result = db
+        .query_one(Statement::from_string(db.get_database_backend(), sql))
+        .await;
+
+    match result {
+        Ok(Some(row)) => {
+            let version = row.try_get::<String>("", "version").unwrap_or_default();
+            let applied = row.try_get::<i64>("", "applied").unwrap_or_default();
+            format!("{} ({} applied)", version, applied)
+        }
+        Ok(None) => "none applied".to_string(),
+        Err(e) => {
+            tracing::warn!(target: target::SYSTEM, "⚠️ Failed to query migration level: {}", e);
+            "unknown".to_string()
+        }
+    }
+}
+
+/// 从 `INFO server` 的输出中读取 redis_version
+async fn redis_version(redis: &ConnectionManager) -> String {
+    let mut conn = redis.clone();
+    let info: Result<String, _> = redis::cmd("INFO").arg("server").query_async(&mut conn).await;
+
+    match info {
+        Ok(info) => info
+            .lines()
+            .find_map(|line| line.strip_prefix("redis_version:"))
+            .map(|version| version.trim().to_string())
+            .unwrap_or_else(|| "unknown".to_string()),
+        Err(e) => {
+            tracing::warn!(target: target::SYSTEM, "⚠️ Failed to query Redis version: {}", e);
+            "unknown".to_string()
+        }
+    }
+}
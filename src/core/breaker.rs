@@ -0,0 +1,137 @@
+// src/core/breaker.rs
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use redis::aio::ConnectionManager;
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+
+use crate::core::log::target;
+use crate::core::{
+    constants::{BREAKER_FAILURE_THRESHOLD, BREAKER_PROBE_INTERVAL, BREAKER_PROBE_TIMEOUT},
+    enums::Dependency,
+};
+
+/// 单个依赖服务的熔断器。由后台探测任务驱动：连续失败达到阈值后打开，
+/// 下一次探测成功后立即关闭。请求路径上只读取状态，不产生额外的网络开销。
+#[derive(Debug, Default)]
+pub struct CircuitBreaker {
+    failures: AtomicU32,
+    open: AtomicBool,
+}
+
+impl CircuitBreaker {
+    pub fn is_open(&self) -> bool {
+        self.open.load(Ordering::Relaxed)
+    }
+
+    /// 记录一次探测结果。
+    ///
+    /// # 返回值
+    /// - `Some(true)`: 本次探测使熔断器打开
+    /// - `Some(false)`: 本次探测使熔断器关闭
+    /// - `None`: 状态未变化
+    fn record(&self, healthy: bool) -> Option<bool> {
+        if healthy {
+            self.failures.store(0, Ordering::Relaxed);
+            return self.open.swap(false, Ordering::Relaxed).then_some(false);
+        }
+
+        let failures = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= BREAKER_FAILURE_THRESHOLD && !self.open.swap(true, Ordering::Relaxed) {
+            return Some(true);
+        }
+        None
+    }
+}
+
+/// 依赖服务的熔断状态快照，供健康检查端点返回
+#[derive(Debug, Serialize)]
+pub struct BreakerStatus {
+    pub dependency: Dependency,
+    /// "closed" 表示正常，"open" 表示已熔断
+    pub state: &'static str,
+}
+
+/// 数据库和Redis的熔断器集合。
+#[derive(Debug, Default)]
+pub struct DependencyBreakers {
+    database: CircuitBreaker,
+    redis: CircuitBreaker,
+}
+
+impl DependencyBreakers {
+    pub fn get(&self, dependency: Dependency) -> &CircuitBreaker {
+        match dependency {
+            Dependency::Database => &self.database,
+            Dependency::Redis => &self.redis,
+        }
+    }
+
+    /// 返回给定依赖中第一个处于熔断状态的服务
+    pub fn first_open(&self, dependencies: &[Dependency]) -> Option<Dependency> {
+        dependencies
+            .iter()
+            .copied()
+            .find(|dependency| self.get(*dependency).is_open())
+    }
+
+    pub fn status(&self) -> Vec<BreakerStatus> {
+        [Dependency::Database, Dependency::Redis]
+            .into_iter()
+            .map(|dependency| BreakerStatus {
+                dependency,
+                state: if self.get(dependency).is_open() { "open" } else { "closed" },
+            })
+            .collect()
+    }
+
+    fn record(&self, dependency: Dependency, healthy: bool) {
+        metrics::gauge!("dependency_up", "dependency" => dependency.to_string())
+            .set(if healthy { 1.0 } else { 0.0 });
+
+        match self.get(dependency).record(healthy) {
+            Some(true) => tracing::error!(target: target::SYSTEM, "🔌 Circuit breaker opened: {} is unavailable", dependency),
+            Some(false) => tracing::info!(target: target::SYSTEM, "✅ Circuit breaker closed: {} recovered", dependency),
+            None => {}
+        }
+    }
+}
+
+/// 启动后台健康探测任务，定期探测数据库和Redis并更新熔断状态。
+///
+/// # 参数
+/// - `breakers`: 共享的熔断器集合（与 `AppState` 中的是同一个实例）
+/// - `db`: 数据库连接
+/// - `redis`: Redis连接管理器
+pub fn spawn_probe(breakers: Arc<DependencyBreakers>, db: DatabaseConnection, redis: ConnectionManager) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(BREAKER_PROBE_INTERVAL));
+        let timeout = Duration::from_secs(BREAKER_PROBE_TIMEOUT);
+
+        loop {
+            ticker.tick().await;
+
+            breakers.record(Dependency::Database, ping_database(&db, timeout).await);
+            breakers.record(Dependency::Redis, ping_redis(&redis, timeout).await);
+        }
+    });
+}
+
+/// 探测数据库是否可用，超时视为不可用。
+pub async fn ping_database(db: &DatabaseConnection, timeout: Duration) -> bool {
+    matches!(tokio::time::timeout(timeout, db.ping()).await, Ok(Ok(())))
+}
+
+/// 探测Redis是否可用，超时视为不可用。
+pub async fn ping_redis(redis: &ConnectionManager, timeout: Duration) -> bool {
+    let mut conn = redis.clone();
+    let cmd = redis::cmd("PING");
+    let ping = cmd.query_async::<String>(&mut conn);
+    matches!(tokio::time::timeout(timeout, ping).await, Ok(Ok(_)))
+}
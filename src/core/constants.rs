@@ -1,3 +1,6 @@
+use crate::core::enums::QuotaPeriod;
+use crate::utils::{limiter::RateLimitMode, quota::Quota};
+
 // ==========================================
 // Redis Key 前缀定义：这些常量用于构建Redis缓存键的前缀部分，确保键名的一致性和可管理性。
 // ==========================================
@@ -5,15 +8,120 @@
 /// Refresh Token 前缀：用于存储刷新令牌的Redis键前缀。
 pub const REDIS_PREFIX_REFRESH: &str = "refresh_token:";
 
+/// 刷新令牌轮换结果前缀：后接旧刷新令牌，值为宽限期内首次轮换签发的令牌对（JSON）。
+pub const REDIS_PREFIX_REFRESH_ROTATED: &str = "refresh_rotated:";
+
 /// 黑名单前缀：用于存储已注销或无效令牌的Redis键前缀。
 pub const REDIS_PREFIX_BLACKLIST: &str = "blacklist:token:";
 
+/// 用户令牌吊销前缀：值为吊销时间戳，在此之前签发的该用户令牌全部失效（用于封禁等强制下线场景）。
+pub const REDIS_PREFIX_USER_REVOKED: &str = "revoked:user:";
+
+/// 委托撤销前缀：后接委托授权ID，键存在期间该委托已签发的委托令牌全部失效，有效期与访问令牌一致。
+pub const REDIS_PREFIX_DELEGATION_REVOKED: &str = "revoked:delegation:";
+
+/// 用户令牌版本前缀：值为递增的版本号，版本号低于当前值的访问令牌需要刷新（用于角色变更等场景）。
+pub const REDIS_PREFIX_TOKEN_VERSION: &str = "token_version:user:";
+
+/// 用户会话索引前缀：后接用户ID，值为该用户的刷新令牌集合，用于列出和吊销某个用户的会话而不必 SCAN 全部令牌。
+/// 集合的过期时间随每次签发延长到刷新令牌的有效期，已失效的成员在读取时清理。
+pub const REDIS_PREFIX_USER_SESSIONS: &str = "sessions:user:";
+
 /// 已使用 Token 前缀：用于标记已使用过的令牌，防止重复使用。
 pub const REDIS_PREFIX_USED: &str = "USED:";
 
 // 用户资料缓存前缀：用于缓存用户资料的Redis键前缀。注意末尾的冒号，确保键名格式正确。
 pub const REDIS_PREFIX_USER_PROFILE: &str = "cache:user:profile:";
 
+/// 一次性标识前缀：后接命名空间和标识，如 "nonce:device_poll:{device_code}"、"nonce:idempotency:{caller}:{key}"。
+pub const REDIS_PREFIX_NONCE: &str = "nonce:";
+
+/// 用户同意状态缓存前缀：后接用户ID，值为各数据处理目的的当前同意状态。
+pub const REDIS_PREFIX_USER_CONSENTS: &str = "cache:user:consents:";
+
+/// 用户权限缓存前缀：用于缓存用户计算后的权限集合。
+pub const REDIS_PREFIX_USER_PERMISSIONS: &str = "cache:user:permissions:";
+
+/// 用户名修改冷却前缀：后接用户ID，键存在期间不允许再次修改用户名。
+pub const REDIS_PREFIX_USERNAME_COOLDOWN: &str = "cooldown:username:";
+
+/// 用户导入任务前缀：后接任务ID，值为任务进度（JSON）。
+pub const REDIS_PREFIX_IMPORT_JOB: &str = "import:job:";
+
+/// 用户导入任务锁前缀：防止同一个任务被并发执行。
+pub const REDIS_PREFIX_IMPORT_LOCK: &str = "import:lock:";
+
+/// 数据导出任务前缀：后接用户ID，值为导出任务状态（JSON）。
+pub const REDIS_PREFIX_EXPORT_JOB: &str = "export:job:";
+
+/// 数据导出文件前缀：后接用户ID，值为生成好的导出文件内容。
+pub const REDIS_PREFIX_EXPORT_DATA: &str = "export:data:";
+
+/// 设备授权前缀：后接 device_code，值为授权状态（JSON），用于设备授权流程（RFC 8628）。
+pub const REDIS_PREFIX_DEVICE_CODE: &str = "device:code:";
+
+/// 设备用户码前缀：后接 user_code，值为对应的 device_code，供用户在浏览器中确认授权。
+pub const REDIS_PREFIX_DEVICE_USER_CODE: &str = "device:user_code:";
+
+/// 维护模式开关：键存在即表示开启，值为展示给客户端的提示消息。
+pub const REDIS_KEY_MAINTENANCE: &str = "maintenance:enabled";
+
+/// 系统开关的运行时覆盖（Hash）：字段为开关名称，值为 1 或 0。
+pub const REDIS_KEY_FLAG_OVERRIDES: &str = "flags:overrides";
+
+/// 功能开关缓存：值为全部开关定义（JSON 数组），管理端修改开关时删除。
+pub const REDIS_KEY_FEATURE_FLAGS: &str = "cache:feature_flags";
+
+/// 进程内缓存失效通知的发布/订阅频道，消息格式为 "{实例ID} {键或模式}"。
+pub const REDIS_CHANNEL_CACHE_INVALIDATE: &str = "cache:invalidate";
+
+/// 缓存重建锁前缀：后接被保护的缓存键，持有者负责查询数据库并回填该键（防止缓存击穿）。
+pub const REDIS_PREFIX_CACHE_LOCK: &str = "lock:cache:";
+
+/// 在线状态前缀：后接用户ID，值为最近一次心跳的时间戳，心跳停止 `PRESENCE_TTL` 秒后自动过期（视为离线）。
+pub const REDIS_PREFIX_PRESENCE_ONLINE: &str = "presence:online:";
+
+/// 最后在线时间前缀：后接用户ID，值为最近一次心跳的时间戳。
+pub const REDIS_PREFIX_PRESENCE_LAST_SEEN: &str = "presence:last_seen:";
+
+/// 在线用户的离线期限（有序集合）：成员为用户ID，分数为心跳停止后视为离线的时间戳，
+/// 由 `presence::spawn_sweeper` 定期清理过期成员并发布离线事件。
+pub const REDIS_KEY_PRESENCE_DEADLINES: &str = "presence:deadlines";
+
+/// 在线状态变更的发布/订阅频道，消息为 `PresenceEvent`（JSON）。
+pub const REDIS_CHANNEL_PRESENCE: &str = "presence:events";
+
+/// 请求统计前缀：后接统计类型和分钟时间戳，如 "stats:requests:{minute}"（按路由计数的哈希）。
+pub const REDIS_PREFIX_STATS: &str = "stats:";
+
+/// 匿名使用统计的原始计数前缀：后接类型和日期，如 "analytics:raw:routes:2024-01-31"。只供聚合任务读取。
+pub const REDIS_PREFIX_ANALYTICS_RAW: &str = "analytics:raw:";
+
+/// 匿名使用统计的日报前缀：后接日期，值为经过 k-匿名处理的统计结果（JSON）。
+pub const REDIS_PREFIX_ANALYTICS_DAILY: &str = "analytics:daily:";
+
+/// 匿名使用统计的聚合锁，多实例部署时同一时间只有一个实例执行聚合。
+pub const REDIS_KEY_ANALYTICS_LOCK: &str = "analytics:lock";
+
+/// 匿名使用统计原始计数的保留天数。聚合任务每小时执行，前一天的计数在次日完成最终聚合后即可过期。
+pub const ANALYTICS_RAW_RETENTION_DAYS: i64 = 2;
+
+/// 匿名使用统计日报的保留天数，也是查询时允许的最大天数。
+pub const ANALYTICS_DAILY_RETENTION_DAYS: i64 = 90;
+
+/// 匿名使用统计聚合任务的执行间隔（秒）。
+pub const ANALYTICS_AGGREGATE_INTERVAL: u64 = 3600;
+
+/// 限流违规冷却前缀：后接操作名称和限流对象，键存在期间该对象在该操作上的请求全部被拒绝。
+pub const REDIS_PREFIX_RATE_LIMIT_BAN: &str = "rate_limit:ban:";
+
+/// 限流违规次数前缀：后接操作名称和限流对象，值为近期的违规次数，决定下一次冷却的时长。
+pub const REDIS_PREFIX_RATE_LIMIT_STRIKES: &str = "rate_limit:strikes:";
+
+/// 限流豁免名单（集合），成员格式与 `RATE_LIMIT_ALLOWLIST` 配置相同，与配置中的名单合并生效。
+pub const REDIS_KEY_RATE_LIMIT_ALLOWLIST: &str = "rate_limit:allowlist";
+
+
 // ==========================================
 // 业务逻辑常量：这些常量控制应用程序的核心业务逻辑，如令牌轮换宽限期、缓存过期时间等。
 // ==========================================
@@ -21,9 +129,233 @@ pub const REDIS_PREFIX_USER_PROFILE: &str = "cache:user:profile:";
 /// Token 轮换宽限期（秒）：在令牌轮换期间允许旧令牌继续使用的宽限时间，单位为秒。
 pub const ROTATION_GRACE_PERIOD: u64 = 10;
 
+/// 宽限期内的重复刷新等待首次轮换完成的最长时间（毫秒），超时按令牌重复使用处理。
+pub const ROTATION_REPLAY_WAIT_MS: u64 = 2000;
+
+/// 等待首次轮换完成时的轮询间隔（毫秒）。
+pub const ROTATION_REPLAY_POLL_MS: u64 = 50;
+
 // 用户资料缓存过期时间（24小时）：用户资料在Redis缓存中存储的有效时间，单位为秒。
 pub const CACHE_EXPIRE_USER_PROFILE: u64 = 60 * 60 * 24;
 
+/// 用户权限缓存过期时间（10分钟）：即使失效通知丢失，权限变更最迟也会在该时间后生效。
+pub const CACHE_EXPIRE_USER_PERMISSIONS: u64 = 60 * 10;
+
+/// 功能开关缓存过期时间（30秒）：多实例下即使缓存删除失败，开关变更也会很快生效。
+pub const CACHE_EXPIRE_FEATURE_FLAGS: u64 = 30;
+
+/// 缓存重建锁有效期（毫秒）：也是其他请求等待重建的最长时间，超时后各自查询数据库。
+pub const CACHE_LOCK_EXPIRE_MS: u64 = 3000;
+
+/// 等待缓存重建时的轮询间隔（毫秒）。
+pub const CACHE_LOCK_POLL_MS: u64 = 50;
+
+/// 缓存值压缩阈值（字节）：序列化后超过该大小的缓存值压缩后再写入 Redis。
+pub const CACHE_COMPRESS_THRESHOLD_BYTES: usize = 1024;
+
+/// 用户同意状态缓存过期时间（1小时）：修改同意时立即失效，隐私政策版本变更后最迟在该时间后生效。
+pub const CACHE_EXPIRE_USER_CONSENTS: u64 = 60 * 60;
+
+/// 在线状态有效期（秒）：客户端需要在该时间内再次发送心跳，否则视为离线。
+pub const PRESENCE_TTL: u64 = 60;
+
+/// 离线检查间隔（秒）：心跳停止后最迟在 `PRESENCE_TTL` 加上该间隔后发布离线事件。
+pub const PRESENCE_SWEEP_INTERVAL: u64 = 10;
+
+/// 每次离线检查最多处理的用户数，剩余的用户在下一次检查时处理。
+pub const PRESENCE_SWEEP_BATCH: usize = 1000;
+
+/// 最后在线时间的保留时长（30天）：超过该时间未上线的用户不再返回最后在线时间。
+pub const PRESENCE_LAST_SEEN_EXPIRE: u64 = 60 * 60 * 24 * 30;
+
+/// 设备授权码有效期（10分钟）：超时未确认的授权请求自动失效。
+pub const DEVICE_CODE_EXPIRE: u64 = 60 * 10;
+
+/// 设备轮询最小间隔（秒）：轮询过快时返回 slow_down。
+pub const DEVICE_POLL_INTERVAL: u64 = 5;
+
+/// 导入任务进度保留时间（7天）：期间可以查询进度或断点续传。
+pub const IMPORT_JOB_EXPIRE: u64 = 60 * 60 * 24 * 7;
+
+/// 导入任务锁有效期（5分钟）：每批处理后续期，进程崩溃后锁自动释放以便续传。
+pub const IMPORT_LOCK_EXPIRE: u64 = 60 * 5;
+
+/// 数据导出文件保留时间（24小时）：期间可以重复下载，过期后才能再次申请导出。
+pub const EXPORT_EXPIRE: u64 = 60 * 60 * 24;
+
+/// 生成失败的导出任务保留时间（1分钟）：期间可以查询失败状态，过期后即可重新申请导出。
+pub const EXPORT_FAILED_EXPIRE: u64 = 60;
+
+/// 委托授权的最长有效期（30天）。
+pub const DELEGATION_MAX_MINUTES: u64 = 60 * 24 * 30;
+
+/// 每个用户同时有效的委托授权数量上限。
+pub const DELEGATION_MAX_ACTIVE: u64 = 20;
+
+/// 用户设置序列化后的最大字节数，防止把设置列当作通用存储使用。
+pub const USER_SETTINGS_MAX_BYTES: usize = 16 * 1024;
+
+/// 熔断阈值：依赖服务连续探测失败达到该次数后打开熔断器。
+pub const BREAKER_FAILURE_THRESHOLD: u32 = 3;
+
+/// 依赖服务健康探测间隔（秒）。
+pub const BREAKER_PROBE_INTERVAL: u64 = 5;
+
+/// 单次健康探测的超时时间（秒），超时视为失败。
+pub const BREAKER_PROBE_TIMEOUT: u64 = 2;
+
+/// 采集数据库连接池状态（使用量、获取连接耗时）的间隔（秒）。
+pub const DB_POOL_MONITOR_INTERVAL: u64 = 10;
+
+/// 数据库瞬时错误（序列化冲突、死锁、连接断开）的最大尝试次数，包括第一次执行。
+pub const DB_RETRY_MAX_ATTEMPTS: u32 = 3;
+
+/// 数据库重试的初始退避时间（毫秒），每次重试翻倍，实际等待时间在 0 到退避时间之间随机取值。
+pub const DB_RETRY_BASE_DELAY_MS: u64 = 50;
+
+/// 数据库重试的最大退避时间（毫秒）。
+pub const DB_RETRY_MAX_DELAY_MS: u64 = 1000;
+
+/// 各实例同步维护模式开关的间隔（秒）。
+pub const MAINTENANCE_POLL_INTERVAL: u64 = 2;
+
+/// 各实例同步系统开关覆盖的间隔（秒）。
+pub const FLAGS_POLL_INTERVAL: u64 = 5;
+
+/// 就绪检查中单个依赖探测的超时时间（毫秒），需要小于 Kubernetes 探针的超时时间。
+pub const READINESS_PROBE_TIMEOUT_MS: u64 = 1000;
+
+/// 就绪检查结果的缓存时间（毫秒）。多个探针、负载均衡器同时检查时，缓存期内共用同一次探测结果，
+/// 不会把探测请求成倍地打到数据库和Redis上。
+pub const READINESS_CACHE_TTL_MS: u64 = 1000;
+
+/// 主备部署中主节点锁的键，值为主节点的锁令牌。
+pub const REDIS_KEY_STANDBY_PRIMARY: &str = "standby:primary";
+
+/// 主备部署中探测对端和续期主节点锁的间隔（秒）。
+pub const STANDBY_PROBE_INTERVAL: u64 = 5;
+
+/// 主节点锁的有效期（秒），需要大于探测间隔的两倍，避免一次续期延迟就丢失主节点身份。
+pub const STANDBY_LOCK_TTL: u64 = 15;
+
+/// 探测对端的超时时间（秒）。
+pub const STANDBY_PROBE_TIMEOUT: u64 = 2;
+
+/// 对端连续探测失败多少次后备节点强制接管。
+pub const STANDBY_TAKEOVER_THRESHOLD: u32 = 3;
+
+/// 启动时执行数据库迁移使用的 PostgreSQL 咨询锁键，所有实例使用同一个值（"migrate" 的 ASCII 编码）。
+pub const MIGRATION_LOCK_KEY: i64 = 0x6d_6967_7261_7465;
+
+/// 从外部密钥管理读取密钥的超时时间（秒）。
+pub const SECRETS_FETCH_TIMEOUT: u64 = 10;
+
+/// 平滑升级时等待新进程完成启动的时间（秒），期间旧进程继续处理请求。
+pub const UPGRADE_HANDOVER_DELAY: u64 = 5;
+
+/// 幂等键的有效期（24小时）：期间重复提交直接回放首次响应。
+pub const IDEMPOTENCY_EXPIRE: u64 = 60 * 60 * 24;
+
+/// 幂等请求处理中状态的最长保留时间（秒），防止进程崩溃后该键一直处于处理中。
+pub const IDEMPOTENCY_LOCK_EXPIRE: u64 = 60 * 5;
+
+/// 可以被缓存回放的最大响应体字节数，超过时不缓存，重复提交会重新执行。
+pub const IDEMPOTENCY_MAX_RESPONSE_BYTES: usize = 1024 * 1024;
+
+/// 请求统计的保留时长（分钟）：每分钟一组计数，过期自动删除。
+pub const STATS_RETENTION_MINUTES: u64 = 60;
+
+/// 请求统计中返回的请求数最多的用户数量。
+pub const STATS_TOP_USERS: usize = 10;
+
+/// 单次审计日志导出的最大记录数，超过时需要缩小时间范围分批导出。
+pub const AUDIT_EXPORT_MAX_ROWS: u64 = 100_000;
+
+/// 各限流操作的内置默认限额，按操作名称查找，可通过 `RATE_LIMITS` 配置按环境覆盖（见 `Config::rate_limit`）。
+/// 新增 `rate_limit!` 调用时需要在这里登记操作名称。
+pub const DEFAULT_RATE_LIMITS: &[(&str, RateLimitMode)] = &[
+    // 路由组限流（按来源IP）
+    ("group:auth", RateLimitMode::FixedWindow { limit: 1000, window: 60 }),
+    // 认证
+    ("register", RateLimitMode::FixedWindow { limit: 5, window: 60 }),
+    ("login", RateLimitMode::FixedWindow { limit: 5, window: 60 }),
+    ("refresh_token", RateLimitMode::FixedWindow { limit: 10, window: 60 }),
+    ("device_code", RateLimitMode::FixedWindow { limit: 10, window: 60 }),
+    // 用户
+    ("read_me", RateLimitMode::TokenBucket { burst: 20, refill_per_sec: 1.0 }),
+    ("update_me", RateLimitMode::FixedWindow { limit: 10, window: 60 }),
+    ("upload_avatar", RateLimitMode::FixedWindow { limit: 5, window: 60 }),
+    ("update_settings", RateLimitMode::FixedWindow { limit: 30, window: 60 }),
+    ("device_approve", RateLimitMode::FixedWindow { limit: 10, window: 60 }),
+    ("delegation_token", RateLimitMode::FixedWindow { limit: 20, window: 60 }),
+];
+
+/// 未在 `DEFAULT_RATE_LIMITS` 中登记、也没有配置覆盖的操作使用的限额。
+pub const FALLBACK_RATE_LIMIT: RateLimitMode = RateLimitMode::FixedWindow { limit: 60, window: 60 };
+
+/// 剩余额度低于上限的这个比例时，在响应中添加 `X-RateLimit-*` 响应头提醒客户端放慢请求。
+/// 被限流的响应总是带有这些响应头。
+pub const RATE_LIMIT_HEADER_THRESHOLD: f64 = 0.2;
+
+/// 路由并发达到上限时建议客户端等待的时间（秒），作为 503 响应的 `Retry-After`。
+pub const CONCURRENCY_RETRY_AFTER: u64 = 5;
+
+/// 重复违反限流时逐级升级的冷却时长（秒）：第1次 1 分钟、第2次 10 分钟、之后每次 1 小时。
+pub const RATE_LIMIT_PENALTY_STEPS: &[u64] = &[60, 600, 3600];
+
+/// 超出限制时不记违规、不进入冷却期的操作。这些操作的限流对象由请求方填写（如注册用户名、登录账号），
+/// 并不是请求方自己的身份，升级冷却会让任何人都能把受害者的账号锁定数小时。
+/// 它们只按限流器自身的窗口拒绝，持续的暴力尝试由按来源IP的路由组限流升级冷却。
+pub const RATE_LIMIT_UNPENALIZED_ACTIONS: &[&str] = &["register", "login"];
+
+/// 限流豁免名单从 Redis 同步的间隔（秒）。
+pub const RATE_LIMIT_ALLOWLIST_REFRESH_INTERVAL: u64 = 30;
+
+/// 违规次数的保留时间（秒）。最后一次违规后这段时间内没有再违规，冷却时长从第一级重新开始。
+pub const RATE_LIMIT_STRIKE_TTL: u64 = 86400;
+
+/// 每个管理员每天最多创建的账号数量。注册接口需要管理用户的权限，按操作者计数，
+/// 限制单个（可能被盗用的）管理员账号批量创建账号；批量迁移使用导入任务（见 `/admin/imports`）。
+pub const REGISTER_DAILY_LIMIT_PER_ADMIN: usize = 100;
+
+/// 每个手机号前缀（号段+地区码）每天最多注册的账号数量。
+pub const REGISTER_DAILY_LIMIT_PER_PHONE_PREFIX: usize = 50;
+
+/// 每个用户每天最多上传头像的次数。
+pub const QUOTA_AVATAR_UPLOAD: Quota = Quota { action: "upload_avatar", period: QuotaPeriod::Day, limit: 20 };
+
+/// 每个被授权人每月最多换取的委托令牌数量。
+pub const QUOTA_DELEGATION_TOKEN: Quota = Quota { action: "delegation_token", period: QuotaPeriod::Month, limit: 1000 };
+
+/// 按用户计数的配额，`GET /users/me/quota` 返回这些配额的剩余额度。新增用户配额时需要加入此列表。
+pub const USER_QUOTAS: &[Quota] = &[QUOTA_AVATAR_UPLOAD, QUOTA_DELEGATION_TOKEN];
+
+/// 统计注册配额时使用的手机号前缀长度（如 "1381234"）。
+pub const PHONE_PREFIX_LEN: usize = 7;
+
+/// 用户自助冻结账户时写入的禁用原因，用于在登录时与管理员封禁区分开。
+pub const ACCOUNT_FROZEN_REASON: &str = "Frozen by account owner";
+
+/// 路由脚本钩子单次执行的最大运算步数，超过时脚本中止，防止死循环拖住请求。
+#[cfg_attr(not(feature = "scripting"), allow(dead_code))]
+pub const SCRIPT_MAX_OPERATIONS: u64 = 100_000;
+
+/// 路由脚本钩子中字符串、数组和 map 的最大长度。
+#[cfg_attr(not(feature = "scripting"), allow(dead_code))]
+pub const SCRIPT_MAX_VALUE_SIZE: usize = 64 * 1024;
+
+/// 路由脚本钩子允许读取的最大请求体或响应体（字节）。
+pub const SCRIPT_MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// JWT 签名密钥的最小长度（字节），HS256 密钥不应短于哈希输出长度。
+pub const MIN_JWT_SECRET_LEN: usize = 32;
+
+/// 非开发环境中 JWT 签名密钥至少包含的不同字符数，拒绝 "aaaa..." 这类长度足够但强度不足的密钥。
+pub const MIN_JWT_SECRET_DISTINCT_CHARS: usize = 10;
+
+/// 非开发环境禁止使用的 JWT 签名密钥（示例配置中的占位值）
+pub const WEAK_JWT_SECRETS: &[&str] = &["change_this_to_a_secure_random_string_min_32_chars"];
+
 #[allow(dead_code)]
 pub const MIN_PASSWORD_LEN: usize = 6;
 
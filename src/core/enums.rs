@@ -1,23 +1,374 @@
 // src/core/enums.rs
+// DeriveActiveEnum 会为 "user.xxx" 形式的取值生成同前缀的内部枚举，这里统一放宽该 lint。
+#![allow(clippy::enum_variant_names)]
 
 use sea_orm::entity::prelude::*;
 use sea_orm::{DeriveActiveEnum, EnumIter};
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use strum::{Display, EnumString};
 
+use crate::utils::cache::CacheSchema;
+
 /// 用户角色枚举
 /// 同时支持：
-/// 1. 数据库映射 (SeaORM) - 存为字符串 "admin" / "user"
+/// 1. 数据库映射 (SeaORM) - 存为字符串 "super_admin" / "admin" / "user"
 /// 2. JSON 序列化 (Serde) - 前端交互
 /// 3. 字符串转换 (Strum) - 代码逻辑判断
+///
+/// 角色之间存在层级关系：SuperAdmin > Admin > User。
+/// 通过 `Ord` 实现可以直接比较角色高低，守卫使用 "该角色或更高" 的语义判断权限。
 #[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, Display, EnumString)]
 #[strum(serialize_all = "lowercase")] // to_string() 输出小写
 #[serde(rename_all = "lowercase")]    // JSON 输出小写
 #[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")] // 映射到数据库 varchar/text
 pub enum UserRole {
+    #[sea_orm(string_value = "super_admin")]
+    #[strum(serialize = "super_admin")]
+    #[serde(rename = "super_admin")]
+    SuperAdmin,
+
     #[sea_orm(string_value = "admin")]
     Admin,
 
     #[sea_orm(string_value = "user")]
     User,
-}
\ No newline at end of file
+}
+
+impl UserRole {
+    /// 角色等级。数值越大权限越高，用于角色之间的比较。
+    pub fn level(&self) -> u8 {
+        match self {
+            UserRole::SuperAdmin => 100,
+            UserRole::Admin => 50,
+            UserRole::User => 10,
+        }
+    }
+
+    /// 判断当前角色是否满足要求的最低角色（即 "该角色或更高"）。
+    pub fn at_least(&self, required: &UserRole) -> bool {
+        self >= required
+    }
+}
+
+impl PartialOrd for UserRole {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 按角色等级排序，而不是按枚举声明顺序。
+impl Ord for UserRole {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.level().cmp(&other.level())
+    }
+}
+
+/// 权限枚举。每个权限代表一类受保护的操作，由用户角色推导得出（见 `UserRole::permissions`）。
+/// 序列化为 snake_case 字符串，便于缓存到 Redis 以及返回给前端。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// 读取自己的资料
+    ReadSelf,
+    /// 修改自己的资料
+    UpdateSelf,
+    /// 查看其他用户
+    ViewUsers,
+    /// 创建、禁用、删除用户
+    ManageUsers,
+    /// 修改用户角色
+    ManageRoles,
+    /// 系统级管理操作
+    ManageSystem,
+}
+
+impl CacheSchema for Permission {
+    const CACHE_VERSION: u32 = 1;
+}
+
+impl UserRole {
+    /// 计算角色拥有的权限集合。高等级角色自动继承低等级角色的全部权限。
+    pub fn permissions(&self) -> Vec<Permission> {
+        let mut perms = vec![Permission::ReadSelf, Permission::UpdateSelf];
+        if self.at_least(&UserRole::Admin) {
+            perms.extend([Permission::ViewUsers, Permission::ManageUsers]);
+        }
+        if self.at_least(&UserRole::SuperAdmin) {
+            perms.extend([Permission::ManageRoles, Permission::ManageSystem]);
+        }
+        perms
+    }
+}
+
+/// 审计操作类型。记录管理员执行的特权操作，存为数据库字符串（如 "user.register"）。
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, Display, EnumString)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+pub enum AuditAction {
+    #[sea_orm(string_value = "user.register")]
+    #[strum(serialize = "user.register")]
+    #[serde(rename = "user.register")]
+    UserRegister,
+
+    #[sea_orm(string_value = "user.ban")]
+    #[strum(serialize = "user.ban")]
+    #[serde(rename = "user.ban")]
+    UserBan,
+
+    #[sea_orm(string_value = "user.unban")]
+    #[strum(serialize = "user.unban")]
+    #[serde(rename = "user.unban")]
+    UserUnban,
+
+    #[sea_orm(string_value = "user.password_reset")]
+    #[strum(serialize = "user.password_reset")]
+    #[serde(rename = "user.password_reset")]
+    UserPasswordReset,
+
+    #[sea_orm(string_value = "user.deactivate")]
+    #[strum(serialize = "user.deactivate")]
+    #[serde(rename = "user.deactivate")]
+    UserDeactivate,
+
+    #[sea_orm(string_value = "user.freeze")]
+    #[strum(serialize = "user.freeze")]
+    #[serde(rename = "user.freeze")]
+    UserFreeze,
+
+    #[sea_orm(string_value = "delegation.grant")]
+    #[strum(serialize = "delegation.grant")]
+    #[serde(rename = "delegation.grant")]
+    DelegationGrant,
+
+    #[sea_orm(string_value = "delegation.revoke")]
+    #[strum(serialize = "delegation.revoke")]
+    #[serde(rename = "delegation.revoke")]
+    DelegationRevoke,
+
+    #[sea_orm(string_value = "user.delete")]
+    #[strum(serialize = "user.delete")]
+    #[serde(rename = "user.delete")]
+    UserDelete,
+
+    #[sea_orm(string_value = "user.role_change")]
+    #[strum(serialize = "user.role_change")]
+    #[serde(rename = "user.role_change")]
+    UserRoleChange,
+
+    #[sea_orm(string_value = "user.import")]
+    #[strum(serialize = "user.import")]
+    #[serde(rename = "user.import")]
+    UserImport,
+
+    #[sea_orm(string_value = "audit.export")]
+    #[strum(serialize = "audit.export")]
+    #[serde(rename = "audit.export")]
+    AuditExport,
+
+    #[sea_orm(string_value = "system.maintenance")]
+    #[strum(serialize = "system.maintenance")]
+    #[serde(rename = "system.maintenance")]
+    SystemMaintenance,
+
+    #[sea_orm(string_value = "feature_flag.update")]
+    #[strum(serialize = "feature_flag.update")]
+    #[serde(rename = "feature_flag.update")]
+    FeatureFlagUpdate,
+
+    #[sea_orm(string_value = "feature_flag.delete")]
+    #[strum(serialize = "feature_flag.delete")]
+    #[serde(rename = "feature_flag.delete")]
+    FeatureFlagDelete,
+
+    #[sea_orm(string_value = "rate_limit.ban_clear")]
+    #[strum(serialize = "rate_limit.ban_clear")]
+    #[serde(rename = "rate_limit.ban_clear")]
+    RateLimitBanClear,
+
+    #[sea_orm(string_value = "system.flag_override")]
+    #[strum(serialize = "system.flag_override")]
+    #[serde(rename = "system.flag_override")]
+    SystemFlagOverride,
+
+    #[sea_orm(string_value = "system.config_reload")]
+    #[strum(serialize = "system.config_reload")]
+    #[serde(rename = "system.config_reload")]
+    SystemConfigReload,
+
+    #[sea_orm(string_value = "system.heap_profile")]
+    #[strum(serialize = "system.heap_profile")]
+    #[serde(rename = "system.heap_profile")]
+    HeapProfileDump,
+}
+
+/// 数据处理目的，用户可以分别同意或撤回（存为数据库字符串）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, DeriveActiveEnum, Serialize, Deserialize, Display)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ConsentPurpose {
+    /// 营销类通知（邮件、短信、推送）
+    #[sea_orm(string_value = "marketing")]
+    Marketing,
+    /// 使用行为分析
+    #[sea_orm(string_value = "analytics")]
+    Analytics,
+}
+
+/// 登录方式，写入登录历史（存为数据库字符串）。
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, Display)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum LoginMethod {
+    /// 用户名或手机号 + 密码
+    #[sea_orm(string_value = "password")]
+    Password,
+    /// 设备授权流程（RFC 8628）
+    #[sea_orm(string_value = "device")]
+    Device,
+}
+
+/// 安全事件类型。记录认证相关的异常，存为数据库字符串（如 "auth.login_failed"）。
+#[derive(Debug, Clone, PartialEq, Eq, EnumIter, DeriveActiveEnum, Serialize, Deserialize, Display, EnumString)]
+#[sea_orm(rs_type = "String", db_type = "String(StringLen::None)")]
+pub enum SecurityEventKind {
+    /// 登录失败：账号不存在或密码错误
+    #[sea_orm(string_value = "auth.login_failed")]
+    #[strum(serialize = "auth.login_failed")]
+    #[serde(rename = "auth.login_failed")]
+    LoginFailed,
+
+    /// 刷新令牌在宽限期之外被重复使用，令牌可能已泄露
+    #[sea_orm(string_value = "auth.refresh_token_reuse")]
+    #[strum(serialize = "auth.refresh_token_reuse")]
+    #[serde(rename = "auth.refresh_token_reuse")]
+    RefreshTokenReuse,
+
+    /// 使用已登出（加入黑名单）或已被强制下线的访问令牌
+    #[sea_orm(string_value = "auth.revoked_token")]
+    #[strum(serialize = "auth.revoked_token")]
+    #[serde(rename = "auth.revoked_token")]
+    RevokedToken,
+
+    /// 角色或权限不足，访问被拒绝
+    #[sea_orm(string_value = "auth.access_denied")]
+    #[strum(serialize = "auth.access_denied")]
+    #[serde(rename = "auth.access_denied")]
+    AccessDenied,
+}
+
+/// JSON 字段命名风格。DTO 在代码中统一使用 snake_case，
+/// 当配置为 camel 时，由响应转换层和请求规范化中间件在边界处完成转换。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Display)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum JsonCase {
+    #[default]
+    Snake,
+    Camel,
+}
+
+/// 刷新令牌的传输方式。`/auth/refresh` 按 body -> header -> cookie 的顺序，
+/// 在配置启用的方式中查找刷新令牌。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum RefreshTransport {
+    /// JSON 请求体中的 `refresh_token` 字段
+    Body,
+    /// `Authorization: Bearer <refresh_token>` 请求头（适用于 CLI 客户端）
+    Header,
+    /// httpOnly Cookie（适用于 Cookie 模式的 SPA）
+    Cookie,
+}
+
+/// 路由依赖的下游服务。对应服务的熔断器打开时，声明依赖它的路由直接返回 503。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Display)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Dependency {
+    Database,
+    Redis,
+}
+
+/// 长周期配额的计数周期，按 UTC 自然日或自然月切换计数。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Display)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaPeriod {
+    Day,
+    Month,
+}
+
+/// 请求优先级通道。每个通道有独立的并发预算，匿名流量激增时不会挤占管理和运维端点。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Lane {
+    /// 管理后台与运维端点（`/admin`、`/health`、`/healthz`、`/readyz`、`/metrics`）
+    Ops,
+    /// 携带访问令牌的请求
+    Authenticated,
+    /// 未携带令牌的请求（登录、设备授权等）
+    Anonymous,
+}
+
+/// 日志输出格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Display)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// 人类可读的文本（默认），控制台带颜色
+    #[default]
+    Text,
+    /// 单行 JSON 事件，便于 Loki / ELK 等日志系统采集
+    Json,
+}
+
+/// 外部密钥管理后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Display)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum SecretsBackend {
+    /// HashiCorp Vault（KV 引擎）
+    Vault,
+    /// AWS Secrets Manager
+    Aws,
+}
+
+/// 访问日志的持久化目标
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Display)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AccessLogSink {
+    /// 不记录（默认），仅保留 TraceLayer 的追踪日志
+    #[default]
+    Off,
+    /// 按天滚动的 NDJSON 文件
+    File,
+    /// Postgres `access_logs` 表
+    Database,
+}
+
+/// 运行环境。决定安全相关配置的默认值：开发环境放宽 CORS 并在响应中返回错误详情，
+/// 预发和生产环境只允许配置的跨域来源、隐藏内部错误，并在启动时强制要求高强度的 JWT 密钥。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Display, EnumString)]
+#[strum(ascii_case_insensitive)]
+#[serde(rename_all = "lowercase")]
+pub enum AppEnv {
+    #[default]
+    #[strum(to_string = "development", serialize = "dev")]
+    #[serde(alias = "dev")]
+    Development,
+    #[strum(to_string = "staging")]
+    Staging,
+    #[strum(to_string = "production", serialize = "prod")]
+    #[serde(alias = "prod")]
+    Production,
+}
+
+impl AppEnv {
+    pub fn is_development(self) -> bool {
+        self == AppEnv::Development
+    }
+}
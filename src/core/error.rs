@@ -1,7 +1,11 @@
 // src/core/error.rs
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use axum::{http::StatusCode, response::{IntoResponse, Response}};
 use thiserror::Error;
+use crate::core::{log::target, reporting};
 use crate::dtos::response::ApiResponse;
+use crate::utils::retry::Transient;
 
 /// 应用程序统一错误类型。这个枚举定义了所有可能发生的错误类型，
 /// 覆盖了数据库、缓存、验证、认证、授权等各个层面的错误。
@@ -22,6 +26,10 @@ pub enum AppError {
     #[error("Validation error: {0}")]
     ValidationError(#[from] validator::ValidationErrors),
 
+    /// 请求格式错误。如JSON语法错误、字段类型不匹配等。返回400 Bad Request。
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+
     /// 认证错误。如令牌无效、用户名密码错误等。返回401 Unauthorized。
     #[error("Authentication failed: {0}")]
     AuthError(String),
@@ -38,15 +46,64 @@ pub enum AppError {
     #[error("Conflict: {0}")]
     Conflict(String),
 
+    /// 请求体过大错误。超过全局或路由级的请求体大小限制。返回413 Payload Too Large。
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
     /// 请求频率限制错误。返回429 Too Many Requests。
     #[error("Rate limit exceeded: {0}")]
     RateLimitExceeded(String),
 
+    /// 违规冷却错误。重复超出请求频率限制后被临时禁止访问，冷却时长逐级升级。返回429 Too Many Requests。
+    #[error("Temporarily banned: {0}")]
+    TemporarilyBanned(String),
+
+    /// 依赖服务不可用错误。如数据库或Redis熔断期间的快速失败。返回503 Service Unavailable。
+    #[error("Service unavailable: {0}")]
+    ServiceUnavailable(String),
+
+    /// 请求超时错误。处理时间超过路由的超时预算。返回504 Gateway Timeout。
+    #[error("Request timeout: {0}")]
+    Timeout(String),
+
     /// 服务器内部错误。用于未预期的错误情况。返回500 Internal Server Error。
     #[error("Internal server error: {0}")]
     InternalServerError(String),
 }
 
+impl AppError {
+    /// 把 axum 内置提取器（如 `Multipart`）的拒绝转换为统一错误。
+    /// 超出请求体大小限制时返回 413，其余情况按请求格式错误处理。
+    ///
+    /// # 参数
+    /// - `status`: 拒绝对应的HTTP状态码
+    /// - `message`: 拒绝的文本描述
+    pub fn from_rejection(status: StatusCode, message: String) -> Self {
+        if status == StatusCode::PAYLOAD_TOO_LARGE {
+            AppError::PayloadTooLarge(message)
+        } else {
+            AppError::BadRequest(message)
+        }
+    }
+}
+
+/// 5xx 响应是否返回内部错误详情，启动时由 `expose_details` 设置（见 `Config::expose_error_details`）
+static EXPOSE_DETAILS: AtomicBool = AtomicBool::new(false);
+
+/// 设置 5xx 响应是否返回内部错误详情。默认不返回，只在开发环境启用，避免泄露数据库结构等敏感信息。
+pub fn expose_details(enabled: bool) {
+    EXPOSE_DETAILS.store(enabled, Ordering::Relaxed);
+}
+
+/// 内部错误返回给客户端的消息：启用错误详情时附带原始错误，否则只返回通用消息
+fn internal_message(generic: &str, detail: impl std::fmt::Display) -> String {
+    if EXPOSE_DETAILS.load(Ordering::Relaxed) {
+        format!("{}: {}", generic, detail)
+    } else {
+        generic.to_string()
+    }
+}
+
 /// 实现 `IntoResponse` trait，将 `AppError` 转换为HTTP响应。
 ///
 /// 这个实现确保所有错误都以统一的 `ApiResponse` 格式返回给客户端，
@@ -54,25 +111,35 @@ pub enum AppError {
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         // 根据错误类型确定HTTP状态码和返回给客户端的错误消息。
-        // 对于内部错误（如数据库、Redis），返回通用的错误消息，避免泄露敏感信息。
+        // 对于内部错误（如数据库、Redis），返回通用的错误消息，避免泄露敏感信息（开发环境可返回详情）。
         let (status, msg) = match &self {
+            // 重试后仍然失败的瞬时错误（序列化冲突、连接中断）返回 503，客户端可稍后重试
+            AppError::DatabaseError(e) if e.is_transient() => {
+                tracing::error!(target: target::HTTP, "❌ Transient Database Error: {}", e);
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    internal_message("Database is temporarily unavailable, please retry later", e),
+                )
+            },
             AppError::DatabaseError(e) => {
                 // 记录详细的数据库错误日志，便于排查问题
-                tracing::error!("❌ Database Error: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Database service error".to_string())
+                tracing::error!(target: target::HTTP, "❌ Database Error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, internal_message("Database service error", e))
             },
             AppError::RedisError(e) => {
                 // 记录详细的Redis错误日志
-                tracing::error!("❌ Redis Error: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Cache service error".to_string())
+                tracing::error!(target: target::HTTP, "❌ Redis Error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, internal_message("Cache service error", e))
             },
             AppError::InternalServerError(msg) => {
                 // 记录内部服务器错误日志
-                tracing::error!("❌ Internal Error: {}", msg);
-                (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string())
+                tracing::error!(target: target::HTTP, "❌ Internal Error: {}", msg);
+                (StatusCode::INTERNAL_SERVER_ERROR, internal_message("Internal server error", msg))
             },
             // 验证错误：直接返回验证失败的详细信息
             AppError::ValidationError(e) => (StatusCode::BAD_REQUEST, e.to_string()),
+            // 请求格式错误：返回具体的字段路径和错误原因
+            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
             // 认证错误：返回具体的认证失败消息
             AppError::AuthError(msg) => (StatusCode::UNAUTHORIZED, msg.clone()),
             // 授权错误：返回具体的权限不足消息
@@ -81,10 +148,21 @@ impl IntoResponse for AppError {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
             // 资源冲突：返回具体的冲突消息
             AppError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
+            // 请求体过大：返回具体的限制说明
+            AppError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg.clone()),
             // 请求频率限制：返回具体的限流消息
             AppError::RateLimitExceeded(msg) => (StatusCode::TOO_MANY_REQUESTS, msg.clone()),
+            // 违规冷却：返回剩余的冷却时间，冷却期间的请求不计入限流次数
+            AppError::TemporarilyBanned(msg) => (StatusCode::TOO_MANY_REQUESTS, msg.clone()),
+            // 依赖服务不可用：返回具体的不可用服务，客户端可稍后重试
+            AppError::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg.clone()),
+            // 请求超时：下游调用过慢，客户端可稍后重试
+            AppError::Timeout(msg) => (StatusCode::GATEWAY_TIMEOUT, msg.clone()),
         };
 
+        // 5xx 错误上报到错误追踪服务（未配置时为空操作）
+        reporting::capture(&self);
+
         // 使用统一的 ApiResponse 格式返回错误，确保API响应的一致性
         ApiResponse::<()>::with_error(status, &msg).into_response()
     }
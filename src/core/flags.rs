@@ -0,0 +1,169 @@
+// src/core/flags.rs
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use redis::{aio::ConnectionManager, AsyncCommands};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+
+use crate::core::log::target;
+use crate::core::{
+    config::Config,
+    constants::{FLAGS_POLL_INTERVAL, REDIS_KEY_FLAG_OVERRIDES},
+    error::AppError,
+};
+
+// 系统开关：整个功能对所有用户打开或关闭（如暂停注册、临时关闭数据导出），用于运维应急和分阶段上线。
+// 与按用户灰度的功能开关（`services::feature`）不同，系统开关不区分用户，也不进入令牌。
+//
+// 开关的取值按优先级：Redis 中的运行时覆盖（`/admin/flags`）> 配置（`FLAGS` 或配置文件中的 `[flags]`）> 内置默认值。
+// Redis 中的覆盖由后台任务定期同步到本地，请求路径上只读取内存。
+
+/// 系统开关。新增开关时在这里加一个变体，并在 `default_enabled` / `description` / `disabled_error` 中补充。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, EnumString, Display, Serialize, Deserialize)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Flag {
+    /// 是否允许注册新用户
+    RegistrationOpen,
+    /// 是否允许设备授权登录（RFC 8628）
+    DeviceLoginEnabled,
+    /// 是否允许用户导出个人数据
+    DataExportEnabled,
+}
+
+impl Flag {
+    /// 未配置也没有运行时覆盖时的取值
+    pub fn default_enabled(self) -> bool {
+        match self {
+            Flag::RegistrationOpen | Flag::DeviceLoginEnabled | Flag::DataExportEnabled => true,
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Flag::RegistrationOpen => "Allow registering new users",
+            Flag::DeviceLoginEnabled => "Allow the device authorization login flow",
+            Flag::DataExportEnabled => "Allow users to export their personal data",
+        }
+    }
+
+    /// 开关关闭时返回给客户端的错误：客户端需要提示用户的返回 403，其余返回 404，与功能不存在时一致。
+    pub fn disabled_error(self) -> AppError {
+        match self {
+            Flag::RegistrationOpen => AppError::Forbidden("Registration is closed".to_string()),
+            Flag::DeviceLoginEnabled | Flag::DataExportEnabled => AppError::NotFound("Not found".to_string()),
+        }
+    }
+}
+
+/// 单个开关的状态，供管理端查询
+#[derive(Debug, Serialize)]
+pub struct FlagStatus {
+    pub key: Flag,
+    pub description: &'static str,
+    /// 生效值
+    pub enabled: bool,
+    /// 配置中的取值，未配置时为 `None`
+    pub configured: Option<bool>,
+    /// Redis 中的运行时覆盖，没有覆盖时为 `None`
+    #[serde(rename = "override")]
+    pub override_value: Option<bool>,
+}
+
+/// 系统开关的运行时覆盖，由后台任务从 Redis 同步。
+#[derive(Debug, Default)]
+pub struct SystemFlags {
+    overrides: RwLock<HashMap<Flag, bool>>,
+}
+
+impl SystemFlags {
+    /// 开关的生效值
+    pub fn is_enabled(&self, flag: Flag, config: &Config) -> bool {
+        self.override_value(flag)
+            .or_else(|| config.flag(flag))
+            .unwrap_or_else(|| flag.default_enabled())
+    }
+
+    /// 全部开关的状态
+    pub fn list(&self, config: &Config) -> Vec<FlagStatus> {
+        Flag::iter()
+            .map(|flag| FlagStatus {
+                key: flag,
+                description: flag.description(),
+                enabled: self.is_enabled(flag, config),
+                configured: config.flag(flag),
+                override_value: self.override_value(flag),
+            })
+            .collect()
+    }
+
+    fn override_value(&self, flag: Flag) -> Option<bool> {
+        self.overrides.read().ok().and_then(|overrides| overrides.get(&flag).copied())
+    }
+
+    /// 更新本地状态，生效值发生变化时记录日志
+    fn apply(&self, overrides: HashMap<Flag, bool>) {
+        let Ok(mut current) = self.overrides.write() else {
+            return;
+        };
+        if *current != overrides {
+            tracing::info!(target: target::SYSTEM, "🚩 System flag overrides changed: {:?}", overrides);
+        }
+        *current = overrides;
+    }
+
+    /// 设置或清除运行时覆盖。写入 Redis 后立即更新本实例的状态，其他实例在下一次同步时生效。
+    ///
+    /// # 参数
+    /// - `redis`: Redis连接管理器
+    /// - `flag`: 开关
+    /// - `enabled`: 覆盖值，`None` 表示清除覆盖，恢复为配置中的取值
+    pub async fn set(&self, redis: &ConnectionManager, flag: Flag, enabled: Option<bool>) -> Result<(), redis::RedisError> {
+        let mut conn = redis.clone();
+        let field = flag.to_string();
+        match enabled {
+            Some(enabled) => conn.hset::<_, _, _, ()>(REDIS_KEY_FLAG_OVERRIDES, &field, enabled).await?,
+            None => conn.hdel::<_, _, ()>(REDIS_KEY_FLAG_OVERRIDES, &field).await?,
+        }
+
+        let mut overrides = self.overrides.read().map(|overrides| overrides.clone()).unwrap_or_default();
+        match enabled {
+            Some(enabled) => overrides.insert(flag, enabled),
+            None => overrides.remove(&flag),
+        };
+        self.apply(overrides);
+        Ok(())
+    }
+}
+
+/// 启动后台同步任务，定期从 Redis 读取运行时覆盖。
+/// Redis 不可用时保持上一次的状态；无法识别的开关名称（如已下线的开关）被忽略。
+///
+/// # 参数
+/// - `flags`: 共享的系统开关（与 `AppState` 中的是同一个实例）
+/// - `redis`: Redis连接管理器
+pub fn spawn_watcher(flags: Arc<SystemFlags>, redis: ConnectionManager) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(FLAGS_POLL_INTERVAL));
+
+        loop {
+            ticker.tick().await;
+
+            let mut conn = redis.clone();
+            match conn.hgetall::<_, HashMap<String, bool>>(REDIS_KEY_FLAG_OVERRIDES).await {
+                Ok(values) => flags.apply(
+                    values
+                        .into_iter()
+                        .filter_map(|(key, enabled)| Flag::from_str(&key).ok().map(|flag| (flag, enabled)))
+                        .collect(),
+                ),
+                Err(e) => tracing::warn!(target: target::SYSTEM, "⚠️ Failed to read system flag overrides: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,83 @@
+// src/core/i18n.rs
+
+/// 支持的语言。根据请求头 `Accept-Language` 协商，默认英文。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Zh,
+}
+
+impl Locale {
+    /// 从 `Accept-Language` 请求头中解析语言，取第一个受支持的语言。
+    /// 例如 "zh-CN,zh;q=0.9,en;q=0.8" -> `Locale::Zh`。
+    pub fn from_accept_language(header: Option<&str>) -> Self {
+        header
+            .into_iter()
+            .flat_map(|value| value.split(','))
+            .filter_map(|item| item.split(';').next())
+            .map(|tag| tag.trim().to_ascii_lowercase())
+            .find_map(|tag| {
+                if tag.starts_with("zh") {
+                    Some(Locale::Zh)
+                } else if tag.starts_with("en") {
+                    Some(Locale::En)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// 消息目录中的键。每个键在每种语言下都有一条对应的文案模板。
+#[derive(Debug, Clone, Copy)]
+pub enum MessageKey {
+    /// 缺少或错误的 Content-Type
+    JsonContentType,
+    /// 请求体无法读取
+    JsonBodyUnreadable,
+    /// 请求体超过大小限制
+    PayloadTooLarge,
+    /// JSON 语法错误
+    JsonSyntax,
+    /// JSON 字段内容不符合要求（类型错误、缺少字段等）
+    JsonField,
+    /// 字段类型错误
+    InvalidType,
+    /// 缺少必填字段
+    MissingField,
+    /// 未知字段
+    UnknownField,
+}
+
+/// 查找消息模板并替换占位符。模板中的 `{name}` 会被 `args` 中同名的值替换。
+///
+/// # 示例
+/// ```ignore
+/// t(Locale::Zh, MessageKey::MissingField, &[("field", "phone")]) // => "缺少必填字段 `phone`"
+/// ```
+pub fn t(locale: Locale, key: MessageKey, args: &[(&str, &str)]) -> String {
+    let template = match (locale, key) {
+        (Locale::En, MessageKey::JsonContentType) => "Expected request with `Content-Type: application/json`",
+        (Locale::Zh, MessageKey::JsonContentType) => "请求头必须为 `Content-Type: application/json`",
+        (Locale::En, MessageKey::JsonBodyUnreadable) => "Failed to read request body",
+        (Locale::Zh, MessageKey::JsonBodyUnreadable) => "无法读取请求体",
+        (Locale::En, MessageKey::PayloadTooLarge) => "Request body is too large",
+        (Locale::Zh, MessageKey::PayloadTooLarge) => "请求体超过大小限制",
+        (Locale::En, MessageKey::JsonSyntax) => "Malformed JSON body: {detail}",
+        (Locale::Zh, MessageKey::JsonSyntax) => "请求体不是合法的 JSON：{detail}",
+        (Locale::En, MessageKey::JsonField) => "{path}: {detail}",
+        (Locale::Zh, MessageKey::JsonField) => "{path}：{detail}",
+        (Locale::En, MessageKey::InvalidType) => "expected {expected}, got {actual}",
+        (Locale::Zh, MessageKey::InvalidType) => "类型错误，期望 {expected}，实际为 {actual}",
+        (Locale::En, MessageKey::MissingField) => "missing field `{field}`",
+        (Locale::Zh, MessageKey::MissingField) => "缺少必填字段 `{field}`",
+        (Locale::En, MessageKey::UnknownField) => "unknown field `{field}`",
+        (Locale::Zh, MessageKey::UnknownField) => "未知字段 `{field}`",
+    };
+
+    args.iter().fold(template.to_string(), |message, (name, value)| {
+        message.replace(&format!("{{{}}}", name), value)
+    })
+}
@@ -0,0 +1,54 @@
+// src/core/lanes.rs
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::core::{config::Config, enums::Lane};
+
+/// 请求优先级通道的并发预算。每个通道一个信号量，请求处理期间持有一个许可；
+/// 通道满载时请求排队等待，超过排队时间仍未拿到许可则放弃。
+#[derive(Debug)]
+pub struct PriorityLanes {
+    ops: Arc<Semaphore>,
+    authenticated: Arc<Semaphore>,
+    anonymous: Arc<Semaphore>,
+    queue_timeout: Duration,
+}
+
+impl PriorityLanes {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            ops: Arc::new(Semaphore::new(config.lane_ops_concurrency.max(1))),
+            authenticated: Arc::new(Semaphore::new(config.lane_authenticated_concurrency.max(1))),
+            anonymous: Arc::new(Semaphore::new(config.lane_anonymous_concurrency.max(1))),
+            queue_timeout: config.lane_queue_timeout,
+        }
+    }
+
+    fn semaphore(&self, lane: Lane) -> &Arc<Semaphore> {
+        match lane {
+            Lane::Ops => &self.ops,
+            Lane::Authenticated => &self.authenticated,
+            Lane::Anonymous => &self.anonymous,
+        }
+    }
+
+    /// 在指定通道中申请一个并发许可，许可在返回值被丢弃时自动归还。
+    ///
+    /// # 返回值
+    /// - `Some(permit)`: 拿到许可，可以继续处理请求
+    /// - `None`: 排队超时
+    pub async fn acquire(&self, lane: Lane) -> Option<OwnedSemaphorePermit> {
+        let semaphore = self.semaphore(lane).clone();
+        match tokio::time::timeout(self.queue_timeout, semaphore.acquire_owned()).await {
+            Ok(Ok(permit)) => Some(permit),
+            // 信号量不会被关闭，这里只可能是排队超时
+            _ => None,
+        }
+    }
+
+    /// 通道当前可用的许可数量
+    pub fn available(&self, lane: Lane) -> usize {
+        self.semaphore(lane).available_permits()
+    }
+}
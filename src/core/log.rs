@@ -1,35 +1,138 @@
-use tracing_appender::non_blocking::WorkerGuard;
+use std::sync::OnceLock;
+
+use tracing::Subscriber;
+use tracing_appender::non_blocking::{NonBlocking, WorkerGuard};
 use tracing_subscriber::{
-    fmt, layer::SubscriberExt, registry, util::SubscriberInitExt, EnvFilter,
+    fmt, layer::SubscriberExt, registry, registry::LookupSpan, reload, util::SubscriberInitExt, EnvFilter, Layer,
+    Registry,
 };
 
-pub fn init(log_level: &str) -> WorkerGuard {
+use crate::core::enums::LogFormat;
+
+/// 稳定的日志 target。各模块记录日志时显式指定 target，而不是使用默认的模块路径，
+/// 这样重构代码目录不会改变 `RUST_LOG` 的过滤效果，如 `RUST_LOG=info,app::auth=debug`。
+pub mod target {
+    /// 认证、令牌、设备授权
+    pub const AUTH: &str = "app::auth";
+    /// Redis 缓存
+    pub const CACHE: &str = "app::cache";
+    /// 限流、配额、优先级通道
+    pub const LIMITER: &str = "app::limiter";
+    /// 用户资料、设置、数据导出
+    pub const USER: &str = "app::user";
+    /// 管理后台、审计、权限、用户导入
+    pub const ADMIN: &str = "app::admin";
+    /// HTTP 层：响应、错误、超时、弃用、访问日志
+    pub const HTTP: &str = "app::http";
+    /// 启动、配置、依赖健康、平滑升级
+    pub const SYSTEM: &str = "app::system";
+}
+
+/// 预置的日志过滤规则，运维人员通过 `LOG_PRESET` 选择，无需手写 `RUST_LOG`。
+///
+/// # 返回值
+/// - `Some(&str)`: 预置的 `EnvFilter` 规则
+/// - `None`: 未知的预置名称
+pub fn preset(name: &str) -> Option<&'static str> {
+    match name {
+        // 只输出警告和错误
+        "quiet" => Some("warn"),
+        // 生产环境的默认选择：应用日志为 info，压低 ORM 和数据库驱动的日志
+        "normal" => Some("info,sea_orm=warn,sqlx=warn"),
+        // 排查登录、令牌、限流问题
+        "debug-auth" => Some("info,app::auth=debug,app::limiter=debug,sea_orm=warn,sqlx=warn"),
+        // 排查缓存命中率和 Redis 问题
+        "debug-cache" => Some("info,app::cache=debug,sea_orm=warn,sqlx=warn"),
+        _ => None,
+    }
+}
+
+/// 过滤规则的重载句柄，配置重新加载时通过 `set_filter` 替换过滤规则
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// 解析生效的过滤规则：预置规则优先，未知的预置名称回退到 `log_level`。
+/// `Config::validate` 已拒绝未知的预置名称，这里的回退只是兜底。
+pub fn resolve_filter<'a>(log_level: &'a str, log_preset: Option<&str>) -> &'a str {
+    match log_preset.map(preset) {
+        Some(Some(rules)) => rules,
+        _ => log_level,
+    }
+}
+
+/// 初始化日志系统。
+///
+/// # 参数
+/// - `filter`: `RUST_LOG` 格式的过滤规则（见 `resolve_filter`）
+/// - `log_format`: 输出格式，同时作用于控制台和日志文件
+pub fn init(filter: &str, log_format: LogFormat) -> WorkerGuard {
     // 1. 文件输出层：按天轮询，存放在 logs 文件夹下
     let file_appender = tracing_appender::rolling::daily("logs", "app.log");
     let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
 
-    // 2. 格式化层（文件）- 不带颜色，包含详细代码位置
-    let file_layer = fmt::layer()
-        .with_ansi(false)
-        .with_writer(non_blocking)
-        .with_file(true)        // ✅ 显示文件名
-        .with_line_number(true) // ✅ 显示行号
-        .with_thread_ids(true)  // (可选) 显示线程ID，方便排查并发问题
-        .with_target(false);    // (可选) 关闭 target (通常是模块路径)，只看文件名更清爽，看个人喜好
-
-    // 3. 格式化层（控制台）- 带颜色，包含详细代码位置
-    let stdout_layer = fmt::layer()
-        .with_writer(std::io::stdout)
-        .with_file(true)        // ✅ 显示文件名
-        .with_line_number(true) // ✅ 显示行号
-        .with_thread_ids(true); // (可选)
-
-    // 4. 注册所有层
+    // 2. 注册过滤规则和输出层。过滤规则包在重载层中，运行期间可以替换
+    let (filter_layer, handle) = reload::Layer::new(EnvFilter::new(filter));
+    let _ = FILTER_HANDLE.set(handle);
     registry()
-        .with(EnvFilter::new(log_level))
-        .with(stdout_layer)
-        .with(file_layer)
+        .with(filter_layer)
+        .with(output_layers(log_format, non_blocking))
         .init();
 
     guard
-}
\ No newline at end of file
+}
+
+/// 运行期间替换过滤规则，用于配置热加载。
+///
+/// # 返回值
+/// - `Ok(())`: 替换成功，新规则立即生效
+/// - `Err(String)`: 规则格式错误或日志系统尚未初始化
+pub fn set_filter(filter: &str) -> Result<(), String> {
+    let handle = FILTER_HANDLE.get().ok_or("logging is not initialized")?;
+    let filter = EnvFilter::try_new(filter).map_err(|e| e.to_string())?;
+    handle.reload(filter).map_err(|e| e.to_string())
+}
+
+/// 按输出格式构建控制台和文件两个输出层。
+fn output_layers<S>(log_format: LogFormat, file_writer: NonBlocking) -> Vec<Box<dyn Layer<S> + Send + Sync>>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    match log_format {
+        LogFormat::Text => vec![
+            // 控制台 - 带颜色，包含详细代码位置
+            fmt::layer()
+                .with_writer(std::io::stdout)
+                .with_file(true)        // ✅ 显示文件名
+                .with_line_number(true) // ✅ 显示行号
+                .with_thread_ids(true)  // (可选)
+                .boxed(),
+            // 文件 - 不带颜色，包含详细代码位置
+            fmt::layer()
+                .with_ansi(false)
+                .with_writer(file_writer)
+                .with_file(true)        // ✅ 显示文件名
+                .with_line_number(true) // ✅ 显示行号
+                .with_thread_ids(true)  // (可选) 显示线程ID，方便排查并发问题
+                .with_target(true)      // ✅ 显示稳定的 target（如 app::auth），便于按模块检索
+                .boxed(),
+        ],
+        // 单行 JSON：事件字段展开到顶层，当前 span（包含 request_id）放在 `span` 字段中
+        LogFormat::Json => vec![
+            fmt::layer()
+                .json()
+                .flatten_event(true)
+                .with_current_span(true)
+                .with_span_list(false)
+                .with_writer(std::io::stdout)
+                .boxed(),
+            fmt::layer()
+                .json()
+                .flatten_event(true)
+                .with_current_span(true)
+                .with_span_list(false)
+                .with_writer(file_writer)
+                .with_file(true)
+                .with_line_number(true)
+                .boxed(),
+        ],
+    }
+}
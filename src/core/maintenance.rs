@@ -0,0 +1,87 @@
+// src/core/maintenance.rs
+use std::{sync::{Arc, RwLock}, time::Duration};
+
+use redis::{aio::ConnectionManager, AsyncCommands};
+use serde::Serialize;
+
+use crate::core::log::target;
+use crate::core::constants::{MAINTENANCE_POLL_INTERVAL, REDIS_KEY_MAINTENANCE};
+
+/// 维护模式开关。开关状态保存在 Redis 中（键存在即表示开启，值为展示给客户端的提示），
+/// 所有实例由后台任务定期同步到本地，请求路径上只读取本地状态，不产生额外的 Redis 访问。
+#[derive(Debug, Default)]
+pub struct MaintenanceMode {
+    /// 开启时为提示消息
+    message: RwLock<Option<String>>,
+}
+
+/// 维护模式状态，供管理端查询
+#[derive(Debug, Serialize)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+    pub message: Option<String>,
+}
+
+impl MaintenanceMode {
+    /// 返回维护提示消息，未开启维护模式时为 `None`
+    pub fn message(&self) -> Option<String> {
+        self.message.read().map(|message| message.clone()).unwrap_or_default()
+    }
+
+    pub fn status(&self) -> MaintenanceStatus {
+        let message = self.message();
+        MaintenanceStatus { enabled: message.is_some(), message }
+    }
+
+    /// 更新本地状态，状态发生变化时记录日志
+    fn apply(&self, message: Option<String>) {
+        let Ok(mut current) = self.message.write() else {
+            return;
+        };
+        if current.is_some() != message.is_some() {
+            match &message {
+                Some(_) => tracing::warn!(target: target::SYSTEM, "🚧 Maintenance mode enabled"),
+                None => tracing::info!(target: target::SYSTEM, "✅ Maintenance mode disabled"),
+            }
+            metrics::gauge!("maintenance_mode").set(if message.is_some() { 1.0 } else { 0.0 });
+        }
+        *current = message;
+    }
+
+    /// 开启或关闭维护模式。写入 Redis 后立即更新本实例的状态，其他实例在下一次同步时生效。
+    ///
+    /// # 参数
+    /// - `redis`: Redis连接管理器
+    /// - `message`: 开启时的提示消息，`None` 表示关闭维护模式
+    pub async fn set(&self, redis: &ConnectionManager, message: Option<String>) -> Result<(), redis::RedisError> {
+        let mut conn = redis.clone();
+        match &message {
+            Some(message) => conn.set::<_, _, ()>(REDIS_KEY_MAINTENANCE, message).await?,
+            None => conn.del::<_, ()>(REDIS_KEY_MAINTENANCE).await?,
+        }
+        self.apply(message);
+        Ok(())
+    }
+}
+
+/// 启动后台同步任务，定期从 Redis 读取维护模式开关。
+/// Redis 不可用时保持上一次的状态，避免 Redis 抖动导致维护模式被意外关闭。
+///
+/// # 参数
+/// - `mode`: 共享的维护模式开关（与 `AppState` 中的是同一个实例）
+/// - `redis`: Redis连接管理器
+pub fn spawn_watcher(mode: Arc<MaintenanceMode>, redis: ConnectionManager) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(MAINTENANCE_POLL_INTERVAL));
+
+        loop {
+            ticker.tick().await;
+
+            let mut conn = redis.clone();
+            match conn.get::<_, Option<String>>(REDIS_KEY_MAINTENANCE).await {
+                Ok(message) => mode.apply(message),
+                Err(e) => tracing::warn!(target: target::SYSTEM, "⚠️ Failed to read maintenance flag: {}", e),
+            }
+        }
+    });
+}
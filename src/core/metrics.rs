@@ -0,0 +1,143 @@
+// src/core/metrics.rs
+use std::time::Duration;
+
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use sea_orm::DatabaseConnection;
+
+use crate::core::{constants::DB_POOL_MONITOR_INTERVAL, log::target};
+
+/// 限流窗口利用率直方图的桶边界：当前计数 / 限额。大于 1.0 的部分表示已被限流的请求。
+const RATE_LIMIT_UTILIZATION_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 0.75, 0.9, 1.0, 1.5, 2.0, 5.0];
+
+/// 数据库语句耗时直方图的桶边界（秒）
+const DB_QUERY_DURATION_BUCKETS: &[f64] = &[0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// 初始化全局指标记录器。安装后，代码中任意位置通过 `metrics::counter!` / `metrics::histogram!`
+/// 记录的指标都会汇总到返回的 `PrometheusHandle`，由 `/metrics` 端点渲染输出。
+///
+/// # 返回值
+/// - `PrometheusHandle`: 用于渲染 Prometheus / OpenMetrics 文本格式的句柄
+pub fn init() -> PrometheusHandle {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full("rate_limit_window_utilization".to_string()),
+            RATE_LIMIT_UTILIZATION_BUCKETS,
+        )
+        .expect("❌ Invalid histogram buckets")
+        .set_buckets_for_metric(Matcher::Full("db_query_duration_seconds".to_string()), DB_QUERY_DURATION_BUCKETS)
+        .expect("❌ Invalid histogram buckets")
+        .set_buckets_for_metric(Matcher::Full("db_pool_acquire_duration_seconds".to_string()), DB_QUERY_DURATION_BUCKETS)
+        .expect("❌ Invalid histogram buckets")
+        .install_recorder()
+        .expect("❌ Failed to install metrics recorder")
+}
+
+/// 为数据库连接注册语句耗时回调。SQLx 的语句日志在启动时被关闭（`sqlx_logging(false)`），
+/// 这里按语句名称记录每条语句的耗时（`db_query_duration_seconds`）和失败次数（`db_query_errors_total`），
+/// 耗时不低于阈值的语句额外输出警告并记录 `slow_queries_total` 指标。
+///
+/// # 参数
+/// - `db`: 数据库连接池
+/// - `threshold`: 慢查询阈值
+pub fn watch_queries(db: &mut DatabaseConnection, threshold: Duration) {
+    db.set_metric_callback(move |info| {
+        let statement = statement_name(&info.statement.sql);
+        metrics::histogram!("db_query_duration_seconds", "statement" => statement.clone())
+            .record(info.elapsed.as_secs_f64());
+        if info.failed {
+            metrics::counter!("db_query_errors_total", "statement" => statement.clone()).increment(1);
+        }
+
+        if info.elapsed < threshold {
+            return;
+        }
+        metrics::counter!("slow_queries_total", "statement" => statement.clone()).increment(1);
+        tracing::warn!(
+            target: target::SYSTEM,
+            statement = %statement,
+            elapsed_ms = info.elapsed.as_millis() as u64,
+            threshold_ms = threshold.as_millis() as u64,
+            failed = info.failed,
+            "🐌 Slow query"
+        );
+    });
+}
+
+/// 启动数据库连接池的监控任务，定期记录连接池状态：
+/// - `db_pool_connections{state="in_use|idle"}`、`db_pool_max_connections`：连接使用量和上限
+/// - `db_pool_acquire_duration_seconds`：探测获取一个连接的耗时，反映请求排队等待连接的时间
+///
+/// 获取耗时不低于阈值、获取超时或连接全部被占用时输出警告并记录 `db_pool_saturation_total`，
+/// 在连接池耗尽导致请求失败之前暴露容量问题。
+///
+/// # 参数
+/// - `db`: 数据库连接池
+/// - `max_connections`: 连接池的最大连接数
+/// - `threshold`: 获取连接的慢等待阈值
+/// - `timeout`: 获取连接的超时时间（与连接池的获取超时一致）
+pub fn spawn_pool_monitor(db: DatabaseConnection, max_connections: u32, threshold: Duration, timeout: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(DB_POOL_MONITOR_INTERVAL));
+        metrics::gauge!("db_pool_max_connections").set(max_connections as f64);
+
+        loop {
+            ticker.tick().await;
+
+            // 第一步：连接使用量
+            let pool = db.get_postgres_connection_pool();
+            let idle = pool.num_idle() as u32;
+            let in_use = pool.size().saturating_sub(idle);
+            metrics::gauge!("db_pool_connections", "state" => "in_use").set(in_use as f64);
+            metrics::gauge!("db_pool_connections", "state" => "idle").set(idle as f64);
+
+            // 第二步：探测获取连接的耗时，拿到连接后立即归还
+            let started = std::time::Instant::now();
+            let acquired = tokio::time::timeout(timeout, pool.acquire()).await;
+            let elapsed = started.elapsed();
+            metrics::histogram!("db_pool_acquire_duration_seconds").record(elapsed.as_secs_f64());
+
+            let reason = match acquired {
+                Ok(Ok(_)) if elapsed >= threshold => "slow_acquire",
+                Ok(Ok(_)) if in_use >= max_connections => "exhausted",
+                Ok(Ok(_)) => continue,
+                Ok(Err(_)) | Err(_) => "acquire_failed",
+            };
+            metrics::counter!("db_pool_saturation_total", "reason" => reason).increment(1);
+            tracing::warn!(
+                target: target::SYSTEM,
+                reason,
+                in_use,
+                idle,
+                max_connections,
+                acquire_ms = elapsed.as_millis() as u64,
+                threshold_ms = threshold.as_millis() as u64,
+                "🚰 Database pool under pressure, consider raising DATABASE_MAX_CONNECTIONS"
+            );
+        }
+    });
+}
+
+/// 从 SQL 中提取语句名称，如 `SELECT users`、`UPDATE audit_logs`。
+/// 语句名称只包含操作类型和主表，不包含参数值，可以安全地写入日志和指标标签。
+fn statement_name(sql: &str) -> String {
+    let mut words = sql.split_whitespace();
+    let Some(verb) = words.next().map(str::to_uppercase) else {
+        return "UNKNOWN".to_string();
+    };
+    // 主表出现在各类语句的固定关键字之后：SELECT/DELETE ... FROM t、INSERT INTO t、UPDATE t
+    let keyword = match verb.as_str() {
+        "SELECT" | "DELETE" => "FROM",
+        "INSERT" => "INTO",
+        "UPDATE" => "UPDATE",
+        _ => return verb,
+    };
+    let table = if keyword == "UPDATE" {
+        words.next()
+    } else {
+        words.skip_while(|word| !word.eq_ignore_ascii_case(keyword)).nth(1)
+    };
+    match table {
+        Some(table) => format!("{} {}", verb, table.trim_matches(|c| c == '"' || c == '`' || c == '(')),
+        None => verb,
+    }
+}
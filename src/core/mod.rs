@@ -1,5 +1,19 @@
+pub mod allocator;
+pub mod backup;
+pub mod banner;
+pub mod breaker;
 pub mod config;
 pub mod constants;
 pub mod enums;
 pub mod error;
-pub mod log;
\ No newline at end of file
+pub mod flags;
+pub mod i18n;
+pub mod lanes;
+pub mod log;
+pub mod maintenance;
+pub mod metrics;
+pub mod reporting;
+pub mod scripting;
+pub mod secrets;
+pub mod standby;
+pub mod upgrade;
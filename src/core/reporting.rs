@@ -0,0 +1,58 @@
+// src/core/reporting.rs
+use std::borrow::Cow;
+
+use secrecy::ExposeSecret;
+
+use crate::core::log::target;
+use crate::core::{config::Config, error::AppError};
+
+/// 初始化错误上报客户端。未配置 `SENTRY_DSN` 时不做任何事，`capture` 等调用随之成为空操作。
+///
+/// 启用后同时安装 Sentry 的 panic 钩子（与 `start.rs` 中记录日志的 panic 钩子串联），
+/// 请求中发生的 panic 会带上 `error_reporting::bind_scope` 设置的请求信息。
+///
+/// # 参数
+/// - `config`: 应用程序配置，提供 DSN 和运行环境名称
+///
+/// # 返回值
+/// - `Some(ClientInitGuard)`: 上报客户端，需要保持到进程退出，drop 时会等待未发送的事件
+/// - `None`: 未配置 DSN 或 DSN 无效
+pub fn init(config: &Config) -> Option<sentry::ClientInitGuard> {
+    let dsn = config.sentry_dsn.as_ref()?;
+    let dsn = match dsn.expose_secret().parse::<sentry::types::Dsn>() {
+        Ok(dsn) => dsn,
+        Err(e) => {
+            tracing::warn!(target: target::SYSTEM, "⚠️ Invalid SENTRY_DSN, error reporting disabled: {}", e);
+            return None;
+        }
+    };
+
+    let guard = sentry::init(sentry::ClientOptions {
+        dsn: Some(dsn),
+        release: Some(Cow::Borrowed(env!("CARGO_PKG_VERSION"))),
+        environment: Some(Cow::Owned(config.app_env.to_string())),
+        attach_stacktrace: true,
+        // 不上报请求头、IP 等个人信息，用户只以用户ID标识
+        send_default_pii: false,
+        ..Default::default()
+    });
+    tracing::info!(target: target::SYSTEM, "✅ Error reporting enabled (environment: {}).", config.app_env);
+    Some(guard)
+}
+
+/// 上报导致 5xx 响应的错误。只上报数据库、Redis 和内部错误这类需要排查的原因，
+/// 503（依赖熔断、维护模式）和 504（超时）是预期内的降级，由指标统计。
+pub fn capture(error: &AppError) {
+    if !matches!(
+        error,
+        AppError::DatabaseError(_) | AppError::RedisError(_) | AppError::InternalServerError(_)
+    ) {
+        return;
+    }
+    sentry::capture_error(error);
+}
+
+/// 错误上报是否已启用。未启用时请求中间件跳过作用域的创建。
+pub fn enabled() -> bool {
+    sentry::Hub::current().client().is_some_and(|client| client.is_enabled())
+}
@@ -0,0 +1,209 @@
+// src/core/scripting.rs
+use std::collections::HashMap;
+
+use axum::http::Method;
+use serde_json::Value;
+
+use crate::core::{config::Config, error::AppError, log::target};
+
+// 路由脚本钩子：为指定路由加载 Rhai 脚本，不修改代码即可做轻量的请求补充或响应过滤（如按租户隐藏字段）。
+// 需要以 `scripting` feature 构建，并通过 SCRIPT_HOOKS 配置路由与脚本的对应关系：
+//   SCRIPT_HOOKS={"GET /users/{id}":"scripts/redact_user.rhai"}
+// 路由使用路由表中的路径模板，与指标中的 route 标签一致。
+//
+// 脚本可以定义以下函数，未定义的阶段直接跳过：
+// - `on_request(req)`：`req` 包含 method、path、query、headers、body 和 ctx，返回修改后的 req
+// - `on_response(res)`：`res` 包含 status、headers、body 和 ctx，返回修改后的 res
+// 返回值中只有 headers 和 body 会生效；body 只在原请求体/响应体为 JSON 时可修改。
+// `ctx` 是调用方信息（user_id、role、tenant_id、ext），匿名请求时为空。
+//
+// 沙箱：脚本不能读写文件、导入模块或调用 eval，运算步数、调用深度以及字符串/数组/map 的大小都有上限，
+// 超出时脚本中止。脚本执行失败时请求返回 500，不会放行未经过滤的响应。
+
+/// 脚本钩子的执行阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    /// 处理器执行之前，转换请求
+    Request,
+    /// 处理器执行之后，转换响应
+    Response,
+}
+
+impl HookStage {
+    /// 脚本中对应的函数名
+    pub fn function(&self) -> &'static str {
+        match self {
+            HookStage::Request => "on_request",
+            HookStage::Response => "on_response",
+        }
+    }
+}
+
+/// 单个路由的脚本
+struct RouteScript {
+    /// 脚本文件路径，用于日志
+    path: String,
+    #[cfg(feature = "scripting")]
+    ast: rhai::AST,
+    on_request: bool,
+    on_response: bool,
+}
+
+/// 已加载的路由脚本钩子，启动时编译，按 "方法 路由模板" 查找。
+pub struct ScriptHooks {
+    #[cfg(feature = "scripting")]
+    engine: rhai::Engine,
+    routes: HashMap<String, RouteScript>,
+}
+
+impl ScriptHooks {
+    /// 按配置加载并编译脚本。配置格式错误或脚本无法编译时直接 panic，在启动阶段暴露问题。
+    ///
+    /// # 参数
+    /// - `config`: 应用程序配置
+    ///
+    /// # 返回值
+    /// - `ScriptHooks`: 未配置脚本时为空
+    pub fn from_config(config: &Config) -> Self {
+        let Some(raw) = config.script_hooks.as_deref() else {
+            return Self::empty();
+        };
+        let entries: HashMap<String, String> =
+            serde_json::from_str(raw).expect("❌ SCRIPT_HOOKS must be a JSON object of \"METHOD /route\": \"path\"");
+
+        #[cfg(not(feature = "scripting"))]
+        {
+            if !entries.is_empty() {
+                tracing::warn!(
+                    target: target::SYSTEM,
+                    "⚠️ SCRIPT_HOOKS is configured but this build does not include the `scripting` feature, {} hooks ignored",
+                    entries.len()
+                );
+            }
+            Self::empty()
+        }
+
+        #[cfg(feature = "scripting")]
+        {
+            let engine = sandboxed_engine();
+            let mut routes = HashMap::new();
+            for (route, path) in entries {
+                let key = normalize_key(&route)
+                    .unwrap_or_else(|| panic!("❌ Invalid script hook route `{}`, expected \"METHOD /route\"", route));
+                let ast = engine
+                    .compile_file(path.clone().into())
+                    .unwrap_or_else(|e| panic!("❌ Failed to compile script hook {}: {}", path, e));
+                let defines = |name: &str| ast.iter_functions().any(|f| f.name == name && f.params.len() == 1);
+                let (on_request, on_response) =
+                    (defines(HookStage::Request.function()), defines(HookStage::Response.function()));
+                if !on_request && !on_response {
+                    panic!("❌ Script hook {} defines neither on_request(req) nor on_response(res)", path);
+                }
+                tracing::info!(target: target::SYSTEM, "📜 Script hook loaded: {} -> {}", key, path);
+                routes.insert(key, RouteScript { path, ast, on_request, on_response });
+            }
+            Self { engine, routes }
+        }
+    }
+
+    fn empty() -> Self {
+        Self {
+            #[cfg(feature = "scripting")]
+            engine: sandboxed_engine(),
+            routes: HashMap::new(),
+        }
+    }
+
+    /// 路由是否在指定阶段配置了脚本
+    pub fn has(&self, method: &Method, route: &str, stage: HookStage) -> bool {
+        self.routes
+            .get(&format!("{} {}", method, route))
+            .is_some_and(|script| match stage {
+                HookStage::Request => script.on_request,
+                HookStage::Response => script.on_response,
+            })
+    }
+
+    /// 执行路由在指定阶段的脚本。
+    ///
+    /// # 参数
+    /// - `method`、`route`: 请求方法和路由模板
+    /// - `stage`: 执行阶段
+    /// - `input`: 传给脚本的 req/res 对象
+    ///
+    /// # 返回值
+    /// - `Ok(Value)`: 脚本返回的对象；路由没有配置脚本时原样返回 `input`
+    /// - `Err(AppError)`: 脚本执行失败、超出沙箱限制或返回值不是对象
+    pub fn run(&self, method: &Method, route: &str, stage: HookStage, input: Value) -> Result<Value, AppError> {
+        let Some(script) = self.routes.get(&format!("{} {}", method, route)) else {
+            return Ok(input);
+        };
+
+        #[cfg(not(feature = "scripting"))]
+        {
+            Err(AppError::InternalServerError(format!(
+                "Script hook {} ({}) is not available in this build",
+                script.path,
+                stage.function()
+            )))
+        }
+
+        #[cfg(feature = "scripting")]
+        {
+            let fail = |reason: String| {
+                metrics::counter!("script_hook_failures_total", "route" => route.to_string(), "stage" => stage.function())
+                    .increment(1);
+                tracing::error!(
+                    target: target::SYSTEM,
+                    route = %route,
+                    script = %script.path,
+                    "❌ Script hook {} failed: {}",
+                    stage.function(),
+                    reason
+                );
+                AppError::InternalServerError("Script hook failed".to_string())
+            };
+
+            let argument = rhai::serde::to_dynamic(&input).map_err(|e| fail(e.to_string()))?;
+            let output: rhai::Dynamic = self
+                .engine
+                .call_fn(&mut rhai::Scope::new(), &script.ast, stage.function(), (argument,))
+                .map_err(|e| fail(e.to_string()))?;
+            let output: Value = rhai::serde::from_dynamic(&output).map_err(|e| fail(e.to_string()))?;
+            if !output.is_object() {
+                return Err(fail(format!("{} must return the modified object", stage.function())));
+            }
+            Ok(output)
+        }
+    }
+}
+
+/// 规范化配置中的路由键：方法转为大写，方法和路由之间保留一个空格。
+#[cfg(feature = "scripting")]
+fn normalize_key(route: &str) -> Option<String> {
+    let (method, path) = route.trim().split_once(char::is_whitespace)?;
+    let method = Method::from_bytes(method.to_uppercase().as_bytes()).ok()?;
+    let path = path.trim();
+    path.starts_with('/').then(|| format!("{} {}", method, path))
+}
+
+/// 创建受限的脚本引擎：禁止导入模块和 eval，限制运算步数、调用深度和值的大小，
+/// print/debug 输出转到日志而不是标准输出。
+#[cfg(feature = "scripting")]
+fn sandboxed_engine() -> rhai::Engine {
+    use crate::core::constants::{SCRIPT_MAX_OPERATIONS, SCRIPT_MAX_VALUE_SIZE};
+
+    let mut engine = rhai::Engine::new();
+    engine
+        .set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new())
+        .disable_symbol("eval")
+        .set_max_operations(SCRIPT_MAX_OPERATIONS)
+        .set_max_call_levels(16)
+        .set_max_expr_depths(32, 32)
+        .set_max_string_size(SCRIPT_MAX_VALUE_SIZE)
+        .set_max_array_size(SCRIPT_MAX_VALUE_SIZE)
+        .set_max_map_size(SCRIPT_MAX_VALUE_SIZE)
+        .on_print(|text| tracing::info!(target: target::SYSTEM, "📜 {}", text))
+        .on_debug(|text, _, pos| tracing::debug!(target: target::SYSTEM, "📜 {} ({})", text, pos));
+    engine
+}
@@ -0,0 +1,119 @@
+// src/core/secrets/aws.rs
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use super::SecretsProvider;
+
+const SERVICE: &str = "secretsmanager";
+const TARGET: &str = "secretsmanager.GetSecretValue";
+const CONTENT_TYPE: &str = "application/x-amz-json-1.1";
+
+/// AWS Secrets Manager。密钥值（`SecretString`）为 JSON 对象，如 `{"database_url": "...", "jwt_secret": "..."}`。
+/// 直接调用 `GetSecretValue` 接口并使用 Signature V4 签名，凭据来自静态访问密钥（可带会话令牌）。
+pub struct AwsSecretsManager {
+    pub(super) client: reqwest::Client,
+    pub(super) region: String,
+    pub(super) secret_id: String,
+    pub(super) access_key_id: String,
+    pub(super) secret_access_key: SecretString,
+    pub(super) session_token: Option<SecretString>,
+}
+
+impl AwsSecretsManager {
+    /// 按 Signature V4 计算 `Authorization` 头。
+    ///
+    /// # 参数
+    /// - `headers`: 参与签名的请求头，名称为小写并按名称排序
+    /// - `payload`: 请求体
+    /// - `amz_date`: 请求时间，格式为 `YYYYMMDDTHHMMSSZ`
+    fn authorization(&self, headers: &[(&str, &str)], payload: &str, amz_date: &str) -> String {
+        let date = &amz_date[..8];
+        let scope = format!("{date}/{}/{SERVICE}/aws4_request", self.region);
+
+        // 第一步：规范请求
+        let canonical_headers: String = headers.iter().map(|(name, value)| format!("{name}:{}\n", value.trim())).collect();
+        let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+        let canonical_request = format!(
+            "POST\n/\n\n{canonical_headers}\n{signed_headers}\n{}",
+            hex::encode(Sha256::digest(payload.as_bytes()))
+        );
+
+        // 第二步：待签名字符串
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        // 第三步：逐级派生签名密钥并签名
+        let secret = format!("AWS4{}", self.secret_access_key.expose_secret());
+        let key = [date, self.region.as_str(), SERVICE, "aws4_request"]
+            .iter()
+            .fold(secret.into_bytes(), |key, part| hmac_sha256(&key, part.as_bytes()));
+        let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            self.access_key_id
+        )
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[async_trait]
+impl SecretsProvider for AwsSecretsManager {
+    fn name(&self) -> &'static str {
+        "aws"
+    }
+
+    async fn fetch(&self) -> Result<HashMap<String, String>, String> {
+        let host = format!("{SERVICE}.{}.amazonaws.com", self.region);
+        let payload = json!({ "SecretId": self.secret_id }).to_string();
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+        let mut headers = vec![("content-type", CONTENT_TYPE), ("host", host.as_str()), ("x-amz-date", amz_date.as_str())];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.expose_secret()));
+        }
+        headers.push(("x-amz-target", TARGET));
+        let authorization = self.authorization(&headers, &payload, &amz_date);
+
+        let mut request = self.client.post(format!("https://{host}/")).body(payload);
+        // host 由客户端根据URL自动设置
+        for (name, value) in headers.iter().filter(|(name, _)| *name != "host") {
+            request = request.header(*name, *value);
+        }
+        let response = request
+            .header("authorization", authorization)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        let status = response.status();
+        let body = response.text().await.map_err(|e| e.to_string())?;
+        if !status.is_success() {
+            return Err(format!("GetSecretValue returned {status}: {body}"));
+        }
+
+        // 响应中的 SecretString 本身是一个 JSON 字符串
+        let body: Value = serde_json::from_str(&body).map_err(|e| format!("invalid response: {e}"))?;
+        let secret = body["SecretString"]
+            .as_str()
+            .ok_or_else(|| format!("secret {} has no SecretString", self.secret_id))?;
+        let values: HashMap<String, Value> =
+            serde_json::from_str(secret).map_err(|e| format!("secret {} is not a JSON object: {e}", self.secret_id))?;
+
+        Ok(values
+            .into_iter()
+            .filter_map(|(key, value)| value.as_str().map(|value| (key, value.to_string())))
+            .collect())
+    }
+}
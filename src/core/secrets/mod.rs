@@ -0,0 +1,204 @@
+// src/core/secrets/mod.rs
+use async_trait::async_trait;
+use sea_orm::DatabaseConnection;
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use crate::core::log::target;
+use crate::core::{config::Config, constants::SECRETS_FETCH_TIMEOUT, enums::SecretsBackend};
+
+mod aws;
+mod vault;
+
+pub use aws::AwsSecretsManager;
+pub use vault::VaultProvider;
+
+// 外部密钥管理：数据库连接串、Redis 连接串和 JWT 密钥可以存放在 Vault 或 AWS Secrets Manager 中，
+// 启动时读取并覆盖环境变量中的值；密钥管理中没有的项仍使用环境变量（或 .env），便于逐步迁移。
+// 数据库凭据轮换后，后台任务定期重新读取，新的用户名和密码用于连接池之后建立的连接。
+
+/// 可以从密钥管理读取的配置项（配置字段名）。密钥管理中的键名不区分大小写，如 `DATABASE_URL` 或 `database_url`。
+pub const SECRET_KEYS: &[&str] = &["database_url", "redis_url", "jwt_secret"];
+
+/// 外部密钥管理后端。实现方只负责读取一组键值，选择哪些键、如何覆盖配置由 `Config` 处理。
+#[async_trait]
+pub trait SecretsProvider: Send + Sync {
+    /// 后端名称，用于日志和错误信息
+    fn name(&self) -> &'static str;
+
+    /// 读取全部键值
+    async fn fetch(&self) -> Result<HashMap<String, String>, String>;
+}
+
+/// 密钥管理的连接参数，与 `Config` 从同样的配置文件和环境变量中读取，
+/// 在解析其余配置之前单独加载（其余配置中的必填项可能正来自密钥管理）。
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SecretsSettings {
+    /// 密钥管理后端：vault 或 aws。未设置时只使用环境变量
+    #[serde(default)]
+    pub secrets_backend: Option<SecretsBackend>,
+
+    /// Vault 服务地址，如 https://vault.internal:8200
+    #[serde(default)]
+    pub vault_addr: Option<String>,
+
+    /// Vault 访问令牌（敏感信息）
+    #[serde(default)]
+    pub vault_token: Option<SecretString>,
+
+    /// Vault 中的密钥路径。KV v2 引擎的路径包含 `data/`，如 `secret/data/app`
+    #[serde(default = "default_vault_secret_path")]
+    pub vault_secret_path: String,
+
+    /// AWS 区域，如 us-east-1
+    #[serde(default)]
+    pub aws_region: Option<String>,
+
+    /// AWS Secrets Manager 中的密钥名称或 ARN，密钥值为 JSON 对象
+    #[serde(default)]
+    pub aws_secret_id: Option<String>,
+
+    /// AWS 访问凭据，读取标准的 `AWS_ACCESS_KEY_ID` / `AWS_SECRET_ACCESS_KEY` / `AWS_SESSION_TOKEN`
+    #[serde(default)]
+    pub aws_access_key_id: Option<String>,
+    #[serde(default)]
+    pub aws_secret_access_key: Option<SecretString>,
+    #[serde(default)]
+    pub aws_session_token: Option<SecretString>,
+
+    /// 重新读取数据库凭据的间隔，可写成 `5m` 这样的时长（不带单位时为秒），0 表示不重新读取。默认 5 分钟
+    #[serde(default = "default_secrets_refresh_interval", alias = "secrets_refresh_interval_secs", deserialize_with = "crate::core::config::duration_secs")]
+    pub secrets_refresh_interval: Duration,
+}
+
+fn default_vault_secret_path() -> String {
+    "secret/data/app".to_string()
+}
+
+fn default_secrets_refresh_interval() -> Duration {
+    Duration::from_secs(300)
+}
+
+impl SecretsSettings {
+    /// 按配置创建密钥管理后端。
+    ///
+    /// # 返回值
+    /// - `Ok(Some(provider))`: 已配置后端
+    /// - `Ok(None)`: 未配置后端，只使用环境变量
+    /// - `Err(String)`: 后端缺少必要的参数
+    pub fn provider(&self) -> Result<Option<Box<dyn SecretsProvider>>, String> {
+        let Some(backend) = self.secrets_backend else {
+            return Ok(None);
+        };
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(SECRETS_FETCH_TIMEOUT))
+            .build()
+            .map_err(|e| format!("Failed to build secrets client: {e}"))?;
+
+        match backend {
+            SecretsBackend::Vault => {
+                let (Some(addr), Some(token)) = (&self.vault_addr, &self.vault_token) else {
+                    return Err("SECRETS_BACKEND=vault requires VAULT_ADDR and VAULT_TOKEN".to_string());
+                };
+                Ok(Some(Box::new(VaultProvider::new(client, addr, token.clone(), &self.vault_secret_path))))
+            }
+            SecretsBackend::Aws => {
+                let (Some(region), Some(secret_id), Some(access_key_id), Some(secret_access_key)) = (
+                    &self.aws_region,
+                    &self.aws_secret_id,
+                    &self.aws_access_key_id,
+                    &self.aws_secret_access_key,
+                ) else {
+                    return Err(
+                        "SECRETS_BACKEND=aws requires AWS_REGION, AWS_SECRET_ID, AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY"
+                            .to_string(),
+                    );
+                };
+                Ok(Some(Box::new(AwsSecretsManager {
+                    client,
+                    region: region.clone(),
+                    secret_id: secret_id.clone(),
+                    access_key_id: access_key_id.clone(),
+                    secret_access_key: secret_access_key.clone(),
+                    session_token: self.aws_session_token.clone(),
+                })))
+            }
+        }
+    }
+}
+
+/// 从密钥管理读取 `SECRET_KEYS` 中的配置项，忽略其他键。
+///
+/// # 返回值
+/// - `Ok(HashMap)`: 配置字段名 -> 值，只包含密钥管理中存在的项
+/// - `Err(String)`: 读取失败
+pub async fn resolve(provider: &dyn SecretsProvider) -> Result<HashMap<&'static str, String>, String> {
+    let values = provider.fetch().await.map_err(|e| format!("Failed to read secrets from {}: {e}", provider.name()))?;
+    metrics::counter!("secrets_fetch_total", "backend" => provider.name()).increment(1);
+
+    Ok(values
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let key = key.to_lowercase();
+            SECRET_KEYS.iter().find(|name| **name == key).map(|name| (*name, value))
+        })
+        .collect())
+}
+
+/// 启动后台任务，定期从密钥管理重新读取数据库连接串。凭据变化时替换连接池的用户名和密码，
+/// 之后新建的连接使用新凭据，已有连接在回收前继续使用旧凭据（轮换时旧凭据应保留一段宽限期）。
+/// 主机、端口、库名的变化以及 Redis 连接串、JWT 密钥的轮换需要重启。
+pub fn spawn_rotation(config: Arc<Config>, db: DatabaseConnection) {
+    let interval = config.secrets.secrets_refresh_interval;
+    let provider = match config.secrets.provider() {
+        Ok(Some(provider)) if !interval.is_zero() => provider,
+        _ => return,
+    };
+
+    tokio::spawn(async move {
+        let mut current = config.database_url.expose_secret().to_string();
+        let mut ticker = tokio::time::interval(interval);
+        // 第一次 tick 立即返回，启动时刚读取过，跳过
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+
+            let database_url = match resolve(provider.as_ref()).await {
+                Ok(mut values) => values.remove("database_url"),
+                Err(e) => {
+                    tracing::warn!(target: target::SYSTEM, "⚠️ {}", e);
+                    continue;
+                }
+            };
+            let Some(database_url) = database_url.filter(|url| *url != current) else {
+                continue;
+            };
+
+            match rotate_credentials(&db, &database_url) {
+                Ok(()) => {
+                    tracing::info!(target: target::SYSTEM, "🔑 Database credentials rotated from {}", provider.name());
+                    current = database_url;
+                }
+                Err(e) => {
+                    tracing::error!(target: target::SYSTEM, "❌ Failed to apply rotated database credentials: {}", e);
+                }
+            }
+        }
+    });
+}
+
+/// 把新连接串中的用户名和密码应用到连接池，其余连接参数（语句超时等）保持不变。
+fn rotate_credentials(db: &DatabaseConnection, database_url: &str) -> Result<(), String> {
+    let url = reqwest::Url::parse(database_url).map_err(|e| format!("invalid database URL: {e}"))?;
+    let decode = |value: &str| percent_encoding::percent_decode_str(value).decode_utf8_lossy().into_owned();
+
+    let pool = db.get_postgres_connection_pool();
+    let mut options = (*pool.connect_options()).clone().username(&decode(url.username()));
+    if let Some(password) = url.password() {
+        options = options.password(&decode(password));
+    }
+    pool.set_connect_options(options);
+    Ok(())
+}
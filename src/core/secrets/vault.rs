@@ -0,0 +1,65 @@
+// src/core/secrets/vault.rs
+use async_trait::async_trait;
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::SecretsProvider;
+
+/// HashiCorp Vault 的 KV 密钥引擎。同时支持 KV v1（值在 `data` 下）和 KV v2（值在 `data.data` 下）。
+pub struct VaultProvider {
+    client: reqwest::Client,
+    url: String,
+    token: SecretString,
+}
+
+impl VaultProvider {
+    /// # 参数
+    /// - `addr`: Vault 服务地址，如 https://vault.internal:8200
+    /// - `token`: 访问令牌，需要对 `path` 有读取权限
+    /// - `path`: 密钥路径，KV v2 引擎包含 `data/`，如 `secret/data/app`
+    pub fn new(client: reqwest::Client, addr: &str, token: SecretString, path: &str) -> Self {
+        Self {
+            client,
+            url: format!("{}/v1/{}", addr.trim_end_matches('/'), path.trim_start_matches('/')),
+            token,
+        }
+    }
+}
+
+#[async_trait]
+impl SecretsProvider for VaultProvider {
+    fn name(&self) -> &'static str {
+        "vault"
+    }
+
+    async fn fetch(&self) -> Result<HashMap<String, String>, String> {
+        let response = self
+            .client
+            .get(&self.url)
+            .header("X-Vault-Token", self.token.expose_secret())
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        let status = response.status();
+        let body = response.text().await.map_err(|e| e.to_string())?;
+        if !status.is_success() {
+            return Err(format!("{} returned {}", self.url, status));
+        }
+
+        let body: Value = serde_json::from_str(&body).map_err(|e| format!("invalid response: {e}"))?;
+        let data = match &body["data"]["data"] {
+            Value::Object(_) => &body["data"]["data"],
+            _ => &body["data"],
+        };
+        let Value::Object(values) = data else {
+            return Err(format!("{} has no secret data", self.url));
+        };
+
+        // 只保留字符串值，其他类型的值不可能是连接串或密钥
+        Ok(values
+            .iter()
+            .filter_map(|(key, value)| value.as_str().map(|value| (key.clone(), value.to_string())))
+            .collect())
+    }
+}
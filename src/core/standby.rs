@@ -0,0 +1,184 @@
+// src/core/standby.rs
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use redis::aio::ConnectionManager;
+use serde::Serialize;
+use tokio::{sync::watch, time::Instant};
+
+use crate::core::log::target;
+use crate::{
+    core::{
+        config::Config,
+        constants::{
+            REDIS_KEY_STANDBY_PRIMARY, STANDBY_LOCK_TTL, STANDBY_PROBE_INTERVAL, STANDBY_PROBE_TIMEOUT,
+            STANDBY_TAKEOVER_THRESHOLD,
+        },
+    },
+    utils::lock::DistributedLock,
+};
+
+// 双节点主备协调：不依赖外部编排系统，两个实例通过 Redis 中的主节点锁选出主节点。
+// - 主节点定期续期锁，续期失败（锁被接管）或 Redis 不可用超过锁的有效期时降为备节点
+// - 备节点定期探测对端的 `/healthz`，锁空闲时直接获取；对端连续 `STANDBY_TAKEOVER_THRESHOLD` 次
+//   探测失败时强制接管，即使对端仍在续期（如进程存活但已无法处理请求）
+// 只应在主节点上执行的工作（定时任务、单实例消费者等）通过 `is_primary` 判断，或 `subscribe` 监听角色切换。
+
+/// 实例在主备部署中的角色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Primary,
+    Standby,
+}
+
+/// 主备状态，在 `/health` 中返回
+#[derive(Debug, Serialize)]
+pub struct StandbyStatus {
+    pub role: Role,
+    /// 当前实例的锁令牌，与 Redis 中的 `standby:primary` 对照即可确认主节点
+    pub instance: String,
+    pub peer_url: String,
+    /// 最近一次对端探测是否成功
+    pub peer_healthy: bool,
+}
+
+/// 主备协调器。由 `spawn` 启动后台任务维护角色，其他模块通过 `is_primary` / `subscribe` 读取。
+pub struct Standby {
+    lock: DistributedLock,
+    peer_url: String,
+    role: watch::Sender<Role>,
+    peer_healthy: AtomicBool,
+}
+
+impl Standby {
+    /// 按配置创建协调器。未配置 `STANDBY_PEER_URL` 时返回 `None`，实例总是按主节点运行。
+    pub fn from_config(config: &Config) -> Option<Arc<Self>> {
+        let peer_url = config.standby_peer_url.as_deref()?.trim_end_matches('/').to_string();
+        let (role, _) = watch::channel(Role::Standby);
+        Some(Arc::new(Self {
+            lock: DistributedLock::new(REDIS_KEY_STANDBY_PRIMARY, Duration::from_secs(STANDBY_LOCK_TTL)),
+            peer_url,
+            role,
+            peer_healthy: AtomicBool::new(false),
+        }))
+    }
+
+    /// 当前实例是否为主节点
+    pub fn is_primary(&self) -> bool {
+        *self.role.borrow() == Role::Primary
+    }
+
+    /// 订阅角色变化。接管信号：备节点成为主节点时，接收方会收到 `Role::Primary`。
+    #[allow(dead_code)]
+    pub fn subscribe(&self) -> watch::Receiver<Role> {
+        self.role.subscribe()
+    }
+
+    pub fn status(&self) -> StandbyStatus {
+        StandbyStatus {
+            role: *self.role.borrow(),
+            instance: self.lock.token().to_string(),
+            peer_url: self.peer_url.clone(),
+            peer_healthy: self.peer_healthy.load(Ordering::Relaxed),
+        }
+    }
+
+    /// 启动后台任务：探测对端并维护主节点锁。
+    pub fn spawn(self: Arc<Self>, redis: ConnectionManager) {
+        tokio::spawn(async move {
+            let client = reqwest::Client::builder()
+                .timeout(Duration::from_secs(STANDBY_PROBE_TIMEOUT))
+                .build()
+                .expect("❌ Failed to build standby probe client");
+            let mut ticker = tokio::time::interval(Duration::from_secs(STANDBY_PROBE_INTERVAL));
+            let mut peer_failures: u32 = 0;
+            let mut last_renewed = Instant::now();
+
+            loop {
+                ticker.tick().await;
+
+                // 第一步：探测对端
+                let peer_up = self.probe_peer(&client).await;
+                self.peer_healthy.store(peer_up, Ordering::Relaxed);
+                peer_failures = if peer_up { 0 } else { peer_failures.saturating_add(1) };
+
+                // 第二步：主节点续期；备节点在锁空闲时获取，对端持续不可用时强制接管
+                let role = if self.is_primary() {
+                    match self.lock.renew(&redis).await {
+                        Ok(true) => {
+                            last_renewed = Instant::now();
+                            Role::Primary
+                        }
+                        Ok(false) => Role::Standby,
+                        // Redis 不可用时无法确认锁的归属，超过锁的有效期后对端可能已经取得锁，必须降级
+                        Err(e) if last_renewed.elapsed() >= Duration::from_secs(STANDBY_LOCK_TTL) => {
+                            tracing::warn!(target: target::SYSTEM, "⚠️ Primary lock renewal failed, stepping down: {}", e);
+                            Role::Standby
+                        }
+                        Err(e) => {
+                            tracing::warn!(target: target::SYSTEM, "⚠️ Primary lock renewal failed: {}", e);
+                            Role::Primary
+                        }
+                    }
+                } else if peer_failures >= STANDBY_TAKEOVER_THRESHOLD {
+                    tracing::warn!(
+                        target: target::SYSTEM,
+                        "🚨 Peer {} failed {} health probes, taking over",
+                        self.peer_url,
+                        peer_failures
+                    );
+                    match self.lock.take_over(&redis).await {
+                        Ok(()) => {
+                            last_renewed = Instant::now();
+                            Role::Primary
+                        }
+                        Err(e) => {
+                            tracing::warn!(target: target::SYSTEM, "⚠️ Takeover failed: {}", e);
+                            Role::Standby
+                        }
+                    }
+                } else {
+                    match self.lock.try_acquire(&redis).await {
+                        Ok(true) => {
+                            last_renewed = Instant::now();
+                            Role::Primary
+                        }
+                        Ok(false) => Role::Standby,
+                        Err(e) => {
+                            tracing::warn!(target: target::SYSTEM, "⚠️ Failed to acquire primary lock: {}", e);
+                            Role::Standby
+                        }
+                    }
+                };
+
+                // 第三步：角色变化时通知订阅方
+                let changed = self.role.send_if_modified(|current| {
+                    let changed = *current != role;
+                    *current = role;
+                    changed
+                });
+                if changed {
+                    tracing::info!(target: target::SYSTEM, "🔁 Standby role changed: {:?}", role);
+                }
+                metrics::gauge!("standby_is_primary").set(if role == Role::Primary { 1.0 } else { 0.0 });
+            }
+        });
+    }
+
+    /// 探测对端的存活探针，返回 200 视为健康
+    async fn probe_peer(&self, client: &reqwest::Client) -> bool {
+        match client.get(format!("{}/healthz", self.peer_url)).send().await {
+            Ok(response) => response.status().is_success(),
+            Err(e) => {
+                tracing::debug!(target: target::SYSTEM, "⚠️ Peer probe failed: {}", e);
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+// src/core/upgrade.rs
+use std::{io, net::SocketAddr};
+
+use tokio::net::TcpListener;
+
+// 平滑升级（不依赖 systemd）：旧进程收到 SIGUSR2 后启动新的可执行文件，并通过环境变量
+// 把监听套接字的文件描述符交给它。新进程直接在继承的套接字上接受连接，旧进程等待新进程
+// 完成启动后再进入优雅关闭。整个过程中监听套接字始终处于打开状态，内核队列中的连接不会丢失。
+//
+// 升级步骤：替换磁盘上的可执行文件，然后执行 `kill -USR2 <pid>`。
+
+/// 传递被继承的监听套接字文件描述符的环境变量
+pub const ENV_INHERITED_FD: &str = "UPGRADE_LISTEN_FD";
+
+/// 创建HTTP监听器。如果当前进程由平滑升级启动，直接使用旧进程交接的套接字，否则绑定新地址。
+///
+/// # 参数
+/// - `addr`: 未继承套接字时绑定的监听地址
+pub async fn bind(addr: SocketAddr) -> io::Result<TcpListener> {
+    #[cfg(unix)]
+    if let Some(listener) = unix::inherited()? {
+        return Ok(listener);
+    }
+
+    TcpListener::bind(addr).await
+}
+
+#[cfg(unix)]
+pub use unix::hand_over;
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        io,
+        os::fd::{FromRawFd, RawFd},
+        process::Command,
+        time::Duration,
+    };
+
+    use tokio::net::TcpListener;
+
+    use super::ENV_INHERITED_FD;
+    use crate::core::{constants::UPGRADE_HANDOVER_DELAY, log::target};
+
+    /// 读取旧进程交接的监听套接字
+    pub(super) fn inherited() -> io::Result<Option<TcpListener>> {
+        let Ok(value) = std::env::var(ENV_INHERITED_FD) else {
+            return Ok(None);
+        };
+        let fd: RawFd = value.parse().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("invalid {}: {}", ENV_INHERITED_FD, value))
+        })?;
+
+        // SAFETY: 文件描述符由旧进程在 exec 前显式保留，且只在这里被接管一次
+        let listener = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+        listener.set_nonblocking(true)?;
+        // 重新设置 CLOEXEC，避免本进程启动的其他子进程继承监听套接字
+        set_cloexec(fd, true)?;
+
+        tracing::info!(target: target::SYSTEM, "♻️ Inherited listening socket from previous process (fd {})", fd);
+        TcpListener::from_std(listener).map(Some)
+    }
+
+    /// 启动新进程并交接监听套接字，等待新进程完成启动。
+    ///
+    /// # 参数
+    /// - `fd`: 当前进程监听套接字的文件描述符
+    ///
+    /// # 返回值
+    /// - `true`: 新进程已接管，当前进程可以进入优雅关闭
+    /// - `false`: 升级失败（无法启动或新进程启动后立即退出），当前进程应继续服务
+    pub async fn hand_over(fd: RawFd) -> bool {
+        let spawned = spawn_successor(fd);
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => {
+                tracing::error!(target: target::SYSTEM, "❌ Upgrade failed, could not start new process: {}", e);
+                return false;
+            }
+        };
+        tracing::info!(target: target::SYSTEM, "♻️ Upgrade started, new process pid {}", child.id());
+
+        // 新进程启动期间（连接数据库、Redis 等）旧进程继续处理请求
+        tokio::time::sleep(Duration::from_secs(UPGRADE_HANDOVER_DELAY)).await;
+
+        match child.try_wait() {
+            Ok(None) => {
+                tracing::info!(target: target::SYSTEM, "✅ New process {} took over the listener", child.id());
+                true
+            }
+            Ok(Some(status)) => {
+                tracing::error!(target: target::SYSTEM, "❌ Upgrade aborted, new process exited early: {}", status);
+                false
+            }
+            Err(e) => {
+                tracing::error!(target: target::SYSTEM, "❌ Upgrade aborted, failed to check new process: {}", e);
+                false
+            }
+        }
+    }
+
+    /// 以相同的参数启动当前可执行文件（已被替换为新版本），临时清除 CLOEXEC 使监听套接字被继承。
+    fn spawn_successor(fd: RawFd) -> io::Result<std::process::Child> {
+        let exe = std::env::current_exe()?;
+
+        set_cloexec(fd, false)?;
+        let result = Command::new(exe)
+            .args(std::env::args_os().skip(1))
+            .env(ENV_INHERITED_FD, fd.to_string())
+            .spawn();
+        set_cloexec(fd, true)?;
+
+        result
+    }
+
+    fn set_cloexec(fd: RawFd, enabled: bool) -> io::Result<()> {
+        // SAFETY: 只读取和修改文件描述符标记，不涉及内存访问
+        unsafe {
+            let flags = libc::fcntl(fd, libc::F_GETFD);
+            if flags < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let flags = if enabled { flags | libc::FD_CLOEXEC } else { flags & !libc::FD_CLOEXEC };
+            if libc::fcntl(fd, libc::F_SETFD, flags) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+// src/dtos/admin.rs
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::core::enums::UserRole;
+
+/// 批量操作类型。通过 `action` 字段区分，角色变更需要额外提供 `role` 字段。
+/// 同时接受 `disable` / `assign_role` 两个别名。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BulkAction {
+    /// 禁用账户
+    #[serde(alias = "disable")]
+    Deactivate,
+    /// 删除账户
+    Delete,
+    /// 变更角色
+    #[serde(alias = "assign_role")]
+    RoleChange { role: UserRole },
+}
+
+/// 批量用户操作请求
+#[derive(Debug, Deserialize, Validate)]
+pub struct BulkUserRequest {
+    #[validate(length(min = 1, max = 500, message = "User ids must contain 1-500 items"))]
+    pub user_ids: Vec<Uuid>,
+
+    #[serde(flatten)]
+    pub action: BulkAction,
+
+    /// 试运行：执行全部校验并返回逐项结果，但回滚事务，不产生任何实际变更
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// 单个用户的操作结果
+#[derive(Debug, Serialize)]
+pub struct BulkItemResult {
+    pub user_id: Uuid,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// 变更已提交但后续处理失败（如吊销会话时 Redis 不可用）时的说明
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    /// 变更内容，仅用于写入审计日志，不返回给调用方
+    #[serde(skip)]
+    pub diff: Option<serde_json::Value>,
+}
+
+/// 批量操作的汇总结果
+#[derive(Debug, Serialize)]
+pub struct BulkResult {
+    pub action: BulkAction,
+    pub dry_run: bool,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BulkItemResult>,
+}
+
+/// 维护模式开关请求
+#[derive(Debug, Deserialize, Validate)]
+pub struct MaintenanceRequest {
+    pub enabled: bool,
+
+    /// 维护期间返回给客户端的提示，未提供时使用默认提示
+    #[validate(length(max = 200, message = "Message must be at most 200 characters"))]
+    pub message: Option<String>,
+}
+
+/// 系统开关覆盖请求
+#[derive(Debug, Deserialize)]
+pub struct SetFlagRequest {
+    pub enabled: bool,
+}
+
+/// 请求统计的查询参数
+#[derive(Debug, Deserialize, Validate)]
+pub struct StatsQuery {
+    /// 统计最近多少分钟的请求，默认15分钟，最多为统计数据的保留时长
+    #[serde(default = "default_stats_minutes")]
+    #[validate(range(min = 1, max = 60, message = "Minutes must be between 1 and 60"))]
+    pub minutes: u64,
+}
+
+fn default_stats_minutes() -> u64 {
+    15
+}
+
+/// 请求统计汇总
+#[derive(Debug, Serialize)]
+pub struct StatsOverview {
+    pub minutes: u64,
+    pub requests: u64,
+    /// 5xx 响应数
+    pub errors: u64,
+    /// 5xx 响应占比（0.0 ~ 1.0）
+    pub error_rate: f64,
+    /// 统计时间内发起过请求的已登录用户数（HyperLogLog 估算值）
+    pub active_users: u64,
+    /// 按请求数倒序排列的路由统计
+    pub routes: Vec<RouteStats>,
+    /// 请求数最多的用户
+    pub top_users: Vec<UserStats>,
+}
+
+/// 单个路由的请求统计，路由为 "方法 路由模板"，如 "GET /admin/users/{id}/history"
+#[derive(Debug, Serialize)]
+pub struct RouteStats {
+    pub route: String,
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate: f64,
+}
+
+/// 单个用户的请求统计
+#[derive(Debug, Serialize)]
+pub struct UserStats {
+    pub user_id: String,
+    pub requests: u64,
+}
+
+/// 匿名使用统计的查询参数
+#[derive(Debug, Deserialize, Validate)]
+pub struct AnalyticsQuery {
+    /// 查询最近多少天（包含今天），默认7天，最多为日报的保留天数
+    #[serde(default = "default_analytics_days")]
+    #[validate(range(min = 1, max = 90, message = "Days must be between 1 and 90"))]
+    pub days: i64,
+}
+
+fn default_analytics_days() -> i64 {
+    7
+}
+
+/// 一天的匿名使用统计。涉及的用户数少于 k 的统计项已被隐藏，不包含任何用户级别的数据。
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DailyAnalytics {
+    /// 日期（UTC），如 "2024-01-31"
+    pub date: String,
+    /// 生成时使用的 k-匿名阈值
+    pub k: u64,
+    /// 日活跃用户数（HyperLogLog 估算值），少于 k 时为 `None`
+    pub dau: Option<u64>,
+    /// 当天注册的用户数，少于 k 时为 `None`
+    pub signups: Option<u64>,
+    /// 按请求数倒序排列的接口热度，只包含至少 k 个不同调用方访问过的接口
+    pub routes: Vec<RoutePopularity>,
+    /// 因调用方少于 k 而被隐藏的接口数量
+    pub suppressed_routes: u64,
+    /// 生成时间。当天的统计每小时更新，次日完成最终聚合
+    pub generated_at: String,
+}
+
+/// 单个接口的热度，路由为 "方法 路由模板"
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoutePopularity {
+    pub route: String,
+    pub requests: u64,
+}
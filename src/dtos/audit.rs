@@ -0,0 +1,98 @@
+// src/dtos/audit.rs
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{core::enums::AuditAction, entity::audit_logs};
+
+/// 审计日志的查询过滤条件，所有字段都是可选的。
+#[derive(Debug, Deserialize)]
+pub struct AuditLogFilter {
+    pub action: Option<AuditAction>,
+    pub actor_id: Option<Uuid>,
+    pub target_id: Option<Uuid>,
+}
+
+/// 审计日志条目，返回给管理端。
+#[derive(Debug, Serialize)]
+pub struct AuditLogItem {
+    pub id: String,
+    pub actor_id: Option<String>,
+    pub action: AuditAction,
+    pub target_id: Option<String>,
+    pub ip: Option<String>,
+    pub diff: Option<serde_json::Value>,
+    pub created_at: String,
+}
+
+impl From<audit_logs::Model> for AuditLogItem {
+    fn from(log: audit_logs::Model) -> Self {
+        Self {
+            id: log.id.to_string(),
+            actor_id: log.actor_id.map(|id| id.to_string()),
+            action: log.action,
+            target_id: log.target_id.map(|id| id.to_string()),
+            ip: log.ip,
+            diff: log.diff,
+            created_at: log.created_at.to_string(),
+        }
+    }
+}
+
+/// 用户变更历史中的一条记录：谁在什么时候对该用户做了哪些变更。
+#[derive(Debug, Serialize)]
+pub struct UserHistoryEntry {
+    pub id: String,
+    pub action: AuditAction,
+    pub actor_id: Option<String>,
+    /// 操作者的用户名，操作者账户已删除或系统操作时为空
+    pub actor_username: Option<String>,
+    pub ip: Option<String>,
+    pub changes: Vec<FieldChange>,
+    pub created_at: String,
+}
+
+/// 单个字段的变更。审计记录只保存了新值时 `from` 为空；密码等敏感字段的值被替换为 `[REDACTED]`。
+#[derive(Debug, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: Option<serde_json::Value>,
+    pub to: Option<serde_json::Value>,
+}
+
+/// 审计日志导出参数，与 `AuditLogFilter` 一起使用。
+#[derive(Debug, Deserialize)]
+pub struct AuditExportQuery {
+    /// 只导出该时间之后（含）的记录
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// 只导出该时间之前的记录
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    /// 是否使用配置的 AES-256-GCM 密钥加密导出内容
+    #[serde(default)]
+    pub encrypt: bool,
+}
+
+/// 导出内容的第一行，说明导出范围。与审计记录一起被签名（加密时一起被加密）。
+#[derive(Debug, Serialize)]
+pub struct AuditExportHeader {
+    pub exported_at: String,
+    pub exported_by: String,
+    pub count: usize,
+    pub filter: serde_json::Value,
+}
+
+/// 签名的审计日志导出文件。
+///
+/// `payload` 为 Base64 编码的 NDJSON（第一行为 `AuditExportHeader`，其后每行一条审计记录）；
+/// 加密时为 `nonce(12字节) || 密文`。`signature` 是对 `payload` 解码后原始字节的 Ed25519 签名，
+/// 校验签名不需要解密密钥。
+#[derive(Debug, Serialize)]
+pub struct AuditExportArchive {
+    pub version: u8,
+    pub signature_algorithm: &'static str,
+    /// Base64 编码的签名公钥，合规方应与线下分发的公钥比对
+    pub public_key: String,
+    pub signature: String,
+    /// 加密算法，未加密时为空
+    pub encryption: Option<&'static str>,
+    pub payload: String,
+}
@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 use validator::Validate;
 
+use crate::core::enums::Permission;
+
 #[derive(Deserialize, Validate)]
 pub struct RegisterRequest {
     #[validate(length(min = 3, message = "Username must be at least 3 characters"))]
@@ -26,16 +30,72 @@ pub struct RefreshRequest {
     pub refresh_token: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
     pub username: String,
     pub role: String,
     pub exp: usize,
+    /// 签发时间（Unix 秒），用于判断令牌是否在用户被强制下线之前签发
+    #[serde(default)]
+    pub iat: usize,
+    /// 签发时用户的令牌版本。管理员变更角色或禁用账户时版本递增，旧版本的令牌需要刷新后才能继续使用
+    #[serde(default)]
+    pub ver: u64,
+    /// 自定义扩展声明（如租户ID、套餐等级、功能授权），由 `ClaimsBuilder` 在签发令牌时填充
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub ext: HashMap<String, Value>,
+    /// 委托令牌中实际执行操作的用户（RFC 8693 `act` 声明）。此时 `sub` 是授权人，
+    /// 令牌只能在 `act.scopes` 范围内访问授权人的资源；普通令牌没有该声明
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>,
 }
 
-#[derive(Serialize)]
+/// 委托令牌的实际操作者
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorClaim {
+    /// 被授权人的用户ID
+    pub sub: String,
+    pub username: String,
+    /// 委托授权ID，授权被撤销后据此拒绝已签发的委托令牌
+    pub grant: String,
+    /// 允许的操作范围
+    pub scopes: Vec<Permission>,
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct LoginResponse {
     pub access_token: String,
     pub refresh_token: String,
-}
\ No newline at end of file
+}
+
+/// 设备授权请求的响应（RFC 8628 第 3.2 节）
+#[derive(Serialize)]
+pub struct DeviceCodeResponse {
+    /// 设备端轮询时使用的设备码，不展示给用户
+    pub device_code: String,
+    /// 展示给用户的短码，格式如 "BCDF-GHJK"
+    pub user_code: String,
+    /// 用户确认授权的页面地址
+    pub verification_uri: String,
+    /// 附带用户码的确认地址，可生成二维码
+    pub verification_uri_complete: String,
+    /// 有效期（秒）
+    pub expires_in: u64,
+    /// 最小轮询间隔（秒）
+    pub interval: u64,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct DeviceTokenRequest {
+    #[validate(length(min = 1, message = "Device code cannot be empty"))]
+    pub device_code: String,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct DeviceApproveRequest {
+    #[validate(length(min = 1, message = "User code cannot be empty"))]
+    pub user_code: String,
+    /// true 表示同意授权，false 表示拒绝
+    pub approve: bool,
+}
@@ -0,0 +1,57 @@
+// src/dtos/consent.rs
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{core::enums::ConsentPurpose, entity::consents, utils::cache::CacheSchema};
+
+/// 某个数据处理目的的当前同意状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsentStatus {
+    pub purpose: ConsentPurpose,
+    /// 是否同意。从未设置过，或同意的是旧版本隐私政策时为 `false`
+    pub granted: bool,
+    /// 最近一次设置时的隐私政策版本，从未设置过时为空
+    pub policy_version: Option<String>,
+    /// 隐私政策已更新、需要用户重新确认
+    pub outdated: bool,
+    pub updated_at: Option<String>,
+}
+
+impl CacheSchema for ConsentStatus {
+    const CACHE_VERSION: u32 = 1;
+}
+
+/// 修改同意状态的请求，只需要包含发生变化的目的
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateConsentsRequest {
+    #[validate(length(min = 1, max = 10, message = "Consents must contain 1-10 entries"))]
+    pub consents: Vec<ConsentChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConsentChange {
+    pub purpose: ConsentPurpose,
+    pub granted: bool,
+}
+
+/// 同意记录，导出个人数据时返回完整的变更历史
+#[derive(Debug, Serialize)]
+pub struct ConsentRecord {
+    pub purpose: ConsentPurpose,
+    pub granted: bool,
+    pub policy_version: String,
+    pub ip: Option<String>,
+    pub created_at: String,
+}
+
+impl From<consents::Model> for ConsentRecord {
+    fn from(consent: consents::Model) -> Self {
+        Self {
+            purpose: consent.purpose,
+            granted: consent.granted,
+            policy_version: consent.policy_version,
+            ip: consent.ip,
+            created_at: consent.created_at.to_string(),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// src/dtos/delegation.rs
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    core::{constants::DELEGATION_MAX_MINUTES, enums::Permission},
+    entity::delegations,
+};
+
+/// 创建委托授权的请求
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateDelegationRequest {
+    /// 被授权人的用户ID
+    pub grantee_id: Uuid,
+
+    /// 允许的操作范围，只能是 `read_self`（读取资料）和 `update_self`（修改资料）
+    #[validate(length(min = 1, max = 2, message = "Scopes must contain 1-2 entries"))]
+    pub scopes: Vec<Permission>,
+
+    /// 有效期（分钟），最长30天
+    #[validate(range(min = 5, max = DELEGATION_MAX_MINUTES, message = "Expiry must be between 5 minutes and 30 days"))]
+    pub expires_in_minutes: u64,
+}
+
+/// 委托授权条目
+#[derive(Debug, Serialize)]
+pub struct DelegationItem {
+    pub id: String,
+    pub grantor_id: String,
+    pub grantee_id: String,
+    pub scopes: Vec<Permission>,
+    pub expires_at: String,
+    pub revoked_at: Option<String>,
+    pub created_at: String,
+}
+
+impl From<delegations::Model> for DelegationItem {
+    fn from(delegation: delegations::Model) -> Self {
+        Self {
+            id: delegation.id.to_string(),
+            grantor_id: delegation.grantor_id.to_string(),
+            grantee_id: delegation.grantee_id.to_string(),
+            scopes: serde_json::from_value(delegation.scopes).unwrap_or_default(),
+            expires_at: delegation.expires_at.to_string(),
+            revoked_at: delegation.revoked_at.map(|t| t.to_string()),
+            created_at: delegation.created_at.to_string(),
+        }
+    }
+}
+
+/// 当前用户有效的委托授权：自己授予他人的，以及他人授予自己的
+#[derive(Debug, Serialize)]
+pub struct DelegationList {
+    pub granted: Vec<DelegationItem>,
+    pub received: Vec<DelegationItem>,
+}
+
+/// 委托访问令牌。只能在委托范围内代表授权人访问，没有刷新令牌，过期后需要重新换取
+#[derive(Debug, Serialize)]
+pub struct DelegatedTokenResponse {
+    pub access_token: String,
+    /// 剩余有效期（秒）
+    pub expires_in: i64,
+}
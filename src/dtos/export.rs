@@ -0,0 +1,66 @@
+// src/dtos/export.rs
+use serde::{Deserialize, Serialize};
+
+/// 导出文件格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    /// 扁平化为 "field,value" 两列，字段名为 JSON 路径，如 "profile.username"
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// 导出任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportStatus {
+    Pending,
+    Ready,
+    Failed,
+}
+
+/// 导出任务信息，保存在 Redis 中，与导出文件同时过期。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportJob {
+    pub status: ExportStatus,
+    pub format: ExportFormat,
+    pub requested_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 用户的一个登录会话（对应一个有效的刷新令牌）
+#[derive(Debug, Serialize)]
+pub struct SessionInfo {
+    /// 刷新令牌的前 8 位，用于识别会话，不泄露完整令牌
+    pub token_hint: String,
+    /// 剩余有效期（秒）
+    pub expires_in: i64,
+    /// 签发会话的区域，旧会话没有区域标签
+    pub region: Option<String>,
+}
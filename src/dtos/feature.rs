@@ -0,0 +1,68 @@
+// src/dtos/feature.rs
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{entity::feature_flags, utils::cache::CacheSchema};
+
+/// 功能开关键名：小写字母、数字、点、下划线和连字符，如 `checkout.new_flow`
+pub static FEATURE_KEY_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[a-z0-9][a-z0-9._-]{0,63}$").expect("Invalid Regex")
+});
+
+/// 功能开关定义，返回给管理端，同时作为 Redis 中缓存的开关列表条目。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFlag {
+    pub key: String,
+    pub description: Option<String>,
+    /// 总开关：关闭时对所有用户都不生效，包括定向用户和租户
+    pub enabled: bool,
+    /// 灰度比例（0-100）
+    pub rollout_percentage: u8,
+    /// 定向开启的用户ID
+    pub target_users: Vec<String>,
+    /// 定向开启的租户ID（对应令牌的 `tenant_id` 扩展声明）
+    pub target_tenants: Vec<String>,
+    pub updated_at: String,
+}
+
+impl CacheSchema for FeatureFlag {
+    const CACHE_VERSION: u32 = 1;
+}
+
+impl From<feature_flags::Model> for FeatureFlag {
+    fn from(flag: feature_flags::Model) -> Self {
+        Self {
+            key: flag.key,
+            description: flag.description,
+            enabled: flag.enabled,
+            rollout_percentage: flag.rollout_percentage.clamp(0, 100) as u8,
+            target_users: serde_json::from_value(flag.target_users).unwrap_or_default(),
+            target_tenants: serde_json::from_value(flag.target_tenants).unwrap_or_default(),
+            updated_at: flag.updated_at.to_string(),
+        }
+    }
+}
+
+/// 创建或更新功能开关的请求，整体覆盖已有的定义
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpsertFeatureFlagRequest {
+    #[validate(length(max = 200, message = "Description must be at most 200 characters"))]
+    pub description: Option<String>,
+
+    pub enabled: bool,
+
+    #[serde(default)]
+    #[validate(range(max = 100, message = "Rollout percentage must be between 0 and 100"))]
+    pub rollout_percentage: u8,
+
+    #[serde(default)]
+    #[validate(length(max = 1000, message = "At most 1000 target users are allowed"))]
+    pub target_users: Vec<Uuid>,
+
+    #[serde(default)]
+    #[validate(length(max = 1000, message = "At most 1000 target tenants are allowed"))]
+    pub target_tenants: Vec<String>,
+}
@@ -0,0 +1,85 @@
+// src/dtos/import.rs
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// 字段映射：目标字段 -> 来源数据中的字段名。未配置的可选字段不导入。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldMapping {
+    /// 用户名来源字段（必填）
+    pub username: String,
+    /// 手机号来源字段
+    pub phone: Option<String>,
+    /// 密码哈希来源字段，仅在 `passthrough` 模式下使用
+    pub password_hash: Option<String>,
+    /// 角色来源字段，值需为 "user" 或 "admin"，无法识别时按普通用户导入
+    pub role: Option<String>,
+    /// 账户状态来源字段，值为 true/false、1/0、yes/no
+    pub is_active: Option<String>,
+}
+
+/// 密码处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordMode {
+    /// 直接沿用来源系统的密码哈希（需为 argon2 的 PHC 格式，如 `$argon2id$...`），
+    /// 其他算法（scrypt、pbkdf2 等）和无法识别的哈希按 `reset` 处理
+    Passthrough,
+    /// 不导入密码，用户需由管理员重置密码（`POST /admin/users/{id}/password`）后才能登录
+    #[default]
+    Reset,
+}
+
+/// 启动（或续传）导入任务的请求。续传时使用相同的 `job_id` 重新提交原请求即可。
+#[derive(Debug, Deserialize, Validate)]
+pub struct ImportRequest {
+    /// 任务ID：为空时创建新任务；指定已存在的任务时从上次中断的位置继续
+    pub job_id: Option<String>,
+    /// 数据来源，对应注册表中的导入器名称，如 "csv"、"firebase"、"database"
+    #[validate(length(min = 1, message = "Source cannot be empty"))]
+    pub source: String,
+    /// 导入器参数，如 `{"file": "users.csv"}`（`IMPORT_DIR` 下的文件名）或 `{"connection": "legacy"}`（`IMPORT_DATABASES` 中的名称）
+    #[serde(default)]
+    pub options: serde_json::Value,
+    /// 字段映射，为空时使用导入器的默认映射
+    pub mapping: Option<FieldMapping>,
+    #[serde(default)]
+    pub password_mode: PasswordMode,
+    /// 每批处理的记录数，每批在一个事务中写入并保存一次进度
+    #[serde(default = "default_batch_size")]
+    #[validate(range(min = 1, max = 5000, message = "Batch size must be between 1 and 5000"))]
+    pub batch_size: usize,
+}
+
+fn default_batch_size() -> usize {
+    500
+}
+
+/// 导入任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// 导入任务进度，保存在 Redis 中，用于查询和断点续传。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportProgress {
+    pub job_id: String,
+    pub source: String,
+    pub status: ImportStatus,
+    /// 已处理的来源记录数，续传时从这里继续
+    pub cursor: usize,
+    /// 成功导入的用户数
+    pub imported: usize,
+    /// 已存在（用户名或手机号冲突）而跳过的记录数
+    pub skipped: usize,
+    /// 数据不合法而失败的记录数
+    pub failed: usize,
+    /// 未能沿用密码、需要重置密码的用户数
+    pub password_resets: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub updated_at: String,
+}
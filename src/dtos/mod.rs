@@ -1,9 +1,20 @@
 use std::sync::LazyLock;
 use regex::Regex;
 
+pub mod admin;
+pub mod audit;
 pub mod auth;
+pub mod consent;
+pub mod delegation;
+pub mod export;
+pub mod feature;
+pub mod import;
+pub mod pagination;
+pub mod presence;
 pub mod response;
+pub mod security;
 pub mod user;
+pub mod visibility;
 
 pub static PHONE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"^1[3-9]\d{9}$").expect("Invalid Regex")
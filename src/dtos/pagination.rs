@@ -0,0 +1,99 @@
+// src/dtos/pagination.rs
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// 通用分页查询参数。作为独立的 `Query` 提取器使用，可以与各端点自己的过滤参数组合：
+/// `Query(page): Query<PageQuery>, Query(filter): Query<XxxFilter>`。
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct PageQuery {
+    /// 页码，从1开始。默认值为1。
+    #[serde(default = "default_page")]
+    #[validate(range(min = 1, message = "Page must be at least 1"))]
+    pub page: u64,
+
+    /// 每页条数。默认值为20，最大100。
+    #[serde(default = "default_per_page")]
+    #[validate(range(min = 1, max = 100, message = "Per page must be between 1 and 100"))]
+    pub per_page: u64,
+}
+
+impl PageQuery {
+    /// SeaORM 分页器使用从0开始的页码
+    pub fn page_index(&self) -> u64 {
+        self.page - 1
+    }
+}
+
+fn default_page() -> u64 {
+    1
+}
+
+fn default_per_page() -> u64 {
+    20
+}
+
+/// 统一的分页响应结构，作为 `ApiResponse` 的 `data` 返回。
+#[derive(Debug, Serialize)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub page: u64,
+    pub per_page: u64,
+    pub total: u64,
+    pub total_pages: u64,
+}
+
+impl<T> Paginated<T> {
+    /// 根据查询参数和统计结果构建分页响应
+    pub fn new(items: Vec<T>, query: &PageQuery, total: u64, total_pages: u64) -> Self {
+        Self {
+            items,
+            page: query.page,
+            per_page: query.per_page,
+            total,
+            total_pages,
+        }
+    }
+}
+
+/// 游标（keyset）分页查询参数。大表上 offset 分页越往后越慢，游标分页直接从上一页最后一条记录之后开始读取，
+/// 耗时与翻到第几页无关。请求中带有 `cursor` 或 `limit` 时，支持游标分页的端点按游标分页返回 `CursorPage`。
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CursorQuery {
+    /// 上一页响应中的 `next_cursor`，不透明字符串，客户端不应解析。首页不传。
+    pub cursor: Option<String>,
+
+    /// 每页条数。默认值为20，最大100。
+    #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
+    pub limit: Option<u64>,
+}
+
+impl CursorQuery {
+    /// 请求是否使用游标分页
+    pub fn is_requested(&self) -> bool {
+        self.cursor.is_some() || self.limit.is_some()
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.limit.unwrap_or_else(default_per_page)
+    }
+}
+
+/// 游标分页响应结构，作为 `ApiResponse` 的 `data` 返回。不统计总数，避免在大表上执行 `COUNT(*)`。
+#[derive(Debug, Serialize)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    /// 下一页的游标，没有更多数据时为 `null`
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+impl<T> CursorPage<T> {
+    /// 转换每一条记录，游标不变
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> CursorPage<U> {
+        CursorPage {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            has_more: self.has_more,
+        }
+    }
+}
@@ -0,0 +1,20 @@
+// src/dtos/presence.rs
+use serde::{Deserialize, Serialize};
+
+/// 用户的在线状态
+#[derive(Debug, Clone, Serialize)]
+pub struct PresenceStatus {
+    /// 最近 `PRESENCE_TTL` 秒内是否发送过心跳
+    pub online: bool,
+    /// 最近一次心跳的时间（RFC 3339），从未上线或已超过保留时长时为空
+    pub last_seen_at: Option<String>,
+}
+
+/// 在线状态变更事件，发布到 `REDIS_CHANNEL_PRESENCE` 频道
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresenceEvent {
+    pub user_id: String,
+    pub online: bool,
+    /// 事件发生时间（Unix 时间戳，秒）
+    pub at: i64,
+}
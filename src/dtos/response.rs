@@ -6,6 +6,12 @@ use axum::{
 };
 use serde::Serialize;
 
+use crate::core::log::target;
+use crate::{
+    core::enums::JsonCase,
+    utils::{json_case, request_id},
+};
+
 /// 统一的API响应格式。所有API端点都使用这个结构体返回响应，
 /// 确保响应格式的一致性。
 ///
@@ -14,12 +20,15 @@ use serde::Serialize;
 /// - `message`: 响应消息，描述请求的处理结果
 /// - `data`: 响应数据，泛型类型T可以是任意可序列化的类型。使用Option包装，
 ///   当无数据时该字段不会被序列化到JSON中
+/// - `request_id`: 当前请求的ID，在转换为HTTP响应时自动填充，便于客户端反馈问题时定位日志
 #[derive(Debug, Serialize)]
 pub struct ApiResponse<T> {
     pub code: u16,
     pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl<T> ApiResponse<T>
@@ -35,6 +44,7 @@ where
             code: StatusCode::OK.as_u16(),
             message: "success".to_string(),
             data: Some(data),
+            request_id: None,
         }
     }
 
@@ -49,6 +59,7 @@ where
             code: code.as_u16(),
             message: message.to_string(),
             data,
+            request_id: None,
         }
     }
 }
@@ -64,6 +75,7 @@ impl ApiResponse<()> {
             code: StatusCode::OK.as_u16(),
             message: message.to_string(),
             data: None,
+            request_id: None,
         }
     }
 
@@ -77,6 +89,7 @@ impl ApiResponse<()> {
             code: code.as_u16(),
             message: message.to_string(),
             data: None,
+            request_id: None,
         }
     }
 }
@@ -84,15 +97,33 @@ impl ApiResponse<()> {
 /// 实现 `IntoResponse` trait，将 `ApiResponse` 转换为HTTP响应。
 ///
 /// 这个实现确保 `ApiResponse` 可以直接作为Axum处理器的返回值，
-/// 自动序列化为JSON并设置正确的HTTP状态码。
+/// 自动序列化为JSON并设置正确的HTTP状态码。当配置了 camelCase 命名风格时，
+/// 所有字段名在这里统一转换，DTO 本身无需关心前端的命名习惯。
 impl<T> IntoResponse for ApiResponse<T>
 where
     T: Serialize,
 {
-    fn into_response(self) -> Response {
+    fn into_response(mut self) -> Response {
+        // 附上当前请求的ID（由请求ID中间件设置），错误响应同样携带
+        if self.request_id.is_none() {
+            self.request_id = request_id::current();
+        }
+
         // 将 code 字段转换为 HTTP 状态码。
         // 如果转换失败（如无效的状态码），默认返回500 Internal Server Error。
         let status = StatusCode::from_u16(self.code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+        // camelCase 模式：先序列化为 JSON 值，再递归转换键名
+        if json_case::current() == JsonCase::Camel {
+            return match serde_json::to_value(&self) {
+                Ok(value) => (status, Json(json_case::convert_keys(value, json_case::snake_to_camel))).into_response(),
+                Err(e) => {
+                    tracing::error!(target: target::HTTP, "❌ Response serialization failed: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                }
+            };
+        }
+
         // 将响应序列化为JSON，并与状态码一起返回
         (status, Json(self)).into_response()
     }
@@ -0,0 +1,42 @@
+// src/dtos/security.rs
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{core::enums::SecurityEventKind, entity::security_events};
+
+/// 安全事件的查询过滤条件，所有字段都是可选的。
+#[derive(Debug, Deserialize)]
+pub struct SecurityEventFilter {
+    pub kind: Option<SecurityEventKind>,
+    pub user_id: Option<Uuid>,
+    /// 事件涉及的账号（精确匹配），用于查询某个用户名或手机号的登录失败记录
+    pub subject: Option<String>,
+}
+
+/// 安全事件条目，返回给管理端。
+#[derive(Debug, Serialize)]
+pub struct SecurityEventItem {
+    pub id: String,
+    pub kind: SecurityEventKind,
+    pub user_id: Option<String>,
+    pub subject: Option<String>,
+    pub ip: Option<String>,
+    pub request_id: Option<String>,
+    pub detail: Option<String>,
+    pub created_at: String,
+}
+
+impl From<security_events::Model> for SecurityEventItem {
+    fn from(event: security_events::Model) -> Self {
+        Self {
+            id: event.id.to_string(),
+            kind: event.kind,
+            user_id: event.user_id.map(|id| id.to_string()),
+            subject: event.subject,
+            ip: event.ip,
+            request_id: event.request_id,
+            detail: event.detail,
+            created_at: event.created_at.to_string(),
+        }
+    }
+}
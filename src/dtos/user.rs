@@ -1,36 +1,201 @@
 // src/dtos/user.rs
 use crate::dtos::PHONE_REGEX;
-use crate::core::enums::UserRole;
+use crate::dtos::visibility::{FieldPolicy, FieldVisibility};
+use crate::core::enums::{LoginMethod, QuotaPeriod, UserRole};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize}; // ✅ 引入 Deserialize
+use std::sync::LazyLock;
 use validator::Validate;
-use crate::entity::users;
+use crate::entity::{login_history, users};
+use crate::utils::cache::CacheSchema;
 
 // ✅ 增加 Deserialize 和 Clone (Clone 用于缓存操作时的所有权转移)
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct UserProfile {
     pub id: String,
+    /// 公开短ID，对外的链接和管理端路由使用该ID
+    #[serde(default)]
+    pub public_id: String,
     pub username: String,
     pub phone: Option<String>,
     pub role: UserRole,
     pub is_active: bool,
+    pub ban_reason: Option<String>,
+    pub banned_until: Option<String>,
+    pub avatar_url: Option<String>,
     pub created_at: String,
 }
 
+/// 版本 2：增加 `public_id`
+impl CacheSchema for UserProfile {
+    const CACHE_VERSION: u32 = 2;
+}
+
 impl From<users::Model> for UserProfile {
     fn from(user: users::Model) -> Self {
         Self {
             id: user.id.to_string(),
+            public_id: user.public_id,
             username: user.username,
             phone: user.phone,
             role: user.role,
             is_active: user.is_active,
+            ban_reason: user.ban_reason,
+            banned_until: user.banned_until.map(|t| t.to_string()),
+            avatar_url: user.avatar_url,
             created_at: user.created_at.to_string(),
         }
     }
 }
 
+/// 用户资料的字段可见性：手机号仅本人和管理员可见，账户状态和封禁信息仅管理员可见。
+static USER_PROFILE_POLICY: LazyLock<FieldPolicy> = LazyLock::new(|| {
+    FieldPolicy::new()
+        .owner_or("phone", UserRole::Admin)
+        .restrict("is_active", UserRole::Admin)
+        .restrict("ban_reason", UserRole::Admin)
+        .restrict("banned_until", UserRole::Admin)
+});
+
+impl FieldVisibility for UserProfile {
+    fn policy() -> &'static FieldPolicy {
+        &USER_PROFILE_POLICY
+    }
+}
+
 #[derive(Deserialize, Validate)]
 pub struct UpdateUserRequest {
     #[validate(regex(path = *PHONE_REGEX, message = "Invalid phone number format"))]
     pub phone: Option<String>,
-}
\ No newline at end of file
+}
+
+/// 用户偏好设置，存储在 `users.settings`（JSONB）列中。
+///
+/// 模板内置的设置项有固定类型；基于本模板的应用可以直接写入其他键（保存在 `extra` 中），
+/// 无需新增数据库迁移。
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct UserSettings {
+    /// 界面语言，如 "zh-CN"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 2, max = 16, message = "Language must be 2-16 characters"))]
+    pub language: Option<String>,
+
+    /// 时区（IANA 名称），如 "Asia/Shanghai"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 64, message = "Timezone must be 1-64 characters"))]
+    pub timezone: Option<String>,
+
+    /// 界面主题，如 "light"、"dark"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 32, message = "Theme must be 1-32 characters"))]
+    pub theme: Option<String>,
+
+    /// 是否允许其他用户查看自己的在线状态，未设置时不允许
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub show_presence: Option<bool>,
+
+    /// 其他自定义设置项
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct ChangeUsernameRequest {
+    #[validate(length(min = 3, message = "Username must be at least 3 characters"))]
+    pub username: String,
+}
+
+/// 用户列表的排序方式。`-` 前缀表示倒序，默认按创建时间倒序。
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub enum UserSort {
+    #[serde(rename = "created_at")]
+    CreatedAtAsc,
+    #[default]
+    #[serde(rename = "-created_at")]
+    CreatedAtDesc,
+    #[serde(rename = "username")]
+    UsernameAsc,
+    #[serde(rename = "-username")]
+    UsernameDesc,
+}
+
+/// 管理端用户列表的过滤条件，所有字段都是可选的。
+#[derive(Debug, Deserialize)]
+pub struct UserListFilter {
+    /// 按角色过滤
+    pub role: Option<UserRole>,
+    /// 按账户状态过滤
+    pub is_active: Option<bool>,
+    /// 按用户名或手机号模糊搜索
+    pub q: Option<String>,
+    /// 排序方式
+    #[serde(default)]
+    pub sort: UserSort,
+}
+
+/// 用户搜索参数：按用户名或手机号的部分内容搜索。
+#[derive(Debug, Deserialize, Validate)]
+pub struct UserSearchQuery {
+    #[validate(length(min = 1, max = 64, message = "Search keyword must be 1-64 characters"))]
+    pub q: String,
+
+    /// 返回的最大条数，默认20，最大50
+    #[serde(default = "default_search_limit")]
+    #[validate(range(min = 1, max = 50, message = "Limit must be between 1 and 50"))]
+    pub limit: u64,
+}
+
+fn default_search_limit() -> u64 {
+    20
+}
+
+/// 封禁用户请求
+#[derive(Deserialize, Validate)]
+pub struct BanUserRequest {
+    /// 封禁原因，会记录到用户资料和审计日志中
+    #[validate(length(min = 1, max = 500, message = "Reason must be 1-500 characters"))]
+    pub reason: String,
+
+    /// 自动解封时间（RFC 3339）。为空表示永久封禁
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// 管理员重置用户密码请求
+#[derive(Deserialize, Validate)]
+pub struct ResetPasswordRequest {
+    /// 新密码，规则与注册时相同
+    #[validate(length(min = 6, message = "Password must be at least 6 characters"))]
+    pub new_password: String,
+}
+/// 一项用户配额的剩余额度，返回给用户本人。
+#[derive(Debug, Serialize)]
+pub struct QuotaStatus {
+    /// 配额对应的操作，如 "upload_avatar"
+    pub action: String,
+    pub period: QuotaPeriod,
+    pub limit: usize,
+    pub used: usize,
+    pub remaining: usize,
+    /// 下一次重置的时间（RFC 3339，UTC 零点）
+    pub resets_at: String,
+}
+
+/// 登录历史中的一条记录，返回给用户本人。
+#[derive(Debug, Serialize)]
+pub struct LoginHistoryItem {
+    pub method: LoginMethod,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: String,
+}
+
+impl From<login_history::Model> for LoginHistoryItem {
+    fn from(login: login_history::Model) -> Self {
+        Self {
+            method: login.method,
+            ip: login.ip,
+            user_agent: login.user_agent,
+            created_at: login.created_at.to_string(),
+        }
+    }
+}
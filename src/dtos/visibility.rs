@@ -0,0 +1,109 @@
+// src/dtos/visibility.rs
+use std::str::FromStr;
+
+use serde::{Serialize, Serializer};
+
+use crate::{core::enums::UserRole, dtos::auth::Claims};
+
+/// 查看者信息：决定某个字段是否可见的依据。
+#[derive(Debug, Clone)]
+pub struct Viewer {
+    /// 查看者的角色
+    pub role: UserRole,
+    /// 查看者是否为数据的所有者（如查看自己的资料）
+    pub is_owner: bool,
+}
+
+impl Viewer {
+    /// 根据JWT Claims构建查看者信息。`owner_id` 为被查看数据所属的用户ID。
+    pub fn from_claims(claims: &Claims, owner_id: &str) -> Self {
+        Self {
+            role: UserRole::from_str(&claims.role).unwrap_or(UserRole::User),
+            is_owner: claims.sub == owner_id,
+        }
+    }
+}
+
+/// 单个字段的可见性规则。
+#[derive(Debug, Clone)]
+enum FieldRule {
+    /// 只有达到指定角色（或更高）的查看者可见
+    MinRole(UserRole),
+    /// 数据所有者，或达到指定角色（或更高）的查看者可见
+    OwnerOr(UserRole),
+}
+
+/// 字段可见性策略（构建器风格）。未声明规则的字段对所有查看者可见。
+///
+/// # 示例
+/// ```ignore
+/// FieldPolicy::new()
+///     .owner_or("phone", UserRole::Admin)
+///     .restrict("is_active", UserRole::Admin)
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FieldPolicy {
+    rules: Vec<(&'static str, FieldRule)>,
+}
+
+impl FieldPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 字段仅对 `min_role` 及以上角色可见。
+    pub fn restrict(mut self, field: &'static str, min_role: UserRole) -> Self {
+        self.rules.push((field, FieldRule::MinRole(min_role)));
+        self
+    }
+
+    /// 字段对数据所有者，以及 `min_role` 及以上角色可见。
+    pub fn owner_or(mut self, field: &'static str, min_role: UserRole) -> Self {
+        self.rules.push((field, FieldRule::OwnerOr(min_role)));
+        self
+    }
+
+    /// 判断字段对当前查看者是否可见。
+    fn allows(&self, field: &str, viewer: &Viewer) -> bool {
+        self.rules
+            .iter()
+            .filter(|(name, _)| *name == field)
+            .all(|(_, rule)| match rule {
+                FieldRule::MinRole(role) => viewer.role.at_least(role),
+                FieldRule::OwnerOr(role) => viewer.is_owner || viewer.role.at_least(role),
+            })
+    }
+}
+
+/// 需要按查看者过滤字段的DTO实现此trait，提供自己的可见性策略。
+pub trait FieldVisibility: Serialize {
+    fn policy() -> &'static FieldPolicy;
+}
+
+/// 可见性包装器：序列化时按照 `T::policy()` 移除当前查看者无权看到的字段。
+///
+/// 这样同一个DTO可以服务于不同角色的调用者，无需为每个角色维护一份平行的DTO。
+pub struct Visible<T> {
+    data: T,
+    viewer: Viewer,
+}
+
+impl<T: FieldVisibility> Visible<T> {
+    pub fn new(data: T, viewer: Viewer) -> Self {
+        Self { data, viewer }
+    }
+}
+
+impl<T: FieldVisibility> Serialize for Visible<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut value = serde_json::to_value(&self.data).map_err(serde::ser::Error::custom)?;
+
+        // 只对对象类型做字段过滤，其他类型原样输出
+        if let Some(object) = value.as_object_mut() {
+            let policy = T::policy();
+            object.retain(|field, _| policy.allows(field, &self.viewer));
+        }
+
+        value.serialize(serializer)
+    }
+}
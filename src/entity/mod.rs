@@ -2,4 +2,12 @@
 
 pub mod prelude;
 
+pub mod access_logs;
+pub mod audit_logs;
+pub mod consents;
+pub mod delegations;
+pub mod feature_flags;
+pub mod login_history;
+pub mod security_events;
+pub mod username_history;
 pub mod users;
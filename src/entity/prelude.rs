@@ -1,3 +1,19 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.19
 #[allow(unused_imports)]
+pub use super::access_logs::Entity as AccessLogs;
+#[allow(unused_imports)]
+pub use super::audit_logs::Entity as AuditLogs;
+#[allow(unused_imports)]
+pub use super::consents::Entity as Consents;
+#[allow(unused_imports)]
+pub use super::delegations::Entity as Delegations;
+#[allow(unused_imports)]
+pub use super::feature_flags::Entity as FeatureFlags;
+#[allow(unused_imports)]
+pub use super::login_history::Entity as LoginHistory;
+#[allow(unused_imports)]
+pub use super::security_events::Entity as SecurityEvents;
+#[allow(unused_imports)]
+pub use super::username_history::Entity as UsernameHistory;
+#[allow(unused_imports)]
 pub use super::users::Entity as Users;
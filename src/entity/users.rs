@@ -10,6 +10,8 @@ pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: Uuid,
     #[sea_orm(unique)]
+    pub public_id: String,
+    #[sea_orm(unique)]
     pub username: String,
     #[serde(skip)]
     pub password_hash: String,
@@ -17,6 +19,12 @@ pub struct Model {
     pub phone: Option<String>,
     pub role: UserRole,
     pub is_active: bool,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub ban_reason: Option<String>,
+    pub banned_until: Option<DateTimeWithTimeZone>,
+    pub avatar_url: Option<String>,
+    #[sea_orm(column_type = "JsonBinary")]
+    pub settings: Json,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
 }
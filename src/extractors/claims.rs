@@ -1,22 +1,34 @@
 use axum::{
-    extract::FromRequestParts,
-    http::request::Parts,
-    RequestPartsExt,
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, Extensions, HeaderMap},
 };
 // Removed: use async_trait::async_trait; 
-use axum_extra::{
-    headers::{authorization::Bearer, Authorization},
-    TypedHeader,
-};
+use axum_extra::headers::{authorization::Bearer, Authorization, HeaderMapExt};
 use jsonwebtoken::{decode, DecodingKey, Validation};
 use secrecy::ExposeSecret;
+use serde::de::DeserializeOwned;
 
+use crate::core::log::target;
 use crate::{
     core::error::AppError,
     dtos::auth::Claims,
     state::AppState,
 };
 
+/// 访问令牌无法使用的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenError {
+    /// 请求没有携带 `Authorization: Bearer <token>`
+    Missing,
+    /// 令牌签名无效或已过期
+    Invalid,
+}
+
+/// 当前请求访问令牌的解码结果，缓存在请求扩展中。
+/// 访问日志、幂等键、令牌撤销检查、管理员守卫和 `Claims` 提取器共享同一份结果，每个请求只解码一次。
+#[derive(Clone)]
+struct DecodedToken(Result<Claims, TokenError>);
+
 /// 自定义提取器：自动从 Header 中解析 Token 并验证
 /// 如果验证失败，请求将直接被拒绝，不会进入 Handler
 // Fix: Removed #[async_trait] - axum 0.8 FromRequestParts does not use it
@@ -24,27 +36,99 @@ impl FromRequestParts<AppState> for Claims {
     type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
-        // 1. 尝试提取 Authorization: Bearer <token>
-        let TypedHeader(Authorization(bearer)) = parts
-            .extract::<TypedHeader<Authorization<Bearer>>>()
-            .await
-            .map_err(|_| AppError::AuthError("Missing or invalid Authorization header".to_string()))?;
-
-        // 2. 从 AppState 中获取密钥 (依赖注入)
-        let secret = state.config.jwt_secret.expose_secret().as_bytes();
-        let decoding_key = DecodingKey::from_secret(secret);
-
-        // 3. 解码并验证 Token
-        let token_data = decode::<Claims>(bearer.token(), &decoding_key, &Validation::default())
-            .map_err(|e| {
-                tracing::warn!("⚠️ Token validation failed: {}", e);
-                AppError::AuthError("Invalid or expired token".to_string())
-            })?;
-
-        // 4. (可选) 这里可以加入 Redis 黑名单校验
-        // let redis_key = format!("blacklist:{}", bearer.token());
-        // ...
-
-        Ok(token_data.claims)
+        // 优先复用中间件已经解码的结果，没有时再解析 Authorization: Bearer <token> 并验证
+        match resolve_claims(state, &parts.headers, &mut parts.extensions) {
+            Ok(claims) => Ok(claims),
+            Err(TokenError::Missing) => Err(AppError::AuthError("Missing or invalid Authorization header".to_string())),
+            Err(TokenError::Invalid) => {
+                tracing::warn!(target: target::AUTH, "⚠️ Rejected invalid or expired token");
+                Err(AppError::AuthError("Invalid or expired token".to_string()))
+            }
+        }
+    }
+}
+
+/// 读取当前请求的访问令牌声明。首次调用时解码 `Authorization` 头并把结果（包括失败原因）
+/// 写入请求扩展，之后的中间件和提取器直接读取缓存。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含JWT密钥
+/// - `headers`: 请求头
+/// - `extensions`: 请求扩展，用于缓存解码结果
+///
+/// # 返回值
+/// - `Ok(Claims)`: 令牌有效
+/// - `Err(TokenError)`: 缺少令牌或令牌无效
+pub fn resolve_claims(
+    state: &AppState,
+    headers: &HeaderMap,
+    extensions: &mut Extensions,
+) -> Result<Claims, TokenError> {
+    if let Some(DecodedToken(decoded)) = extensions.get::<DecodedToken>() {
+        return decoded.clone();
+    }
+
+    let decoded = decode_authorization(state, headers);
+    extensions.insert(DecodedToken(decoded.clone()));
+    decoded
+}
+
+/// 与 `resolve_claims` 相同，供持有完整 `Request` 的中间件使用。
+pub fn request_claims(state: &AppState, req: &mut Request) -> Result<Claims, TokenError> {
+    if let Some(DecodedToken(decoded)) = req.extensions().get::<DecodedToken>() {
+        return decoded.clone();
+    }
+
+    let decoded = decode_authorization(state, req.headers());
+    req.extensions_mut().insert(DecodedToken(decoded.clone()));
+    decoded
+}
+
+/// 解析 `Authorization: Bearer <token>` 并验证令牌
+fn decode_authorization(state: &AppState, headers: &HeaderMap) -> Result<Claims, TokenError> {
+    let Authorization(bearer) = headers
+        .typed_get::<Authorization<Bearer>>()
+        .ok_or(TokenError::Missing)?;
+
+    // 刷新令牌等非 JWT 的 Bearer 值也会走到这里，只记录 debug 日志，是否拒绝由使用方决定
+    decode_token(state, bearer.token()).map_err(|e| {
+        tracing::debug!(target: target::AUTH, "Token validation failed: {}", e);
+        TokenError::Invalid
+    })
+}
+
+/// 使用配置中的密钥解码并验证访问令牌。请求处理中应使用 `resolve_claims` 以复用已解码的结果。
+pub fn decode_token(state: &AppState, token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+    // 从 AppState 中获取密钥 (依赖注入)
+    let secret = state.config.jwt_secret.expose_secret().as_bytes();
+    let decoding_key = DecodingKey::from_secret(secret);
+    decode::<Claims>(token, &decoding_key, &Validation::default()).map(|data| data.claims)
+}
+
+/// 扩展声明的类型化访问器。扩展声明由 `ClaimsBuilder` 在签发令牌时写入，
+/// 处理器通过这些方法读取，而不必直接操作 `HashMap<String, Value>`。
+#[allow(dead_code)]
+impl Claims {
+    /// 读取任意扩展声明并反序列化为指定类型。不存在或类型不匹配时返回 `None`。
+    pub fn ext<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.ext
+            .get(key)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    /// 租户ID（扩展声明 `tenant_id`）
+    pub fn tenant_id(&self) -> Option<String> {
+        self.ext("tenant_id")
+    }
+
+    /// 套餐等级（扩展声明 `plan_tier`）
+    pub fn plan_tier(&self) -> Option<String> {
+        self.ext("plan_tier")
+    }
+
+    /// 是否被授予某项功能（扩展声明 `features`，字符串数组）
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.ext::<Vec<String>>("features")
+            .is_some_and(|features| features.iter().any(|f| f == feature))
     }
 }
\ No newline at end of file
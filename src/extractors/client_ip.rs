@@ -0,0 +1,91 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::OnceLock,
+};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::request::Parts,
+};
+use ipnet::IpNet;
+
+use crate::core::error::AppError;
+use crate::core::log::target;
+
+/// 可信反向代理名单，启动时根据配置初始化一次（见 `Config::trusted_proxies`）。
+static TRUSTED_PROXIES: OnceLock<Vec<IpNet>> = OnceLock::new();
+
+/// 初始化可信反向代理名单。重复调用时保留第一次设置的值。
+pub fn init(proxies: Vec<IpNet>) {
+    if TRUSTED_PROXIES.set(proxies).is_err() {
+        tracing::warn!(target: target::HTTP, "⚠️ Trusted proxies already initialized, ignoring");
+    }
+}
+
+fn is_trusted(ip: &IpAddr) -> bool {
+    TRUSTED_PROXIES
+        .get()
+        .is_some_and(|proxies| proxies.iter().any(|network| network.contains(ip)))
+}
+
+/// 客户端IP提取器：以 TCP 连接的对端地址为准（需要以 `into_make_service_with_connect_info` 启动服务）。
+/// 对端是可信反向代理（见 `TRUSTED_PROXIES`）时才读取转发头：
+/// 1. `X-Forwarded-For` 从右向左跳过可信代理，第一个不可信的地址即为客户端
+/// 2. 没有 `X-Forwarded-For` 时使用 `X-Real-IP`
+///
+/// 转发头最左侧的地址由客户端自己填写，不能直接采用，否则任何人都能伪造来源IP。
+/// 无法解析时返回 "unknown"，不会拒绝请求。
+#[derive(Debug, Clone)]
+pub struct ClientIp(pub String);
+
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+
+        let ip = match peer {
+            Some(peer) if is_trusted(&peer) => forwarded_client(parts).unwrap_or(peer).to_string(),
+            Some(peer) => peer.to_string(),
+            None => "unknown".to_string(),
+        };
+
+        Ok(ClientIp(ip))
+    }
+}
+
+/// 从可信代理转发的请求头中解析客户端地址
+fn forwarded_client(parts: &Parts) -> Option<IpAddr> {
+    // 代理可能追加独立的一行 `X-Forwarded-For`，多行按出现顺序拼接
+    let forwarded: Vec<&str> = parts
+        .headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+
+    if !forwarded.is_empty() {
+        // 从右向左，每一跳都由其右侧的代理追加；遇到无法解析的地址时停止，采用最后一个可信代理记录的地址
+        let mut client = None;
+        for hop in forwarded.iter().rev().flat_map(|value| value.rsplit(',')).map(str::trim) {
+            let Ok(ip) = hop.parse::<IpAddr>() else { break };
+            client = Some(ip);
+            if !is_trusted(&ip) {
+                break;
+            }
+        }
+        return client;
+    }
+
+    parts
+        .headers
+        .get("x-real-ip")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+}
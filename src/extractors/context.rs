@@ -0,0 +1,152 @@
+use std::{future::Future, time::Duration};
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DatabaseTransaction, Statement, TransactionTrait};
+use tokio::time::Instant;
+
+use crate::core::log::target;
+use crate::{
+    core::{error::AppError, i18n::Locale},
+    dtos::auth::Claims,
+    extractors::{claims::resolve_claims, client_ip::ClientIp},
+    middleware::timeout::TimeoutBudget,
+    state::AppState,
+    utils::request_id::RequestId,
+};
+
+/// 单个请求的上下文：请求ID、调用方身份、语言、来源IP、客户端标识和截止时间。
+///
+/// 由 `context::attach` 中间件在请求进入时创建并放入请求扩展，处理器通过提取器取得后
+/// 以 `&RequestContext` 传给服务函数，代替逐个传递 `claims.sub`、客户端IP等参数。
+/// 服务函数通过 `run` 让数据库和Redis调用遵守请求的截止时间。
+#[derive(Clone)]
+pub struct RequestContext {
+    pub request_id: Option<String>,
+    /// 访问令牌中的声明，匿名请求或令牌无效时为 `None`
+    pub claims: Option<Claims>,
+    pub locale: Locale,
+    pub client_ip: String,
+    pub user_agent: Option<String>,
+    budget: Option<TimeoutBudget>,
+}
+
+impl RequestContext {
+    /// 从请求中收集上下文信息。
+    ///
+    /// # 参数
+    /// - `state`: 应用程序状态，用于解码访问令牌
+    /// - `parts`: 请求头和请求扩展
+    pub async fn from_parts(state: &AppState, parts: &mut Parts) -> Self {
+        let claims = resolve_claims(state, &parts.headers, &mut parts.extensions).ok();
+        let locale = Locale::from_accept_language(
+            parts
+                .headers
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|value| value.to_str().ok()),
+        );
+        let ClientIp(client_ip) = ClientIp::from_request_parts(parts, state)
+            .await
+            .unwrap_or_else(|_| ClientIp("unknown".to_string()));
+
+        Self {
+            request_id: parts.extensions.get::<RequestId>().map(|id| id.0.clone()),
+            claims,
+            locale,
+            client_ip,
+            user_agent: parts
+                .headers
+                .get(header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+            budget: parts.extensions.get::<TimeoutBudget>().cloned(),
+        }
+    }
+
+    /// 返回已认证的调用方。需要登录的服务函数通过它获取操作者身份。
+    ///
+    /// # 返回值
+    /// - `Ok(&Claims)`: 请求携带了有效的访问令牌
+    /// - `Err(AppError::AuthError)`: 匿名请求或令牌无效
+    pub fn actor(&self) -> Result<&Claims, AppError> {
+        self.claims
+            .as_ref()
+            .ok_or(AppError::AuthError("Missing or invalid Authorization header".to_string()))
+    }
+
+    /// 请求的截止时间。未经过超时中间件的请求（如后台任务构造的上下文）没有截止时间。
+    pub fn deadline(&self) -> Option<Instant> {
+        self.budget.as_ref().map(TimeoutBudget::deadline)
+    }
+
+    /// 距离截止时间的剩余时间，已超时时为零。没有截止时间时返回 `None`。
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// 开启一个遵守请求截止时间的数据库事务。
+    ///
+    /// `run` 只能让当前请求放弃等待，已发送的 SQL 仍会在数据库中执行到结束；
+    /// 这里通过 `SET LOCAL statement_timeout` 把剩余时间同步给 PostgreSQL，
+    /// 超时的语句由数据库主动取消，释放连接和锁。事务结束后设置自动失效，不影响连接池中的其他请求。
+    ///
+    /// # 返回值
+    /// - `Ok(DatabaseTransaction)`: 已设置语句超时的事务
+    /// - `Err(AppError)`: 请求已超时或数据库错误
+    pub async fn begin(&self, db: &DatabaseConnection) -> Result<DatabaseTransaction, AppError> {
+        let txn = self.run("begin_transaction", db.begin()).await?;
+        if let Some(remaining) = self.remaining() {
+            // statement_timeout 为 0 表示不限制，剩余时间不足 1 毫秒时按 1 毫秒处理
+            let timeout_ms = remaining.as_millis().max(1);
+            let statement = Statement::from_string(
+                txn.get_database_backend(),
+                format!("SET LOCAL statement_timeout = {}", timeout_ms),
+            );
+            self.run("set_statement_timeout", txn.execute(statement)).await?;
+        }
+        Ok(txn)
+    }
+
+    /// 在请求截止时间内执行数据库或Redis调用。超过截止时间时放弃等待并返回 504，
+    /// 客户端已经超时的请求不再继续占用连接。
+    ///
+    /// # 参数
+    /// - `operation`: 用于日志的操作名称，如 "find_user"
+    /// - `future`: 数据库或Redis调用
+    pub async fn run<T, E>(&self, operation: &str, future: impl Future<Output = Result<T, E>>) -> Result<T, AppError>
+    where
+        E: Into<AppError>,
+    {
+        let Some(deadline) = self.deadline() else {
+            return future.await.map_err(Into::into);
+        };
+
+        match tokio::time::timeout_at(deadline, future).await {
+            Ok(result) => result.map_err(Into::into),
+            Err(_) => {
+                metrics::counter!("deadline_exceeded_total", "operation" => operation.to_string()).increment(1);
+                tracing::warn!(
+                    target: target::HTTP,
+                    request_id = self.request_id.as_deref().unwrap_or_default(),
+                    "⏱️ Deadline exceeded during {}",
+                    operation
+                );
+                Err(AppError::Timeout("Request timed out".to_string()))
+            }
+        }
+    }
+}
+
+/// 从请求扩展中取得上下文；未挂载 `context::attach` 中间件时现场构建。
+impl FromRequestParts<AppState> for RequestContext {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if let Some(context) = parts.extensions.get::<RequestContext>() {
+            return Ok(context.clone());
+        }
+        Ok(RequestContext::from_parts(state, parts).await)
+    }
+}
@@ -0,0 +1,49 @@
+use std::marker::PhantomData;
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+
+use crate::{
+    core::{error::AppError, flags::Flag},
+    state::AppState,
+};
+
+/// 标记类型与系统开关的对应关系，供 `RequireFlag` 在类型参数中指定开关。
+pub trait FlagGate {
+    const FLAG: Flag;
+}
+
+/// 为每个系统开关定义同名的标记类型
+macro_rules! flag_gates {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[derive(Debug)]
+            pub struct $name;
+
+            impl FlagGate for $name {
+                const FLAG: Flag = Flag::$name;
+            }
+        )*
+    };
+}
+
+flag_gates!(RegistrationOpen, DeviceLoginEnabled, DataExportEnabled);
+
+/// 系统开关提取器：开关关闭时直接拒绝请求（403 或 404，见 `Flag::disabled_error`），处理器无需再判断。
+///
+/// ```ignore
+/// pub async fn request_code(_: RequireFlag<DeviceLoginEnabled>, ...) -> Result<impl IntoResponse, AppError>
+/// ```
+#[derive(Debug)]
+pub struct RequireFlag<F>(PhantomData<F>);
+
+impl<F: FlagGate> FromRequestParts<AppState> for RequireFlag<F> {
+    type Rejection = AppError;
+
+    async fn from_request_parts(_parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if state.flags.is_enabled(F::FLAG, &state.config) {
+            Ok(Self(PhantomData))
+        } else {
+            Err(F::FLAG.disabled_error())
+        }
+    }
+}
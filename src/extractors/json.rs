@@ -0,0 +1,127 @@
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap, StatusCode},
+};
+use serde::de::DeserializeOwned;
+
+use crate::core::{
+    error::AppError,
+    i18n::{self, Locale, MessageKey},
+};
+
+/// JSON 请求体提取器，用于替代 `axum::Json`。
+///
+/// 与 `axum::Json` 的区别在于错误处理：反序列化失败时返回统一的 `ApiResponse` 格式，
+/// 并把 serde 的原始错误转换为带字段路径的精确消息（如 "body.phone: expected a string, got integer `1`"），
+/// 消息文案根据 `Accept-Language` 本地化。
+pub struct AppJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for AppJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let locale = Locale::from_accept_language(
+            req.headers()
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|value| value.to_str().ok()),
+        );
+
+        // 1. 检查 Content-Type，与 axum::Json 的行为保持一致
+        if !is_json_content_type(req.headers()) {
+            return Err(AppError::BadRequest(i18n::t(locale, MessageKey::JsonContentType, &[])));
+        }
+
+        // 2. 读取请求体。超过 `DefaultBodyLimit` 限制时返回 413
+        let bytes = Bytes::from_request(req, state).await.map_err(|rejection| {
+            if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+                AppError::PayloadTooLarge(i18n::t(locale, MessageKey::PayloadTooLarge, &[]))
+            } else {
+                AppError::BadRequest(i18n::t(locale, MessageKey::JsonBodyUnreadable, &[]))
+            }
+        })?;
+
+        // 3. 反序列化并记录出错的字段路径
+        parse_json(&bytes, locale).map(AppJson)
+    }
+}
+
+/// 将字节解析为 JSON DTO，出错时返回带字段路径的本地化消息。
+/// 供需要手动读取请求体的处理器（如请求体可选的端点）复用。
+pub fn parse_json<T: DeserializeOwned>(bytes: &[u8], locale: Locale) -> Result<T, AppError> {
+    let deserializer = &mut serde_json::Deserializer::from_slice(bytes);
+    serde_path_to_error::deserialize(deserializer).map_err(|err| {
+        let path = match err.path().to_string().as_str() {
+            "." => "body".to_string(),
+            path => format!("body.{}", path),
+        };
+        let inner = err.into_inner();
+
+        // 语法错误（非法 JSON、提前结束等）与字段路径无关，单独提示
+        if inner.is_syntax() || inner.is_eof() {
+            let detail = strip_position(&inner.to_string());
+            return AppError::BadRequest(i18n::t(locale, MessageKey::JsonSyntax, &[("detail", &detail)]));
+        }
+
+        let detail = describe(&strip_position(&inner.to_string()), locale);
+        AppError::BadRequest(i18n::t(
+            locale,
+            MessageKey::JsonField,
+            &[("path", &path), ("detail", &detail)],
+        ))
+    })
+}
+
+fn is_json_content_type(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|mime| {
+            let mime = mime.trim();
+            mime == "application/json" || (mime.starts_with("application/") && mime.ends_with("+json"))
+        })
+        .unwrap_or(false)
+}
+
+/// 去掉 serde_json 错误消息末尾的位置信息，如 " at line 1 column 12"
+fn strip_position(message: &str) -> String {
+    match message.rsplit_once(" at line ") {
+        Some((head, _)) => head.to_string(),
+        None => message.to_string(),
+    }
+}
+
+/// 把常见的 serde 错误消息映射到消息目录，未识别的消息保持原文。
+fn describe(message: &str, locale: Locale) -> String {
+    if let Some((actual, expected)) = message
+        .strip_prefix("invalid type: ")
+        .and_then(|rest| rest.split_once(", expected "))
+    {
+        return i18n::t(
+            locale,
+            MessageKey::InvalidType,
+            &[("expected", expected), ("actual", actual)],
+        );
+    }
+
+    let field = |prefix: &str| {
+        message
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.split('`').next())
+            .map(str::to_string)
+    };
+
+    if let Some(name) = field("missing field `") {
+        return i18n::t(locale, MessageKey::MissingField, &[("field", &name)]);
+    }
+    if let Some(name) = field("unknown field `") {
+        return i18n::t(locale, MessageKey::UnknownField, &[("field", &name)]);
+    }
+
+    message.to_string()
+}
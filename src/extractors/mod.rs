@@ -1 +1,6 @@
-pub mod claims;
\ No newline at end of file
+pub mod claims;
+pub mod client_ip;
+pub mod context;
+pub mod flag;
+pub mod json;
+pub mod user_ref;
@@ -0,0 +1,24 @@
+use axum::{
+    extract::{FromRequestParts, Path},
+    http::request::Parts,
+};
+use uuid::Uuid;
+
+use crate::{core::error::AppError, state::AppState, utils::public_id};
+
+/// 路径中的用户标识提取器：路由参数可以是公开短ID或UUID，提取后统一为内部UUID。
+/// 用于 `/users/{id}/...` 形式的路由，路由只能有这一个路径参数。
+#[derive(Debug, Clone, Copy)]
+pub struct UserRef(pub Uuid);
+
+impl FromRequestParts<AppState> for UserRef {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let Path(id) = Path::<String>::from_request_parts(parts, state)
+            .await
+            .map_err(|e| AppError::BadRequest(e.body_text()))?;
+
+        public_id::resolve_user(&state.db, &id).await.map(UserRef)
+    }
+}
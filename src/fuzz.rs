@@ -0,0 +1,321 @@
+// src/fuzz.rs
+//! 端到端请求模糊测试：用随机生成的畸形请求和边界值驱动路由，断言响应始终使用统一的
+//! `ApiResponse` 格式，并且不会因为用户输入返回 500。
+//!
+//! - `extractor_rejections_use_envelope`：只挂载请求 DTO 的提取和校验，不依赖外部服务，随常规测试运行
+//! - `router_responses_use_envelope`：驱动完整的应用路由，需要 `.env` 中配置可用的数据库和 Redis，
+//!   使用 `cargo test -- --ignored` 运行
+//!
+//! 用例数量和随机种子可以通过 `FUZZ_CASES`、`FUZZ_SEED` 环境变量指定。默认使用固定种子，
+//! 每次运行生成相同的请求，测试结果可以复现；探索新的输入时指定其他种子，失败时输出种子以便复现。
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Method, Request, StatusCode},
+    routing::{patch, post, put},
+    Router,
+};
+use chrono::Utc;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use tower::ServiceExt;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    core::{enums::UserRole, error::AppError},
+    dtos::{
+        admin::{BulkUserRequest, MaintenanceRequest},
+        auth::{Claims, DeviceTokenRequest, LoginRequest, RegisterRequest},
+        consent::UpdateConsentsRequest,
+        delegation::CreateDelegationRequest,
+        feature::UpsertFeatureFlagRequest,
+        response::ApiResponse,
+        user::{BanUserRequest, ChangeUsernameRequest, UpdateUserRequest, UserSettings},
+    },
+    extractors::json::AppJson,
+};
+
+/// 请求 DTO 中出现的字段名，生成对象时优先使用，使随机请求能通过反序列化进入校验逻辑
+const FIELD_NAMES: &[&str] = &[
+    "account", "password", "username", "phone", "refresh_token", "device_code", "user_code", "grantee_id",
+    "scopes", "expires_in_minutes", "consents", "purpose", "granted", "description", "enabled",
+    "rollout_percentage", "target_users", "target_tenants", "reason", "until", "user_ids", "action", "role",
+    "dry_run", "message", "language", "timezone", "theme", "show_presence",
+];
+
+/// 边界字符串：空串、超长、控制字符、注入片段、非 BMP 字符等
+const EDGE_STRINGS: &[&str] = &[
+    "", " ", "\0", "\u{feff}", "null", "true", "-1", "1e400", "NaN", "' OR '1'='1", "<script>alert(1)</script>",
+    "../../etc/passwd", "%00", "🚀🚀🚀", "\u{202e}evil", "13800138000", "read_self", "marketing", "admin",
+    "super_admin", "2026-02-30T25:61:61Z", "00000000-0000-0000-0000-000000000000",
+];
+
+fn case_count() -> usize {
+    std::env::var("FUZZ_CASES").ok().and_then(|v| v.parse().ok()).unwrap_or(300)
+}
+
+/// 未指定 `FUZZ_SEED` 时使用的固定种子
+const DEFAULT_SEED: u64 = 0x5eed_2568;
+
+fn seed() -> u64 {
+    std::env::var("FUZZ_SEED").ok().and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_SEED)
+}
+
+/// 由随机数生成器产生的 UUID，与种子一起保证用例可复现
+fn arbitrary_uuid(rng: &mut StdRng) -> Uuid {
+    uuid::Builder::from_random_bytes(rng.r#gen()).into_uuid()
+}
+
+fn arbitrary_string(rng: &mut StdRng) -> String {
+    match rng.gen_range(0..4) {
+        0 => EDGE_STRINGS.choose(rng).copied().unwrap_or_default().to_string(),
+        1 => "a".repeat(rng.gen_range(0..20_000)),
+        2 => (0..rng.gen_range(0..64)).map(|_| rng.r#gen::<char>()).collect(),
+        _ => arbitrary_uuid(rng).to_string(),
+    }
+}
+
+fn arbitrary_number(rng: &mut StdRng) -> Value {
+    match rng.gen_range(0..6) {
+        0 => json!(i64::MIN),
+        1 => json!(i64::MAX),
+        2 => json!(u64::MAX),
+        3 => json!(-1),
+        4 => json!(f64::MAX),
+        _ => json!(rng.gen_range(-1000..1000)),
+    }
+}
+
+fn arbitrary_value(rng: &mut StdRng, depth: u32) -> Value {
+    let kinds = if depth >= 3 { 4 } else { 6 };
+    match rng.gen_range(0..kinds) {
+        0 => Value::Null,
+        1 => Value::Bool(rng.r#gen()),
+        2 => arbitrary_number(rng),
+        3 => Value::String(arbitrary_string(rng)),
+        4 => Value::Array((0..rng.gen_range(0..5)).map(|_| arbitrary_value(rng, depth + 1)).collect()),
+        _ => arbitrary_object(rng, depth + 1),
+    }
+}
+
+fn arbitrary_object(rng: &mut StdRng, depth: u32) -> Value {
+    let mut object = Map::new();
+    for _ in 0..rng.gen_range(0..8) {
+        let key = if rng.gen_bool(0.8) {
+            FIELD_NAMES.choose(rng).copied().unwrap_or_default().to_string()
+        } else {
+            arbitrary_string(rng)
+        };
+        object.insert(key, arbitrary_value(rng, depth));
+    }
+    Value::Object(object)
+}
+
+/// 生成一个随机请求体及其 Content-Type
+fn arbitrary_body(rng: &mut StdRng) -> (Vec<u8>, Option<&'static str>) {
+    let json_type = Some("application/json");
+    match rng.gen_range(0..8) {
+        0..=2 => (arbitrary_object(rng, 0).to_string().into_bytes(), json_type),
+        3 => (arbitrary_value(rng, 0).to_string().into_bytes(), json_type),
+        4 => {
+            // 截断的 JSON
+            let text = arbitrary_object(rng, 0).to_string();
+            let cut = rng.gen_range(0..=text.len());
+            (text.as_bytes()[..cut].to_vec(), json_type)
+        }
+        5 => ((0..rng.gen_range(0..256)).map(|_| rng.r#gen::<u8>()).collect(), json_type),
+        6 => {
+            let content_type = [None, Some("text/plain"), Some("multipart/form-data")].choose(rng).copied().flatten();
+            (arbitrary_object(rng, 0).to_string().into_bytes(), content_type)
+        }
+        _ => (Vec::new(), json_type),
+    }
+}
+
+/// 构建随机请求。随机生成的路径或令牌不构成合法的 HTTP 请求时返回 `None`。
+fn build_request(rng: &mut StdRng, method: &Method, path: &str, token: Option<&str>) -> Option<Request<Body>> {
+    let (body, content_type) = arbitrary_body(rng);
+    let mut builder = Request::builder().method(method.clone()).uri(path);
+    if let Some(content_type) = content_type {
+        builder = builder.header(header::CONTENT_TYPE, content_type);
+    }
+    if let Some(token) = token {
+        builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    if rng.gen_bool(0.2) {
+        builder = builder.header(header::ACCEPT_LANGUAGE, ["zh-CN", "en", "*", ";q=abc", ""].choose(rng).copied().unwrap_or_default());
+    }
+    if method == Method::POST && rng.gen_bool(0.2) {
+        builder = builder.header("idempotency-key", "k".repeat(rng.gen_range(0..300)));
+    }
+    builder.body(Body::from(body)).ok()
+}
+
+/// 检查一个响应：不是 500，且响应体是带 `code` 和 `message` 的统一格式。
+/// 不满足时返回问题描述。
+async fn check_response(method: &Method, path: &str, response: axum::response::Response) -> Option<String> {
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap_or_default();
+    let snippet: String = String::from_utf8_lossy(&body).chars().take(200).collect();
+
+    if status == StatusCode::INTERNAL_SERVER_ERROR {
+        return Some(format!("{} {} -> 500: {}", method, path, snippet));
+    }
+    let envelope = serde_json::from_slice::<Value>(&body).ok().filter(|value| {
+        value.get("code").is_some_and(Value::is_u64) && value.get("message").is_some_and(Value::is_string)
+    });
+    if envelope.is_none() {
+        return Some(format!("{} {} -> {} without envelope: {}", method, path, status, snippet));
+    }
+    None
+}
+
+fn report(seed: u64, failures: &[String]) {
+    assert!(
+        failures.is_empty(),
+        "{} failing requests (FUZZ_SEED={}):\n{}",
+        failures.len(),
+        seed,
+        failures.iter().take(20).cloned().collect::<Vec<_>>().join("\n"),
+    );
+}
+
+/// 只做提取和校验的处理器，与真实处理器的前两步一致
+async fn accept<T: DeserializeOwned + Validate>(AppJson(payload): AppJson<T>) -> Result<ApiResponse<()>, AppError> {
+    payload.validate()?;
+    Ok(ApiResponse::with_message("ok"))
+}
+
+async fn accept_unvalidated<T: DeserializeOwned>(AppJson(_): AppJson<T>) -> ApiResponse<()> {
+    ApiResponse::with_message("ok")
+}
+
+#[tokio::test]
+async fn extractor_rejections_use_envelope() {
+    let router: Router = Router::new()
+        .route("/login", post(accept::<LoginRequest>))
+        .route("/register", post(accept::<RegisterRequest>))
+        .route("/device/token", post(accept::<DeviceTokenRequest>))
+        .route("/me", patch(accept::<UpdateUserRequest>))
+        .route("/me/username", post(accept::<ChangeUsernameRequest>))
+        .route("/me/settings", patch(accept::<UserSettings>))
+        .route("/me/consents", patch(accept::<UpdateConsentsRequest>))
+        .route("/me/delegations", post(accept::<CreateDelegationRequest>))
+        .route("/ban", post(accept::<BanUserRequest>))
+        .route("/bulk", post(accept::<BulkUserRequest>))
+        .route("/maintenance", post(accept::<MaintenanceRequest>))
+        .route("/feature-flags", put(accept::<UpsertFeatureFlagRequest>))
+        .route("/any", post(accept_unvalidated::<Value>));
+    let endpoints = [
+        (Method::POST, "/login"),
+        (Method::POST, "/register"),
+        (Method::POST, "/device/token"),
+        (Method::PATCH, "/me"),
+        (Method::POST, "/me/username"),
+        (Method::PATCH, "/me/settings"),
+        (Method::PATCH, "/me/consents"),
+        (Method::POST, "/me/delegations"),
+        (Method::POST, "/ban"),
+        (Method::POST, "/bulk"),
+        (Method::POST, "/maintenance"),
+        (Method::PUT, "/feature-flags"),
+        (Method::POST, "/any"),
+    ];
+
+    let seed = seed();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut failures = Vec::new();
+    for _ in 0..case_count() {
+        let (method, path) = endpoints.choose(&mut rng).expect("endpoints");
+        let request = build_request(&mut rng, method, path, None).expect("valid request");
+        let response = router.clone().oneshot(request).await.expect("infallible");
+        failures.extend(check_response(method, path, response).await);
+    }
+    report(seed, &failures);
+}
+
+/// 为一个不存在的用户签发访问令牌，使随机请求能越过认证进入处理器
+fn sign_token(secret: &str, role: UserRole) -> String {
+    let now = Utc::now().timestamp() as usize;
+    let claims = Claims {
+        sub: Uuid::new_v4().to_string(),
+        username: "fuzz".to_string(),
+        role: role.to_string(),
+        exp: now + 600,
+        iat: now,
+        ver: 0,
+        ext: HashMap::new(),
+        act: None,
+    };
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())).expect("sign token")
+}
+
+#[tokio::test]
+#[ignore = "requires the database and Redis configured in .env"]
+async fn router_responses_use_envelope() {
+    use metrics_exporter_prometheus::PrometheusBuilder;
+    use secrecy::ExposeSecret;
+
+    use crate::{core::config::Config, routes, state::AppState};
+
+    let config = Config::new().await;
+    let db = sea_orm::Database::connect(config.database_url.expose_secret())
+        .await
+        .expect("connect database");
+    let redis = redis::Client::open(config.redis_url.expose_secret())
+        .expect("redis url")
+        .get_connection_manager()
+        .await
+        .expect("connect redis");
+    let secret = config.jwt_secret.expose_secret().to_string();
+    let metrics = PrometheusBuilder::new().build_recorder().handle();
+    let router = routes::create_router(AppState::new(db, redis, config, metrics));
+
+    let endpoints = [
+        (Method::POST, "/auth/login"),
+        (Method::POST, "/auth/refresh"),
+        (Method::POST, "/auth/logout"),
+        (Method::POST, "/auth/device/token"),
+        (Method::GET, "/users/me"),
+        (Method::PATCH, "/users/me"),
+        (Method::POST, "/users/me/username"),
+        (Method::PATCH, "/users/me/settings"),
+        (Method::PATCH, "/users/me/consents"),
+        (Method::POST, "/users/me/delegations"),
+        (Method::DELETE, "/users/me/delegations/{id}"),
+        (Method::GET, "/users/{id}/presence"),
+        (Method::POST, "/admin/users/{id}/ban"),
+        (Method::GET, "/admin/users/{id}/history"),
+        (Method::POST, "/admin/users/bulk"),
+        (Method::PUT, "/admin/feature-flags/{id}"),
+    ];
+
+    let seed = seed();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut failures = Vec::new();
+    for _ in 0..case_count() {
+        let (method, template) = endpoints.choose(&mut rng).expect("endpoints");
+        let id = match rng.gen_range(0..3) {
+            0 => arbitrary_uuid(&mut rng).to_string(),
+            1 => arbitrary_string(&mut rng).bytes().map(|b| format!("%{:02X}", b)).collect(),
+            _ => "x".repeat(rng.gen_range(1..64)),
+        };
+        let path = template.replace("{id}", &id);
+        let token = match rng.gen_range(0..4) {
+            0 => None,
+            1 => Some(arbitrary_string(&mut rng)),
+            2 => Some(sign_token(&secret, UserRole::User)),
+            _ => Some(sign_token(&secret, UserRole::Admin)),
+        };
+        let Some(request) = build_request(&mut rng, method, &path, token.as_deref()) else {
+            continue;
+        };
+        let response = router.clone().oneshot(request).await.expect("infallible");
+        failures.extend(check_response(method, &path, response).await);
+    }
+    report(seed, &failures);
+}
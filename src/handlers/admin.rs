@@ -0,0 +1,830 @@
+// src/handlers/admin.rs
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use validator::Validate;
+
+use crate::{
+    core::{
+        allocator,
+        enums::{AuditAction, Permission},
+        error::AppError,
+        flags::{Flag, FlagStatus},
+    },
+    dtos::{
+        admin::{AnalyticsQuery, BulkAction, BulkUserRequest, MaintenanceRequest, SetFlagRequest, StatsQuery},
+        audit::{AuditExportQuery, AuditLogFilter},
+        auth::Claims,
+        feature::UpsertFeatureFlagRequest,
+        import::ImportRequest,
+        pagination::{CursorQuery, PageQuery},
+        response::ApiResponse,
+        security::SecurityEventFilter,
+        user::{BanUserRequest, ResetPasswordRequest, UserListFilter, UserSearchQuery},
+    },
+    extractors::{context::RequestContext, json::AppJson, user_ref::UserRef},
+    services::{
+        admin as AdminService,
+        analytics as AnalyticsService,
+        audit::{self as AuditService, AuditEntry},
+        delegation as DelegationService,
+        feature as FeatureService,
+        importer as ImportService,
+        permission as PermissionService,
+        security as SecurityService,
+        stats as StatsService,
+        user as UserService,
+    },
+    state::AppState,
+    utils::limiter,
+};
+
+/// 审计日志查询处理器。分页返回管理员执行的特权操作记录。
+/// 带有 `cursor` 或 `limit` 参数时按游标分页，适合翻阅大量历史记录。
+///
+/// # 参数
+/// - `state`: 应用程序状态
+/// - `page`: 分页参数（page、per_page）
+/// - `cursor`: 游标分页参数（cursor、limit）
+/// - `filter`: 过滤条件（action、actor_id、target_id）
+///
+/// # 返回值
+/// - `Ok(Response)`: 当前页的审计日志
+/// - `Err(AppError)`: 参数校验失败、游标无效或查询失败
+pub async fn list_audit_logs(
+    State(state): State<AppState>,
+    Query(page): Query<PageQuery>,
+    Query(cursor): Query<CursorQuery>,
+    Query(filter): Query<AuditLogFilter>,
+) -> Result<Response, AppError> {
+    if cursor.is_requested() {
+        cursor.validate()?;
+        let logs = AuditService::list_by_cursor(&state, cursor, filter).await?;
+        return Ok(ApiResponse::with_data(logs).into_response());
+    }
+
+    page.validate()?;
+    let logs = AuditService::list(&state, page, filter).await?;
+    Ok(ApiResponse::with_data(logs).into_response())
+}
+
+/// 审计日志导出处理器。以附件形式返回 Ed25519 签名（可选 AES-256-GCM 加密）的导出归档，
+/// 供合规方校验导出记录的完整性与来源。导出操作本身也会记录审计日志。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，操作者需要具备系统管理权限；来源IP写入审计日志
+/// - `state`: 应用程序状态
+/// - `filter`: 过滤条件（action、actor_id、target_id）
+/// - `query`: 时间范围（since、until）以及是否加密（encrypt）
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 导出归档（JSON 文件）
+/// - `Err(AppError)`: 权限不足、未配置密钥、记录过多或查询失败
+pub async fn export_audit_logs(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    Query(filter): Query<AuditLogFilter>,
+    Query(query): Query<AuditExportQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+
+    let diff = serde_json::json!({
+        "action": filter.action,
+        "actor_id": filter.actor_id,
+        "target_id": filter.target_id,
+        "since": query.since,
+        "until": query.until,
+        "encrypt": query.encrypt,
+    });
+    let archive = AuditService::export(&state, &ctx, filter, query).await?;
+
+    AuditService::record(
+        &state,
+        AuditEntry::from_context(&ctx, AuditAction::AuditExport)
+            .diff(diff),
+    )
+    .await;
+
+    let disposition = format!(
+        "attachment; filename=\"audit-export-{}.json\"",
+        chrono::Utc::now().format("%Y%m%dT%H%M%SZ")
+    );
+    Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(archive)))
+}
+
+/// 管理端用户列表处理器。支持分页、排序、按角色/状态过滤以及用户名/手机号搜索。
+/// 带有 `cursor` 或 `limit` 参数时按游标分页，不统计总数，翻页耗时不随页码增长。
+///
+/// # 参数
+/// - `claims`: 操作者的JWT信息，需要具备查看用户的权限
+/// - `state`: 应用程序状态
+/// - `page`: 分页参数（page、per_page）
+/// - `cursor`: 游标分页参数（cursor、limit）
+/// - `filter`: 过滤与排序参数（role、is_active、q、sort）
+///
+/// # 返回值
+/// - `Ok(Response)`: 当前页的用户列表
+/// - `Err(AppError)`: 权限不足、参数校验失败、游标无效或查询失败
+pub async fn list_users(
+    claims: Claims,
+    State(state): State<AppState>,
+    Query(page): Query<PageQuery>,
+    Query(cursor): Query<CursorQuery>,
+    Query(filter): Query<UserListFilter>,
+) -> Result<Response, AppError> {
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ViewUsers).await?;
+
+    if cursor.is_requested() {
+        cursor.validate()?;
+        let users = UserService::list_users_by_cursor(&state, cursor, filter).await?;
+        return Ok(ApiResponse::with_data(users).into_response());
+    }
+
+    page.validate()?;
+    let users = UserService::list_users(&state, page, filter).await?;
+    Ok(ApiResponse::with_data(users).into_response())
+}
+
+/// 管理端用户搜索处理器。按用户名或手机号的部分内容快速查找账户。
+///
+/// # 参数
+/// - `claims`: 操作者的JWT信息，需要具备查看用户的权限
+/// - `state`: 应用程序状态
+/// - `query`: 搜索参数（q、limit）
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 按相似度排序的用户列表
+/// - `Err(AppError)`: 权限不足、参数校验失败或查询失败
+pub async fn search_users(
+    claims: Claims,
+    State(state): State<AppState>,
+    Query(query): Query<UserSearchQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ViewUsers).await?;
+    query.validate()?;
+
+    let users = UserService::search_users(&state, query).await?;
+    Ok(ApiResponse::with_data(users))
+}
+
+/// 封禁用户处理器。禁用账户、记录原因和可选的自动解封时间，并立即吊销该用户的全部令牌。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，操作者需要具备管理用户的权限；来源IP写入审计日志
+/// - `state`: 应用程序状态
+/// - `user_id`: 被封禁的用户（公开短ID或UUID）
+/// - `payload`: 封禁原因和自动解封时间
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 封禁后的用户资料
+/// - `Err(AppError)`: 权限不足、用户不存在或参数错误
+pub async fn ban_user(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    UserRef(user_id): UserRef,
+    AppJson(payload): AppJson<BanUserRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageUsers).await?;
+    payload.validate()?;
+
+    let diff = serde_json::json!({
+        "is_active": false,
+        "reason": payload.reason,
+        "until": payload.until,
+    });
+    let profile = AdminService::ban_user(&state, &ctx, user_id, payload).await?;
+
+    AuditService::record(
+        &state,
+        AuditEntry::from_context(&ctx, AuditAction::UserBan)
+            .target(user_id)
+            .diff(diff),
+    )
+    .await;
+
+    Ok(ApiResponse::with_data(profile))
+}
+
+/// 用户变更历史处理器。分页返回以该用户为操作对象的审计记录，逐字段展示变更前后的值，
+/// 密码哈希等敏感字段的值被隐藏。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，操作者需要具备查看用户的权限
+/// - `state`: 应用程序状态
+/// - `user_id`: 用户（公开短ID或UUID）
+/// - `page`: 分页参数（page、per_page）
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 当前页的变更记录，按时间倒序
+/// - `Err(AppError)`: 权限不足、参数校验失败或用户不存在
+pub async fn user_history(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    UserRef(user_id): UserRef,
+    Query(page): Query<PageQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ViewUsers).await?;
+    page.validate()?;
+
+    let history = AuditService::user_history(&state, &ctx, user_id, page).await?;
+    Ok(ApiResponse::with_data(history))
+}
+
+/// 解封用户处理器。恢复账户状态并清除封禁信息。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，操作者需要具备管理用户的权限；来源IP写入审计日志
+/// - `state`: 应用程序状态
+/// - `user_id`: 被解封的用户（公开短ID或UUID）
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 解封后的用户资料
+/// - `Err(AppError)`: 权限不足或用户不存在
+pub async fn unban_user(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    UserRef(user_id): UserRef,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageUsers).await?;
+
+    let profile = AdminService::unban_user(&state, &ctx, user_id).await?;
+
+    AuditService::record(
+        &state,
+        AuditEntry::from_context(&ctx, AuditAction::UserUnban)
+            .target(user_id)
+            .diff(serde_json::json!({ "is_active": true })),
+    )
+    .await;
+
+    Ok(ApiResponse::with_data(profile))
+}
+
+/// 重置用户密码处理器。为导入时未沿用密码的用户设置初始密码，或协助用户重置遗忘的密码。
+/// 审计日志只记录发生了重置，不包含密码。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，操作者需要具备管理用户的权限；来源IP写入审计日志
+/// - `state`: 应用程序状态
+/// - `user_id`: 被重置密码的用户（公开短ID或UUID）
+/// - `payload`: 新密码
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 重置后的用户资料
+/// - `Err(AppError)`: 权限不足、用户不存在或参数错误
+pub async fn reset_password(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    UserRef(user_id): UserRef,
+    AppJson(payload): AppJson<ResetPasswordRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageUsers).await?;
+    payload.validate()?;
+
+    let profile = AdminService::reset_password(&state, &ctx, user_id, payload).await?;
+
+    AuditService::record(&state, AuditEntry::from_context(&ctx, AuditAction::UserPasswordReset).target(user_id)).await;
+
+    Ok(ApiResponse::with_data(profile))
+}
+
+/// 批量用户操作处理器。对一组用户执行禁用、删除或角色变更，在单个事务中执行并逐项返回结果。
+/// 每个成功的用户各写入一条审计日志；`dry_run` 为 true 时只返回预期结果，不做任何变更。
+///
+/// # 参数
+/// - `ctx`: 请求上下文。禁用/删除需要管理用户权限，角色变更需要管理角色权限；来源IP写入审计日志
+/// - `state`: 应用程序状态
+/// - `payload`: 用户ID列表、操作类型和试运行标记
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 每个用户的执行结果
+/// - `Err(AppError)`: 权限不足、参数错误或数据库错误（已回滚）
+pub async fn bulk_users(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    AppJson(payload): AppJson<BulkUserRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    let (required, action) = match payload.action {
+        BulkAction::Deactivate => (Permission::ManageUsers, AuditAction::UserDeactivate),
+        BulkAction::Delete => (Permission::ManageUsers, AuditAction::UserDelete),
+        BulkAction::RoleChange { .. } => (Permission::ManageRoles, AuditAction::UserRoleChange),
+    };
+    PermissionService::ensure_permission(&state, &claims.sub, required).await?;
+    payload.validate()?;
+
+    let result = AdminService::bulk_update_users(&state, &ctx, payload).await?;
+
+    // 记录审计日志：每个成功变更的用户一条，试运行不记录
+    if !result.dry_run {
+        for item in result.results.iter().filter(|item| item.success) {
+            let mut entry = AuditEntry::from_context(&ctx, action.clone())
+                .target(item.user_id);
+            if let Some(diff) = item.diff.clone() {
+                entry = entry.diff(diff);
+            }
+            AuditService::record(&state, entry).await;
+        }
+    }
+
+    Ok(ApiResponse::with_data(result))
+}
+
+/// 配置查看处理器。返回当前生效的配置及每一项的来源（系统环境变量、.env 文件或默认值），
+/// 用于排查 "线上为什么用了这个值" 之类的问题。敏感字段不输出实际值。
+///
+/// # 参数
+/// - `claims`: 操作者的JWT信息，需要具备系统管理权限
+/// - `state`: 应用程序状态
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 配置项列表
+/// - `Err(AppError)`: 权限不足
+pub async fn get_config(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+
+    Ok(ApiResponse::with_data(state.config.describe()))
+}
+
+/// 配置重新加载处理器。重新读取配置文件和环境变量，限额、限流豁免名单、日志级别立即生效，
+/// 其余配置项的变化只在结果中列出，需要重启才能生效。等同于向进程发送 SIGHUP，
+/// 但只作用于处理请求的实例，多实例部署需要逐个调用。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，操作者需要具备系统管理权限
+/// - `state`: 应用程序状态
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 已生效和需要重启的配置项
+/// - `Err(AppError)`: 权限不足，或新配置有问题（保持原配置不变）
+pub async fn reload_config(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+
+    let result = state
+        .config
+        .reload()
+        .await
+        .map_err(|problems| AppError::BadRequest(format!("Invalid configuration: {}", problems.join("; "))))?;
+
+    AuditService::record(
+        &state,
+        AuditEntry::from_context(&ctx, AuditAction::SystemConfigReload)
+            .diff(serde_json::json!({ "reloaded": result.reloaded, "restart_required": result.restart_required })),
+    )
+    .await;
+
+    Ok(ApiResponse::with_data(result))
+}
+
+/// 内存分配器统计处理器。返回当前使用的分配器和（jemalloc 下的）已分配、驻留等内存统计，
+/// 用于排查长时间运行后的内存增长。
+///
+/// # 参数
+/// - `claims`: 操作者的JWT信息，需要具备系统管理权限
+/// - `state`: 应用程序状态
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 分配器统计，非 jemalloc 构建的统计字段为空
+/// - `Err(AppError)`: 权限不足
+pub async fn get_memory_stats(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+
+    Ok(ApiResponse::with_data(allocator::stats()))
+}
+
+/// 堆分析文件导出处理器。需要使用 `heap-profiling` feature 构建，并在启动时开启 jemalloc 堆采样，
+/// 导出的文件使用 `jeprof` 分析。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，操作者需要具备系统管理权限
+/// - `state`: 应用程序状态
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 堆分析文件
+/// - `Err(AppError)`: 权限不足、未启用堆分析或导出失败
+pub async fn dump_heap_profile(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+
+    let profile = tokio::task::spawn_blocking(allocator::dump_heap_profile)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Heap profile task failed: {}", e)))??;
+
+    AuditService::record(&state, AuditEntry::from_context(&ctx, AuditAction::HeapProfileDump)).await;
+
+    let disposition = format!(
+        "attachment; filename=\"heap-{}.heap\"",
+        chrono::Utc::now().format("%Y%m%dT%H%M%SZ")
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        profile,
+    ))
+}
+
+/// 安全事件查询处理器。分页返回登录失败、刷新令牌重复使用、已吊销令牌的访问、越权访问等认证异常。
+///
+/// # 参数
+/// - `claims`: 操作者的JWT信息，需要具备系统管理权限
+/// - `state`: 应用程序状态
+/// - `page`: 分页参数（page、per_page）
+/// - `filter`: 过滤条件（kind、user_id、subject）
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 当前页的安全事件
+/// - `Err(AppError)`: 权限不足、参数校验失败或查询失败
+pub async fn list_security_events(
+    claims: Claims,
+    State(state): State<AppState>,
+    Query(page): Query<PageQuery>,
+    Query(filter): Query<SecurityEventFilter>,
+) -> Result<impl IntoResponse, AppError> {
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+    page.validate()?;
+
+    let events = SecurityService::list(&state, page, filter).await?;
+    Ok(ApiResponse::with_data(events))
+}
+
+/// 请求统计处理器。汇总最近若干分钟的请求数、5xx 错误率、活跃用户数，以及各路由和请求最多的用户，
+/// 作为运维看板的数据源。
+///
+/// # 参数
+/// - `claims`: 操作者的JWT信息，需要具备系统管理权限
+/// - `state`: 应用程序状态
+/// - `query`: 统计时间范围（minutes，默认15分钟）
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 统计汇总
+/// - `Err(AppError)`: 权限不足、参数校验失败或 Redis 读取失败
+pub async fn get_stats(
+    claims: Claims,
+    State(state): State<AppState>,
+    Query(query): Query<StatsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+    query.validate()?;
+
+    let stats = StatsService::overview(&state, query.minutes).await?;
+    Ok(ApiResponse::with_data(stats))
+}
+
+/// 匿名使用统计处理器。返回最近若干天的日活跃用户数、注册数和接口热度，
+/// 数据来自后台聚合的日报，涉及用户过少的统计项已按 k-匿名阈值隐藏，不包含任何用户级别的数据。
+///
+/// # 参数
+/// - `claims`: 操作者的JWT信息，需要具备查看用户权限
+/// - `state`: 应用程序状态
+/// - `query`: 查询天数（days，默认7天）
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 按日期倒序的日报列表
+/// - `Err(AppError)`: 权限不足、参数校验失败或 Redis 读取失败
+pub async fn get_analytics(
+    claims: Claims,
+    State(state): State<AppState>,
+    Query(query): Query<AnalyticsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ViewUsers).await?;
+    query.validate()?;
+
+    let reports = AnalyticsService::daily(&state, query.days).await?;
+    Ok(ApiResponse::with_data(reports))
+}
+
+/// 查询维护模式状态的处理器。
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 是否处于维护模式及维护提示
+/// - `Err(AppError)`: 权限不足
+pub async fn get_maintenance(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+
+    Ok(ApiResponse::with_data(state.maintenance.status()))
+}
+
+/// 维护模式开关处理器。开启后除健康检查和本端点外的所有请求返回 503，
+/// 用于数据库迁移等场景下无需重新部署即可排空流量。开关保存在 Redis 中，所有实例在数秒内同步生效。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，操作者需要具备系统管理权限；来源IP写入审计日志
+/// - `state`: 应用程序状态
+/// - `payload`: 是否开启，以及维护期间返回给客户端的提示
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 切换后的维护模式状态
+/// - `Err(AppError)`: 权限不足、参数错误或写入 Redis 失败
+pub async fn set_maintenance(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    AppJson(payload): AppJson<MaintenanceRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+    payload.validate()?;
+
+    let message = payload.enabled.then(|| {
+        payload
+            .message
+            .clone()
+            .unwrap_or_else(|| "Service is under maintenance, please retry later".to_string())
+    });
+    state.maintenance.set(&state.redis, message.clone()).await?;
+
+    AuditService::record(
+        &state,
+        AuditEntry::from_context(&ctx, AuditAction::SystemMaintenance)
+            .diff(serde_json::json!({ "enabled": payload.enabled, "message": message })),
+    )
+    .await;
+
+    Ok(ApiResponse::with_data(state.maintenance.status()))
+}
+
+/// 系统开关列表处理器。返回每个开关的生效值、配置中的取值和运行时覆盖。
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 全部系统开关的状态
+/// - `Err(AppError)`: 权限不足
+pub async fn list_flags(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+
+    Ok(ApiResponse::with_data(state.flags.list(&state.config)))
+}
+
+/// 系统开关覆盖处理器。在 Redis 中设置运行时覆盖，优先于配置中的取值，所有实例在数秒内同步生效。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，操作者需要具备系统管理权限；来源IP写入审计日志
+/// - `state`: 应用程序状态
+/// - `key`: 开关名称，如 `registration_open`
+/// - `payload`: 覆盖值
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 全部系统开关的最新状态
+/// - `Err(AppError)`: 权限不足、开关不存在或写入 Redis 失败
+pub async fn set_flag(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+    AppJson(payload): AppJson<SetFlagRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    override_flag(ctx, state, &key, Some(payload.enabled)).await
+}
+
+/// 清除系统开关覆盖的处理器，开关恢复为配置中的取值。
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 全部系统开关的最新状态
+/// - `Err(AppError)`: 权限不足、开关不存在或写入 Redis 失败
+pub async fn clear_flag(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    override_flag(ctx, state, &key, None).await
+}
+
+async fn override_flag(
+    ctx: RequestContext,
+    state: AppState,
+    key: &str,
+    enabled: Option<bool>,
+) -> Result<ApiResponse<Vec<FlagStatus>>, AppError> {
+    let claims = ctx.actor()?;
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+
+    let flag: Flag = key
+        .parse()
+        .map_err(|_| AppError::NotFound(format!("System flag '{}' not found", key)))?;
+    state.flags.set(&state.redis, flag, enabled).await?;
+
+    AuditService::record(
+        &state,
+        AuditEntry::from_context(&ctx, AuditAction::SystemFlagOverride)
+            .diff(serde_json::json!({ "flag": flag, "override": enabled })),
+    )
+    .await;
+
+    Ok(ApiResponse::with_data(state.flags.list(&state.config)))
+}
+
+/// 委托列表处理器。分页返回全部有效的用户间委托，用于排查代操作行为。
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的用户信息，需要具备查看用户权限
+/// - `state`: 应用程序状态
+/// - `page`: 分页参数（page、per_page）
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 当前页的有效委托
+/// - `Err(AppError)`: 权限不足、参数错误或查询失败
+pub async fn list_delegations(
+    claims: Claims,
+    State(state): State<AppState>,
+    Query(page): Query<PageQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ViewUsers).await?;
+    page.validate()?;
+
+    let delegations = DelegationService::list_active(&state, page).await?;
+    Ok(ApiResponse::with_data(delegations))
+}
+
+/// 功能开关列表处理器。返回全部开关定义，包括灰度比例和定向的用户、租户列表。
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的用户信息，需要具备系统管理权限
+/// - `state`: 应用程序状态
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 全部功能开关定义
+/// - `Err(AppError)`: 权限不足或查询失败
+pub async fn list_feature_flags(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+
+    let flags = FeatureService::list_flags(&state).await?;
+    Ok(ApiResponse::with_data(flags))
+}
+
+/// 创建或更新功能开关处理器。请求体整体覆盖已有的定义，修改在各实例上数秒内生效。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，操作者需要具备系统管理权限
+/// - `state`: 应用程序状态
+/// - `key`: 开关键名，如 `checkout.new_flow`
+/// - `payload`: 总开关、灰度比例、定向用户和租户列表
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 保存后的开关定义
+/// - `Err(AppError)`: 权限不足、参数错误或写入失败
+pub async fn upsert_feature_flag(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+    AppJson(payload): AppJson<UpsertFeatureFlagRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+    payload.validate()?;
+
+    let flag = FeatureService::upsert_flag(&state, &key, payload).await?;
+
+    AuditService::record(
+        &state,
+        AuditEntry::from_context(&ctx, AuditAction::FeatureFlagUpdate).diff(serde_json::json!({
+            "key": flag.key,
+            "enabled": flag.enabled,
+            "rollout_percentage": flag.rollout_percentage,
+            "target_users": flag.target_users.len(),
+            "target_tenants": flag.target_tenants.len(),
+        })),
+    )
+    .await;
+
+    Ok(ApiResponse::with_data(flag))
+}
+
+/// 删除功能开关处理器。删除后该开关对所有用户评估为关闭。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，操作者需要具备系统管理权限
+/// - `state`: 应用程序状态
+/// - `key`: 开关键名
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 删除成功
+/// - `Err(AppError)`: 权限不足、开关不存在或删除失败
+pub async fn delete_feature_flag(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+
+    FeatureService::delete_flag(&state, &key).await?;
+
+    AuditService::record(
+        &state,
+        AuditEntry::from_context(&ctx, AuditAction::FeatureFlagDelete).diff(serde_json::json!({ "key": key })),
+    )
+    .await;
+
+    Ok(ApiResponse::<()>::with_message("Feature flag deleted"))
+}
+
+/// 解除限流冷却处理器。清除限流对象（用户ID、账号或来源IP）在某个操作上的冷却期和违规次数，
+/// 用于处理误伤的正常用户。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，操作者需要具备系统管理权限
+/// - `state`: 应用程序状态
+/// - `action`: 限流操作名称，如 "login"、"group:auth"
+/// - `key`: 限流对象
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 已解除
+/// - `Err(AppError)`: 权限不足、没有冷却记录或 Redis 访问失败
+pub async fn clear_rate_limit_ban(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    Path((action, key)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+
+    if !limiter::clear_ban(&state.redis, &action, &key).await? {
+        return Err(AppError::NotFound(format!("No rate limit ban for {} on {}", key, action)));
+    }
+
+    AuditService::record(
+        &state,
+        AuditEntry::from_context(&ctx, AuditAction::RateLimitBanClear)
+            .diff(serde_json::json!({ "action": action, "key": key })),
+    )
+    .await;
+
+    Ok(ApiResponse::<()>::with_message("Rate limit ban cleared"))
+}
+
+/// 启动用户导入任务处理器。从外部系统（CSV、其他数据库、Firebase 导出文件）导入用户，
+/// 任务在后台执行；使用相同的 `job_id` 重新提交可以从中断处继续。
+///
+/// # 参数
+/// - `claims`: 操作者的JWT信息，需要具备系统管理权限
+/// - `state`: 应用程序状态
+/// - `payload`: 数据来源、参数、字段映射和密码处理方式
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 任务已启动，返回202 Accepted和当前进度
+/// - `Err(AppError)`: 权限不足、来源未注册、参数不合法或任务正在执行
+pub async fn start_import(
+    claims: Claims,
+    State(state): State<AppState>,
+    AppJson(payload): AppJson<ImportRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+    payload.validate()?;
+
+    let progress = ImportService::start(&state, &claims.sub, payload).await?;
+    Ok(ApiResponse::with_code(
+        axum::http::StatusCode::ACCEPTED,
+        "Import job started",
+        Some(progress),
+    ))
+}
+
+/// 查询用户导入任务进度处理器。
+///
+/// # 参数
+/// - `claims`: 操作者的JWT信息，需要具备系统管理权限
+/// - `state`: 应用程序状态
+/// - `job_id`: 任务ID
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 任务进度
+/// - `Err(AppError)`: 权限不足或任务不存在
+pub async fn get_import(
+    claims: Claims,
+    State(state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageSystem).await?;
+
+    let progress = ImportService::get_progress(&state, &job_id).await?;
+    Ok(ApiResponse::with_data(progress))
+}
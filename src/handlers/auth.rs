@@ -1,6 +1,8 @@
 // src/handlers/auth.rs
 use axum::{
-    extract::{Json, State},
+    body::Bytes,
+    extract::State,
+    http::{header, HeaderMap, HeaderValue},
     response::IntoResponse,
 };
 use axum_extra::{
@@ -9,25 +11,46 @@ use axum_extra::{
 };
 use validator::Validate;
 
+use crate::core::log::target;
 use crate::{
-    core::error::AppError,
+    core::{
+        constants::{PHONE_PREFIX_LEN, REGISTER_DAILY_LIMIT_PER_ADMIN, REGISTER_DAILY_LIMIT_PER_PHONE_PREFIX},
+        enums::{AuditAction, Permission, RefreshTransport, SecurityEventKind},
+        error::AppError,
+    },
     dtos::{
         auth::{LoginRequest, RefreshRequest, RegisterRequest},
         response::ApiResponse,
     },
-    services::auth as AuthService,
+    extractors::{
+        context::RequestContext,
+        flag::{RegistrationOpen, RequireFlag},
+        json::{self, AppJson},
+    },
+    services::{
+        audit::{self as AuditService, AuditEntry},
+        auth as AuthService,
+        permission as PermissionService,
+        security::{self as SecurityService, SecurityEvent},
+    },
     state::AppState,
+    utils::quota,
     rate_limit,
 };
 
 /// 用户注册处理器。处理新用户的注册请求。
 ///
 /// # 功能说明
+/// - 系统开关 `registration_open` 关闭时返回 403
+/// - 校验操作者拥有创建用户的权限（权限集合缓存在Redis中）
 /// - 验证请求数据格式（使用 validator crate）
 /// - 对用户名进行请求频率限制（防止暴力注册）
+/// - 按IP和手机号前缀检查每日注册配额（减缓批量注册）
 /// - 调用认证服务创建新用户
+/// - 记录审计日志（操作者、新用户、来源IP）
 ///
 /// # 参数
+/// - `ctx`: 请求上下文，包含操作者（管理员）和请求来源IP
 /// - `state`: 应用程序状态，包含数据库、Redis等资源
 /// - `payload`: 注册请求数据，包含用户名、密码等信息
 ///
@@ -35,16 +58,60 @@ use crate::{
 /// - `Ok(impl IntoResponse)`: 注册成功，返回201 Created状态码
 /// - `Err(AppError)`: 注册失败，返回相应的错误信息
 pub async fn register(
+    _: RequireFlag<RegistrationOpen>,
+    ctx: RequestContext,
     State(state): State<AppState>,
-    Json(payload): Json<RegisterRequest>,
+    AppJson(payload): AppJson<RegisterRequest>,
 ) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    PermissionService::ensure_permission(&state, &claims.sub, Permission::ManageUsers).await?;
+
     payload.validate()?;
 
-    // 请求频率限制：每个用户名每60秒最多可以注册5次
-    rate_limit!(&state.redis, "register", &payload.username, 5, 60);
+    // 请求频率限制（限额可通过 RATE_LIMITS 配置）：默认每个用户名每60秒最多可以注册5次
+    rate_limit!(state, "register", &payload.username);
+
+    // 每日配额：与上面的分钟级限流互相独立，按操作的管理员和手机号前缀分别计数。
+    // 配额在注册前先扣除（并发请求不能同时越过限额），注册失败时退还，只有成功的注册占用配额
+    let phone_prefix = payload
+        .phone
+        .as_deref()
+        .and_then(|phone| phone.get(..PHONE_PREFIX_LEN))
+        .map(str::to_string);
+    let admin = claims.sub.clone();
+    quota::check_daily_quota(&state.redis, "register:admin", &admin, REGISTER_DAILY_LIMIT_PER_ADMIN).await?;
+    if let Some(prefix) = &phone_prefix
+        && let Err(e) =
+            quota::check_daily_quota(&state.redis, "register:phone", prefix, REGISTER_DAILY_LIMIT_PER_PHONE_PREFIX).await
+    {
+        quota::refund_daily_quota(&state.redis, "register:admin", &admin).await;
+        return Err(e);
+    }
 
     // 调用认证服务执行用户注册逻辑
-    AuthService::register(&state, payload).await?;
+    let user = match AuthService::register(&state, payload).await {
+        Ok(user) => user,
+        Err(e) => {
+            quota::refund_daily_quota(&state.redis, "register:admin", &admin).await;
+            if let Some(prefix) = &phone_prefix {
+                quota::refund_daily_quota(&state.redis, "register:phone", prefix).await;
+            }
+            return Err(e);
+        }
+    };
+
+    // 记录审计日志：注册属于管理员的特权操作
+    AuditService::record(
+        &state,
+        AuditEntry::from_context(&ctx, AuditAction::UserRegister)
+            .target(user.id)
+            .diff(serde_json::json!({
+                "username": user.username,
+                "phone": user.phone,
+                "role": user.role,
+            })),
+    )
+    .await;
 
     // 返回创建成功的响应，状态码为201 Created
     Ok(ApiResponse::<()>::with_code(
@@ -61,8 +128,10 @@ pub async fn register(
 /// - 对账号进行请求频率限制（防止暴力破解）
 /// - 验证用户凭据（用户名/邮箱和密码）
 /// - 生成JWT访问令牌和刷新令牌
+/// - 启用 Cookie 传输时，同时通过 httpOnly Cookie 下发刷新令牌
 ///
 /// # 参数
+/// - `ctx`: 请求上下文，来源IP写入登录历史（登录失败时写入安全事件）
 /// - `state`: 应用程序状态
 /// - `payload`: 登录请求数据，包含账号和密码
 ///
@@ -70,42 +139,61 @@ pub async fn register(
 /// - `Ok(impl IntoResponse)`: 登录成功，返回访问令牌和刷新令牌
 /// - `Err(AppError)`: 登录失败，返回相应的错误信息
 pub async fn login(
+    ctx: RequestContext,
     State(state): State<AppState>,
-    Json(payload): Json<LoginRequest>,
+    AppJson(payload): AppJson<LoginRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     payload.validate()?;
 
-    // 请求频率限制：每个账号每60秒最多可以登录5次
-    rate_limit!(&state.redis, "login", &payload.account, 5, 60);
+    // 请求频率限制（限额可通过 RATE_LIMITS 配置）：默认每个账号每60秒最多可以登录5次
+    rate_limit!(state, "login", &payload.account);
 
-    // 调用认证服务执行登录逻辑，返回令牌对
-    let response = AuthService::login(&state, payload).await?;
+    // 调用认证服务执行登录逻辑，返回令牌对。凭据错误记录为安全事件，便于发现撞库和暴力破解
+    let account = payload.account.clone();
+    let response = AuthService::login(&state, &ctx, payload).await.inspect_err(|e| {
+        if matches!(e, AppError::AuthError(_)) {
+            SecurityService::record(
+                &state,
+                SecurityEvent::from_context(&ctx, SecurityEventKind::LoginFailed).subject(account),
+            );
+        }
+    })?;
+    let headers = refresh_cookie_headers(&state, Some(&response.refresh_token));
 
     // 返回令牌对（访问令牌和刷新令牌）
-    Ok(ApiResponse::with_data(response))
+    Ok((headers, ApiResponse::with_data(response)))
 }
 
 /// 令牌刷新处理器。处理使用刷新令牌获取新的访问令牌的请求。
 ///
 /// # 功能说明
+/// - 按配置启用的传输方式（JSON 请求体 / Authorization 头 / httpOnly Cookie）提取刷新令牌
 /// - 验证刷新令牌的有效性
 /// - 生成新的访问令牌和刷新令牌
 ///
 /// # 参数
+/// - `ctx`: 请求上下文，解析 JSON 请求体出错时按其中的语言返回消息
 /// - `state`: 应用程序状态
-/// - `payload`: 刷新令牌请求数据
+/// - `headers`: 请求头，用于读取 Authorization 和 Cookie
+/// - `body`: 原始请求体，Header/Cookie 模式下可以为空
 ///
 /// # 返回值
 /// - `Ok(impl IntoResponse)`: 刷新成功，返回新的令牌对
 /// - `Err(AppError)`: 刷新失败，返回相应的错误信息
 pub async fn refresh(
+    ctx: RequestContext,
     State(state): State<AppState>,
-    Json(payload): Json<RefreshRequest>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> Result<impl IntoResponse, AppError> {
+    let refresh_token = extract_refresh_token(&state, &ctx, &headers, &body)?;
+
     // 调用认证服务执行令牌刷新逻辑
-    let response = AuthService::refresh(&state, payload.refresh_token).await?;
+    let response = AuthService::refresh(&state, &ctx, refresh_token).await?;
+    let headers = refresh_cookie_headers(&state, Some(&response.refresh_token));
+
     // 返回新的令牌对
-    Ok(ApiResponse::with_data(response))
+    Ok((headers, ApiResponse::with_data(response)))
 }
 
 /// 用户登出处理器。处理用户的登出请求。
@@ -131,6 +219,74 @@ pub async fn logout(
     // 调用认证服务执行登出逻辑，将令牌加入黑名单
     AuthService::logout(&state, token).await?;
 
-    // 返回登出成功的消息
-    Ok(ApiResponse::<()>::with_message("Logged out successfully"))
+    // 返回登出成功的消息（Cookie 模式下同时清除刷新令牌 Cookie）
+    let headers = refresh_cookie_headers(&state, None);
+    Ok((headers, ApiResponse::<()>::with_message("Logged out successfully")))
+}
+
+/// 按配置启用的传输方式依次查找刷新令牌：JSON 请求体 -> Authorization 头 -> Cookie。
+fn extract_refresh_token(
+    state: &AppState,
+    ctx: &RequestContext,
+    headers: &HeaderMap,
+    body: &Bytes,
+) -> Result<String, AppError> {
+    for transport in state.config.refresh_transports() {
+        let token = match transport {
+            RefreshTransport::Body if !body.is_empty() => {
+                let payload: RefreshRequest = json::parse_json(body, ctx.locale)?;
+                Some(payload.refresh_token)
+            }
+            RefreshTransport::Header => headers
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .map(str::to_string),
+            RefreshTransport::Cookie => headers
+                .get_all(header::COOKIE)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(';'))
+                .filter_map(|pair| pair.trim().split_once('='))
+                .find(|(name, _)| *name == state.config.refresh_cookie_name)
+                .map(|(_, value)| value.to_string()),
+            _ => None,
+        };
+
+        if let Some(token) = token.filter(|token| !token.is_empty()) {
+            return Ok(token);
+        }
+    }
+
+    Err(AppError::AuthError("Missing refresh token".to_string()))
+}
+
+/// 构建下发（或清除）刷新令牌 Cookie 的响应头。未启用 Cookie 传输时返回空的响应头。
+///
+/// # 参数
+/// - `token`: 新的刷新令牌；为 `None` 时生成立即过期的 Cookie，用于登出
+pub(crate) fn refresh_cookie_headers(state: &AppState, token: Option<&str>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if !state.config.refresh_transports().contains(&RefreshTransport::Cookie) {
+        return headers;
+    }
+
+    let max_age = if token.is_some() { state.config.refresh_token_expiration.as_secs() } else { 0 };
+    let mut cookie = format!(
+        "{}={}; HttpOnly; Path=/auth; SameSite=Strict; Max-Age={}",
+        state.config.refresh_cookie_name,
+        token.unwrap_or_default(),
+        max_age,
+    );
+    if state.config.refresh_cookie_secure {
+        cookie.push_str("; Secure");
+    }
+
+    match HeaderValue::from_str(&cookie) {
+        Ok(value) => {
+            headers.insert(header::SET_COOKIE, value);
+        }
+        Err(e) => tracing::error!(target: target::AUTH, "❌ Invalid refresh cookie header: {}", e),
+    }
+    headers
 }
\ No newline at end of file
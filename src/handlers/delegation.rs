@@ -0,0 +1,122 @@
+// src/handlers/delegation.rs
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    core::{constants::QUOTA_DELEGATION_TOKEN, enums::AuditAction, error::AppError},
+    dtos::{auth::Claims, delegation::CreateDelegationRequest, response::ApiResponse},
+    extractors::{context::RequestContext, json::AppJson},
+    services::{
+        audit::{self as AuditService, AuditEntry},
+        delegation as DelegationService,
+    },
+    state::AppState,
+    utils::quota,
+    rate_limit,
+};
+
+/// 授予委托处理器。当前用户允许另一位用户在有效期内以自己的身份读取或修改资料。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，调用方为授权人；来源IP写入审计日志
+/// - `state`: 应用程序状态
+/// - `payload`: 被授权人、操作范围和有效期
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 新创建的委托
+/// - `Err(AppError)`: 参数错误、被授权人不存在或有效委托数量超限
+pub async fn create(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    AppJson(payload): AppJson<CreateDelegationRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    payload.validate()?;
+
+    let delegation = DelegationService::grant(&state, &claims.sub, payload).await?;
+
+    let mut entry = AuditEntry::from_context(&ctx, AuditAction::DelegationGrant).diff(serde_json::json!({
+        "delegation_id": delegation.id,
+        "scopes": delegation.scopes,
+        "expires_at": delegation.expires_at,
+    }));
+    if let Ok(grantee_id) = Uuid::parse_str(&delegation.grantee_id) {
+        entry = entry.target(grantee_id);
+    }
+    AuditService::record(&state, entry).await;
+
+    Ok(ApiResponse::with_data(delegation))
+}
+
+/// 查询当前用户有效委托的处理器，包括授予他人的和他人授予自己的。
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的用户信息
+/// - `state`: 应用程序状态
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 有效委托列表
+/// - `Err(AppError)`: 查询失败
+pub async fn list(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let delegations = DelegationService::list_for_user(&state, &claims.sub).await?;
+    Ok(ApiResponse::with_data(delegations))
+}
+
+/// 撤销委托处理器。授权人和被授权人都可以撤销，已签发的委托令牌立即失效。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，调用方为授权人或被授权人；来源IP写入审计日志
+/// - `state`: 应用程序状态
+/// - `id`: 委托ID
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 撤销后的委托
+/// - `Err(AppError)`: 委托不存在或与当前用户无关
+pub async fn revoke(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    let delegation = DelegationService::revoke(&state, &claims.sub, id).await?;
+
+    AuditService::record(
+        &state,
+        AuditEntry::from_context(&ctx, AuditAction::DelegationRevoke)
+            .diff(serde_json::json!({ "delegation_id": delegation.id })),
+    )
+    .await;
+
+    Ok(ApiResponse::with_data(delegation))
+}
+
+/// 换取委托访问令牌的处理器。被授权人使用有效的委托换取代表授权人的短期访问令牌。
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的用户信息（被授权人）
+/// - `state`: 应用程序状态
+/// - `id`: 委托ID
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 委托访问令牌及有效期
+/// - `Err(AppError)`: 委托不存在、已撤销、已过期，或授权人账户已被禁用
+pub async fn issue_token(
+    claims: Claims,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    // 请求频率限制（限额可通过 RATE_LIMITS 配置）：默认每个用户每60秒最多换取20次委托令牌
+    rate_limit!(state, "delegation_token", &claims.sub);
+    // 每月配额：限制单个被授权人长期批量换取令牌
+    quota::consume(&state.redis, &QUOTA_DELEGATION_TOKEN, &claims.sub).await?;
+
+    let response = DelegationService::issue_token(&state, &claims, id).await?;
+    Ok(ApiResponse::with_data(response))
+}
@@ -0,0 +1,95 @@
+// src/handlers/device.rs
+use axum::{extract::State, response::IntoResponse};
+use validator::Validate;
+
+use crate::{
+    core::error::AppError,
+    dtos::{
+        auth::{Claims, DeviceApproveRequest, DeviceTokenRequest},
+        response::ApiResponse,
+    },
+    extractors::{
+        client_ip::ClientIp,
+        context::RequestContext,
+        flag::{DeviceLoginEnabled, RequireFlag},
+        json::AppJson,
+    },
+    services::device as DeviceService,
+    state::AppState,
+    rate_limit,
+};
+
+/// 设备授权请求处理器。CLI、电视等设备调用此端点获取设备码和用户码。
+///
+/// # 功能说明
+/// - 系统开关 `device_login_enabled` 关闭时设备授权的端点都返回 404
+/// - 按来源IP进行请求频率限制（防止耗尽用户码空间）
+/// - 生成设备码（设备端保存）和用户码（展示给用户）
+///
+/// # 参数
+/// - `client_ip`: 请求来源IP
+/// - `state`: 应用程序状态
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 设备码、用户码、确认地址、有效期和轮询间隔
+/// - `Err(AppError)`: 请求失败，返回相应的错误信息
+pub async fn request_code(
+    _: RequireFlag<DeviceLoginEnabled>,
+    ClientIp(client_ip): ClientIp,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    // 请求频率限制（限额可通过 RATE_LIMITS 配置）：默认每个IP每60秒最多可以发起10次设备授权
+    rate_limit!(state, "device_code", &client_ip);
+
+    let response = DeviceService::request_code(&state).await?;
+    Ok(ApiResponse::with_data(response))
+}
+
+/// 设备令牌轮询处理器。设备端按 `interval` 间隔轮询，用户确认后换取令牌。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，设备的来源IP和客户端标识写入登录历史
+/// - `state`: 应用程序状态
+/// - `payload`: 设备码
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 用户已同意，返回访问令牌和刷新令牌
+/// - `Err(AppError)`: `authorization_pending`、`slow_down`、`access_denied` 或 `expired_token`
+pub async fn poll_token(
+    _: RequireFlag<DeviceLoginEnabled>,
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    AppJson(payload): AppJson<DeviceTokenRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate()?;
+
+    let response = DeviceService::poll_token(&state, &ctx, &payload.device_code).await?;
+    Ok(ApiResponse::with_data(response))
+}
+
+/// 设备授权确认处理器。已登录用户在确认页面输入设备上展示的用户码，同意或拒绝授权。
+///
+/// # 参数
+/// - `claims`: 当前登录用户的JWT信息，授权成功后设备将以该用户身份登录
+/// - `state`: 应用程序状态
+/// - `payload`: 用户码和是否同意
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 处理成功
+/// - `Err(AppError)`: 用户码无效或已过期
+pub async fn approve(
+    _: RequireFlag<DeviceLoginEnabled>,
+    claims: Claims,
+    State(state): State<AppState>,
+    AppJson(payload): AppJson<DeviceApproveRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate()?;
+
+    // 请求频率限制（限额可通过 RATE_LIMITS 配置）：默认每个用户每60秒最多可以提交10次，防止暴力猜测用户码
+    rate_limit!(state, "device_approve", &claims.sub);
+
+    DeviceService::confirm(&state, &claims.sub, &payload.user_code, payload.approve).await?;
+
+    let message = if payload.approve { "Device authorized" } else { "Device authorization denied" };
+    Ok(ApiResponse::<()>::with_message(message))
+}
@@ -0,0 +1,115 @@
+// src/handlers/health.rs
+use std::{sync::LazyLock, time::Duration};
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
+use serde::Serialize;
+use tokio::{sync::Mutex, time::Instant};
+
+use crate::{
+    core::{
+        breaker::{self, BreakerStatus},
+        constants::{READINESS_CACHE_TTL_MS, READINESS_PROBE_TIMEOUT_MS},
+        enums::Dependency,
+        standby::StandbyStatus,
+    },
+    dtos::response::ApiResponse,
+    state::AppState,
+};
+
+#[derive(Serialize)]
+pub struct HealthResponse {
+    /// "ok" 表示所有依赖正常，"degraded" 表示部分依赖已熔断
+    pub status: &'static str,
+    pub dependencies: Vec<BreakerStatus>,
+    /// 主备状态，未启用主备协调时不返回
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub standby: Option<StandbyStatus>,
+}
+
+/// 单个依赖服务的实时探测结果
+#[derive(Clone, Serialize)]
+pub struct DependencyCheck {
+    pub dependency: Dependency,
+    /// "up" 表示探测成功，"down" 表示探测失败或超时
+    pub status: &'static str,
+    pub latency_ms: u64,
+}
+
+#[derive(Serialize)]
+pub struct ReadinessResponse {
+    /// "ready" 表示所有依赖可用，"not_ready" 表示至少一个依赖不可用
+    pub status: &'static str,
+    pub dependencies: Vec<DependencyCheck>,
+}
+
+/// 健康检查处理器。只读取熔断器状态，不访问数据库或Redis，
+/// 因此依赖服务故障时仍然返回 200，供负载均衡器判断进程存活。
+pub async fn check(State(state): State<AppState>) -> impl IntoResponse {
+    let dependencies = state.breakers.status();
+    let status = if dependencies.iter().any(|item| item.state == "open") {
+        "degraded"
+    } else {
+        "ok"
+    };
+
+    let standby = state.standby.as_ref().map(|standby| standby.status());
+    ApiResponse::with_data(HealthResponse { status, dependencies, standby })
+}
+
+/// 存活探针（Kubernetes livenessProbe）。只要进程能处理请求就返回 200，
+/// 不检查任何依赖服务，避免数据库故障时所有实例被反复重启。
+pub async fn liveness() -> impl IntoResponse {
+    ApiResponse::with_message("alive")
+}
+
+/// 一次就绪探测的时间和结果
+type ReadinessProbe = (Instant, Vec<DependencyCheck>);
+
+/// 最近一次就绪探测。探测期间持有锁，并发的检查等待这次探测完成后直接使用其结果。
+static LAST_READINESS: LazyLock<Mutex<Option<ReadinessProbe>>> = LazyLock::new(|| Mutex::new(None));
+
+/// 就绪探针（Kubernetes readinessProbe）。并行探测 Postgres 和 Redis，每个依赖的超时为
+/// `READINESS_PROBE_TIMEOUT_MS`，任一依赖不可用时返回 503，使负载均衡器暂停向该实例转发流量。
+///
+/// 与 `/health` 不同，这里实际访问依赖服务，而不是读取熔断器状态。
+/// 探测结果缓存 `READINESS_CACHE_TTL_MS`，缓存期内的检查不再重复探测。
+pub async fn readiness(State(state): State<AppState>) -> impl IntoResponse {
+    let mut last = LAST_READINESS.lock().await;
+    let dependencies = match last.as_ref() {
+        Some((probed_at, dependencies)) if probed_at.elapsed() < Duration::from_millis(READINESS_CACHE_TTL_MS) => {
+            dependencies.clone()
+        }
+        _ => {
+            let timeout = Duration::from_millis(READINESS_PROBE_TIMEOUT_MS);
+            let (database, redis) = tokio::join!(
+                timed(Dependency::Database, breaker::ping_database(&state.db, timeout)),
+                timed(Dependency::Redis, breaker::ping_redis(&state.redis, timeout)),
+            );
+            let dependencies = vec![database, redis];
+            *last = Some((Instant::now(), dependencies.clone()));
+            dependencies
+        }
+    };
+    drop(last);
+
+    if dependencies.iter().all(|item| item.status == "up") {
+        ApiResponse::with_data(ReadinessResponse { status: "ready", dependencies })
+    } else {
+        ApiResponse::with_code(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Service not ready",
+            Some(ReadinessResponse { status: "not_ready", dependencies }),
+        )
+    }
+}
+
+/// 执行一次探测并记录耗时
+async fn timed(dependency: Dependency, probe: impl Future<Output = bool>) -> DependencyCheck {
+    let started = Instant::now();
+    let up = probe.await;
+    DependencyCheck {
+        dependency,
+        status: if up { "up" } else { "down" },
+        latency_ms: started.elapsed().as_millis() as u64,
+    }
+}
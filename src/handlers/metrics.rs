@@ -0,0 +1,51 @@
+// src/handlers/metrics.rs
+use std::net::IpAddr;
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, header},
+    response::IntoResponse,
+};
+use secrecy::ExposeSecret;
+use sha2::{Digest, Sha256};
+
+use crate::core::error::AppError;
+use crate::extractors::client_ip::ClientIp;
+use crate::state::AppState;
+
+/// 指标导出处理器。以 Prometheus / OpenMetrics 文本格式返回当前进程采集的全部指标，
+/// 供 Prometheus 等监控系统抓取。
+///
+/// 指标中包含路由、客户端和连接池等内部信息，不对外公开：配置了 `METRICS_TOKEN` 时校验
+/// `Authorization: Bearer <token>`，否则只接受来自本机回环地址的请求。
+///
+/// 注意：该端点不经过统一的 `ApiResponse` 包装，因为抓取方要求纯文本格式。
+pub async fn export(
+    State(state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    match &state.config.metrics_token {
+        Some(token) => {
+            let provided = headers
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .unwrap_or_default();
+            // 比较摘要而不是原文，比较耗时与令牌内容无关
+            if Sha256::digest(provided.as_bytes()) != Sha256::digest(token.expose_secret().as_bytes()) {
+                return Err(AppError::AuthError("Invalid metrics token".to_string()));
+            }
+        }
+        None => {
+            if !client_ip.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback()) {
+                return Err(AppError::Forbidden("Metrics are only available from localhost".to_string()));
+            }
+        }
+    }
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        state.metrics.render(),
+    ))
+}
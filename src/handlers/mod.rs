@@ -1,2 +1,7 @@
+pub mod admin;
 pub mod auth;
+pub mod delegation;
+pub mod device;
+pub mod health;
+pub mod metrics;
 pub mod users;  
\ No newline at end of file
@@ -1,12 +1,44 @@
 // src/handlers/users.rs
-use axum::{extract::{State, Json}, response::IntoResponse};
+use axum::{
+    extract::{multipart::MultipartRejection, Multipart, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    core::error::AppError,
-    dtos::{auth::Claims, user::UpdateUserRequest, response::ApiResponse},
-    services::user as UserService,
+    core::{
+        constants::{QUOTA_AVATAR_UPLOAD, USER_QUOTAS},
+        enums::AuditAction,
+        error::AppError,
+    },
+    dtos::{
+        auth::Claims,
+        consent::UpdateConsentsRequest,
+        export::ExportQuery,
+        pagination::PageQuery,
+        user::{ChangeUsernameRequest, QuotaStatus, UpdateUserRequest},
+        response::ApiResponse,
+        visibility::{Viewer, Visible},
+    },
+    extractors::{
+        context::RequestContext,
+        flag::{DataExportEnabled, RequireFlag},
+        json::AppJson,
+        user_ref::UserRef,
+    },
+    handlers::auth::refresh_cookie_headers,
+    services::{
+        audit::{self as AuditService, AuditEntry},
+        consent as ConsentService,
+        export as ExportService,
+        feature as FeatureService,
+        presence as PresenceService,
+        user as UserService,
+    },
     state::AppState,
+    utils::quota,
     rate_limit,
 };
 
@@ -29,13 +61,15 @@ pub async fn get_me(
     State(state): State<AppState>
 ) -> Result<impl IntoResponse, AppError> {
 
-    // 请求频率限制：每个用户ID每60秒最多可以读取资料60次
-    rate_limit!(&state.redis, "read_me", &claims.sub, 60, 60);
+    // 请求频率限制（默认令牌桶，可通过 RATE_LIMITS 配置）：客户端启动时连续读取资料不受影响，最多连续20次，
+    // 持续读取时每个用户ID每秒最多1次
+    rate_limit!(state, "read_me", &claims.sub);
 
     // 调用用户服务获取用户资料（会先检查Redis缓存）
     let profile = UserService::get_user_profile(&state, &claims.sub).await?;
-    // 返回用户资料数据
-    Ok(ApiResponse::with_data(profile))
+    // 返回用户资料数据（按查看者角色过滤字段）
+    let viewer = Viewer::from_claims(&claims, &profile.id);
+    Ok(ApiResponse::with_data(Visible::new(profile, viewer)))
 }
 
 /// 更新当前用户资料的处理器。处理登录用户的个人资料更新请求。
@@ -57,17 +91,376 @@ pub async fn get_me(
 pub async fn update_me(
     claims: Claims,
     State(state): State<AppState>,
-    Json(payload): Json<UpdateUserRequest>,
+    AppJson(payload): AppJson<UpdateUserRequest>,
 ) -> Result<impl IntoResponse, AppError> {
 
     // 验证请求数据格式
     payload.validate()?;
 
-    // 请求频率限制：每个用户ID每60秒最多可以更新资料10次
-    rate_limit!(&state.redis, "update_me", &claims.sub, 10, 60);
+    // 请求频率限制（限额可通过 RATE_LIMITS 配置）：默认每个用户ID每60秒最多可以更新资料10次
+    rate_limit!(state, "update_me", &claims.sub);
 
     // 调用用户服务更新用户资料（同时更新数据库和Redis缓存）
     let profile = UserService::update_user_profile(&state, &claims.sub, payload).await?;
-    // 返回更新后的用户资料数据
-    Ok(ApiResponse::with_data(profile))
-}
\ No newline at end of file
+    // 返回更新后的用户资料数据（按查看者角色过滤字段）
+    let viewer = Viewer::from_claims(&claims, &profile.id);
+    Ok(ApiResponse::with_data(Visible::new(profile, viewer)))
+}
+
+/// 修改当前用户用户名的处理器。
+///
+/// # 功能说明
+/// - 验证新用户名格式
+/// - 调用用户服务检查唯一性和冷却时间，更新用户名并记录变更历史
+///
+/// 注意：已签发的访问令牌中仍是旧用户名，直到下次刷新令牌。
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的用户信息
+/// - `state`: 应用程序状态
+/// - `payload`: 新的用户名
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 修改成功，返回更新后的用户资料
+/// - `Err(AppError)`: 用户名已被占用（409）、冷却期内（429）等
+pub async fn change_username(
+    claims: Claims,
+    State(state): State<AppState>,
+    AppJson(payload): AppJson<ChangeUsernameRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate()?;
+
+    let profile = UserService::change_username(&state, &claims.sub, payload.username).await?;
+    let viewer = Viewer::from_claims(&claims, &profile.id);
+    Ok(ApiResponse::with_data(Visible::new(profile, viewer)))
+}
+
+/// 冻结当前账户的处理器。供怀疑凭证被盗的用户使用：立即吊销全部会话并禁用账户，
+/// 之后只能通过账户恢复流程恢复访问。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，调用方为被冻结的用户；来源IP写入审计日志
+/// - `state`: 应用程序状态
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 冻结成功（Cookie 模式下同时清除刷新令牌 Cookie）
+/// - `Err(AppError)`: 用户不存在或操作失败
+pub async fn freeze_account(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let claims = ctx.actor()?;
+    UserService::freeze_account(&state, &claims.sub).await?;
+
+    let mut entry = AuditEntry::from_context(&ctx, AuditAction::UserFreeze);
+    if let Ok(user_id) = Uuid::parse_str(&claims.sub) {
+        entry = entry.target(user_id);
+    }
+    AuditService::record(&state, entry).await;
+
+    let headers = refresh_cookie_headers(&state, None);
+    Ok((headers, ApiResponse::<()>::with_message("Account frozen, all sessions have been signed out")))
+}
+
+/// 查询当前用户登录历史的处理器。返回最近的成功登录记录（时间、来源IP、客户端、登录方式），
+/// 用户可以据此发现未经授权的登录。
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的用户信息
+/// - `state`: 应用程序状态
+/// - `page`: 分页参数（page、per_page）
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 当前页的登录记录，按时间倒序
+/// - `Err(AppError)`: 参数校验失败或查询失败
+pub async fn list_logins(
+    claims: Claims,
+    State(state): State<AppState>,
+    Query(page): Query<PageQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    page.validate()?;
+
+    let logins = UserService::list_logins(&state, &claims.sub, page).await?;
+    Ok(ApiResponse::with_data(logins))
+}
+
+/// 查询当前用户数据处理同意状态的处理器。每个数据处理目的（营销、分析）返回一项，
+/// 隐私政策更新后旧的同意标记为需要重新确认。
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的用户信息
+/// - `state`: 应用程序状态
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 全部数据处理目的的当前同意状态
+/// - `Err(AppError)`: 查询失败
+pub async fn get_consents(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::AuthError("Invalid User ID format".to_string()))?;
+
+    let consents = ConsentService::current(&state, user_id).await?;
+    Ok(ApiResponse::with_data(consents))
+}
+
+/// 修改当前用户数据处理同意状态的处理器。只需提交发生变化的目的，每次变更都会保留记录。
+///
+/// # 参数
+/// - `ctx`: 请求上下文，来源IP写入同意记录
+/// - `state`: 应用程序状态
+/// - `payload`: 需要修改的目的及是否同意
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 修改后的全部同意状态
+/// - `Err(AppError)`: 参数错误或写入失败
+pub async fn update_consents(
+    ctx: RequestContext,
+    State(state): State<AppState>,
+    AppJson(payload): AppJson<UpdateConsentsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    payload.validate()?;
+
+    let consents = ConsentService::update(&state, &ctx, payload).await?;
+    Ok(ApiResponse::with_data(consents))
+}
+
+/// 查询当前用户功能开关的处理器。一次返回全部开关对当前用户的评估结果，
+/// 前端启动时调用一次即可决定展示哪些功能。
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的用户信息（用户ID、租户和令牌授予的功能参与评估）
+/// - `state`: 应用程序状态
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 开关键名到是否开启的映射
+/// - `Err(AppError)`: 读取开关定义失败
+pub async fn get_features(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let features = FeatureService::evaluate_all(&state, &claims).await?;
+    Ok(ApiResponse::with_data(features))
+}
+
+/// 查询当前用户配额的处理器。返回每项按用户计数的长周期配额（见 `USER_QUOTAS`）的已用次数、
+/// 剩余次数和重置时间，客户端可以在额度用完前提示用户。
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的用户信息
+/// - `state`: 应用程序状态
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 各项配额的使用情况
+/// - `Err(AppError)`: 读取 Redis 失败
+pub async fn get_quota(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let usages = quota::usages(&state.redis, USER_QUOTAS, &claims.sub).await?;
+    let quotas: Vec<QuotaStatus> = USER_QUOTAS
+        .iter()
+        .zip(usages)
+        .map(|(quota, usage)| QuotaStatus {
+            action: quota.action.to_string(),
+            period: quota.period,
+            limit: quota.limit,
+            used: usage.used,
+            remaining: usage.remaining,
+            resets_at: usage.resets_at.to_rfc3339(),
+        })
+        .collect();
+    Ok(ApiResponse::with_data(quotas))
+}
+
+/// 在线心跳的处理器。客户端在线期间每隔不超过 `PRESENCE_TTL` 秒调用一次，停止调用后自动变为离线。
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的用户信息
+/// - `state`: 应用程序状态
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 心跳后的在线状态
+/// - `Err(AppError)`: 记录心跳失败
+pub async fn presence_heartbeat(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::AuthError("Invalid User ID format".to_string()))?;
+
+    let presence = PresenceService::heartbeat(&state, user_id).await?;
+    Ok(ApiResponse::with_data(presence))
+}
+
+/// 查询指定用户在线状态的处理器。路径参数可以是公开短ID或UUID，
+/// 对方未在设置中公开在线状态时只有本人和管理员可以查看。
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的查看者信息
+/// - `state`: 应用程序状态
+/// - `user_id`: 被查看的用户
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 是否在线和最后在线时间
+/// - `Err(AppError)`: 无权查看、用户不存在或查询失败
+pub async fn get_presence(
+    claims: Claims,
+    State(state): State<AppState>,
+    UserRef(user_id): UserRef,
+) -> Result<impl IntoResponse, AppError> {
+    let presence = PresenceService::get(&state, &claims, user_id).await?;
+    Ok(ApiResponse::with_data(presence))
+}
+
+/// 上传当前用户头像的处理器。接收 `multipart/form-data` 请求，文件字段名为 `avatar`。
+///
+/// # 功能说明
+/// - 对用户ID进行请求频率限制
+/// - 读取 `avatar` 字段的文件内容和声明的 Content-Type
+/// - 调用用户服务校验大小/格式、写入存储后端并更新资料
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的用户信息
+/// - `state`: 应用程序状态
+/// - `multipart`: 表单数据
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 上传成功，返回更新后的用户资料（包含 `avatar_url`）
+/// - `Err(AppError)`: 缺少文件、文件过大、格式不支持等
+pub async fn upload_avatar(
+    claims: Claims,
+    State(state): State<AppState>,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> Result<impl IntoResponse, AppError> {
+    let mut multipart = multipart.map_err(|e| AppError::from_rejection(e.status(), e.body_text()))?;
+
+    // 请求频率限制（限额可通过 RATE_LIMITS 配置）：默认每个用户ID每60秒最多可以上传头像5次
+    rate_limit!(state, "upload_avatar", &claims.sub);
+    // 每日配额：限制头像存储的长期写入量
+    quota::consume(&state.redis, &QUOTA_AVATAR_UPLOAD, &claims.sub).await?;
+
+    // 查找名为 avatar 的文件字段，忽略其他字段
+    let mut upload = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::from_rejection(e.status(), e.body_text()))?
+    {
+        if field.name() == Some("avatar") {
+            let content_type = field.content_type().map(str::to_string);
+            let bytes = field
+                .bytes()
+                .await
+                .map_err(|e| AppError::from_rejection(e.status(), e.body_text()))?;
+            upload = Some((bytes, content_type));
+            break;
+        }
+    }
+    let (bytes, content_type) = upload.ok_or(AppError::BadRequest("Missing avatar field".to_string()))?;
+
+    let profile = UserService::update_avatar(&state, &claims.sub, &bytes, content_type.as_deref()).await?;
+    let viewer = Viewer::from_claims(&claims, &profile.id);
+    Ok(ApiResponse::with_data(Visible::new(profile, viewer)))
+}
+
+/// 申请导出个人数据的处理器。在后台汇总用户资料、用户名历史、登录会话和相关审计日志，
+/// 生成 JSON 或 CSV 文件。已有未过期的导出任务时直接返回该任务（每24小时最多导出一次）。
+/// 系统开关 `data_export_enabled` 关闭时导出相关的端点都返回 404。
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的用户信息
+/// - `state`: 应用程序状态
+/// - `query`: 导出格式（`format=json` 或 `format=csv`，默认 json）
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 202 Accepted，返回导出任务状态，之后通过 `/users/me/export/status` 轮询
+/// - `Err(AppError)`: 申请失败
+pub async fn request_export(
+    _: RequireFlag<DataExportEnabled>,
+    claims: Claims,
+    State(state): State<AppState>,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let job = ExportService::request_export(&state, &claims.sub, query.format).await?;
+    Ok(ApiResponse::with_code(StatusCode::ACCEPTED, "Data export requested", Some(job)))
+}
+
+/// 查询个人数据导出状态的处理器。
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 导出任务状态（pending / ready / failed）
+/// - `Err(AppError)`: 没有导出任务或已过期
+pub async fn export_status(
+    _: RequireFlag<DataExportEnabled>,
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let job = ExportService::get_job(&state, &claims.sub)
+        .await?
+        .ok_or(AppError::NotFound("No data export found".to_string()))?;
+    Ok(ApiResponse::with_data(job))
+}
+
+/// 下载个人数据导出文件的处理器。以附件形式返回原始文件，而不是统一的 JSON 响应格式。
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 导出文件
+/// - `Err(AppError)`: 没有导出任务、任务未完成或已过期
+pub async fn download_export(
+    _: RequireFlag<DataExportEnabled>,
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let (format, data) = ExportService::download(&state, &claims.sub).await?;
+    let disposition = format!(
+        "attachment; filename=\"user-export-{}.{}\"",
+        claims.sub,
+        format.extension()
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        data,
+    ))
+}
+
+/// 获取当前用户设置的处理器。
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 用户设置
+/// - `Err(AppError)`: 获取失败
+pub async fn get_settings(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let settings = UserService::get_settings(&state, &claims.sub).await?;
+    Ok(ApiResponse::with_data(settings))
+}
+
+/// 部分更新当前用户设置的处理器。请求体按 JSON Merge Patch 合并：
+/// 只修改出现的键，值为 null 的键被删除，未出现的键保持不变。
+///
+/// # 参数
+/// - `claims`: JWT令牌中解析出的用户信息
+/// - `state`: 应用程序状态
+/// - `patch`: 需要合并的设置
+///
+/// # 返回值
+/// - `Ok(impl IntoResponse)`: 合并后的完整设置
+/// - `Err(AppError)`: 请求格式错误、校验失败或超过大小限制
+pub async fn update_settings(
+    claims: Claims,
+    State(state): State<AppState>,
+    AppJson(patch): AppJson<serde_json::Value>,
+) -> Result<impl IntoResponse, AppError> {
+    // 请求频率限制（限额可通过 RATE_LIMITS 配置）：默认每个用户ID每60秒最多可以更新设置30次
+    rate_limit!(state, "update_settings", &claims.sub);
+
+    let settings = UserService::update_settings(&state, &claims.sub, patch).await?;
+    Ok(ApiResponse::with_data(settings))
+}
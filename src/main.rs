@@ -1,10 +1,14 @@
 // src/main.rs
+mod cli;
 mod core;
 mod dtos;
 mod entity;
 mod extractors;
+#[cfg(test)]
+mod fuzz;
 mod handlers;
 mod middleware;
+mod mock;
 mod routes;
 mod services;
 mod start;
@@ -13,5 +17,6 @@ mod utils;
 
 #[tokio::main]
 async fn main() {
-    start::run().await;
-}
\ No newline at end of file
+    // 解析命令行参数：默认启动HTTP服务器，带运维子命令时只执行该命令
+    cli::run().await;
+}
@@ -0,0 +1,58 @@
+// src/middleware/access_log.rs
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::{
+    extractors::{claims::resolve_claims, client_ip::ClientIp},
+    services::access_log::AccessRecord,
+    state::AppState,
+    utils::request_id::RequestId,
+};
+
+/// 访问日志中间件。记录请求方法、路径、状态码、耗时、用户ID、请求ID和客户端IP，
+/// 按配置写入 Postgres 或 NDJSON 文件。未启用访问日志时直接放行。
+///
+/// 用户ID从访问令牌中解析，令牌无效时留空（请求本身会被认证提取器拒绝，状态码会体现出来）。
+pub async fn record(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let Some(logger) = state.access_log.clone() else {
+        return next.run(req).await;
+    };
+
+    let started = Instant::now();
+    let (mut parts, body) = req.into_parts();
+
+    let method = parts.method.to_string();
+    let path = parts.uri.path().to_string();
+    let request_id = parts.extensions.get::<RequestId>().map(|id| id.0.clone());
+    let user_id = resolve_claims(&state, &parts.headers, &mut parts.extensions)
+        .ok()
+        .and_then(|claims| Uuid::parse_str(&claims.sub).ok());
+    let ip = ClientIp::from_request_parts(&mut parts, &state)
+        .await
+        .ok()
+        .map(|ClientIp(ip)| ip);
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    let status = response.status().as_u16();
+    if logger.should_record(status) {
+        logger.log(AccessRecord {
+            method,
+            path,
+            status,
+            latency_ms: started.elapsed().as_millis() as u64,
+            user_id,
+            request_id,
+            ip,
+            timestamp: Utc::now(),
+        });
+    }
+
+    response
+}
@@ -5,14 +5,17 @@ use axum::{
     middleware::Next,
     response::Response,
 };
-use jsonwebtoken::{decode, DecodingKey, Validation};
 use redis::AsyncCommands;
-use secrecy::ExposeSecret;
 use std::str::FromStr;
 
+use crate::core::log::target;
 use crate::{
-    core::{error::AppError, enums::UserRole},
-    dtos::auth::Claims,
+    core::{error::AppError, enums::{SecurityEventKind, UserRole}},
+    extractors::claims::{request_claims, TokenError},
+    services::{
+        auth::{blacklist_key, token_version_key, user_revoked_key},
+        security::{self as SecurityService, SecurityEvent},
+    },
     state::AppState,
 };
 
@@ -24,6 +27,8 @@ use crate::{
 /// # 功能说明
 /// - 从请求头中提取Bearer令牌
 /// - 检查Redis黑名单，判断令牌是否已被撤销
+/// - 检查用户级吊销记录，判断令牌是否在用户被强制下线（如封禁）之前签发
+/// - 检查令牌版本，判断签发后用户的角色或状态是否已被变更
 /// - 如果令牌已被撤销，返回401 Unauthorized错误
 ///
 /// # 参数
@@ -36,7 +41,7 @@ use crate::{
 /// - `Err(AppError)`: 令牌已被撤销，返回认证错误
 pub async fn check_token_revocation(
     State(state): State<AppState>,
-    req: Request,
+    mut req: Request,
     next: Next,
 ) -> Result<Response, AppError> {
     // 从请求头中提取Authorization字段的值，并解析出Bearer令牌
@@ -52,35 +57,70 @@ pub async fn check_token_revocation(
         return Ok(next.run(req).await);
     };
 
-    // 构建Redis黑名单键，格式为 "blacklist:token:{token_string}"
-    let redis_key = format!("blacklist:token:{}", token_str);
+    // 构建Redis黑名单键，与登出时写入的键保持一致
+    let redis_key = blacklist_key(token_str);
     let mut redis_conn = state.redis.clone();
 
     // 检查令牌是否在黑名单中
     let is_blacklisted: bool = redis_conn
         .exists(&redis_key)
         .await
-        .map_err(|e| AppError::RedisError(e))?;
+        .map_err(AppError::RedisError)?;
 
     if is_blacklisted {
-        tracing::warn!("🚫 Blocked blacklisted token");
+        tracing::warn!(target: target::AUTH, "🚫 Blocked blacklisted token");
+        SecurityService::record(
+            &state,
+            SecurityEvent::from_extensions(req.extensions(), SecurityEventKind::RevokedToken).detail("logged out"),
+        );
         return Err(AppError::AuthError("Token has been revoked".to_string()));
     }
 
+    // 检查用户级吊销：在吊销时间点之前签发的令牌全部失效。两者都是秒级时间戳，吊销同一秒内签发的令牌
+    // （如重置密码后立即登录）不受影响；同一秒内吊销前签发的令牌由随吊销递增的令牌版本拒绝。
+    // 无法解码的令牌交给后续的 Claims 提取器处理，这里不重复报错。
+    if let Ok(claims) = request_claims(&state, &mut req) {
+        // 吊销时间和令牌版本一次读取，避免额外的 Redis 往返
+        let (revoked_at, token_version): (Option<i64>, Option<u64>) = redis::cmd("MGET")
+            .arg(user_revoked_key(&claims.sub))
+            .arg(token_version_key(&claims.sub))
+            .query_async(&mut redis_conn)
+            .await
+            .map_err(AppError::RedisError)?;
+
+        if revoked_at.is_some_and(|revoked_at| (claims.iat as i64) < revoked_at) {
+            tracing::warn!(target: target::AUTH, "🚫 Blocked token issued before user revocation: {}", claims.username);
+            SecurityService::record(
+                &state,
+                SecurityEvent::from_extensions(req.extensions(), SecurityEventKind::RevokedToken)
+                    .user(&claims.sub)
+                    .subject(claims.username.clone())
+                    .detail("issued before user revocation"),
+            );
+            return Err(AppError::AuthError("Token has been revoked".to_string()));
+        }
+
+        // 令牌版本落后：签发后用户的角色或状态已被管理员变更，需要刷新令牌以获取最新的角色
+        if token_version.is_some_and(|version| claims.ver < version) {
+            tracing::info!(target: target::AUTH, "🔁 Rejected outdated token version for {}", claims.username);
+            return Err(AppError::AuthError("Token is outdated, please refresh".to_string()));
+        }
+    }
+
     // 令牌未被撤销，继续处理请求
     Ok(next.run(req).await)
 }
 
-/// 管理员权限守卫中间件。验证请求中的用户是否具有管理员权限。
+/// 管理员权限守卫中间件。验证请求中的用户是否具有管理员或更高权限。
 ///
-/// 这个中间件用于保护需要管理员权限的端点，确保只有具有Admin角色的用户才能访问。
+/// 这个中间件用于保护需要管理员权限的端点，确保只有Admin及以上角色（如SuperAdmin）的用户才能访问。
 /// 与 check_token_revocation 不同，这个中间件要求请求必须携带有效的令牌。
 ///
 /// # 功能说明
 /// - 从请求头中提取并验证Bearer令牌
 /// - 解码JWT并获取用户角色信息
-/// - 检查用户角色是否为Admin
-/// - 如果不是管理员，返回403 Forbidden错误
+/// - 检查用户角色是否不低于Admin
+/// - 如果权限不足，返回403 Forbidden错误
 ///
 /// # 参数
 /// - `state`: 应用程序状态，包含JWT密钥
@@ -88,38 +128,55 @@ pub async fn check_token_revocation(
 /// - `next`: 下一个中间件或处理器的调用链
 ///
 /// # 返回值
-/// - `Ok(Response)`: 用户是管理员，继续处理请求
+/// - `Ok(Response)`: 用户是管理员或更高角色，继续处理请求
 /// - `Err(AppError)`: 无管理员权限，返回403 Forbidden错误
 pub async fn admin_guard(
     State(state): State<AppState>,
-    req: Request,
+    mut req: Request,
     next: Next,
 ) -> Result<Response, AppError> {
-    // 提取Bearer令牌。如果没有令牌，直接返回认证错误
-    let token = req
-        .headers()
-        .get(header::AUTHORIZATION)
-        .and_then(|value| value.to_str().ok())
-        .and_then(|value| value.strip_prefix("Bearer "))
-        .ok_or(AppError::AuthError("Missing token".to_string()))?;
+    require_role(&state, &mut req, UserRole::Admin)?;
+    Ok(next.run(req).await)
+}
 
-    // 使用JWT密钥创建解码密钥
-    let secret = state.config.jwt_secret.expose_secret().as_bytes();
-    let decoding_key = DecodingKey::from_secret(secret);
+/// 角色校验的通用逻辑：解码请求中的JWT令牌，并检查用户角色是否不低于 `required`。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含JWT密钥
+/// - `req`: HTTP请求
+/// - `required`: 访问所需的最低角色
+///
+/// # 返回值
+/// - `Ok(())`: 用户角色满足要求
+/// - `Err(AppError)`: 令牌缺失/无效（401）或角色不足（403）
+fn require_role(state: &AppState, req: &mut Request, required: UserRole) -> Result<(), AppError> {
+    // 复用本请求已解码的令牌（令牌撤销检查已经解码过），没有时再解码
+    let claims = request_claims(state, req).map_err(|e| match e {
+        TokenError::Missing => AppError::AuthError("Missing token".to_string()),
+        TokenError::Invalid => AppError::AuthError("Invalid token".to_string()),
+    })?;
 
-    // 解码并验证JWT令牌
-    let token_data = decode::<Claims>(token, &decoding_key, &Validation::default())
-        .map_err(|_| AppError::AuthError("Invalid token".to_string()))?;
+    // 委托令牌只能在委托范围内访问授权人的资源，不能访问任何需要角色的端点
+    if let Some(actor) = &claims.act {
+        tracing::warn!(target: target::AUTH, "🚫 Access denied: delegated token of {} (requires {})", actor.username, required);
+        return Err(AppError::Forbidden("Delegated tokens cannot access this endpoint".to_string()));
+    }
 
     // 将字符串角色转换为UserRole枚举。如果转换失败，默认为User角色
-    let role_enum = UserRole::from_str(&token_data.claims.role).unwrap_or(UserRole::User);
+    let role_enum = UserRole::from_str(&claims.role).unwrap_or(UserRole::User);
 
-    // 检查用户是否具有管理员权限
-    if role_enum != UserRole::Admin {
-        tracing::warn!("🚫 Admin access denied: {}", token_data.claims.username);
-        return Err(AppError::Forbidden("Requires Administrator privileges".to_string()));
+    // 检查用户角色是否满足 "该角色或更高" 的要求
+    if !role_enum.at_least(&required) {
+        tracing::warn!(target: target::AUTH, "🚫 Access denied: {} (requires {})", claims.username, required);
+        SecurityService::record(
+            state,
+            SecurityEvent::from_extensions(req.extensions(), SecurityEventKind::AccessDenied)
+                .user(&claims.sub)
+                .subject(claims.username.clone())
+                .detail(format!("requires {}", required)),
+        );
+        return Err(AppError::Forbidden(format!("Requires {} privileges", required)));
     }
 
-    // 用户是管理员，继续处理请求
-    Ok(next.run(req).await)
-}
\ No newline at end of file
+    Ok(())
+}
@@ -0,0 +1,36 @@
+// src/middleware/breaker.rs
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{
+    core::{enums::Dependency, error::AppError},
+    state::AppState,
+};
+
+/// 依赖熔断中间件。路由组在 `routes.rs` 中声明自己依赖的下游服务，
+/// 任一依赖的熔断器处于打开状态时直接返回 503，不再进入处理器等待数据库或Redis超时。
+///
+/// 未挂载该中间件的路由（如 `/health`、`/metrics`、静态文件）不受熔断影响。
+///
+/// # 返回值
+/// - `Ok(Response)`: 依赖服务均可用，继续执行后续处理
+/// - `Err(AppError::ServiceUnavailable)`: 至少一个依赖服务已熔断
+pub async fn require_dependencies(
+    State((state, dependencies)): State<(AppState, &'static [Dependency])>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if let Some(dependency) = state.breakers.first_open(dependencies) {
+        metrics::counter!("breaker_rejections_total", "dependency" => dependency.to_string())
+            .increment(1);
+        return Err(AppError::ServiceUnavailable(format!(
+            "{} is temporarily unavailable, please retry later",
+            dependency
+        )));
+    }
+
+    Ok(next.run(req).await)
+}
@@ -0,0 +1,45 @@
+// src/middleware/concurrency.rs
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::sync::Semaphore;
+
+use crate::core::log::target;
+use crate::core::{constants::CONCURRENCY_RETRY_AFTER, error::AppError};
+
+/// 单个路由的并发上限。每个实例独立计数，多实例部署时总并发为上限乘以实例数。
+#[derive(Debug, Clone)]
+pub struct RouteConcurrency {
+    /// 路由名称，用作指标标签和日志
+    name: &'static str,
+    semaphore: Arc<Semaphore>,
+}
+
+impl RouteConcurrency {
+    pub fn new(name: &'static str, max_in_flight: usize) -> Self {
+        Self { name, semaphore: Arc::new(Semaphore::new(max_in_flight.max(1))) }
+    }
+}
+
+/// 路由并发限制中间件。用于导出、批量操作等占用数据库连接较久的路由，
+/// 同时处理中的请求达到上限时不排队，直接返回 503 并带上 `Retry-After`，避免耗尽数据库连接池。
+///
+/// 许可在处理器返回时归还；流式响应体的发送不计入并发。
+pub async fn limit(State(limit): State<RouteConcurrency>, req: Request, next: Next) -> Response {
+    let Ok(_permit) = limit.semaphore.clone().try_acquire_owned() else {
+        metrics::counter!("route_concurrency_rejections_total", "route" => limit.name).increment(1);
+        tracing::warn!(target: target::LIMITER, "🚦 Route {} saturated, request rejected", limit.name);
+
+        let mut response =
+            AppError::ServiceUnavailable("Too many concurrent requests, please retry later".to_string()).into_response();
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(CONCURRENCY_RETRY_AFTER));
+        return response;
+    };
+
+    next.run(req).await
+}
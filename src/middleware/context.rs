@@ -0,0 +1,19 @@
+// src/middleware/context.rs
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{extractors::context::RequestContext, state::AppState};
+
+/// 请求上下文中间件。在请求进入时收集请求ID、调用方身份、语言、来源IP和截止时间，
+/// 作为 `RequestContext` 放入请求扩展，供后续中间件、处理器和服务函数共享。
+///
+/// 需要位于请求ID中间件和超时中间件之内，才能读取到请求ID和截止时间。
+pub async fn attach(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let (mut parts, body) = req.into_parts();
+    let context = RequestContext::from_parts(&state, &mut parts).await;
+    parts.extensions.insert(context);
+    next.run(Request::from_parts(parts, body)).await
+}
@@ -0,0 +1,69 @@
+// src/middleware/delegation.rs
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::core::log::target;
+use crate::{
+    core::{
+        enums::{Permission, SecurityEventKind},
+        error::AppError,
+    },
+    extractors::claims::request_claims,
+    services::{
+        delegation as DelegationService,
+        security::{self as SecurityService, SecurityEvent},
+    },
+    state::AppState,
+};
+
+/// 委托令牌范围检查中间件。普通令牌直接放行；委托令牌（带 `act` 声明）需要满足：
+/// - 委托未被撤销
+/// - 读取请求（GET/HEAD）需要 `read_self` 范围，其余请求需要 `update_self` 范围
+///
+/// 必须位于 `check_token_revocation` 之内，授权人被吊销令牌时由后者拒绝。
+///
+/// # 返回值
+/// - `Ok(Response)`: 普通令牌或委托范围满足要求，继续处理请求
+/// - `Err(AppError)`: 委托已撤销（401）或超出委托范围（403）
+pub async fn enforce_scope(State(state): State<AppState>, mut req: Request, next: Next) -> Result<Response, AppError> {
+    let Some(actor) = request_claims(&state, &mut req).ok().and_then(|claims| claims.act) else {
+        return Ok(next.run(req).await);
+    };
+
+    if DelegationService::is_revoked(&state, &actor.grant).await? {
+        tracing::warn!(target: target::AUTH, "🚫 Blocked token of revoked delegation {}", actor.grant);
+        SecurityService::record(
+            &state,
+            SecurityEvent::from_extensions(req.extensions(), SecurityEventKind::RevokedToken)
+                .detail(format!("delegation {} revoked", actor.grant)),
+        );
+        return Err(AppError::AuthError("Delegation has been revoked".to_string()));
+    }
+
+    let required = match *req.method() {
+        Method::GET | Method::HEAD => Permission::ReadSelf,
+        _ => Permission::UpdateSelf,
+    };
+    if !actor.scopes.contains(&required) {
+        tracing::warn!(target: target::AUTH, "🚫 Delegated token of {} lacks scope {}", actor.username, required);
+        return Err(AppError::Forbidden(format!("Delegation does not include {}", required)));
+    }
+
+    Ok(next.run(req).await)
+}
+
+/// 拒绝委托令牌的中间件，挂在不能委托的路由上（冻结账户、管理委托、导出个人数据、确认设备授权等），
+/// 这些操作只能由账户所有者本人执行。
+pub async fn deny_delegated(State(state): State<AppState>, mut req: Request, next: Next) -> Result<Response, AppError> {
+    if let Ok(claims) = request_claims(&state, &mut req)
+        && let Some(actor) = claims.act
+    {
+        tracing::warn!(target: target::AUTH, "🚫 Delegated token of {} used on owner-only route", actor.username);
+        return Err(AppError::Forbidden("This action cannot be performed with a delegated token".to_string()));
+    }
+    Ok(next.run(req).await)
+}
@@ -0,0 +1,84 @@
+// src/middleware/deprecation.rs
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::core::log::target;
+use crate::utils::deprecation::{self, Deprecation};
+
+/// 客户端标识请求头，未提供时使用 `User-Agent` 的第一段（如 "okhttp/4.12"）
+const CLIENT_HEADER: &str = "x-client-id";
+
+/// 作为指标标签的已知客户端名称（小写）。客户端标识由调用方填写，直接用作标签会使指标基数不受控制，
+/// 因此只取 "/" 之前的名称与列表比较，其余一律记为 "other"。接入新的官方客户端时在这里添加。
+const KNOWN_CLIENTS: &[&str] = &["web", "ios", "android", "cli", "mozilla", "okhttp", "curl"];
+
+fn client_name(headers: &HeaderMap) -> &'static str {
+    headers
+        .get(CLIENT_HEADER)
+        .or_else(|| headers.get(header::USER_AGENT))
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_whitespace().next())
+        .and_then(|value| value.split('/').next())
+        .and_then(|name| KNOWN_CLIENTS.iter().find(|known| known.eq_ignore_ascii_case(name)).copied())
+        .unwrap_or("other")
+}
+
+/// 弃用追踪中间件。收集请求处理期间使用到的弃用端点和字段，
+/// 在响应中添加 `Deprecation`、`Sunset`、`Warning` 头，并按客户端记录使用次数。
+pub async fn track(req: Request, next: Next) -> Response {
+    let client = client_name(req.headers());
+    let (mut response, used) = deprecation::scope(next.run(req)).await;
+
+    if used.is_empty() {
+        return response;
+    }
+
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+
+    for item in used {
+        metrics::counter!(
+            "deprecated_api_usage_total",
+            "feature" => item.feature,
+            "client" => client,
+        )
+        .increment(1);
+        tracing::debug!(target: target::HTTP, "🕰️ Deprecated API used by {}: {}", client, item.feature);
+
+        if let Some(sunset) = item.sunset.and_then(|value| HeaderValue::from_str(value).ok()) {
+            headers.insert("sunset", sunset);
+        }
+
+        let message = match item.replacement {
+            Some(replacement) => format!(
+                "299 - \"{} is deprecated since {}, use {} instead\"",
+                item.feature, item.since, replacement
+            ),
+            None => format!("299 - \"{} is deprecated since {}\"", item.feature, item.since),
+        };
+        if let Ok(value) = HeaderValue::from_str(&message) {
+            headers.append(header::WARNING, value);
+        }
+    }
+
+    response
+}
+
+/// 路由级弃用标记中间件，把整个端点标记为弃用：
+///
+/// ```ignore
+/// .route("/old", post(handler).layer(middleware::from_fn_with_state(&OLD_ENDPOINT, deprecated)))
+/// ```
+#[allow(dead_code)]
+pub async fn deprecated(
+    State(deprecation): State<&'static Deprecation>,
+    req: Request,
+    next: Next,
+) -> Response {
+    deprecation::record(deprecation);
+    next.run(req).await
+}
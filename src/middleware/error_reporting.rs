@@ -0,0 +1,43 @@
+// src/middleware/error_reporting.rs
+use std::sync::Arc;
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use sentry::{Hub, SentryFutureExt};
+
+use crate::{core::reporting, extractors::context::RequestContext};
+
+/// 错误上报作用域中间件。为每个请求创建独立的上报作用域，写入请求ID、路由和用户ID，
+/// 请求处理期间上报的错误和 panic 都会带上这些信息，可以直接对应到访问日志。
+///
+/// 需要位于请求上下文中间件之内，才能读取到 `RequestContext`。未启用错误上报时直接放行。
+pub async fn bind_scope(req: Request, next: Next) -> Response {
+    if !reporting::enabled() {
+        return next.run(req).await;
+    }
+
+    let hub = Arc::new(Hub::new_from_top(Hub::current()));
+    let route = req.extensions().get::<MatchedPath>().map(|path| path.as_str().to_string());
+    let context = req.extensions().get::<RequestContext>();
+    let request_id = context.and_then(|ctx| ctx.request_id.clone());
+    let user_id = context.and_then(|ctx| ctx.claims.as_ref()).map(|claims| claims.sub.clone());
+    let method = req.method().to_string();
+
+    hub.configure_scope(|scope| {
+        scope.set_tag("http.method", method);
+        if let Some(route) = route {
+            scope.set_tag("route", route);
+        }
+        if let Some(request_id) = request_id {
+            scope.set_tag("request_id", request_id);
+        }
+        if let Some(user_id) = user_id {
+            scope.set_user(Some(sentry::User { id: Some(user_id), ..Default::default() }));
+        }
+    });
+
+    next.run(req).bind_hub(hub).await
+}
@@ -0,0 +1,339 @@
+// src/middleware/idempotency.rs
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{FromRequestParts, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use futures_util::{stream, StreamExt};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::core::log::target;
+use crate::{
+    core::{
+        constants::{IDEMPOTENCY_EXPIRE, IDEMPOTENCY_LOCK_EXPIRE, IDEMPOTENCY_MAX_RESPONSE_BYTES},
+        error::AppError,
+    },
+    extractors::{claims::resolve_claims, client_ip::ClientIp},
+    state::AppState,
+    utils::nonce,
+};
+
+/// 幂等键请求头
+const HEADER: &str = "idempotency-key";
+
+/// 回放的响应上附加的标记头
+const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// 幂等键的最大长度
+const MAX_KEY_LEN: usize = 255;
+
+/// 幂等键在一次性标识中的命名空间（见 `nonce::claim`），值为处理状态或首次响应（JSON）
+const NONCE_NAMESPACE: &str = "idempotency";
+
+/// 随响应一起保存、回放时恢复的响应头。回放的响应应与首次响应一致，
+/// 包括登录类接口设置的 Cookie 和限流信息
+const REPLAYED_HEADERS: &[&str] = &[
+    "content-type",
+    "location",
+    "set-cookie",
+    "x-ratelimit-limit",
+    "x-ratelimit-remaining",
+    "x-ratelimit-reset",
+];
+
+/// 幂等键在 Redis 中保存的状态
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+enum Entry {
+    /// 首次请求正在处理
+    Processing { fingerprint: String },
+    /// 首次请求已完成，保存其响应供重复提交时回放
+    Completed {
+        fingerprint: String,
+        status: u16,
+        /// `REPLAYED_HEADERS` 中的响应头，同名的多个值（如 Set-Cookie）按顺序保存
+        headers: Vec<(String, String)>,
+        body: String,
+    },
+}
+
+impl Entry {
+    fn fingerprint(&self) -> &str {
+        match self {
+            Entry::Processing { fingerprint } | Entry::Completed { fingerprint, .. } => fingerprint,
+        }
+    }
+}
+
+/// 请求指纹：方法、路径和请求体的 SHA-256（十六进制），用于发现同一个幂等键被用于不同的请求。
+/// 各部分之间以 0 字节分隔，避免路径和请求体的拼接产生歧义；结果跨进程、跨版本稳定。
+fn fingerprint(method: &Method, path: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str().as_bytes());
+    hasher.update([0]);
+    hasher.update(path.as_bytes());
+    hasher.update([0]);
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+/// 可以缓存回放的状态码：2xx 和结果确定的 4xx（请求本身有误，原样重试结果不变）。
+/// 429、408、401 等与时机或令牌状态有关的 4xx 以及全部 5xx 不缓存，客户端可以使用同一个键重试。
+fn is_cacheable(status: StatusCode) -> bool {
+    status.is_success() || matches!(status.as_u16(), 400 | 403 | 404 | 405 | 409 | 410 | 413 | 415 | 422)
+}
+
+/// 提取需要随响应保存的响应头
+fn replayed_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    REPLAYED_HEADERS
+        .iter()
+        .flat_map(|name| {
+            headers
+                .get_all(*name)
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .map(|value| (name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// 读取响应体用于缓存。超过 `limit` 或读取出错时返回 `Err`，其中的响应体依次包含已读取的部分和剩余部分，
+/// 原样转发给客户端——处理器已经执行完毕，不能因为响应无法缓存而把成功的结果变成错误。
+async fn buffer_body(body: Body, limit: usize) -> Result<Bytes, Body> {
+    let mut chunks = body.into_data_stream();
+    let mut buffered = Vec::new();
+    while let Some(chunk) = chunks.next().await {
+        match chunk {
+            Ok(chunk) if buffered.len() + chunk.len() <= limit => buffered.extend_from_slice(&chunk),
+            Ok(chunk) => {
+                let head = stream::iter([Ok(Bytes::from(buffered)), Ok(chunk)]);
+                return Err(Body::from_stream(head.chain(chunks)));
+            }
+            Err(e) => return Err(Body::from_stream(stream::iter([Ok(Bytes::from(buffered)), Err(e)]))),
+        }
+    }
+    Ok(Bytes::from(buffered))
+}
+
+/// 幂等键中间件。POST 请求携带 `Idempotency-Key` 时，首次请求的响应保存在 Redis 中，
+/// 有效期内使用同一个键的重复提交直接回放该响应，不会再次执行处理器（如重复注册、重复扣款）。
+///
+/// - 幂等键按调用方隔离（已登录用户按用户ID，匿名请求按客户端IP），不同调用方的相同键互不影响
+/// - 首次请求仍在处理时，重复提交返回 409
+/// - 同一个键用于不同的请求（路径或请求体不同）时返回 409
+/// - 只缓存 2xx 和结果确定的 4xx（见 `is_cacheable`），其余响应不缓存，客户端可以使用同一个键重试
+/// - 响应体超过 `IDEMPOTENCY_MAX_RESPONSE_BYTES` 或不是文本时原样返回、不缓存
+/// - 文件上传（multipart）请求不参与幂等处理
+///
+/// # 返回值
+/// - `Ok(Response)`: 处理器的响应，或回放的首次响应（带 `Idempotent-Replayed: true`）
+/// - `Err(AppError)`: 幂等键不合法、首次请求处理中或请求不一致
+pub async fn enforce(State(state): State<AppState>, req: Request, next: Next) -> Result<Response, AppError> {
+    // 文件上传请求体较大且有自己的大小上限，不参与幂等处理
+    let is_multipart = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("multipart/"));
+    if req.method() != Method::POST || is_multipart {
+        return Ok(next.run(req).await);
+    }
+    let Some(key) = req
+        .headers()
+        .get(HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+    else {
+        return Ok(next.run(req).await);
+    };
+    if key.is_empty() || key.len() > MAX_KEY_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Idempotency-Key must be 1-{} characters",
+            MAX_KEY_LEN
+        )));
+    }
+
+    // 第一步：确定调用方标识并计算请求指纹
+    let (mut parts, body) = req.into_parts();
+    let caller = resolve_claims(&state, &parts.headers, &mut parts.extensions)
+        .ok()
+        .map(|claims| format!("user:{}", claims.sub));
+    let caller = match caller {
+        Some(caller) => caller,
+        None => {
+            let ClientIp(ip) = ClientIp::from_request_parts(&mut parts, &state).await?;
+            format!("ip:{}", ip)
+        }
+    };
+
+    let body = to_bytes(body, state.config.body_limit_bytes)
+        .await
+        .map_err(|_| AppError::PayloadTooLarge("Request body is too large".to_string()))?;
+    let fingerprint = fingerprint(&parts.method, parts.uri.path(), &body);
+    let scoped_key = format!("{}:{}", caller, key);
+    let redis_key = nonce::key(NONCE_NAMESPACE, &scoped_key);
+
+    // 第二步：原子地占用幂等键。占用失败说明之前已有请求使用过该键
+    let mut redis = state.redis.clone();
+    let processing = serde_json::to_string(&Entry::Processing { fingerprint: fingerprint.clone() })
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+    let acquired = nonce::claim(&state.redis, NONCE_NAMESPACE, &scoped_key, &processing, IDEMPOTENCY_LOCK_EXPIRE).await?;
+
+    if !acquired {
+        let existing: Option<String> = redis.get(&redis_key).await?;
+        let entry = existing.and_then(|value| serde_json::from_str::<Entry>(&value).ok());
+        return match entry {
+            Some(entry) if entry.fingerprint() != fingerprint.as_str() => Err(AppError::Conflict(
+                "Idempotency-Key was already used for a different request".to_string(),
+            )),
+            Some(Entry::Completed { status, headers, body, .. }) => {
+                tracing::debug!(target: target::HTTP, "🔁 Replaying idempotent response: {}", key);
+                Ok(replay(status, headers, body))
+            }
+            _ => Err(AppError::Conflict(
+                "A request with this Idempotency-Key is still being processed".to_string(),
+            )),
+        };
+    }
+
+    // 第三步：执行处理器，并保存可回放的响应
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let status = response.status();
+
+    if !is_cacheable(status) {
+        let _: () = redis.del(&redis_key).await.unwrap_or_default();
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match buffer_body(body, IDEMPOTENCY_MAX_RESPONSE_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(body) => {
+            // 响应体过大或读取失败：无法回放，释放幂等键，响应原样返回
+            tracing::debug!(target: target::HTTP, "⚠️ Idempotent response not cached, body too large: {}", key);
+            let _: () = redis.del(&redis_key).await.unwrap_or_default();
+            return Ok(Response::from_parts(parts, body));
+        }
+    };
+
+    match std::str::from_utf8(&bytes) {
+        Ok(text) => {
+            let entry = Entry::Completed {
+                fingerprint,
+                status: status.as_u16(),
+                headers: replayed_headers(&parts.headers),
+                body: text.to_string(),
+            };
+            if let Ok(value) = serde_json::to_string(&entry)
+                && let Err(e) = redis.set_ex::<_, _, ()>(&redis_key, value, IDEMPOTENCY_EXPIRE).await
+            {
+                tracing::warn!(target: target::HTTP, "⚠️ Failed to store idempotent response: {}", e);
+            }
+        }
+        // 非文本响应不缓存，释放幂等键
+        Err(_) => {
+            let _: () = redis.del(&redis_key).await.unwrap_or_default();
+        }
+    }
+
+    Ok(Response::from_parts(parts, Body::from(bytes)))
+}
+
+fn replay(status: u16, headers: Vec<(String, String)>, body: String) -> Response {
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::OK);
+    let mut response = (status, body).into_response();
+    let replayed = response.headers_mut();
+    let mut seen: Vec<HeaderName> = Vec::new();
+    for (name, value) in headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+            // 首次出现时替换默认值（如 Content-Type），同名的后续值追加（如多个 Set-Cookie）
+            if seen.contains(&name) {
+                replayed.append(name, value);
+            } else {
+                seen.push(name.clone());
+                replayed.insert(name, value);
+            }
+        }
+    }
+    replayed.insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint_is_stable_and_separates_parts() {
+        let first = fingerprint(&Method::POST, "/auth/register", br#"{"username":"alice"}"#);
+        assert_eq!(first, fingerprint(&Method::POST, "/auth/register", br#"{"username":"alice"}"#));
+        assert_eq!(first.len(), 64);
+
+        assert_ne!(first, fingerprint(&Method::POST, "/auth/register", br#"{"username":"bob"}"#));
+        assert_ne!(first, fingerprint(&Method::PUT, "/auth/register", br#"{"username":"alice"}"#));
+        // 路径和请求体之间有分隔符，移动边界不会得到相同的指纹
+        assert_ne!(fingerprint(&Method::POST, "/a", b"bc"), fingerprint(&Method::POST, "/ab", b"c"));
+    }
+
+    #[test]
+    fn only_deterministic_statuses_are_cacheable() {
+        for status in [
+            StatusCode::OK,
+            StatusCode::CREATED,
+            StatusCode::BAD_REQUEST,
+            StatusCode::CONFLICT,
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ] {
+            assert!(is_cacheable(status), "{status} should be cached");
+        }
+        for status in [
+            StatusCode::UNAUTHORIZED,
+            StatusCode::REQUEST_TIMEOUT,
+            StatusCode::TOO_MANY_REQUESTS,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::SERVICE_UNAVAILABLE,
+        ] {
+            assert!(!is_cacheable(status), "{status} should not be cached");
+        }
+    }
+
+    #[test]
+    fn replay_restores_allowlisted_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.append(header::SET_COOKIE, HeaderValue::from_static("refresh=a; HttpOnly"));
+        headers.append(header::SET_COOKIE, HeaderValue::from_static("theme=dark"));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("3"));
+        headers.insert("x-request-id", HeaderValue::from_static("not-replayed"));
+
+        let response = replay(201, replayed_headers(&headers), "{}".to_string());
+        let replayed = response.headers();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(replayed.get(header::CONTENT_TYPE).unwrap(), "application/json");
+        let cookies: Vec<_> = replayed.get_all(header::SET_COOKIE).iter().collect();
+        assert_eq!(cookies, ["refresh=a; HttpOnly", "theme=dark"]);
+        assert_eq!(replayed.get("x-ratelimit-remaining").unwrap(), "3");
+        assert_eq!(replayed.get(REPLAYED_HEADER).unwrap(), "true");
+        assert!(replayed.get("x-request-id").is_none());
+    }
+
+    #[tokio::test]
+    async fn oversized_body_is_passed_through_intact() {
+        let chunks = ["first-", "second-", "third"].map(|chunk| Ok::<_, std::io::Error>(Bytes::from(chunk)));
+        let body = Body::from_stream(stream::iter(chunks));
+
+        let Err(passthrough) = buffer_body(body, 8).await else {
+            panic!("body over the limit must not be buffered");
+        };
+        let bytes = to_bytes(passthrough, usize::MAX).await.expect("read passthrough body");
+        assert_eq!(&bytes[..], b"first-second-third");
+
+        let small = buffer_body(Body::from("ok"), 8).await.expect("body within the limit");
+        assert_eq!(&small[..], b"ok");
+    }
+}
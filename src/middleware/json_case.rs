@@ -0,0 +1,120 @@
+// src/middleware/json_case.rs
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, Uri},
+    middleware::Next,
+    response::Response,
+};
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+use crate::{
+    core::{enums::JsonCase, error::AppError},
+    state::AppState,
+    utils::json_case,
+};
+
+/// 查询参数名重新编码时保留原样的字符：字母数字之外只有 RFC 3986 的非保留字符
+const QUERY_KEY_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+/// 把查询字符串中的参数名转换为 snake_case，参数值原样保留。
+///
+/// 参数名先做百分号解码再转换（客户端可能把 `perPage` 编码为 `per%50age`），转换后重新编码。
+fn normalize_query(query: &str) -> String {
+    let convert = |key: &str| {
+        let decoded = percent_decode_str(key).decode_utf8_lossy();
+        utf8_percent_encode(&json_case::camel_to_snake(&decoded), QUERY_KEY_ENCODE_SET).to_string()
+    };
+    query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => format!("{}={}", convert(key), value),
+            None => convert(pair),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// 请求命名风格规范化中间件。当配置为 camelCase 时，把请求中的 JSON 请求体字段名
+/// 和查询参数名转换为 snake_case，使处理器中的 DTO 无需任何改动即可正常反序列化。
+///
+/// snake_case 模式（默认）下直接放行，不会读取请求体。读取请求体时的上限与全局
+/// `body_limit_bytes` 一致，不会拒绝本可以被处理器接受的请求。
+///
+/// # 返回值
+/// - `Ok(Response)`: 请求已规范化（或无需处理），继续执行后续处理
+/// - `Err(AppError)`: 请求体读取失败或不是合法的JSON
+pub async fn normalize_request_case(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if json_case::current() != JsonCase::Camel {
+        return Ok(next.run(req).await);
+    }
+
+    let (mut parts, body) = req.into_parts();
+
+    // 第一步：转换查询参数名，如 ?perPage=10 -> ?per_page=10
+    if let Some(query) = parts.uri.query() {
+        let path_and_query = format!("{}?{}", parts.uri.path(), normalize_query(query));
+        let mut uri_parts = parts.uri.clone().into_parts();
+        uri_parts.path_and_query = path_and_query.parse().ok();
+        if let Ok(uri) = Uri::from_parts(uri_parts) {
+            parts.uri = uri;
+        }
+    }
+
+    // 第二步：转换 JSON 请求体的字段名。非 JSON 请求体（如文件上传）原样放行。
+    let is_json = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+
+    if !is_json {
+        return Ok(next.run(Request::from_parts(parts, body)).await);
+    }
+
+    // 超出上限是读取失败的主要原因，与提取器保持一致返回 413
+    let bytes = to_bytes(body, state.config.body_limit_bytes)
+        .await
+        .map_err(|_| AppError::PayloadTooLarge("Request body is too large".to_string()))?;
+
+    let body = if bytes.is_empty() {
+        Body::empty()
+    } else {
+        // 无法解析的请求体原样传递，由处理器的 JSON 提取器返回具体的错误
+        match serde_json::from_slice(&bytes) {
+            Ok(value) => {
+                let value = json_case::convert_keys(value, json_case::camel_to_snake);
+                parts.headers.remove(header::CONTENT_LENGTH);
+                Body::from(serde_json::to_vec(&value).unwrap_or_else(|_| bytes.to_vec()))
+            }
+            Err(_) => Body::from(bytes),
+        }
+    };
+
+    Ok(next.run(Request::from_parts(parts, body)).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_query_keys_only() {
+        assert_eq!(normalize_query("perPage=10&sortBy=createdAt"), "per_page=10&sort_by=createdAt");
+        assert_eq!(normalize_query("includeDeleted"), "include_deleted");
+        assert_eq!(normalize_query("q=a%20b&page=2"), "q=a%20b&page=2");
+    }
+
+    #[test]
+    fn decodes_query_keys_before_converting() {
+        assert_eq!(normalize_query("per%50age=10"), "per_page=10");
+        assert_eq!(normalize_query("sort%42y=name"), "sort_by=name");
+        // 解码后仍需编码的字符重新编码，不会破坏查询字符串的结构
+        assert_eq!(normalize_query("a%26b=1"), "a%26b=1");
+    }
+}
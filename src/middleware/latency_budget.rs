@@ -0,0 +1,70 @@
+// src/middleware/latency_budget.rs
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use tokio::time::Instant;
+
+use crate::core::log::target;
+use crate::utils::request_id::RequestId;
+
+/// 当前请求的响应时间预算（毫秒）。由路由组的 `watch` 放入请求扩展，单个路由可以用 `override_budget` 调整。
+#[derive(Clone)]
+struct LatencyBudget(Arc<AtomicU64>);
+
+/// 响应时间预算检查中间件。路由组在 `routes.rs` 中声明预算，请求处理完成后比较实际耗时：
+/// 超出预算时记录 `latency_budget_violations_total` 指标并输出带请求ID的结构化警告，
+/// 在 SLO 被突破之前发现变慢的接口。
+///
+/// 所有请求都会记录 `latency_budget_ratio`（实际耗时 / 预算），便于观察接口距离预算还有多少余量。
+pub async fn watch(State(default_budget): State<Duration>, mut req: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let budget = Arc::new(AtomicU64::new(default_budget.as_millis() as u64));
+    req.extensions_mut().insert(LatencyBudget(budget.clone()));
+
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| req.uri().path().to_string());
+    let request_id = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+
+    let response = next.run(req).await;
+
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    let budget_ms = budget.load(Ordering::Relaxed).max(1);
+    metrics::histogram!("latency_budget_ratio", "route" => route.clone())
+        .record(elapsed_ms as f64 / budget_ms as f64);
+
+    if elapsed_ms > budget_ms {
+        metrics::counter!("latency_budget_violations_total", "route" => route.clone()).increment(1);
+        tracing::warn!(
+            target: target::HTTP,
+            route = %route,
+            request_id = request_id.as_deref().unwrap_or_default(),
+            elapsed_ms,
+            budget_ms,
+            status = response.status().as_u16(),
+            "🐢 Latency budget exceeded"
+        );
+    }
+
+    response
+}
+
+/// 路由级预算覆盖：为单个路由设置不同于路由组的预算（如文件上传、批量操作）。
+pub async fn override_budget(State(budget): State<Duration>, req: Request, next: Next) -> Response {
+    if let Some(LatencyBudget(current)) = req.extensions().get::<LatencyBudget>() {
+        current.store(budget.as_millis() as u64, Ordering::Relaxed);
+    }
+    next.run(req).await
+}
@@ -0,0 +1,34 @@
+// src/middleware/maintenance.rs
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{core::error::AppError, state::AppState};
+
+/// 维护期间仍然可以访问的路径：健康检查与探针，以及用于关闭维护模式的管理端开关。
+const MAINTENANCE_EXEMPT_PATHS: &[&str] = &["/health", "/healthz", "/readyz", "/admin/maintenance"];
+
+/// 维护模式中间件。维护模式开启时，除健康检查和管理端开关外的所有请求直接返回 503，
+/// 响应体使用统一的 `ApiResponse` 格式，消息为管理员设置的维护提示。
+///
+/// 只读取本地同步的开关状态，不访问 Redis，维护期间即使 Redis 不可用也能正常拒绝请求。
+///
+/// # 返回值
+/// - `Ok(Response)`: 未开启维护模式或路径在豁免列表中，继续执行后续处理
+/// - `Err(AppError::ServiceUnavailable)`: 维护模式已开启
+pub async fn reject_during_maintenance(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if let Some(message) = state.maintenance.message()
+        && !MAINTENANCE_EXEMPT_PATHS.contains(&req.uri().path())
+    {
+        metrics::counter!("maintenance_rejections_total").increment(1);
+        return Err(AppError::ServiceUnavailable(message));
+    }
+
+    Ok(next.run(req).await)
+}
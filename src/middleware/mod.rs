@@ -1 +1,20 @@
-pub mod auth;
\ No newline at end of file
+pub mod access_log;
+pub mod auth;
+pub mod delegation;
+pub mod breaker;
+pub mod concurrency;
+pub mod context;
+pub mod deprecation;
+pub mod error_reporting;
+pub mod idempotency;
+pub mod json_case;
+pub mod latency_budget;
+pub mod maintenance;
+pub mod pipeline;
+pub mod priority;
+pub mod rate_limit;
+pub mod request_id;
+pub mod script_hook;
+pub mod slow_request;
+pub mod stats;
+pub mod timeout;
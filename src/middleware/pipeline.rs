@@ -0,0 +1,151 @@
+// src/middleware/pipeline.rs
+use std::time::Duration;
+
+use axum::{middleware, Router};
+
+use crate::core::log::target;
+use crate::{
+    core::enums::Dependency,
+    middleware::{self as app_middleware, rate_limit::GroupRateLimit},
+    state::AppState,
+};
+
+/// 路由组中间件管道中的一个阶段。
+///
+/// 路由组的中间件在一张表中按请求经过的顺序（从外到内）声明，由 `apply` 统一套用到路由上，
+/// 不再依赖 `.layer()` 调用的书写顺序（后调用的反而先执行，很容易写反）。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+    /// 响应时间预算，超出时记录指标并输出警告
+    LatencyBudget(Duration),
+    /// 依赖服务熔断时直接返回 503
+    Dependencies(&'static [Dependency]),
+    /// 按来源IP限制整个路由组的请求频率
+    RateLimit(GroupRateLimit),
+    /// 令牌撤销、用户级吊销和令牌版本检查
+    TokenRevocation,
+    /// 委托令牌的操作范围和撤销检查
+    DelegationScope,
+    /// 要求管理员或更高角色
+    AdminGuard,
+    /// 路由脚本钩子，紧挨着处理器执行，只看到已通过各项检查的请求
+    ScriptHooks,
+}
+
+impl Stage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::LatencyBudget(_) => "latency_budget",
+            Stage::Dependencies(_) => "dependencies",
+            Stage::RateLimit(_) => "rate_limit",
+            Stage::TokenRevocation => "token_revocation",
+            Stage::DelegationScope => "delegation_scope",
+            Stage::AdminGuard => "admin_guard",
+            Stage::ScriptHooks => "script_hooks",
+        }
+    }
+
+    /// 必须位于该阶段之外（先执行）的阶段，声明时如果存在则必须排在前面
+    fn must_follow(&self) -> &'static [&'static str] {
+        match self {
+            Stage::LatencyBudget(_) | Stage::Dependencies(_) => &[],
+            // 熔断期间不应再访问 Redis 检查令牌或计数；超出限流的请求在令牌检查之前被拒绝
+            Stage::RateLimit(_) => &["dependencies"],
+            Stage::TokenRevocation => &["dependencies", "rate_limit"],
+            Stage::DelegationScope | Stage::AdminGuard => &["dependencies", "rate_limit", "token_revocation"],
+            // 脚本可以改写请求头，必须在认证相关的检查完成之后执行
+            Stage::ScriptHooks => {
+                &["dependencies", "rate_limit", "token_revocation", "delegation_scope", "admin_guard"]
+            }
+        }
+    }
+
+    /// 该阶段存在时必须同时声明的阶段
+    fn requires(&self) -> &'static [&'static str] {
+        match self {
+            // 守卫依赖已撤销令牌被提前拒绝，单独使用会放行已登出的令牌
+            Stage::DelegationScope | Stage::AdminGuard => &["token_revocation"],
+            _ => &[],
+        }
+    }
+}
+
+/// 校验路由组的中间件声明：阶段不能重复，响应时间预算必须位于最外层，
+/// 依赖认证的守卫必须位于令牌检查之后。
+///
+/// # 参数
+/// - `group`: 路由组名称，用于错误消息
+/// - `stages`: 按请求经过的顺序（从外到内）声明的阶段
+///
+/// # 返回值
+/// - `Err(String)`: 违反的约束
+pub fn validate(group: &str, stages: &[Stage]) -> Result<(), String> {
+    for (index, stage) in stages.iter().enumerate() {
+        let name = stage.name();
+        if stages[..index].iter().any(|earlier| earlier.name() == name) {
+            return Err(format!("{}: `{}` is declared more than once", group, name));
+        }
+        if matches!(stage, Stage::LatencyBudget(_)) && index != 0 {
+            return Err(format!("{}: `{}` must be the outermost stage", group, name));
+        }
+        for required in stage.requires() {
+            if !stages.iter().any(|other| other.name() == *required) {
+                return Err(format!("{}: `{}` requires `{}`", group, name, required));
+            }
+        }
+        for outer in stage.must_follow() {
+            if stages[index + 1..].iter().any(|later| later.name() == *outer) {
+                return Err(format!("{}: `{}` must come after `{}`", group, name, outer));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 校验并套用路由组的中间件管道。声明不合法时直接 panic，在启动阶段暴露配置错误。
+///
+/// # 参数
+/// - `group`: 路由组名称
+/// - `router`: 路由组
+/// - `state`: 应用程序状态
+/// - `stages`: 按请求经过的顺序（从外到内）声明的阶段
+pub fn apply(group: &str, router: Router<AppState>, state: &AppState, stages: &[Stage]) -> Router<AppState> {
+    if let Err(e) = validate(group, stages) {
+        panic!("❌ Invalid middleware pipeline for {}", e);
+    }
+    tracing::debug!(
+        target: target::SYSTEM,
+        "🧱 Middleware pipeline for {}: {}",
+        group,
+        stages.iter().map(Stage::name).collect::<Vec<_>>().join(" -> ")
+    );
+
+    // 后套用的层位于外层，因此从最内层的阶段开始套用
+    stages.iter().rev().fold(router, |router, stage| match *stage {
+        Stage::LatencyBudget(budget) => {
+            router.layer(middleware::from_fn_with_state(budget, app_middleware::latency_budget::watch))
+        }
+        Stage::Dependencies(dependencies) => router.layer(middleware::from_fn_with_state(
+            (state.clone(), dependencies),
+            app_middleware::breaker::require_dependencies,
+        )),
+        Stage::RateLimit(limit) => router.layer(middleware::from_fn_with_state(
+            (state.clone(), limit),
+            app_middleware::rate_limit::limit_by_ip,
+        )),
+        Stage::TokenRevocation => router.layer(middleware::from_fn_with_state(
+            state.clone(),
+            app_middleware::auth::check_token_revocation,
+        )),
+        Stage::DelegationScope => router.layer(middleware::from_fn_with_state(
+            state.clone(),
+            app_middleware::delegation::enforce_scope,
+        )),
+        Stage::AdminGuard => {
+            router.layer(middleware::from_fn_with_state(state.clone(), app_middleware::auth::admin_guard))
+        }
+        Stage::ScriptHooks => {
+            router.layer(middleware::from_fn_with_state(state.clone(), app_middleware::script_hook::transform))
+        }
+    })
+}
@@ -0,0 +1,59 @@
+// src/middleware/priority.rs
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::core::log::target;
+use crate::{
+    core::{enums::Lane, error::AppError},
+    extractors::claims::request_claims,
+    state::AppState,
+};
+
+/// 按路径和令牌对请求分级：管理与运维端点 > 已认证请求 > 匿名请求。
+///
+/// 只有携带有效令牌的请求才进入已认证通道（管理端点进入运维通道），令牌无效或缺失时一律按匿名请求处理，
+/// 避免随便填写一个 Bearer 头就能挤占已认证用户的并发额度。令牌解析结果缓存在请求扩展中，
+/// 后续的认证提取器不会重复验证。
+fn classify(state: &AppState, req: &mut Request) -> Lane {
+    let path = req.uri().path();
+    if matches!(path, "/health" | "/healthz" | "/readyz" | "/metrics") {
+        return Lane::Ops;
+    }
+    let admin = path.starts_with("/admin");
+
+    match request_claims(state, req) {
+        Ok(_) if admin => Lane::Ops,
+        Ok(_) => Lane::Authenticated,
+        Err(_) => Lane::Anonymous,
+    }
+}
+
+/// 请求优先级中间件。每个通道使用独立的并发预算，匿名流量激增时只会在匿名通道内排队，
+/// 管理和运维端点始终保有自己的并发额度。
+///
+/// # 返回值
+/// - `Ok(Response)`: 拿到通道许可，请求处理完成
+/// - `Err(AppError::ServiceUnavailable)`: 通道满载且排队超时
+pub async fn prioritize(
+    State(state): State<AppState>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let lane = classify(&state, &mut req);
+
+    let Some(_permit) = state.lanes.acquire(lane).await else {
+        metrics::counter!("lane_rejections_total", "lane" => lane.to_string()).increment(1);
+        tracing::warn!(target: target::LIMITER, "🚦 Lane {} saturated, request rejected: {}", lane, req.uri().path());
+        return Err(AppError::ServiceUnavailable(
+            "Server is busy, please retry later".to_string(),
+        ));
+    };
+
+    metrics::gauge!("lane_available_permits", "lane" => lane.to_string())
+        .set(state.lanes.available(lane) as f64);
+
+    Ok(next.run(req).await)
+}
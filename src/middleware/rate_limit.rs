@@ -0,0 +1,76 @@
+// src/middleware/rate_limit.rs
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::core::log::target;
+use crate::{
+    core::{constants::RATE_LIMIT_HEADER_THRESHOLD, error::AppError},
+    extractors::client_ip::ClientIp,
+    state::AppState,
+    utils::limiter,
+};
+
+/// 路由组级别的限流：同一来源IP对整个路由组的请求共享一个计数。
+/// 限额按操作名称从配置中读取（见 `Config::rate_limit`）。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroupRateLimit {
+    /// 限流计数的操作名称，不同路由组使用不同的名称
+    pub action: &'static str,
+}
+
+/// 路由组限流中间件。在 `routes.rs` 中通过路由组的中间件管道声明（见 `Stage::RateLimit`），
+/// 按来源IP限制整个路由组的请求频率，处理器中的 `rate_limit!` 仍可以按用户或账号做更细的限制。
+///
+/// Redis 故障时放行请求，避免限流器本身导致整个路由组不可用。
+///
+/// # 返回值
+/// - `Ok(Response)`: 未超出限制，继续执行后续处理
+/// - `Err(AppError::RateLimitExceeded)`: 来源IP超出路由组的限制
+pub async fn limit_by_ip(
+    State((state, limit)): State<(AppState, GroupRateLimit)>,
+    req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let (mut parts, body) = req.into_parts();
+    let ClientIp(client_ip) = ClientIp::from_request_parts(&mut parts, &state)
+        .await
+        .unwrap_or_else(|_| ClientIp("unknown".to_string()));
+
+    let mode = state.config.rate_limit(limit.action);
+    match limiter::check_rate_limit_mode(&state.redis, limit.action, &client_ip, mode).await {
+        Ok(_) => {}
+        Err(AppError::RedisError(e)) => {
+            tracing::warn!(target: target::LIMITER, "⚠️ Group rate limit {} skipped: {}", limit.action, e);
+        }
+        Err(e) => return Err(e),
+    }
+
+    Ok(next.run(Request::from_parts(parts, body)).await)
+}
+
+/// 限流响应头中间件。收集请求处理期间的限流检查结果（路由组限流和处理器中的 `rate_limit!`），
+/// 被限流或剩余额度低于 `RATE_LIMIT_HEADER_THRESHOLD` 时添加
+/// `X-RateLimit-Limit`、`X-RateLimit-Remaining`、`X-RateLimit-Reset` 响应头，被限流时另加 `Retry-After`。
+///
+/// 同时把来源IP带入限流作用域，豁免名单中的 IP/CIDR 按它匹配，而不是按处理器传入的限流对象。
+pub async fn expose_headers(ClientIp(client_ip): ClientIp, req: Request, next: Next) -> Response {
+    let (mut response, info) = limiter::scope(client_ip.parse().ok(), next.run(req)).await;
+
+    let Some(info) = info.filter(|info| info.is_near_limit(RATE_LIMIT_HEADER_THRESHOLD)) else {
+        return response;
+    };
+
+    let headers = response.headers_mut();
+    headers.insert("x-ratelimit-limit", HeaderValue::from(info.limit));
+    headers.insert("x-ratelimit-remaining", HeaderValue::from(info.remaining));
+    headers.insert("x-ratelimit-reset", HeaderValue::from(info.reset));
+    if let Some(retry_after) = info.retry_after {
+        headers.insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    }
+
+    response
+}
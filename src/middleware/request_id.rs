@@ -0,0 +1,32 @@
+// src/middleware/request_id.rs
+use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::utils::request_id::{self, RequestId};
+
+/// 请求ID中间件。沿用客户端传入的 `X-Request-Id`（校验通过时）或生成新的ID，
+/// 存入请求扩展供追踪 span 和处理器读取，并在响应头中原样返回。
+///
+/// 该中间件需要位于 `TraceLayer` 之外，追踪层创建 span 时才能读取到请求ID。
+pub async fn propagate_request_id(mut req: Request, next: Next) -> Response {
+    let incoming = req
+        .headers()
+        .get(request_id::HEADER)
+        .and_then(|value| value.to_str().ok());
+    let id = request_id::accept_or_generate(incoming);
+
+    req.extensions_mut().insert(RequestId(id.clone()));
+
+    // 在请求ID作用域内执行后续处理，ApiResponse 序列化时从中读取
+    let mut response = request_id::scope(id.clone(), next.run(req)).await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(request_id::HEADER, value);
+    }
+
+    response
+}
@@ -0,0 +1,192 @@
+// src/middleware/script_hook.rs
+use axum::{
+    body::{to_bytes, Body},
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::{json, Map, Value};
+
+use crate::{
+    core::{
+        constants::SCRIPT_MAX_BODY_BYTES,
+        error::AppError,
+        scripting::{HookStage, ScriptHooks},
+    },
+    extractors::context::RequestContext,
+    state::AppState,
+};
+
+/// 路由脚本钩子中间件。路由配置了脚本时，在处理器执行前后调用脚本的 `on_request` / `on_response`，
+/// 按脚本的返回值改写请求头、响应头和 JSON 请求体/响应体。没有配置脚本的路由直接放行，不会读取请求体。
+///
+/// 脚本格式和沙箱限制见 `core::scripting`。
+///
+/// # 返回值
+/// - `Ok(Response)`: 处理完成的响应
+/// - `Err(AppError)`: 请求体或响应体超出上限，或脚本执行失败
+pub async fn transform(State(state): State<AppState>, req: Request, next: Next) -> Result<Response, AppError> {
+    let Some(route) = req.extensions().get::<MatchedPath>().map(|path| path.as_str().to_string()) else {
+        return Ok(next.run(req).await);
+    };
+    let hooks = &state.script_hooks;
+    let method = req.method().clone();
+    let on_request = hooks.has(&method, &route, HookStage::Request);
+    let on_response = hooks.has(&method, &route, HookStage::Response);
+    if !on_request && !on_response {
+        return Ok(next.run(req).await);
+    }
+
+    let ctx = script_context(req.extensions().get::<RequestContext>());
+
+    // 第一步：处理器执行之前转换请求
+    let req = if on_request {
+        transform_request(hooks, &method, &route, &ctx, req).await?
+    } else {
+        req
+    };
+
+    let response = next.run(req).await;
+    if !on_response {
+        return Ok(response);
+    }
+
+    // 第二步：处理器执行之后转换响应（包括错误响应）
+    transform_response(hooks, &method, &route, &ctx, response).await
+}
+
+async fn transform_request(
+    hooks: &ScriptHooks,
+    method: &Method,
+    route: &str,
+    ctx: &Value,
+    req: Request,
+) -> Result<Request, AppError> {
+    let (mut parts, body) = req.into_parts();
+    let bytes = to_bytes(body, SCRIPT_MAX_BODY_BYTES)
+        .await
+        .map_err(|_| AppError::PayloadTooLarge("Request body is too large".to_string()))?;
+    let body = json_body(&parts.headers, &bytes);
+    let headers = header_object(&parts.headers);
+
+    let input = json!({
+        "method": method.as_str(),
+        "path": parts.uri.path(),
+        "query": parts.uri.query(),
+        "headers": headers,
+        "body": body,
+        "ctx": ctx,
+    });
+    let output = hooks.run(method, route, HookStage::Request, input)?;
+
+    apply_headers(&mut parts.headers, &headers, output.get("headers"))?;
+    let body = match (body, output.get("body")) {
+        (Some(_), Some(modified)) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Body::from(serde_json::to_vec(modified).unwrap_or_else(|_| bytes.to_vec()))
+        }
+        _ => Body::from(bytes),
+    };
+    Ok(Request::from_parts(parts, body))
+}
+
+async fn transform_response(
+    hooks: &ScriptHooks,
+    method: &Method,
+    route: &str,
+    ctx: &Value,
+    response: Response,
+) -> Result<Response, AppError> {
+    let (mut parts, body) = response.into_parts();
+    // 超出上限时无法交给脚本过滤，不能原样放行
+    let bytes = to_bytes(body, SCRIPT_MAX_BODY_BYTES)
+        .await
+        .map_err(|_| AppError::InternalServerError("Response body is too large for script hook".to_string()))?;
+    let body = json_body(&parts.headers, &bytes);
+    let headers = header_object(&parts.headers);
+
+    let input = json!({
+        "status": parts.status.as_u16(),
+        "headers": headers,
+        "body": body,
+        "ctx": ctx,
+    });
+    let output = hooks.run(method, route, HookStage::Response, input)?;
+
+    apply_headers(&mut parts.headers, &headers, output.get("headers"))?;
+    let body = match (body, output.get("body")) {
+        (Some(_), Some(modified)) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Body::from(serde_json::to_vec(modified).unwrap_or_else(|_| bytes.to_vec()))
+        }
+        _ => Body::from(bytes),
+    };
+    Ok(Response::from_parts(parts, body))
+}
+
+/// 传给脚本的调用方信息，匿名请求时为空对象
+fn script_context(context: Option<&RequestContext>) -> Value {
+    match context.and_then(|ctx| ctx.claims.as_ref()) {
+        Some(claims) => json!({
+            "user_id": claims.sub,
+            "role": claims.role,
+            "tenant_id": claims.tenant_id(),
+            "ext": claims.ext,
+        }),
+        None => json!({}),
+    }
+}
+
+/// 内容类型为 JSON 且可以解析时返回解析后的请求体/响应体
+fn json_body(headers: &HeaderMap, bytes: &[u8]) -> Option<Value> {
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !is_json || bytes.is_empty() {
+        return None;
+    }
+    serde_json::from_slice(bytes).ok()
+}
+
+/// 把头部转换为脚本中的 map。同名的多个头部只取第一个，非 UTF-8 的值跳过。
+fn header_object(headers: &HeaderMap) -> Map<String, Value> {
+    headers
+        .keys()
+        .filter_map(|name| {
+            let value = headers.get(name)?.to_str().ok()?;
+            Some((name.as_str().to_string(), Value::String(value.to_string())))
+        })
+        .collect()
+}
+
+/// 按脚本返回的 headers 改写头部：新增或值有变化的头部覆盖写入，从 map 中删除的头部移除，
+/// 未变化的头部保持原样（保留同名的多个值）。
+fn apply_headers(
+    headers: &mut HeaderMap,
+    original: &Map<String, Value>,
+    modified: Option<&Value>,
+) -> Result<(), AppError> {
+    let Some(modified) = modified.and_then(Value::as_object) else {
+        return Ok(());
+    };
+    let invalid = |name: &str| AppError::InternalServerError(format!("Script hook returned an invalid header `{}`", name));
+
+    for name in original.keys().filter(|name| !modified.contains_key(*name)) {
+        headers.remove(name.as_str());
+    }
+    for (name, value) in modified {
+        if original.get(name) == Some(value) {
+            continue;
+        }
+        let value = match value {
+            Value::String(value) => value.clone(),
+            other => other.to_string(),
+        };
+        let header_name = HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid(name))?;
+        let header_value = HeaderValue::from_str(&value).map_err(|_| invalid(name))?;
+        headers.insert(header_name, header_value);
+    }
+    Ok(())
+}
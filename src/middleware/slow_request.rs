@@ -0,0 +1,45 @@
+// src/middleware/slow_request.rs
+use std::time::Duration;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use tokio::time::Instant;
+
+use crate::core::log::target;
+use crate::utils::request_id::RequestId;
+
+/// 慢请求日志中间件。与路由组的响应时间预算不同，这里对所有请求使用统一的阈值（`SLOW_REQUEST_MS`），
+/// 超过阈值时记录 `slow_requests_total` 指标并输出带路由和请求ID的警告，用于发现新引入的性能退化。
+pub async fn log_slow_requests(State(threshold): State<Duration>, req: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = req.method().clone();
+    // 使用路由模板（如 /admin/users/{id}）而不是实际路径，避免指标标签基数过高
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let request_id = req.extensions().get::<RequestId>().map(|id| id.0.clone());
+
+    let response = next.run(req).await;
+
+    let elapsed = started.elapsed();
+    if elapsed >= threshold {
+        metrics::counter!("slow_requests_total", "route" => route.clone()).increment(1);
+        tracing::warn!(
+            target: target::HTTP,
+            method = %method,
+            route = %route,
+            request_id = request_id.as_deref().unwrap_or_default(),
+            elapsed_ms = elapsed.as_millis() as u64,
+            threshold_ms = threshold.as_millis() as u64,
+            status = response.status().as_u16(),
+            "🐌 Slow request"
+        );
+    }
+
+    response
+}
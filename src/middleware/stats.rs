@@ -0,0 +1,36 @@
+// src/middleware/stats.rs
+use axum::{
+    extract::{FromRequestParts, MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{
+    extractors::{claims::resolve_claims, client_ip::ClientIp},
+    services::stats::{self as StatsService, RequestSample},
+    state::AppState,
+};
+
+/// 请求统计中间件。按路由和用户统计请求数和 5xx 错误数，写入 Redis 供 `/admin/stats` 汇总。
+/// 未同意使用行为分析的用户按匿名请求统计（见 `stats::record`）。
+///
+/// 位于维护模式、优先级通道和超时层之外，被这些层拒绝的请求（503、504）同样计入错误率。
+/// 路由使用路由模板（如 `/admin/users/{id}/ban`）而不是实际路径，避免统计键无限增长。
+pub async fn count_requests(State(state): State<AppState>, req: Request, next: Next) -> Response {
+    let (mut parts, body) = req.into_parts();
+    let route = match parts.extensions.get::<MatchedPath>() {
+        Some(path) => format!("{} {}", parts.method, path.as_str()),
+        None => format!("{} unmatched", parts.method),
+    };
+    let user_id = resolve_claims(&state, &parts.headers, &mut parts.extensions)
+        .ok()
+        .map(|claims| claims.sub);
+    let ClientIp(client_ip) = ClientIp::from_request_parts(&mut parts, &state)
+        .await
+        .unwrap_or_else(|_| ClientIp("unknown".to_string()));
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    StatsService::record(&state, RequestSample { route, user_id, client_ip, status: response.status().as_u16() });
+    response
+}
@@ -0,0 +1,80 @@
+// src/middleware/timeout.rs
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use tokio::time::Instant;
+
+use crate::core::log::target;
+use crate::core::error::AppError;
+
+/// 当前请求的超时预算（毫秒）。由全局超时中间件放入请求扩展，路由级中间件可以放宽。
+#[derive(Clone)]
+pub struct TimeoutBudget {
+    started: Instant,
+    budget_ms: Arc<AtomicU64>,
+}
+
+impl TimeoutBudget {
+    /// 当前请求的截止时间。路由级中间件放宽预算后，截止时间随之推后。
+    pub fn deadline(&self) -> Instant {
+        self.started + Duration::from_millis(self.budget_ms.load(Ordering::Relaxed))
+    }
+}
+
+/// 全局请求超时中间件。超过预算仍未完成的请求被取消（数据库、Redis 调用随之中止），
+/// 返回 504，避免缓慢的下游调用一直占用连接。
+///
+/// 默认预算来自配置；需要更长时间的路由（文件上传、批量操作等）使用 `extend` 放宽预算。
+/// 超时计时从请求进入该中间件开始，放宽后的预算同样从这一刻算起。
+///
+/// # 返回值
+/// - `Ok(Response)`: 请求在预算内完成
+/// - `Err(AppError::Timeout)`: 请求超时
+pub async fn enforce(
+    State(default_budget): State<Duration>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let started = Instant::now();
+    let budget = Arc::new(AtomicU64::new(default_budget.as_millis() as u64));
+    req.extensions_mut().insert(TimeoutBudget { started, budget_ms: budget.clone() });
+
+    let path = req.uri().path().to_string();
+    let handler = next.run(req);
+    tokio::pin!(handler);
+
+    loop {
+        let deadline = started + Duration::from_millis(budget.load(Ordering::Relaxed));
+        tokio::select! {
+            response = &mut handler => return Ok(response),
+            _ = tokio::time::sleep_until(deadline) => {
+                // 处理期间预算可能已被路由级中间件放宽，按新的截止时间继续等待
+                let extended = started + Duration::from_millis(budget.load(Ordering::Relaxed));
+                if extended > deadline {
+                    continue;
+                }
+                metrics::counter!("request_timeouts_total").increment(1);
+                tracing::warn!(target: target::HTTP, "⏱️ Request timed out after {:?}: {}", started.elapsed(), path);
+                return Err(AppError::Timeout("Request timed out".to_string()));
+            }
+        }
+    }
+}
+
+/// 路由级超时放宽中间件：为耗时较长的路由设置更长的预算。只能放宽，不能收紧全局预算。
+pub async fn extend(State(budget): State<Duration>, req: Request, next: Next) -> Response {
+    if let Some(current) = req.extensions().get::<TimeoutBudget>() {
+        current.budget_ms.fetch_max(budget.as_millis() as u64, Ordering::Relaxed);
+    }
+    next.run(req).await
+}
@@ -0,0 +1,202 @@
+// src/mock.rs
+use std::{collections::BTreeMap, net::SocketAddr};
+
+use axum::{
+    http::StatusCode,
+    middleware,
+    routing::{get, post},
+    Router,
+};
+use serde::Serialize;
+use tower_http::cors::CorsLayer;
+
+use crate::core::log::target;
+use crate::{
+    core::{
+        constants::USER_QUOTAS,
+        enums::{ConsentPurpose, JsonCase, LoginMethod, UserRole},
+    },
+    dtos::{
+        auth::LoginResponse,
+        consent::ConsentStatus,
+        pagination::Paginated,
+        presence::PresenceStatus,
+        response::ApiResponse,
+        user::{LoginHistoryItem, QuotaStatus, UserProfile, UserSettings},
+    },
+    middleware as app_middleware,
+    utils::json_case,
+};
+
+// 模拟服务器模式（`serve --mock`）：不连接 PostgreSQL 和 Redis，按 DTO 返回固定的示例响应，
+// 前端可以在后端环境就绪之前按接口结构开发和联调。
+//
+// 示例数据直接由响应 DTO 构造并经过同样的 `ApiResponse` 序列化（包括 JSON_CASE 命名风格），
+// DTO 字段变化时示例响应自动保持一致。模拟接口不校验请求体和令牌；未模拟的路由返回 501。
+
+/// 响应 DTO 的示例值
+trait Example {
+    fn example() -> Self;
+}
+
+const EXAMPLE_TIME: &str = "2025-01-01T08:00:00+00:00";
+
+impl Example for UserProfile {
+    fn example() -> Self {
+        Self {
+            id: "00000000-0000-4000-8000-000000000001".to_string(),
+            public_id: "u_8f3kz2m1".to_string(),
+            username: "alice".to_string(),
+            phone: Some("13800138000".to_string()),
+            role: UserRole::User,
+            is_active: true,
+            ban_reason: None,
+            banned_until: None,
+            avatar_url: Some("/uploads/avatars/alice.png".to_string()),
+            created_at: EXAMPLE_TIME.to_string(),
+        }
+    }
+}
+
+impl Example for LoginResponse {
+    fn example() -> Self {
+        Self {
+            access_token: "mock.access.token".to_string(),
+            refresh_token: "mock-refresh-token".to_string(),
+        }
+    }
+}
+
+impl Example for UserSettings {
+    fn example() -> Self {
+        Self {
+            language: Some("zh-CN".to_string()),
+            timezone: Some("Asia/Shanghai".to_string()),
+            theme: Some("light".to_string()),
+            show_presence: Some(true),
+            extra: Default::default(),
+        }
+    }
+}
+
+impl Example for PresenceStatus {
+    fn example() -> Self {
+        Self { online: true, last_seen_at: Some(EXAMPLE_TIME.to_string()) }
+    }
+}
+
+impl Example for LoginHistoryItem {
+    fn example() -> Self {
+        Self {
+            method: LoginMethod::Password,
+            ip: Some("203.0.113.10".to_string()),
+            user_agent: Some("Mozilla/5.0".to_string()),
+            created_at: EXAMPLE_TIME.to_string(),
+        }
+    }
+}
+
+impl Example for Vec<ConsentStatus> {
+    fn example() -> Self {
+        [ConsentPurpose::Marketing, ConsentPurpose::Analytics]
+            .into_iter()
+            .map(|purpose| ConsentStatus {
+                granted: purpose == ConsentPurpose::Analytics,
+                purpose,
+                policy_version: Some("2025-01".to_string()),
+                outdated: false,
+                updated_at: Some(EXAMPLE_TIME.to_string()),
+            })
+            .collect()
+    }
+}
+
+impl Example for Vec<QuotaStatus> {
+    fn example() -> Self {
+        USER_QUOTAS
+            .iter()
+            .map(|quota| QuotaStatus {
+                action: quota.action.to_string(),
+                period: quota.period,
+                limit: quota.limit,
+                used: 1,
+                remaining: quota.limit.saturating_sub(1),
+                resets_at: "2025-01-02T00:00:00+00:00".to_string(),
+            })
+            .collect()
+    }
+}
+
+/// 功能开关评估结果
+impl Example for BTreeMap<String, bool> {
+    fn example() -> Self {
+        BTreeMap::from([("checkout.new_flow".to_string(), true), ("profile.badges".to_string(), false)])
+    }
+}
+
+impl<T: Example> Example for Paginated<T> {
+    fn example() -> Self {
+        Self { items: vec![T::example()], page: 1, per_page: 20, total: 1, total_pages: 1 }
+    }
+}
+
+/// 返回指定类型示例值的处理器
+async fn respond<T: Example + Serialize>() -> ApiResponse<T> {
+    ApiResponse::with_data(T::example())
+}
+
+/// 模拟服务器的路由，路径与 `routes::create_router` 保持一致。
+fn router() -> Router {
+    let auth_routes = Router::new()
+        .route("/login", post(respond::<LoginResponse>))
+        .route("/refresh", post(respond::<LoginResponse>))
+        .route("/logout", post(|| async { ApiResponse::<()>::with_message("Logged out successfully") }));
+
+    let user_routes = Router::new()
+        .route("/me", get(respond::<UserProfile>).patch(respond::<UserProfile>))
+        .route("/me/logins", get(respond::<Paginated<LoginHistoryItem>>))
+        .route("/me/features", get(respond::<BTreeMap<String, bool>>))
+        .route("/me/quota", get(respond::<Vec<QuotaStatus>>))
+        .route("/me/presence", post(respond::<PresenceStatus>))
+        .route("/{id}/presence", get(respond::<PresenceStatus>))
+        .route("/me/consents", get(respond::<Vec<ConsentStatus>>).patch(respond::<Vec<ConsentStatus>>))
+        .route("/me/settings", get(respond::<UserSettings>).patch(respond::<UserSettings>));
+
+    let admin_routes = Router::new().route("/users", get(respond::<Paginated<UserProfile>>));
+
+    Router::new()
+        .route("/health", get(|| async { ApiResponse::<()>::with_message("ok") }))
+        .nest("/auth", auth_routes)
+        .nest("/users", user_routes)
+        .nest("/admin", admin_routes)
+        .fallback(|| async {
+            ApiResponse::<()>::with_error(StatusCode::NOT_IMPLEMENTED, "This endpoint is not available in mock mode")
+        })
+        .layer(middleware::from_fn(app_middleware::request_id::propagate_request_id))
+        .layer(CorsLayer::permissive())
+}
+
+/// 以模拟模式启动HTTP服务器。只读取 HOST、PORT 和 JSON_CASE，不需要数据库、Redis 和其他配置。
+pub async fn run() {
+    dotenvy::dotenv().ok();
+    tracing_subscriber::fmt().with_target(false).init();
+
+    let host = std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".to_string());
+    let port = std::env::var("PORT").ok().and_then(|port| port.parse().ok()).unwrap_or(3000u16);
+    if std::env::var("JSON_CASE").is_ok_and(|case| case.eq_ignore_ascii_case("camel")) {
+        json_case::init(JsonCase::Camel);
+    }
+
+    let addr: SocketAddr = format!("{}:{}", host, port)
+        .parse()
+        .expect("❌ Invalid address configuration");
+    let listener = tokio::net::TcpListener::bind(addr).await.expect("❌ Failed to bind listener");
+    tracing::info!(target: target::SYSTEM, "🎭 Mock server listening on: http://{}", addr);
+
+    axum::serve(listener, router())
+        .with_graceful_shutdown(async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await
+        .expect("❌ Server error");
+}
@@ -1,16 +1,80 @@
 // src/routes.rs
 use axum::{
+    body::Body,
+    extract::{DefaultBodyLimit, Request},
+    http::{header, HeaderMap, HeaderValue},
     middleware,
-    routing::{get, post},
+    routing::{delete, get, patch, post, put},
     Router,
 };
 use tower_http::{
-    cors::CorsLayer,
-    trace::{TraceLayer, DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse},
+    compression::{predicate::SizeAbove, CompressionLayer, Predicate},
+    cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer},
+    decompression::RequestDecompressionLayer,
+    services::ServeDir,
+    trace::{TraceLayer, DefaultOnRequest, DefaultOnResponse},
 };
+use std::time::Duration;
 use tracing::Level;
 
-use crate::{handlers, state::AppState, middleware as app_middleware};
+use crate::{
+    core::{config::Config, enums::Dependency},
+    handlers,
+    middleware::{self as app_middleware, pipeline::{self, Stage}, concurrency::RouteConcurrency, rate_limit::GroupRateLimit},
+    state::AppState,
+    utils::request_id::RequestId,
+};
+
+// 各路由组依赖的下游服务。对应服务熔断期间，这些路由直接返回 503；
+// 未声明依赖的路由（健康检查、指标、静态文件）不受影响。
+const AUTH_DEPENDENCIES: &[Dependency] = &[Dependency::Database, Dependency::Redis];
+const USER_DEPENDENCIES: &[Dependency] = &[Dependency::Database, Dependency::Redis];
+const ADMIN_DEPENDENCIES: &[Dependency] = &[Dependency::Database, Dependency::Redis];
+
+// 各路由组的响应时间预算。超出预算的请求记录 `latency_budget_violations_total` 指标并输出警告，
+// 预算应略低于对应的 SLO，在 SLO 被突破之前发现变慢的接口。
+// 认证路由包含密码哈希计算，预算比普通接口宽松。
+const AUTH_LATENCY_BUDGET: Duration = Duration::from_millis(500);
+const USER_LATENCY_BUDGET: Duration = Duration::from_millis(300);
+const ADMIN_LATENCY_BUDGET: Duration = Duration::from_millis(1000);
+// 上传、下载、批量操作等耗时路由的预算
+const SLOW_ROUTE_LATENCY_BUDGET: Duration = Duration::from_secs(10);
+
+// 耗时路由的单实例并发上限，超出时直接返回 503，保护数据库连接池
+const EXPORT_DOWNLOAD_CONCURRENCY: usize = 4;
+const AUDIT_EXPORT_CONCURRENCY: usize = 2;
+const BULK_USERS_CONCURRENCY: usize = 2;
+
+// 路由组级别的限流：按来源IP限制整个路由组的请求频率，防止单个来源压垮登录、刷新等接口。
+// 处理器中的 `rate_limit!` 按用户或账号做更细的限制，二者同时生效。
+const AUTH_RATE_LIMIT: GroupRateLimit = GroupRateLimit { action: "group:auth" };
+
+// 各路由组的中间件管道，按请求经过的顺序（从外到内）声明，启动时校验顺序约束（见 `pipeline::validate`）：
+// - 响应时间预算位于最外层，覆盖令牌检查等全部耗时
+// - 依赖熔断检查位于限流和令牌检查之前，熔断期间不再访问 Redis
+// - 路由组限流位于令牌检查之前，超出限制的请求尽早被拒绝
+// - 管理员守卫和委托范围检查位于令牌撤销检查之后，已撤销的令牌先被拒绝
+// - 路由脚本钩子位于最内层，只处理已通过各项检查的请求（未配置脚本的路由直接放行）
+const AUTH_PIPELINE: &[Stage] = &[
+    Stage::LatencyBudget(AUTH_LATENCY_BUDGET),
+    Stage::Dependencies(AUTH_DEPENDENCIES),
+    Stage::RateLimit(AUTH_RATE_LIMIT),
+    Stage::ScriptHooks,
+];
+const USER_PIPELINE: &[Stage] = &[
+    Stage::LatencyBudget(USER_LATENCY_BUDGET),
+    Stage::Dependencies(USER_DEPENDENCIES),
+    Stage::TokenRevocation,
+    Stage::DelegationScope,
+    Stage::ScriptHooks,
+];
+const ADMIN_PIPELINE: &[Stage] = &[
+    Stage::LatencyBudget(ADMIN_LATENCY_BUDGET),
+    Stage::Dependencies(ADMIN_DEPENDENCIES),
+    Stage::TokenRevocation,
+    Stage::AdminGuard,
+    Stage::ScriptHooks,
+];
 
 /// 创建并配置应用程序的路由器。这个函数构建了整个应用的HTTP路由结构，
 /// 包括认证路由、用户路由、管理员路由，以及全局中间件层（如CORS和请求追踪）。
@@ -21,54 +85,241 @@ use crate::{handlers, state::AppState, middleware as app_middleware};
 /// # 返回值
 /// - `Router`: 配置完成的Axum路由器，可直接用于启动HTTP服务。
 pub fn create_router(state: AppState) -> Router {
-    // 认证相关路由：登录、刷新令牌、登出。这些端点不需要认证即可访问。
+    // 认证相关路由：登录、刷新令牌、登出、设备授权。这些端点不需要认证即可访问，但依赖数据库和Redis。
+    // 按来源IP的路由组限流等中间件见 AUTH_PIPELINE。
     let auth_routes = Router::new()
         .route("/login", post(handlers::auth::login))
         .route("/refresh", post(handlers::auth::refresh))
-        .route("/logout", post(handlers::auth::logout));
+        .route("/logout", post(handlers::auth::logout))
+        .route("/device/code", post(handlers::device::request_code))
+        .route("/device/token", post(handlers::device::poll_token));
+    let auth_routes = pipeline::apply("auth", auth_routes, &state, AUTH_PIPELINE);
+
+    // 头像上传的请求体上限：文件大小上限加上 multipart 边界等开销，覆盖全局的默认上限
+    let avatar_body_limit = state.config.avatar_max_bytes + 64 * 1024;
 
-    // 用户相关路由：获取个人信息、更新个人信息。这些端点需要有效的JWT令牌。
-    // 使用 check_token_revocation 中间件来验证令牌是否已被撤销（黑名单检查）。
+    // 耗时较长的路由（上传、下载、批量操作）放宽全局超时预算
+    let long_timeout = || {
+        middleware::from_fn_with_state(
+            state.config.long_request_timeout,
+            app_middleware::timeout::extend,
+        )
+    };
+    let slow_budget = || {
+        middleware::from_fn_with_state(SLOW_ROUTE_LATENCY_BUDGET, app_middleware::latency_budget::override_budget)
+    };
+    // 占用数据库较久的路由限制同时处理的请求数
+    let concurrency_limit = |name: &'static str, max_in_flight: usize| {
+        middleware::from_fn_with_state(
+            RouteConcurrency::new(name, max_in_flight),
+            app_middleware::concurrency::limit,
+        )
+    };
+    // 只能由账户所有者本人执行的操作，拒绝委托令牌
+    let owner_only = || middleware::from_fn_with_state(state.clone(), app_middleware::delegation::deny_delegated);
+
+    // 用户相关路由：获取/更新个人信息、修改用户名、冻结账户、委托授权、登录历史、功能开关评估、配额查询、在线状态、数据处理同意、上传头像、偏好设置、导出个人数据、确认设备授权。这些端点需要有效的JWT令牌。
+    // 令牌撤销检查、委托范围检查等中间件见 USER_PIPELINE。
     let user_routes = Router::new()
         .route("/me", get(handlers::users::get_me))
         .route("/me", post(handlers::users::update_me))
-        // 检查令牌是否已被撤销（如用户登出后令牌应失效）
-        .layer(middleware::from_fn_with_state(
-            state.clone(),
-            app_middleware::auth::check_token_revocation,
-        ));
+        .route("/me/username", post(handlers::users::change_username))
+        .route("/me/logins", get(handlers::users::list_logins))
+        .route("/me/freeze", post(handlers::users::freeze_account).layer(owner_only()))
+        .route("/me/delegations", get(handlers::delegation::list).layer(owner_only()))
+        .route("/me/delegations", post(handlers::delegation::create).layer(owner_only()))
+        .route("/me/delegations/{id}", delete(handlers::delegation::revoke).layer(owner_only()))
+        .route("/me/delegations/{id}/token", post(handlers::delegation::issue_token).layer(owner_only()))
+        .route("/me/features", get(handlers::users::get_features))
+        .route("/me/quota", get(handlers::users::get_quota))
+        .route("/me/presence", post(handlers::users::presence_heartbeat).layer(owner_only()))
+        .route("/{id}/presence", get(handlers::users::get_presence))
+        .route("/me/consents", get(handlers::users::get_consents))
+        .route("/me/consents", patch(handlers::users::update_consents).layer(owner_only()))
+        .route("/me/settings", get(handlers::users::get_settings))
+        .route("/me/settings", patch(handlers::users::update_settings))
+        .route(
+            "/me/avatar",
+            post(handlers::users::upload_avatar)
+                .layer(DefaultBodyLimit::max(avatar_body_limit))
+                .layer(long_timeout())
+                .layer(slow_budget()),
+        )
+        .route("/me/export", get(handlers::users::request_export).layer(owner_only()))
+        .route("/me/export/status", get(handlers::users::export_status).layer(owner_only()))
+        .route(
+            "/me/export/download",
+            get(handlers::users::download_export)
+                .layer(owner_only())
+                .layer(concurrency_limit("users.export_download", EXPORT_DOWNLOAD_CONCURRENCY))
+                .layer(long_timeout())
+                .layer(slow_budget()),
+        )
+        .route("/device", post(handlers::device::approve).layer(owner_only()));
+    let user_routes = pipeline::apply("users", user_routes, &state, USER_PIPELINE);
 
-    // 管理员路由：用户注册等管理功能。这些端点需要管理员权限。
-    // 中间件按顺序执行：先检查是否为管理员，再检查令牌是否被撤销。
+    // 管理员路由：用户注册、用户列表、封禁/解封、用户变更历史、用户导入、审计日志查询与导出、安全事件查询、委托查看、配置查看、请求统计、匿名使用统计、限流冷却解除、内存诊断、功能开关管理、维护模式开关等管理功能。这些端点需要管理员权限。
+    // 令牌撤销检查和管理员守卫等中间件见 ADMIN_PIPELINE。
     let admin_routes = Router::new()
         .route("/register", post(handlers::auth::register))
-        // 第一层：验证用户是否具有管理员权限
-        .layer(middleware::from_fn_with_state(
-            state.clone(),
-            app_middleware::auth::admin_guard,
-        ))
-        // 第二层：检查令牌是否已被撤销
-        .layer(middleware::from_fn_with_state(
-            state.clone(),
-            app_middleware::auth::check_token_revocation,
-        ));
+        .route("/users", get(handlers::admin::list_users))
+        .route("/users/search", get(handlers::admin::search_users))
+        .route(
+            "/users/bulk",
+            post(handlers::admin::bulk_users)
+                .layer(concurrency_limit("admin.bulk_users", BULK_USERS_CONCURRENCY))
+                .layer(long_timeout())
+                .layer(slow_budget()),
+        )
+        .route("/users/{id}/ban", post(handlers::admin::ban_user))
+        .route("/users/{id}/unban", post(handlers::admin::unban_user))
+        .route("/users/{id}/password", post(handlers::admin::reset_password))
+        .route("/users/{id}/history", get(handlers::admin::user_history))
+        .route("/imports", post(handlers::admin::start_import))
+        .route("/imports/{job_id}", get(handlers::admin::get_import))
+        .route("/audit-logs", get(handlers::admin::list_audit_logs))
+        .route(
+            "/audit-logs/export",
+            get(handlers::admin::export_audit_logs)
+                .layer(concurrency_limit("admin.audit_export", AUDIT_EXPORT_CONCURRENCY))
+                .layer(long_timeout())
+                .layer(slow_budget()),
+        )
+        .route("/security-events", get(handlers::admin::list_security_events))
+        .route("/delegations", get(handlers::admin::list_delegations))
+        .route("/config", get(handlers::admin::get_config))
+        .route("/config/reload", post(handlers::admin::reload_config))
+        .route("/stats", get(handlers::admin::get_stats))
+        .route("/stats/analytics", get(handlers::admin::get_analytics))
+        .route("/feature-flags", get(handlers::admin::list_feature_flags))
+        .route(
+            "/feature-flags/{key}",
+            put(handlers::admin::upsert_feature_flag).delete(handlers::admin::delete_feature_flag),
+        )
+        .route("/rate-limits/{action}/{key}/ban", delete(handlers::admin::clear_rate_limit_ban))
+        .route("/debug/memory", get(handlers::admin::get_memory_stats))
+        .route("/debug/heap-profile", post(handlers::admin::dump_heap_profile).layer(long_timeout()))
+        .route("/flags", get(handlers::admin::list_flags))
+        .route("/flags/{key}", put(handlers::admin::set_flag).delete(handlers::admin::clear_flag))
+        .route("/maintenance", get(handlers::admin::get_maintenance))
+        .route("/maintenance", post(handlers::admin::set_maintenance));
+    let admin_routes = pipeline::apply("admin", admin_routes, &state, ADMIN_PIPELINE);
 
     // 构建主路由器，整合所有子路由并应用全局中间件。
     // 注意：中间件的执行顺序与定义顺序相反，最后定义的中间件最先执行。
     Router::new()
         .route("/", get(|| async { "🚀 Axum Server is Running!" }))
+        // 健康检查：只读取熔断器状态，依赖服务故障时仍可访问
+        .route("/health", get(handlers::health::check))
+        // Kubernetes 探针：存活探针只检查进程，就绪探针实际探测数据库和Redis
+        .route("/healthz", get(handlers::health::liveness))
+        .route("/readyz", get(handlers::health::readiness))
+        // 指标端点：供 Prometheus 抓取，需要 METRICS_TOKEN 令牌，未配置令牌时只允许本机访问
+        .route("/metrics", get(handlers::metrics::export))
+        // 本地存储的上传文件（头像等）。使用对象存储时由 CDN 直接提供，此路由不会被访问
+        .nest_service("/uploads", ServeDir::new(&state.config.storage_local_dir))
         .nest("/auth", auth_routes)
         .nest("/users", user_routes)
         .nest("/admin", admin_routes)
-        // 追踪层：记录HTTP请求的详细信息，包括请求开始、请求接收、响应发送等事件
+        // 幂等键：POST 请求携带 Idempotency-Key 时回放首次响应。位于压缩层之内，缓存的是未压缩的响应
+        .layer(middleware::from_fn_with_state(state.clone(), app_middleware::idempotency::enforce))
+        // 响应压缩：按客户端的 Accept-Encoding 选择 gzip/br，列表类接口收益明显
+        .layer(compression_layer(&state.config))
+        // 全局请求体大小上限，路由可通过自己的 DefaultBodyLimit 覆盖（如头像上传）
+        .layer(DefaultBodyLimit::max(state.config.body_limit_bytes))
+        // 命名风格规范化：camelCase 模式下把请求字段名转换为 snake_case
+        .layer(middleware::from_fn_with_state(state.clone(), app_middleware::json_case::normalize_request_case))
+        // 弃用追踪：为使用了弃用端点或字段的请求添加 Deprecation / Sunset / Warning 响应头
+        .layer(middleware::from_fn(app_middleware::deprecation::track))
+        // 限流响应头：被限流或接近限额时添加 X-RateLimit-* 和 Retry-After 响应头，覆盖路由组限流和处理器中的 rate_limit!
+        .layer(middleware::from_fn(app_middleware::rate_limit::expose_headers))
+        // 请求解压：支持客户端以 gzip/br 压缩上传的请求体。请求体大小上限按解压后的大小计算。
+        // 位于命名风格规范化之外，使其读取到的是解压后的 JSON
+        .layer(RequestDecompressionLayer::new())
+        // 错误上报作用域：处理期间上报的 5xx 错误和 panic 带上请求ID、路由和用户ID。位于请求上下文之内以读取调用方
+        .layer(middleware::from_fn(app_middleware::error_reporting::bind_scope))
+        // 请求上下文：收集请求ID、调用方身份、语言、来源IP和截止时间。位于超时层之内以读取截止时间
+        .layer(middleware::from_fn_with_state(state.clone(), app_middleware::context::attach))
+        // 请求超时：超过预算的请求被取消并返回 504，路由可通过 timeout::extend 放宽
+        .layer(middleware::from_fn_with_state(
+            state.config.request_timeout,
+            app_middleware::timeout::enforce,
+        ))
+        // 优先级通道：按请求类别分配并发预算，满载时排队，排队超时返回 503
+        .layer(middleware::from_fn_with_state(state.clone(), app_middleware::priority::prioritize))
+        // 维护模式：开启期间除健康检查和管理端开关外直接返回 503，不占用并发预算
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            app_middleware::maintenance::reject_during_maintenance,
+        ))
+        // 追踪层：记录HTTP请求的详细信息，包括请求开始、请求接收、响应发送等事件。
+        // span 中带上请求ID，同一请求产生的所有日志都可以按 request_id 检索
         .layer(
             TraceLayer::new_for_http()
-                .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
+                .make_span_with(|req: &Request<Body>| {
+                    let request_id = req
+                        .extensions()
+                        .get::<RequestId>()
+                        .map(|id| id.0.as_str())
+                        .unwrap_or_default();
+                    tracing::info_span!(
+                        "request",
+                        method = %req.method(),
+                        uri = %req.uri(),
+                        version = ?req.version(),
+                        request_id = %request_id,
+                    )
+                })
                 .on_request(DefaultOnRequest::new().level(Level::INFO))
                 .on_response(DefaultOnResponse::new().level(Level::INFO))
         )
-        // CORS层：允许跨域请求，使用 permissive() 配置允许任何来源（开发环境适用）
-        .layer(CorsLayer::permissive())
+        // 访问日志层：按配置把访问记录写入数据库或文件，需要位于请求ID层之内以读取请求ID
+        .layer(middleware::from_fn_with_state(state.clone(), app_middleware::access_log::record))
+        // 请求统计层：按路由和用户统计请求数和错误数，供 /admin/stats 汇总
+        .layer(middleware::from_fn_with_state(state.clone(), app_middleware::stats::count_requests))
+        // 慢请求日志层：统计包括排队、维护检查在内的完整耗时，超过阈值时输出警告
+        .layer(middleware::from_fn_with_state(
+            state.config.slow_request_threshold,
+            app_middleware::slow_request::log_slow_requests,
+        ))
+        // 请求ID层：必须位于追踪层之外，追踪层创建 span 时才能读取到请求ID
+        .layer(middleware::from_fn(app_middleware::request_id::propagate_request_id))
+        // CORS层：开发环境默认允许任何来源，其他环境只允许 CORS_ALLOWED_ORIGINS 中的来源
+        .layer(cors_layer(&state.config))
         // 注入应用程序状态，使所有处理器都能访问共享资源
         .with_state(state)
-}
\ No newline at end of file
+}
+
+/// 构建CORS层。开发环境未配置来源（或配置为 `*`）时使用 permissive()；
+/// 否则只允许列出的来源并允许携带凭据（Cookie 模式的刷新令牌需要），未配置来源的非开发环境不允许任何跨域请求。
+fn cors_layer(config: &Config) -> CorsLayer {
+    let origins = config.cors_origins();
+    if config.app_env.is_development() && (origins.is_empty() || origins.iter().any(|origin| origin == "*")) {
+        return CorsLayer::permissive();
+    }
+
+    // 来源格式已在启动时校验，无法转换为请求头的值直接忽略
+    let origins: Vec<HeaderValue> = origins.iter().filter_map(|origin| origin.parse().ok()).collect();
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods(AllowMethods::mirror_request())
+        .allow_headers(AllowHeaders::mirror_request())
+        .allow_credentials(true)
+        .max_age(Duration::from_secs(3600))
+}
+
+/// 构建响应压缩层：只压缩超过大小阈值、且内容类型在配置白名单中的响应。
+fn compression_layer(config: &Config) -> CompressionLayer<impl Predicate + use<>> {
+    let content_types = config.compression_content_types();
+    let allowed = move |_status, _version, headers: &HeaderMap, _extensions: &_| {
+        headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| content_types.iter().any(|prefix| value.starts_with(prefix.as_str())))
+    };
+
+    CompressionLayer::new()
+        .gzip(true)
+        .br(true)
+        .compress_when(SizeAbove::new(config.compression_min_bytes).and(allowed))
+}
@@ -0,0 +1,152 @@
+// src/services/access_log.rs
+use std::{io::Write, time::Duration};
+
+use chrono::{DateTime, Utc};
+use sea_orm::{DatabaseConnection, EntityTrait, Set};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::core::log::target;
+use crate::{
+    core::{config::Config, enums::AccessLogSink},
+    entity::access_logs,
+};
+
+/// 写入队列容量。队列满时丢弃新记录（并计数），访问日志不能反过来拖慢请求处理。
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// 写入数据库时每批的最大记录数
+const DB_BATCH_SIZE: usize = 200;
+
+/// 写入数据库的最长攒批时间
+const DB_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 一条访问记录
+#[derive(Debug, Serialize)]
+pub struct AccessRecord {
+    pub method: String,
+    /// 只记录路径，不记录查询参数（可能包含令牌等敏感信息）
+    pub path: String,
+    pub status: u16,
+    pub latency_ms: u64,
+    pub user_id: Option<Uuid>,
+    pub request_id: Option<String>,
+    pub ip: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// 访问日志记录器。请求路径上只把记录放入队列，由后台任务批量写入文件或数据库。
+#[derive(Clone)]
+pub struct AccessLogger {
+    sender: mpsc::Sender<AccessRecord>,
+    sample_rate: f64,
+}
+
+impl AccessLogger {
+    /// 根据配置启动后台写入任务。sink 为 off 时返回 `None`。
+    ///
+    /// # 参数
+    /// - `config`: 应用配置，读取 sink、采样率和文件目录
+    /// - `db`: 数据库连接（sink 为 database 时使用）
+    pub fn spawn(config: &Config, db: DatabaseConnection) -> Option<Self> {
+        let (sender, receiver) = mpsc::channel(QUEUE_CAPACITY);
+
+        match config.access_log_sink {
+            AccessLogSink::Off => return None,
+            AccessLogSink::File => {
+                let appender = tracing_appender::rolling::daily(&config.access_log_dir, "access.ndjson");
+                tokio::spawn(write_file(receiver, appender));
+            }
+            AccessLogSink::Database => {
+                tokio::spawn(write_database(receiver, db));
+            }
+        }
+
+        tracing::info!(
+            target: target::HTTP,
+            "📝 Access log enabled: sink={}, sample_rate={}",
+            config.access_log_sink,
+            config.access_log_sample_rate
+        );
+        Some(Self {
+            sender,
+            sample_rate: config.access_log_sample_rate.clamp(0.0, 1.0),
+        })
+    }
+
+    /// 判断是否记录该请求。出错的请求（状态码 >= 400）始终记录，便于安全审查。
+    pub fn should_record(&self, status: u16) -> bool {
+        status >= 400 || self.sample_rate >= 1.0 || rand::random::<f64>() < self.sample_rate
+    }
+
+    /// 把记录放入写入队列，队列满时丢弃。
+    pub fn log(&self, record: AccessRecord) {
+        if self.sender.try_send(record).is_err() {
+            metrics::counter!("access_log_dropped_total").increment(1);
+        }
+    }
+}
+
+/// 逐条写入 NDJSON 文件（每行一个 JSON 对象），文件按天滚动。
+async fn write_file(mut receiver: mpsc::Receiver<AccessRecord>, mut appender: impl Write + Send + 'static) {
+    while let Some(record) = receiver.recv().await {
+        let line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::error!(target: target::HTTP, "❌ Access log serialization failed: {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = writeln!(appender, "{}", line) {
+            tracing::error!(target: target::HTTP, "❌ Access log write failed: {}", e);
+        }
+    }
+}
+
+/// 攒批写入 `access_logs` 表：达到批量大小或超过刷新间隔时写入一次。
+async fn write_database(mut receiver: mpsc::Receiver<AccessRecord>, db: DatabaseConnection) {
+    let mut batch: Vec<AccessRecord> = Vec::with_capacity(DB_BATCH_SIZE);
+    let mut ticker = tokio::time::interval(DB_FLUSH_INTERVAL);
+
+    loop {
+        tokio::select! {
+            record = receiver.recv() => match record {
+                Some(record) => {
+                    batch.push(record);
+                    if batch.len() < DB_BATCH_SIZE {
+                        continue;
+                    }
+                }
+                None => {
+                    flush(&db, &mut batch).await;
+                    return;
+                }
+            },
+            _ = ticker.tick() => {}
+        }
+        flush(&db, &mut batch).await;
+    }
+}
+
+async fn flush(db: &DatabaseConnection, batch: &mut Vec<AccessRecord>) {
+    if batch.is_empty() {
+        return;
+    }
+
+    let models = batch.drain(..).map(|record| access_logs::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        method: Set(record.method),
+        path: Set(record.path),
+        status: Set(record.status as i16),
+        latency_ms: Set(record.latency_ms as i64),
+        user_id: Set(record.user_id),
+        request_id: Set(record.request_id),
+        ip: Set(record.ip),
+        created_at: Set(record.timestamp.into()),
+    });
+
+    if let Err(e) = access_logs::Entity::insert_many(models).exec(db).await {
+        tracing::error!(target: target::HTTP, "❌ Access log insert failed: {}", e);
+    }
+}
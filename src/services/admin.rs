@@ -0,0 +1,316 @@
+// src/services/admin.rs
+use chrono::Utc;
+use sea_orm::*;
+use std::str::FromStr;
+use uuid::Uuid;
+
+use crate::core::log::target;
+use crate::{
+    core::{enums::UserRole, error::AppError},
+    dtos::{
+        admin::{BulkAction, BulkItemResult, BulkResult, BulkUserRequest},
+        auth::Claims,
+        user::{BanUserRequest, ResetPasswordRequest, UserProfile},
+    },
+    entity::users,
+    extractors::context::RequestContext,
+    services::{auth as AuthService, permission as PermissionService, user as UserService},
+    state::AppState,
+};
+
+/// 查找被操作的用户，并校验操作者有权管理该用户：不能操作自己，只能操作角色低于自己的用户。
+/// 接受任意数据库连接（包括事务），以便在批量操作中复用。
+async fn find_manageable_user<C: ConnectionTrait>(db: &C, actor: &Claims, target_id: Uuid) -> Result<users::Model, AppError> {
+    if actor.sub == target_id.to_string() {
+        return Err(AppError::Forbidden("Cannot perform this action on yourself".to_string()));
+    }
+
+    let target = users::Entity::find_by_id(target_id)
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+    let actor_role = UserRole::from_str(&actor.role).unwrap_or(UserRole::User);
+    if target.role >= actor_role {
+        return Err(AppError::Forbidden("Cannot manage a user with an equal or higher role".to_string()));
+    }
+
+    Ok(target)
+}
+
+/// 用户状态变更后的统一收尾：递增令牌版本并清除资料缓存和权限缓存，使变更在下一次请求时生效，
+/// 而不是等到已签发的访问令牌过期（令牌中的角色在签发时确定）。
+async fn propagate_user_change(state: &AppState, user_id: &str) -> Result<(), AppError> {
+    AuthService::bump_token_version(state, user_id).await?;
+    UserService::purge_profile_cache(state, user_id).await;
+    PermissionService::invalidate_user_permissions(state, user_id).await;
+    Ok(())
+}
+
+/// 封禁用户。设置账户为禁用状态并记录原因和自动解封时间，
+/// 同时立即吊销该用户已签发的全部令牌，并清除相关缓存，使封禁即时生效。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `ctx`: 请求上下文，操作者（管理员）为其中的调用方。
+/// - `target_id`: 被封禁的用户ID。
+/// - `req`: 封禁原因和可选的自动解封时间。
+///
+/// # 返回值
+/// - `Ok(UserProfile)`: 封禁后的用户资料。
+/// - `Err(AppError)`: 用户不存在、权限不足、解封时间无效或数据库错误。
+pub async fn ban_user(
+    state: &AppState,
+    ctx: &RequestContext,
+    target_id: Uuid,
+    req: BanUserRequest,
+) -> Result<UserProfile, AppError> {
+    let actor = ctx.actor()?;
+    if req.until.is_some_and(|until| until <= Utc::now()) {
+        return Err(AppError::BadRequest("Ban expiry must be in the future".to_string()));
+    }
+
+    let target = ctx.run("find_user", find_manageable_user(&state.db, actor, target_id)).await?;
+
+    // 第一步：更新数据库中的账户状态和封禁信息
+    let mut active: users::ActiveModel = target.into();
+    active.is_active = Set(false);
+    active.ban_reason = Set(Some(req.reason));
+    active.banned_until = Set(req.until.map(Into::into));
+    let updated = ctx.run("ban_user", active.update(&state.db)).await?;
+
+    // 第二步：吊销已签发的令牌并清除缓存，使封禁立即生效而不是等到令牌过期
+    let user_id = updated.id.to_string();
+    AuthService::revoke_user_tokens(state, &user_id).await?;
+    propagate_user_change(state, &user_id).await?;
+
+    tracing::warn!(target: target::ADMIN, "⛔ User {} banned by {}", updated.username, actor.username);
+    Ok(updated.into())
+}
+
+/// 解封用户。恢复账户为启用状态并清除封禁信息。
+///
+/// # 返回值
+/// - `Ok(UserProfile)`: 解封后的用户资料。
+/// - `Err(AppError)`: 用户不存在、权限不足或数据库错误。
+pub async fn unban_user(state: &AppState, ctx: &RequestContext, target_id: Uuid) -> Result<UserProfile, AppError> {
+    let actor = ctx.actor()?;
+    let target = ctx.run("find_user", find_manageable_user(&state.db, actor, target_id)).await?;
+    let updated = clear_ban(state, target).await?;
+
+    tracing::info!(target: target::ADMIN, "✅ User {} unbanned by {}", updated.username, actor.username);
+    Ok(updated.into())
+}
+
+/// 重置用户密码。导入时未沿用密码的用户（见 `PasswordMode::Reset`）由管理员设置初始密码后才能登录，
+/// 也用于用户遗忘密码时由管理员协助重置。重置后吊销该用户已签发的全部令牌，旧密码建立的会话不再有效。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `ctx`: 请求上下文，操作者（管理员）为其中的调用方。
+/// - `target_id`: 被重置密码的用户ID。
+/// - `req`: 新密码。
+///
+/// # 返回值
+/// - `Ok(UserProfile)`: 重置后的用户资料。
+/// - `Err(AppError)`: 用户不存在、权限不足或数据库错误。
+pub async fn reset_password(
+    state: &AppState,
+    ctx: &RequestContext,
+    target_id: Uuid,
+    req: ResetPasswordRequest,
+) -> Result<UserProfile, AppError> {
+    let actor = ctx.actor()?;
+    let target = ctx.run("find_user", find_manageable_user(&state.db, actor, target_id)).await?;
+
+    // 第一步：计算新密码的哈希并写入数据库
+    let mut active: users::ActiveModel = target.into();
+    active.password_hash = Set(AuthService::hash_password(&req.new_password)?);
+    let updated = ctx.run("reset_password", active.update(&state.db)).await?;
+
+    // 第二步：吊销已签发的令牌并清除缓存
+    let user_id = updated.id.to_string();
+    AuthService::revoke_user_tokens(state, &user_id).await?;
+    propagate_user_change(state, &user_id).await?;
+
+    tracing::warn!(target: target::ADMIN, "🔑 Password of user {} reset by {}", updated.username, actor.username);
+    Ok(updated.into())
+}
+
+/// 自动解封：如果用户的封禁已过期，则恢复账户状态。在登录和刷新令牌时调用，
+/// 这样无需后台任务即可实现 "到期自动解封"。
+///
+/// # 返回值
+/// - `Ok(users::Model)`: 最新的用户数据（未过期或未封禁时原样返回）。
+pub async fn lift_expired_ban(state: &AppState, user: users::Model) -> Result<users::Model, AppError> {
+    let expired = !user.is_active && user.banned_until.is_some_and(|until| until <= Utc::now());
+    if !expired {
+        return Ok(user);
+    }
+
+    tracing::info!(target: target::ADMIN, "⏰ Ban expired for user {}", user.username);
+    clear_ban(state, user).await
+}
+
+/// 清除封禁状态和封禁时记录的吊销标记，并刷新缓存
+async fn clear_ban(state: &AppState, user: users::Model) -> Result<users::Model, AppError> {
+    let mut active: users::ActiveModel = user.into();
+    active.is_active = Set(true);
+    active.ban_reason = Set(None);
+    active.banned_until = Set(None);
+    let updated = active.update(&state.db).await?;
+
+    let user_id = updated.id.to_string();
+    AuthService::clear_user_revocation(state, &user_id).await?;
+    propagate_user_change(state, &user_id).await?;
+    Ok(updated)
+}
+
+/// 批量用户操作。在单个数据库事务中对每个用户执行相同的操作（禁用、删除、变更角色），
+/// 并逐项返回执行结果。
+///
+/// 单个用户的业务错误（不存在、权限不足）只记录在该项结果中，不影响其他用户；
+/// 数据库错误会使整个事务回滚并返回错误。事务提交后再统一吊销令牌、清除缓存。
+///
+/// 试运行模式下同样在事务中执行全部操作以得到准确的逐项结果，最后回滚事务，不产生任何副作用。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `ctx`: 请求上下文，操作者（管理员）为其中的调用方。
+/// - `req`: 用户ID列表和操作类型。
+///
+/// # 返回值
+/// - `Ok(BulkResult)`: 每个用户的执行结果及汇总。
+/// - `Err(AppError)`: 角色越权或数据库错误（此时所有变更均已回滚）。
+pub async fn bulk_update_users(
+    state: &AppState,
+    ctx: &RequestContext,
+    req: BulkUserRequest,
+) -> Result<BulkResult, AppError> {
+    let actor = ctx.actor()?;
+
+    // 不能授予与自己同级或更高的角色
+    let actor_role = UserRole::from_str(&actor.role).unwrap_or(UserRole::User);
+    if matches!(&req.action, BulkAction::RoleChange { role } if *role >= actor_role) {
+        return Err(AppError::Forbidden("Cannot assign a role equal to or higher than your own".to_string()));
+    }
+
+    // 去重，保持请求中的顺序
+    let mut user_ids = req.user_ids;
+    let mut seen = std::collections::HashSet::new();
+    user_ids.retain(|id| seen.insert(*id));
+
+    // 第一步：在事务中逐个执行操作。事务遵守请求的截止时间，客户端超时后数据库侧的语句随之取消
+    let txn = ctx.begin(&state.db).await?;
+    let mut results = Vec::with_capacity(user_ids.len());
+
+    for user_id in user_ids {
+        match apply_bulk_action(&txn, actor, user_id, &req.action).await {
+            Ok(diff) => {
+                results.push(BulkItemResult { user_id, success: true, error: None, warning: None, diff: Some(diff) });
+            }
+            // 数据库错误：直接返回，事务在 drop 时自动回滚
+            Err(AppError::DatabaseError(e)) => return Err(AppError::DatabaseError(e)),
+            Err(e) => results.push(BulkItemResult {
+                user_id,
+                success: false,
+                error: Some(e.to_string()),
+                warning: None,
+                diff: None,
+            }),
+        }
+    }
+
+    let succeeded = results.iter().filter(|item| item.success).count();
+
+    // 试运行：回滚事务，直接返回预期结果
+    if req.dry_run {
+        txn.rollback().await?;
+        tracing::info!(target: target::ADMIN, "🧪 Bulk {:?} dry run by {}: {}/{} would succeed", req.action, actor.username, succeeded, results.len());
+        return Ok(BulkResult {
+            action: req.action,
+            dry_run: true,
+            succeeded,
+            failed: results.len() - succeeded,
+            results,
+        });
+    }
+
+    txn.commit().await?;
+
+    // 第二步：事务提交后处理 Redis 中的副作用。禁用和删除需要强制下线，
+    // 所有操作都需要递增令牌版本并清除缓存（角色变更后旧令牌中的角色立即失效）。
+    // 变更已经提交，副作用失败时不再返回错误：每个用户都执行一遍，失败记录日志并在该项结果中给出警告，
+    // 调用方据此知道哪些用户的变更要等现有访问令牌过期后才完全生效
+    let mut side_effect_failures = 0;
+    for item in results.iter_mut().filter(|item| item.success) {
+        let user_id = item.user_id.to_string();
+        if let Err(e) = apply_bulk_side_effects(state, &user_id, &req.action).await {
+            side_effect_failures += 1;
+            tracing::error!(target: target::ADMIN, "❌ Bulk {:?} committed but side effects failed for {}: {}", req.action, user_id, e);
+            item.warning = Some(
+                "Change committed, but existing sessions could not be updated; it takes full effect when current tokens expire"
+                    .to_string(),
+            );
+        }
+    }
+    if side_effect_failures > 0 {
+        tracing::warn!(target: target::ADMIN, "⚠️ Bulk {:?}: side effects failed for {}/{} users", req.action, side_effect_failures, succeeded);
+    }
+
+    tracing::info!(target: target::ADMIN, "📦 Bulk {:?} by {}: {}/{} succeeded", req.action, actor.username, succeeded, results.len());
+
+    Ok(BulkResult {
+        action: req.action,
+        dry_run: false,
+        succeeded,
+        failed: results.len() - succeeded,
+        results,
+    })
+}
+
+/// 批量操作提交后对单个用户执行的 Redis 副作用。吊销令牌失败时仍然递增令牌版本、清除缓存，
+/// 返回遇到的第一个错误。
+async fn apply_bulk_side_effects(state: &AppState, user_id: &str, action: &BulkAction) -> Result<(), AppError> {
+    let revoked = if matches!(action, BulkAction::Deactivate | BulkAction::Delete) {
+        AuthService::revoke_user_tokens(state, user_id).await
+    } else {
+        Ok(())
+    };
+    let propagated = propagate_user_change(state, user_id).await;
+    revoked.and(propagated)
+}
+
+/// 在事务中对单个用户执行批量操作，返回用于审计日志的变更内容
+async fn apply_bulk_action(
+    txn: &DatabaseTransaction,
+    actor: &Claims,
+    user_id: Uuid,
+    action: &BulkAction,
+) -> Result<serde_json::Value, AppError> {
+    let target = find_manageable_user(txn, actor, user_id).await?;
+
+    let diff = match action {
+        BulkAction::Deactivate => {
+            let diff = serde_json::json!({ "is_active": { "from": target.is_active, "to": false } });
+            let mut active: users::ActiveModel = target.into();
+            active.is_active = Set(false);
+            active.update(txn).await?;
+            diff
+        }
+        BulkAction::Delete => {
+            let diff = serde_json::json!({ "username": target.username, "role": target.role });
+            users::Entity::delete_by_id(target.id).exec(txn).await?;
+            diff
+        }
+        BulkAction::RoleChange { role } => {
+            let diff = serde_json::json!({ "role": { "from": target.role, "to": role } });
+            let mut active: users::ActiveModel = target.into();
+            active.role = Set(role.clone());
+            active.update(txn).await?;
+            diff
+        }
+    };
+
+    Ok(diff)
+}
@@ -0,0 +1,207 @@
+// src/services/analytics.rs
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{Days, NaiveDate, Utc};
+use sea_orm::*;
+
+use crate::core::log::target;
+use crate::{
+    core::{
+        constants::{
+            ANALYTICS_AGGREGATE_INTERVAL, ANALYTICS_DAILY_RETENTION_DAYS, ANALYTICS_RAW_RETENTION_DAYS,
+            REDIS_KEY_ANALYTICS_LOCK, REDIS_PREFIX_ANALYTICS_DAILY, REDIS_PREFIX_ANALYTICS_RAW,
+        },
+        error::AppError,
+    },
+    dtos::admin::{DailyAnalytics, RoutePopularity},
+    entity::users,
+    services::stats::RequestSample,
+    state::AppState,
+    utils::lock::DistributedLock,
+};
+
+// 匿名使用统计：请求进入时只累加当天的原始计数（按路由的请求数、按路由的调用方 HyperLogLog、
+// 已登录用户的 HyperLogLog），由后台任务定期聚合为日报，按 k-匿名阈值隐藏涉及用户过少的统计项。
+// 统计接口只读取聚合后的日报，不接触原始计数，也不包含任何用户ID；原始计数在两天后自动过期。
+
+fn raw_key(kind: &str, date: &str) -> String {
+    format!("{}{}:{}", REDIS_PREFIX_ANALYTICS_RAW, kind, date)
+}
+
+fn route_actors_key(date: &str, route: &str) -> String {
+    format!("{}actors:{}:{}", REDIS_PREFIX_ANALYTICS_RAW, date, route)
+}
+
+fn daily_key(date: &str) -> String {
+    format!("{}{}", REDIS_PREFIX_ANALYTICS_DAILY, date)
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+/// 把一个请求累加到当天的原始计数。追加到请求统计的写入管道中，不单独访问 Redis。
+///
+/// 调用方按用户ID区分，匿名请求按来源IP区分；二者都只写入 HyperLogLog，无法从中还原出具体的调用方。
+/// 未同意使用行为分析（`ConsentPurpose::Analytics`）的用户按匿名请求记录，不计入日活跃用户。
+///
+/// # 参数
+/// - `pipe`: 请求统计的写入管道
+/// - `sample`: 请求的统计信息
+/// - `consented`: 已登录用户是否同意使用行为分析（见 `consent::has_consent`），匿名请求为 `false`
+pub fn append(pipe: &mut redis::Pipeline, sample: &RequestSample, consented: bool) {
+    let date = format_date(Utc::now().date_naive());
+    let expire = ANALYTICS_RAW_RETENTION_DAYS * 86400;
+    let user_id = sample.user_id.as_ref().filter(|_| consented);
+    let actor = match user_id {
+        Some(user_id) => user_id.clone(),
+        None => format!("ip:{}", sample.client_ip),
+    };
+
+    let routes_key = raw_key("routes", &date);
+    let actors_key = route_actors_key(&date, &sample.route);
+    pipe.hincr(&routes_key, &sample.route, 1).ignore();
+    pipe.expire(&routes_key, expire).ignore();
+    pipe.pfadd(&actors_key, actor).ignore();
+    pipe.expire(&actors_key, expire).ignore();
+    if let Some(user_id) = user_id {
+        let active_key = raw_key("active", &date);
+        pipe.pfadd(&active_key, user_id).ignore();
+        pipe.expire(&active_key, expire).ignore();
+    }
+}
+
+/// 聚合某一天的匿名使用统计并写入日报：日活跃用户数、注册数和接口热度。
+/// 涉及的用户数少于 k（`ANALYTICS_K_ANONYMITY`）的统计项不写入日报。
+///
+/// # 参数
+/// - `state`: 应用程序状态
+/// - `date`: 统计日期（UTC）
+///
+/// # 返回值
+/// - `Ok(DailyAnalytics)`: 写入的日报
+/// - `Err(AppError)`: Redis 或数据库读取失败
+pub async fn aggregate(state: &AppState, date: NaiveDate) -> Result<DailyAnalytics, AppError> {
+    let k = state.config.analytics_k_anonymity;
+    let day = format_date(date);
+    let mut redis = state.redis.clone();
+
+    // 第一步：读取当天的原始计数和各路由的调用方数量
+    let (active_users, route_requests): (u64, HashMap<String, u64>) = redis::pipe()
+        .pfcount(raw_key("active", &day))
+        .hgetall(raw_key("routes", &day))
+        .query_async(&mut redis)
+        .await?;
+
+    let mut pipe = redis::pipe();
+    let routes: Vec<(String, u64)> = route_requests.into_iter().collect();
+    for (route, _) in &routes {
+        pipe.pfcount(route_actors_key(&day, route));
+    }
+    let route_actors: Vec<u64> = if routes.is_empty() { Vec::new() } else { pipe.query_async(&mut redis).await? };
+
+    // 第二步：从数据库统计当天的注册数
+    let start = date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    let end = start + chrono::Duration::days(1);
+    let signups = users::Entity::find()
+        .filter(users::Column::CreatedAt.gte(start))
+        .filter(users::Column::CreatedAt.lt(end))
+        .count(&state.db)
+        .await?;
+
+    // 第三步：k-匿名处理，隐藏涉及用户过少的统计项
+    let total_routes = routes.len() as u64;
+    let mut popular: Vec<RoutePopularity> = routes
+        .into_iter()
+        .zip(route_actors)
+        .filter(|(_, actors)| *actors >= k)
+        .map(|((route, requests), _)| RoutePopularity { route, requests })
+        .collect();
+    popular.sort_by(|a, b| b.requests.cmp(&a.requests).then_with(|| a.route.cmp(&b.route)));
+
+    let report = DailyAnalytics {
+        date: day.clone(),
+        k,
+        dau: (active_users >= k).then_some(active_users),
+        signups: (signups >= k).then_some(signups),
+        suppressed_routes: total_routes - popular.len() as u64,
+        routes: popular,
+        generated_at: Utc::now().to_rfc3339(),
+    };
+
+    // 第四步：写入日报
+    let json = serde_json::to_string(&report)
+        .map_err(|e| AppError::InternalServerError(format!("Serialize analytics failed: {}", e)))?;
+    let _: () = redis::cmd("SET")
+        .arg(daily_key(&day))
+        .arg(json)
+        .arg("EX")
+        .arg(ANALYTICS_DAILY_RETENTION_DAYS * 86400)
+        .query_async(&mut redis)
+        .await?;
+
+    Ok(report)
+}
+
+/// 读取最近若干天的日报，按日期倒序。尚未聚合的日期不出现在结果中。
+///
+/// # 参数
+/// - `state`: 应用程序状态
+/// - `days`: 最近多少天（包含今天）
+///
+/// # 返回值
+/// - `Ok(Vec<DailyAnalytics>)`: 已聚合的日报
+/// - `Err(AppError)`: Redis 读取失败
+pub async fn daily(state: &AppState, days: i64) -> Result<Vec<DailyAnalytics>, AppError> {
+    let today = Utc::now().date_naive();
+    let keys: Vec<String> = (0..days as u64)
+        .filter_map(|offset| today.checked_sub_days(Days::new(offset)))
+        .map(|date| daily_key(&format_date(date)))
+        .collect();
+
+    let mut redis = state.redis.clone();
+    let values: Vec<Option<String>> = redis::cmd("MGET").arg(&keys).query_async(&mut redis).await?;
+
+    Ok(values
+        .into_iter()
+        .flatten()
+        .filter_map(|json| serde_json::from_str(&json).ok())
+        .collect())
+}
+
+/// 启动匿名使用统计的聚合任务：每小时聚合一次当天（部分）和前一天（最终）的统计。
+/// 多实例部署时通过 Redis 锁保证同一时间只有一个实例执行聚合。
+pub fn spawn_aggregator(state: AppState) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(ANALYTICS_AGGREGATE_INTERVAL));
+        // 锁在下一次执行前过期，不需要主动释放
+        let lock = DistributedLock::new(REDIS_KEY_ANALYTICS_LOCK, Duration::from_secs(ANALYTICS_AGGREGATE_INTERVAL / 2));
+
+        loop {
+            ticker.tick().await;
+
+            match lock.try_acquire(&state.redis).await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    tracing::warn!(target: target::SYSTEM, "⚠️ Failed to acquire analytics lock: {}", e);
+                    continue;
+                }
+            }
+
+            let today = Utc::now().date_naive();
+            for date in [today.pred_opt(), Some(today)].into_iter().flatten() {
+                match aggregate(&state, date).await {
+                    Ok(report) => tracing::debug!(
+                        target: target::SYSTEM,
+                        "📊 Analytics aggregated for {} ({} routes, {} suppressed)",
+                        report.date,
+                        report.routes.len(),
+                        report.suppressed_routes
+                    ),
+                    Err(e) => tracing::warn!(target: target::SYSTEM, "⚠️ Analytics aggregation for {} failed: {}", date, e),
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,439 @@
+// src/services/audit.rs
+use aes_gcm::{
+    aead::{Aead, AeadCore, OsRng},
+    Aes256Gcm, KeyInit,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use ed25519_dalek::{Signer, SigningKey};
+use secrecy::{ExposeSecret, SecretString};
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::core::log::target;
+use crate::{
+    core::{constants::AUDIT_EXPORT_MAX_ROWS, enums::AuditAction, error::AppError},
+    dtos::{
+        audit::{
+            AuditExportArchive, AuditExportHeader, AuditExportQuery, AuditLogFilter, AuditLogItem, FieldChange,
+            UserHistoryEntry,
+        },
+        pagination::{CursorPage, CursorQuery, PageQuery, Paginated},
+    },
+    entity::{audit_logs, users},
+    extractors::context::RequestContext,
+    state::AppState,
+    utils::{pagination, retry},
+};
+
+/// 一条待记录的审计事件。
+pub struct AuditEntry {
+    /// 操作者ID（管理员），系统操作时为空
+    pub actor_id: Option<Uuid>,
+    /// 操作类型
+    pub action: AuditAction,
+    /// 操作对象ID（如被注册的用户）
+    pub target_id: Option<Uuid>,
+    /// 操作者的请求来源IP
+    pub ip: Option<String>,
+    /// 变更内容（字段差异）
+    pub diff: Option<serde_json::Value>,
+}
+
+impl AuditEntry {
+    /// 以操作者ID字符串（通常来自 `claims.sub`）和操作类型构建审计事件。
+    pub fn new(actor_id: &str, action: AuditAction) -> Self {
+        Self {
+            actor_id: Uuid::parse_str(actor_id).ok(),
+            action,
+            target_id: None,
+            ip: None,
+            diff: None,
+        }
+    }
+
+    /// 以请求上下文构建审计事件，操作者和来源IP取自上下文。委托令牌的操作者是被授权人（`act.sub`）。
+    pub fn from_context(ctx: &RequestContext, action: AuditAction) -> Self {
+        let actor_id = ctx
+            .claims
+            .as_ref()
+            .map(|claims| claims.act.as_ref().map_or(claims.sub.as_str(), |actor| actor.sub.as_str()))
+            .unwrap_or_default();
+        Self::new(actor_id, action).ip(ctx.client_ip.clone())
+    }
+
+    pub fn target(mut self, target_id: Uuid) -> Self {
+        self.target_id = Some(target_id);
+        self
+    }
+
+    pub fn ip(mut self, ip: impl Into<String>) -> Self {
+        self.ip = Some(ip.into());
+        self
+    }
+
+    pub fn diff(mut self, diff: serde_json::Value) -> Self {
+        self.diff = Some(diff);
+        self
+    }
+}
+
+/// 记录一条审计日志。
+///
+/// 审计记录发生在特权操作成功之后，此时业务操作已经生效，因此写入失败只记录错误日志，
+/// 不向调用方返回错误（与缓存的 Soft Fail 策略一致），避免出现 "操作成功却返回500" 的情况。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接。
+/// - `entry`: 待记录的审计事件。
+pub async fn record(state: &AppState, entry: AuditEntry) {
+    let action = entry.action.clone();
+    let log = audit_logs::ActiveModel {
+        actor_id: Set(entry.actor_id),
+        action: Set(entry.action),
+        target_id: Set(entry.target_id),
+        ip: Set(entry.ip),
+        diff: Set(entry.diff),
+        ..Default::default()
+    };
+
+    if let Err(e) = audit_logs::Entity::insert(log).exec(&state.db).await {
+        tracing::error!(target: target::ADMIN, "❌ Failed to record audit log {}: {}", action, e);
+    } else {
+        tracing::info!(target: target::ADMIN, "📝 Audit: {} by {:?}", action, entry.actor_id);
+    }
+}
+
+/// 分页查询审计日志，按时间倒序返回。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接。
+/// - `page`: 分页参数。
+/// - `filter`: 过滤条件（操作类型、操作者、操作对象）。
+///
+/// # 返回值
+/// - `Ok(Paginated<AuditLogItem>)`: 当前页的审计日志及分页信息。
+/// - `Err(AppError)`: 数据库查询失败。
+pub async fn list(
+    state: &AppState,
+    page: PageQuery,
+    filter: AuditLogFilter,
+) -> Result<Paginated<AuditLogItem>, AppError> {
+    let paginator = audit_logs::Entity::find()
+        .filter(audit_log_condition(filter))
+        .order_by_desc(audit_logs::Column::CreatedAt)
+        .paginate(&state.db, page.per_page);
+
+    let counts = retry::with_retry("count_audit_logs", || paginator.num_items_and_pages()).await?;
+    let items = retry::with_retry("list_audit_logs", || paginator.fetch_page(page.page_index()))
+        .await?
+        .into_iter()
+        .map(AuditLogItem::from)
+        .collect();
+
+    Ok(Paginated::new(items, &page, counts.number_of_items, counts.number_of_pages))
+}
+
+/// 按游标分页查询审计日志，按时间倒序返回，时间相同的记录按ID排序。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接。
+/// - `query`: 游标和每页条数。
+/// - `filter`: 过滤条件（操作类型、操作者、操作对象）。
+///
+/// # 返回值
+/// - `Ok(CursorPage<AuditLogItem>)`: 当前页的审计日志及下一页的游标。
+/// - `Err(AppError)`: 游标无效或数据库查询失败。
+pub async fn list_by_cursor(
+    state: &AppState,
+    query: CursorQuery,
+    filter: AuditLogFilter,
+) -> Result<CursorPage<AuditLogItem>, AppError> {
+    let page = pagination::fetch_cursor_page(
+        &state.db,
+        audit_logs::Entity::find().filter(audit_log_condition(filter)),
+        (audit_logs::Column::CreatedAt, audit_logs::Column::Id),
+        Order::Desc,
+        "audit_logs:-created_at",
+        &query,
+        |log| (log.created_at, log.id),
+    )
+    .await?;
+
+    Ok(page.map(AuditLogItem::from))
+}
+
+/// 审计日志的过滤条件：按操作类型、操作者、操作对象过滤
+fn audit_log_condition(filter: AuditLogFilter) -> Condition {
+    let mut condition = Condition::all();
+    if let Some(action) = filter.action {
+        condition = condition.add(audit_logs::Column::Action.eq(action));
+    }
+    if let Some(actor_id) = filter.actor_id {
+        condition = condition.add(audit_logs::Column::ActorId.eq(actor_id));
+    }
+    if let Some(target_id) = filter.target_id {
+        condition = condition.add(audit_logs::Column::TargetId.eq(target_id));
+    }
+    condition
+}
+
+/// 变更历史中需要隐藏值的字段：字段名包含其中任一片段（不区分大小写）即隐藏。
+const REDACTED_HISTORY_FIELDS: &[&str] = &["password", "secret", "token"];
+
+/// 分页查询单个用户的变更历史，按时间倒序返回以该用户为操作对象的审计记录，
+/// 并把记录中的变更内容展开为逐字段的 `from` / `to`。
+///
+/// 审计记录的变更内容有两种写法：`{"role": {"from": "user", "to": "admin"}}` 和只记录新值的
+/// `{"is_active": true}`，两种都会被展开；密码哈希等敏感字段只保留字段名，值被隐藏。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接。
+/// - `ctx`: 请求上下文，查询遵守其中的截止时间。
+/// - `user_id`: 用户ID。
+/// - `page`: 分页参数。
+///
+/// # 返回值
+/// - `Ok(Paginated<UserHistoryEntry>)`: 当前页的变更记录及分页信息。
+/// - `Err(AppError)`: 用户不存在或数据库查询失败。
+pub async fn user_history(
+    state: &AppState,
+    ctx: &RequestContext,
+    user_id: Uuid,
+    page: PageQuery,
+) -> Result<Paginated<UserHistoryEntry>, AppError> {
+    // 已删除的用户仍然可以查看历史：只有没有任何记录时才确认用户是否存在，以区分 "无记录" 和 "用户不存在"
+    let paginator = audit_logs::Entity::find()
+        .filter(audit_logs::Column::TargetId.eq(user_id))
+        .order_by_desc(audit_logs::Column::CreatedAt)
+        .order_by_desc(audit_logs::Column::Id)
+        .paginate(&state.db, page.per_page);
+
+    let counts = ctx.run("count_user_history", retry::with_retry("count_user_history", || paginator.num_items_and_pages())).await?;
+    if counts.number_of_items == 0
+        && ctx.run("find_user", users::Entity::find_by_id(user_id).one(&state.db)).await?.is_none()
+    {
+        return Err(AppError::NotFound("User not found".to_string()));
+    }
+    let logs = ctx.run("fetch_user_history", retry::with_retry("fetch_user_history", || paginator.fetch_page(page.page_index()))).await?;
+
+    // 一次查询当前页所有操作者的用户名
+    let actor_ids: Vec<Uuid> = logs.iter().filter_map(|log| log.actor_id).collect();
+    let actors: std::collections::HashMap<Uuid, String> = if actor_ids.is_empty() {
+        Default::default()
+    } else {
+        let select = users::Entity::find()
+            .filter(users::Column::Id.is_in(actor_ids))
+            .select_only()
+            .column(users::Column::Id)
+            .column(users::Column::Username)
+            .into_tuple::<(Uuid, String)>();
+        ctx.run("find_history_actors", select.all(&state.db)).await?.into_iter().collect()
+    };
+
+    let items = logs
+        .into_iter()
+        .map(|log| UserHistoryEntry {
+            id: log.id.to_string(),
+            action: log.action,
+            actor_id: log.actor_id.map(|id| id.to_string()),
+            actor_username: log.actor_id.and_then(|id| actors.get(&id).cloned()),
+            ip: log.ip,
+            changes: log.diff.map(field_changes).unwrap_or_default(),
+            created_at: log.created_at.to_string(),
+        })
+        .collect();
+
+    Ok(Paginated::new(items, &page, counts.number_of_items, counts.number_of_pages))
+}
+
+/// 把审计记录的变更内容展开为逐字段的变更，敏感字段的值被隐藏。非对象的变更内容无法按字段展开，忽略。
+fn field_changes(diff: serde_json::Value) -> Vec<FieldChange> {
+    let serde_json::Value::Object(fields) = diff else {
+        return Vec::new();
+    };
+
+    fields
+        .into_iter()
+        .map(|(field, value)| {
+            let (from, to) = match value {
+                serde_json::Value::Object(mut change) if change.contains_key("from") || change.contains_key("to") => {
+                    (change.remove("from"), change.remove("to"))
+                }
+                value => (None, Some(value)),
+            };
+            if is_redacted(&field) {
+                let hide = |value: Option<serde_json::Value>| value.map(|_| serde_json::json!("[REDACTED]"));
+                return FieldChange { field, from: hide(from), to: hide(to) };
+            }
+            FieldChange { field, from: from.map(redact), to: to.map(redact) }
+        })
+        .collect()
+}
+
+/// 隐藏嵌套对象中的敏感字段（如批量导入记录中的用户对象）
+fn redact(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(fields) => fields
+            .into_iter()
+            .map(|(key, value)| {
+                let value = if is_redacted(&key) { serde_json::json!("[REDACTED]") } else { redact(value) };
+                (key, value)
+            })
+            .collect(),
+        serde_json::Value::Array(items) => items.into_iter().map(redact).collect(),
+        value => value,
+    }
+}
+
+fn is_redacted(field: &str) -> bool {
+    let field = field.to_ascii_lowercase();
+    REDACTED_HISTORY_FIELDS.iter().any(|pattern| field.contains(pattern))
+}
+
+/// 导出审计日志为签名（可选加密）的归档，供合规方离线校验完整性与来源。
+///
+/// 第一步：按过滤条件和时间范围按时间正序读取记录，超过 `AUDIT_EXPORT_MAX_ROWS` 时要求缩小范围，不做静默截断；
+/// 第二步：生成 NDJSON，第一行为导出说明；
+/// 第三步：按需使用 AES-256-GCM 加密；
+/// 第四步：对最终内容做 Ed25519 签名。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接和导出密钥配置。
+/// - `ctx`: 请求上下文，执行导出的管理员ID写入导出说明。
+/// - `filter`: 过滤条件（操作类型、操作者、操作对象）。
+/// - `query`: 时间范围以及是否加密。
+///
+/// # 返回值
+/// - `Ok(AuditExportArchive)`: 签名后的导出归档。
+/// - `Err(AppError)`: 未配置签名/加密密钥、记录过多或数据库查询失败。
+pub async fn export(
+    state: &AppState,
+    ctx: &RequestContext,
+    filter: AuditLogFilter,
+    query: AuditExportQuery,
+) -> Result<AuditExportArchive, AppError> {
+    let exported_by = ctx.actor()?.sub.as_str();
+    let signing_key = load_key(state.config.audit_export_signing_key.as_ref(), "signing")?
+        .map(|bytes| SigningKey::from_bytes(&bytes))
+        .ok_or(AppError::ServiceUnavailable("Audit export signing key is not configured".to_string()))?;
+    let encryption_key = if query.encrypt {
+        let key = load_key(state.config.audit_export_encryption_key.as_ref(), "encryption")?
+            .ok_or(AppError::BadRequest("Audit export encryption is not configured".to_string()))?;
+        Some(key)
+    } else {
+        None
+    };
+
+    // 第一步：读取记录。导出说明中保留原始过滤条件，便于合规方核对导出范围
+    let filter_description = serde_json::json!({
+        "action": filter.action,
+        "actor_id": filter.actor_id,
+        "target_id": filter.target_id,
+        "since": query.since,
+        "until": query.until,
+    });
+
+    let mut condition = Condition::all();
+    if let Some(action) = filter.action {
+        condition = condition.add(audit_logs::Column::Action.eq(action));
+    }
+    if let Some(actor_id) = filter.actor_id {
+        condition = condition.add(audit_logs::Column::ActorId.eq(actor_id));
+    }
+    if let Some(target_id) = filter.target_id {
+        condition = condition.add(audit_logs::Column::TargetId.eq(target_id));
+    }
+    if let Some(since) = query.since {
+        condition = condition.add(audit_logs::Column::CreatedAt.gte(since));
+    }
+    if let Some(until) = query.until {
+        condition = condition.add(audit_logs::Column::CreatedAt.lt(until));
+    }
+
+    let select = audit_logs::Entity::find()
+        .filter(condition)
+        .order_by_asc(audit_logs::Column::CreatedAt)
+        .order_by_asc(audit_logs::Column::Id)
+        .limit(AUDIT_EXPORT_MAX_ROWS + 1);
+    // 导出可能扫描大量记录，在设置了语句超时的事务中查询，请求超时后数据库侧的查询随之取消
+    let txn = ctx.begin(&state.db).await?;
+    let logs = ctx.run("export_audit_logs", select.all(&txn)).await?;
+    txn.commit().await?;
+    if logs.len() as u64 > AUDIT_EXPORT_MAX_ROWS {
+        return Err(AppError::BadRequest(format!(
+            "Too many audit logs to export (max {}), narrow the time range",
+            AUDIT_EXPORT_MAX_ROWS
+        )));
+    }
+
+    // 第二步：生成 NDJSON
+    let header = AuditExportHeader {
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        exported_by: exported_by.to_string(),
+        count: logs.len(),
+        filter: filter_description,
+    };
+    let mut payload = to_line(&header)?;
+    for log in logs {
+        payload.extend(to_line(&AuditLogItem::from(log))?);
+    }
+
+    // 第三步：按需加密，随机 nonce 放在密文之前
+    let encryption = match encryption_key {
+        Some(key) => {
+            let cipher = Aes256Gcm::new(&key.into());
+            let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+            let ciphertext = cipher
+                .encrypt(&nonce, payload.as_slice())
+                .map_err(|_| AppError::InternalServerError("Failed to encrypt audit export".to_string()))?;
+            payload = [&nonce[..], &ciphertext[..]].concat();
+            Some("AES-256-GCM")
+        }
+        None => None,
+    };
+
+    // 第四步：签名最终内容（先加密后签名，校验签名不需要解密密钥）
+    let signature = signing_key.sign(&payload);
+
+    tracing::info!(
+        target: target::ADMIN,
+        "📦 Audit logs exported by {} ({} bytes, encrypted: {})",
+        exported_by,
+        payload.len(),
+        encryption.is_some()
+    );
+
+    Ok(AuditExportArchive {
+        version: 1,
+        signature_algorithm: "Ed25519",
+        public_key: BASE64.encode(signing_key.verifying_key().as_bytes()),
+        signature: BASE64.encode(signature.to_bytes()),
+        encryption,
+        payload: BASE64.encode(payload),
+    })
+}
+
+/// 解析 Base64 编码的 32 字节密钥。未配置时返回 `None`，格式错误视为服务端配置错误。
+fn load_key(secret: Option<&SecretString>, name: &str) -> Result<Option<[u8; 32]>, AppError> {
+    let Some(secret) = secret else {
+        return Ok(None);
+    };
+
+    BASE64
+        .decode(secret.expose_secret().trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .map(Some)
+        .ok_or_else(|| {
+            tracing::error!(target: target::ADMIN, "❌ Invalid audit export {} key, expected 32 bytes in base64", name);
+            AppError::InternalServerError("Invalid audit export key".to_string())
+        })
+}
+
+/// 序列化为一行 JSON（末尾带换行）。
+fn to_line<T: serde::Serialize>(value: &T) -> Result<Vec<u8>, AppError> {
+    let mut line = serde_json::to_vec(value)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to serialize audit export: {}", e)))?;
+    line.push(b'\n');
+    Ok(line)
+}
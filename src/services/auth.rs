@@ -1,25 +1,37 @@
 use argon2::{
     password_hash::SaltString, Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
 };
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{encode, EncodingKey, Header};
 use rand::rngs::OsRng;
 use redis::AsyncCommands;
 use sea_orm::*;
 use secrecy::ExposeSecret;
+use serde_json::Value;
+use std::collections::HashMap;
 use uuid::Uuid;
 
+use crate::core::log::target;
 use crate::{
     core::{
         constants::*,
-        enums::UserRole,
+        enums::{LoginMethod, SecurityEventKind, UserRole},
         error::AppError,
         config::Config,
     },
-    dtos::auth::{Claims, LoginRequest, LoginResponse, RegisterRequest},
-    entity::users,
+    dtos::{
+        auth::{ActorClaim, Claims, LoginRequest, LoginResponse, RegisterRequest},
+        export::SessionInfo,
+    },
+    entity::{login_history, users},
+    extractors::context::RequestContext,
+    services::{
+        admin as AdminService,
+        presence as PresenceService,
+        security::{self as SecurityService, SecurityEvent},
+    },
     state::AppState,
-    utils::limiter::check_rate_limit,
+    utils::{limiter::check_rate_limit_mode, public_id, retry},
 };
 
 // --- 辅助函数模块：提供认证服务中使用的工具函数，如密钥生成、令牌处理等 ---
@@ -27,10 +39,52 @@ use crate::{
 fn refresh_key(token: &str) -> String {
     format!("{}{}", REDIS_PREFIX_REFRESH, token)
 }
+
+/// 刷新令牌在 Redis 中的值：`{user_id}@{region}`。区域标签用于区分会话由哪个区域签发，
+/// 兼容没有区域标签的旧格式（只有用户ID）。
+#[inline]
+fn session_value(user_id: &str, region: &str) -> String {
+    format!("{}@{}", user_id, region)
+}
+
+/// 解析刷新令牌的值，返回用户ID和签发区域（旧格式没有区域）。
+fn parse_session_value(value: &str) -> (&str, Option<&str>) {
+    match value.split_once('@') {
+        Some((user_id, region)) => (user_id, Some(region)),
+        None => (value, None),
+    }
+}
+
+/// 旧刷新令牌在宽限期内的轮换结果
+fn rotated_key(token: &str) -> String {
+    format!("{}{}", REDIS_PREFIX_REFRESH_ROTATED, token)
+}
+
 #[inline]
-fn blacklist_key(token: &str) -> String {
+pub(crate) fn blacklist_key(token: &str) -> String {
     format!("{}{}", REDIS_PREFIX_BLACKLIST, token)
 }
+#[inline]
+pub fn user_revoked_key(user_id: &str) -> String {
+    format!("{}{}", REDIS_PREFIX_USER_REVOKED, user_id)
+}
+
+pub fn token_version_key(user_id: &str) -> String {
+    format!("{}{}", REDIS_PREFIX_TOKEN_VERSION, user_id)
+}
+
+fn user_sessions_key(user_id: &str) -> String {
+    format!("{}{}", REDIS_PREFIX_USER_SESSIONS, user_id)
+}
+
+/// 被禁用账户登录时返回的错误。用户自助冻结的账户给出单独的提示，引导用户走恢复流程而不是等待解封。
+pub fn inactive_account_error(user: &users::Model) -> AppError {
+    if user.ban_reason.as_deref() == Some(ACCOUNT_FROZEN_REASON) {
+        AppError::Forbidden("Account is frozen, contact support to restore access".to_string())
+    } else {
+        AppError::Forbidden("Account is disabled".to_string())
+    }
+}
 
 /// 生成访问令牌（Access Token）。这是一个纯函数，没有副作用，只负责根据用户信息生成 JWT 令牌。
 /// 令牌包含用户身份信息（ID、用户名、角色）和过期时间，使用配置中的密钥进行签名。
@@ -40,6 +94,8 @@ fn blacklist_key(token: &str) -> String {
 /// - `user_id`: 用户唯一标识符（UUID 字符串格式）。
 /// - `username`: 用户名，用于在令牌中标识用户。
 /// - `role`: 用户角色（Admin 或 User），用于权限控制。
+/// - `token_version`: 用户当前的令牌版本，见 `bump_token_version`。
+/// - `ext`: 自定义扩展声明，由 `ClaimsBuilder` 钩子生成。
 ///
 /// # 返回值
 /// - `Ok(String)`: 成功时返回签名的 JWT 令牌字符串。
@@ -49,25 +105,129 @@ fn generate_access_token(
     user_id: &str,
     username: &str,
     role: UserRole,
+    token_version: u64,
+    ext: HashMap<String, Value>,
 ) -> Result<String, AppError> {
     let now = Utc::now();
-    let exp = (now + Duration::seconds(config.jwt_expiration)).timestamp() as usize;
-    
+    let exp = (now + Duration::seconds(config.jwt_expiration.as_secs() as i64)).timestamp() as usize;
+
     let claims = Claims {
         sub: user_id.to_string(),
         username: username.to_string(),
         role: role.to_string(),
         exp,
+        iat: now.timestamp() as usize,
+        ver: token_version,
+        ext,
+        act: None,
     };
 
+    sign_claims(config, &claims)
+}
+
+/// 使用配置中的密钥签名令牌声明。
+fn sign_claims(config: &Config, claims: &Claims) -> Result<String, AppError> {
     encode(
         &Header::default(),
-        &claims,
+        claims,
         &EncodingKey::from_secret(config.jwt_secret.expose_secret().as_bytes()),
     )
     .map_err(|e| AppError::InternalServerError(format!("Token generation failed: {}", e)))
 }
 
+/// 为被授权人签发委托访问令牌：`sub` 为授权人，`act` 声明记录实际操作者和允许的操作范围。
+///
+/// 委托令牌只有访问令牌、没有刷新令牌，有效期取访问令牌有效期和委托到期时间中较早的一个；
+/// 角色固定为普通用户，即使授权人是管理员也不能通过委托访问管理端。
+/// 令牌记录授权人当前的令牌版本，授权人被封禁或角色变更时委托令牌一并失效。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含 Redis 客户端和配置信息。
+/// - `grantor`: 授权人。
+/// - `actor`: 实际操作者（被授权人）声明。
+/// - `expires_at`: 委托的到期时间。
+///
+/// # 返回值
+/// - `Ok((String, i64))`: 委托访问令牌及其剩余有效期（秒）。
+/// - `Err(AppError)`: 令牌生成失败或 Redis 读取失败。
+pub async fn issue_delegated_token(
+    state: &AppState,
+    grantor: &users::Model,
+    actor: ActorClaim,
+    expires_at: DateTime<Utc>,
+) -> Result<(String, i64), AppError> {
+    let user_id = grantor.id.to_string();
+    let mut redis = state.redis.clone();
+    let token_version: Option<u64> = redis.get(token_version_key(&user_id)).await?;
+
+    let now = Utc::now();
+    let exp = (now + Duration::seconds(state.config.jwt_expiration.as_secs() as i64)).min(expires_at);
+    let claims = Claims {
+        sub: user_id,
+        username: grantor.username.clone(),
+        role: UserRole::User.to_string(),
+        exp: exp.timestamp() as usize,
+        iat: now.timestamp() as usize,
+        ver: token_version.unwrap_or_default(),
+        ext: HashMap::new(),
+        act: Some(actor),
+    };
+
+    let token = sign_claims(&state.config, &claims)?;
+    Ok((token, (exp - now).num_seconds()))
+}
+
+/// 为已通过认证的用户签发令牌对。生成访问令牌（JWT）和刷新令牌（UUID v4），
+/// 并将刷新令牌存入 Redis，有效期与刷新令牌的有效期一致。
+///
+/// 登录、令牌刷新、设备授权等所有签发令牌的入口共用此函数，保证令牌格式和存储方式一致。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含 Redis 客户端和配置信息。
+/// - `user`: 已通过认证且处于激活状态的用户。
+///
+/// # 返回值
+/// - `Ok(LoginResponse)`: 新签发的访问令牌和刷新令牌。
+/// - `Err(AppError)`: 令牌生成失败或 Redis 写入失败。
+pub async fn issue_token_pair(state: &AppState, user: &users::Model) -> Result<LoginResponse, AppError> {
+    let user_id = user.id.to_string();
+    let ext = state.claims_builder.build(user).await;
+
+    // 令牌中记录当前的令牌版本，用户角色或状态变更后旧令牌即被拒绝
+    let mut redis = state.redis.clone();
+    let token_version: Option<u64> = redis.get(token_version_key(&user_id)).await?;
+    let access_token = generate_access_token(
+        &state.config,
+        &user_id,
+        &user.username,
+        user.role.clone(),
+        token_version.unwrap_or_default(),
+        ext,
+    )?;
+    let refresh_token = Uuid::new_v4().to_string();
+
+    // 存储刷新令牌与用户ID的关联（带签发区域标签），用于后续的令牌验证和刷新操作；
+    // 同时把令牌加入该用户的会话索引，供列出和吊销会话使用。
+    // 类型提示：显式指定 Redis 操作返回类型为 ()，以满足 FromRedisValue trait 的要求。
+    let ttl = state.config.refresh_token_expiration.as_secs();
+    let sessions_key = user_sessions_key(&user_id);
+    let _: () = redis::pipe()
+        .atomic()
+        .set_ex(refresh_key(&refresh_token), session_value(&user_id, &state.config.region), ttl)
+        .ignore()
+        .sadd(&sessions_key, &refresh_token)
+        .ignore()
+        .expire(&sessions_key, ttl as i64)
+        .ignore()
+        .query_async(&mut redis)
+        .await?;
+
+    Ok(LoginResponse {
+        access_token,
+        refresh_token,
+    })
+}
+
 // --- 业务逻辑模块：实现认证服务的核心功能，如注册、登录、刷新令牌、登出等 ---
 
 /// 用户注册服务。这个函数处理新用户的注册流程，包括密码哈希、数据验证和数据库插入。
@@ -79,32 +239,52 @@ fn generate_access_token(
 /// - `req`: 注册请求数据，包含用户名、密码、手机号等信息。
 ///
 /// # 返回值
-/// - `Ok(())`: 成功时返回空值，表示用户注册成功。
+/// - `Ok(users::Model)`: 成功时返回新创建的用户。
 /// - `Err(AppError)`: 失败时返回相应的错误，如用户已存在、数据库错误、密码哈希失败等。
-pub async fn register(state: &AppState, req: RegisterRequest) -> Result<(), AppError> {
+pub async fn register(state: &AppState, req: RegisterRequest) -> Result<users::Model, AppError> {
+    create_user(&state.db, req, UserRole::User).await
+}
+
+/// 使用 Argon2 和随机盐值计算密码哈希（PHC 格式），注册和管理员重置密码共用。
+pub fn hash_password(password: &str) -> Result<String, AppError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AppError::InternalServerError(format!("Hash failed: {}", e)))
+}
+
+/// 创建指定角色的用户。注册接口创建普通用户，`create-admin` 命令创建管理员。
+///
+/// # 参数
+/// - `db`: 数据库连接
+/// - `req`: 用户名、密码和手机号（调用方负责校验）
+/// - `role`: 用户角色
+///
+/// # 返回值
+/// - `Ok(users::Model)`: 创建的用户
+/// - `Err(AppError)`: 用户名或手机号已存在，或数据库写入失败
+pub async fn create_user(db: &DatabaseConnection, req: RegisterRequest, role: UserRole) -> Result<users::Model, AppError> {
     // 第一步：密码哈希。使用 Argon2 算法和随机盐值对用户密码进行安全哈希。
     // Argon2 是密码哈希竞赛的获胜者，能有效抵抗暴力破解和彩虹表攻击。
-    let salt = SaltString::generate(&mut OsRng);
-    let password_hash = Argon2::default()
-        .hash_password(req.password.as_bytes(), &salt)
-        .map_err(|e| AppError::InternalServerError(format!("Hash failed: {}", e)))?
-        .to_string();
+    let password_hash = hash_password(&req.password)?;
 
     // 第二步：构建数据模型。将请求数据转换为 SeaORM 的 ActiveModel，
-    // 设置用户的默认角色为普通用户（User），并激活账户状态。
+    // 设置用户角色，并激活账户状态。
     let new_user = users::ActiveModel {
+        public_id: Set(public_id::generate()),
         username: Set(req.username),
         password_hash: Set(password_hash),
         phone: Set(req.phone),
-        role: Set(UserRole::User),
+        role: Set(role),
         is_active: Set(true),
         ..Default::default()
     };
 
     // 第三步：插入数据库。将构建好的用户模型保存到 PostgreSQL 数据库中。
     // 如果发生唯一键冲突（用户名或手机号已存在），返回适当的错误信息。
-    users::Entity::insert(new_user)
-        .exec(&state.db)
+    let user = users::Entity::insert(new_user)
+        .exec_with_returning(db)
         .await
         .map_err(|e| {
             // 处理唯一键冲突：检查数据库错误信息是否包含 "duplicate key"，
@@ -116,7 +296,7 @@ pub async fn register(state: &AppState, req: RegisterRequest) -> Result<(), AppE
             }
         })?;
 
-    Ok(())
+    Ok(user)
 }
 
 /// 用户登录服务。这个函数处理用户登录认证，支持使用用户名或手机号登录。
@@ -125,23 +305,26 @@ pub async fn register(state: &AppState, req: RegisterRequest) -> Result<(), AppE
 ///
 /// # 参数
 /// - `state`: 应用程序状态，包含数据库连接、Redis 客户端和配置信息。
+/// - `ctx`: 请求上下文，来源IP和客户端标识写入登录历史。
 /// - `req`: 登录请求数据，包含账户标识（用户名或手机号）和密码。
 ///
 /// # 返回值
 /// - `Ok(LoginResponse)`: 成功时返回包含访问令牌和刷新令牌的响应。
 /// - `Err(AppError)`: 失败时返回相应的错误，如凭证无效、账户禁用、密码错误等。
-pub async fn login(state: &AppState, req: LoginRequest) -> Result<LoginResponse, AppError> {
+pub async fn login(state: &AppState, ctx: &RequestContext, req: LoginRequest) -> Result<LoginResponse, AppError> {
     // 第一步：查找用户。支持使用用户名或手机号登录，使用 Condition::any() 构建 OR 查询条件。
     // 如果找不到对应的用户，返回统一的"无效凭证"错误，避免泄露用户存在信息。
-    let user = users::Entity::find()
-        .filter(
-            Condition::any()
-                .add(users::Column::Username.eq(&req.account))
-                .add(users::Column::Phone.eq(&req.account)),
-        )
-        .one(&state.db)
-        .await?
-        .ok_or(AppError::AuthError("Invalid credentials".to_string()))?;
+    let user = retry::with_retry("find_login_user", || {
+        users::Entity::find()
+            .filter(
+                Condition::any()
+                    .add(users::Column::Username.eq(&req.account))
+                    .add(users::Column::Phone.eq(&req.account)),
+            )
+            .one(&state.db)
+    })
+    .await?
+    .ok_or(AppError::AuthError("Invalid credentials".to_string()))?;
 
     // 第二步：校验密码。使用 Argon2 算法验证用户输入的密码是否与存储的哈希值匹配。
     // 密码验证失败时返回统一的"无效凭证"错误，避免泄露具体的失败原因。
@@ -152,110 +335,203 @@ pub async fn login(state: &AppState, req: LoginRequest) -> Result<LoginResponse,
         .verify_password(req.password.as_bytes(), &parsed_hash)
         .map_err(|_| AppError::AuthError("Invalid credentials".to_string()))?;
 
+    // 封禁已到期的账户在这里自动解封
+    let user = AdminService::lift_expired_ban(state, user).await?;
     if !user.is_active {
-        return Err(AppError::Forbidden("Account is disabled".to_string()));
+        return Err(inactive_account_error(&user));
     }
 
-    // 第三步：生成令牌。创建访问令牌（JWT）和刷新令牌（UUID v4）。
-    // 访问令牌用于 API 身份验证，刷新令牌用于获取新的访问令牌。
-    let access_token = generate_access_token(&state.config, &user.id.to_string(), &user.username, user.role.clone())?;
-    let refresh_token = Uuid::new_v4().to_string();
-
-    // 第四步：将刷新令牌存入 Redis。设置过期时间与刷新令牌的有效期一致。
-    // 存储用户ID与刷新令牌的关联，用于后续的令牌验证和刷新操作。
-    let mut redis = state.redis.clone();
-    
-    // 类型提示：显式指定 Redis 操作返回类型为 ()，以满足 FromRedisValue trait 的要求。
-    // 这是 Redis-rs 库的常见用法，确保编译器能正确推断返回类型。
-    let _: () = redis
-        .set_ex(
-            refresh_key(&refresh_token),
-            user.id.to_string(),
-            state.config.refresh_token_expiration as u64,
-        )
-        .await?;
+    // 第三步：签发令牌对（访问令牌 + 刷新令牌），刷新令牌存入 Redis，并记录登录历史。
+    let response = issue_token_pair(state, &user).await?;
+    record_login(state, ctx, user.id, LoginMethod::Password).await;
+    Ok(response)
+}
 
-    Ok(LoginResponse {
-        access_token,
-        refresh_token,
-    })
+/// 记录一次成功登录，供用户在登录历史中发现异常登录。
+///
+/// 登录已经成功、令牌已经签发，写入失败只记录错误日志，不向调用方返回错误（与审计日志的 Soft Fail 策略一致）。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接。
+/// - `ctx`: 请求上下文，提供来源IP和客户端标识（User-Agent）。
+/// - `user_id`: 登录的用户ID。
+/// - `method`: 登录方式。
+pub async fn record_login(state: &AppState, ctx: &RequestContext, user_id: Uuid, method: LoginMethod) {
+    let login = login_history::ActiveModel {
+        user_id: Set(user_id),
+        method: Set(method),
+        ip: Set(Some(ctx.client_ip.clone())),
+        user_agent: Set(ctx.user_agent.clone()),
+        ..Default::default()
+    };
+    if let Err(e) = login_history::Entity::insert(login).exec(&state.db).await {
+        tracing::error!(target: target::AUTH, "❌ Failed to record login history for {}: {}", user_id, e);
+    }
 }
 
 /// 令牌刷新服务。这个函数处理刷新令牌的验证和轮换，生成新的访问令牌和刷新令牌。
 /// 实现令牌轮转（Token Rotation）机制，防止令牌重用攻击，支持并发刷新的宽限期。
 /// 每个刷新令牌只能使用一次，使用后会被标记为已使用，并在宽限期后自动过期。
+/// 宽限期内使用同一个旧令牌的重复请求返回相同的新令牌对，而不是各自签发不同的令牌对。
 ///
 /// # 参数
 /// - `state`: 应用程序状态，包含数据库连接、Redis 客户端和配置信息。
+/// - `ctx`: 请求上下文，标记旧令牌之前的查询遵守其中的截止时间。
 /// - `old_token`: 旧的刷新令牌字符串，需要验证和轮换。
 ///
 /// # 返回值
 /// - `Ok(LoginResponse)`: 成功时返回包含新令牌的响应。
 /// - `Err(AppError)`: 失败时返回相应的错误，如令牌无效、已使用、用户不存在等。
-pub async fn refresh(state: &AppState, old_token: String) -> Result<LoginResponse, AppError> {
+pub async fn refresh(state: &AppState, ctx: &RequestContext, old_token: String) -> Result<LoginResponse, AppError> {
     let redis_key_old = refresh_key(&old_token);
     let mut redis = state.redis.clone();
 
-    // 第一步：从 Redis 获取与刷新令牌关联的用户ID。如果令牌不存在或已过期，返回验证错误。
-    let user_id_raw: String = redis
-        .get(&redis_key_old)
-        .await
-        .map_err(|_| AppError::AuthError("Invalid or expired refresh token".to_string()))?;
+    // 第一步：从 Redis 获取与刷新令牌关联的用户ID。本区域没有时再查询跨区域复制的从库，
+    // 使其他区域故障转移过来的用户无需重新登录。如果令牌不存在或已过期，返回验证错误。
+    let session: Option<String> = ctx.run("get_refresh_session", redis.get(&redis_key_old)).await?;
+    let session = match session {
+        Some(value) => Some(value),
+        None => lookup_replicated_session(state, &redis_key_old).await,
+    };
+    let user_id_raw = session.ok_or(AppError::AuthError("Invalid or expired refresh token".to_string()))?;
 
     // 第二步：检查令牌轮转状态。如果值以 "USED:" 前缀开头，表示该令牌已被使用过。
     // 这是令牌轮转机制的一部分，防止刷新令牌被重复使用。
-    let (user_id, is_used) = if let Some(stripped) = user_id_raw.strip_prefix(REDIS_PREFIX_USED) {
+    let (session, is_used) = if let Some(stripped) = user_id_raw.strip_prefix(REDIS_PREFIX_USED) {
         (stripped, true)
     } else {
         (user_id_raw.as_str(), false)
     };
+    let (user_id, issued_region) = parse_session_value(session);
 
-    // 针对刷新操作的限流检查：每个用户每分钟最多刷新 10 次令牌，防止滥用刷新功能。
-    check_rate_limit(&state.redis, "refresh_token", user_id, 10, 60).await?;
+    // 针对刷新操作的限流检查：按用户限制刷新频率（默认每分钟 10 次），防止滥用刷新功能。
+    check_rate_limit_mode(&state.redis, "refresh_token", user_id, state.config.rate_limit("refresh_token")).await?;
 
     if is_used {
+        // 宽限期内的重复刷新（网络重试、多个标签页并发刷新）：回放首次轮换签发的令牌对，
+        // 所有客户端最终持有同一个刷新令牌
+        if let Some(response) = replay_rotation(state, ctx, &old_token).await? {
+            return Ok(response);
+        }
+
         // 🚨 安全警告：刷新令牌被重复使用，这可能意味着令牌已泄露或被窃取。
         // 在生产环境中，应该考虑吊销该用户的所有令牌，并通知用户重新认证。
-        tracing::warn!("🚨 Refresh token reused! User: {}", user_id);
+        tracing::warn!(target: target::AUTH, "🚨 Refresh token reused! User: {}", user_id);
+        SecurityService::record(
+            state,
+            SecurityEvent::from_context(ctx, SecurityEventKind::RefreshTokenReuse).user(user_id),
+        );
         return Err(AppError::Conflict("Token reused. Please login again.".to_string()));
     }
 
     // 第三步：根据用户ID查找用户信息。验证用户是否存在且账户处于激活状态。
     let uid = Uuid::parse_str(user_id).map_err(|_| AppError::InternalServerError("ID error".to_string()))?;
-    let user = users::Entity::find_by_id(uid).one(&state.db).await?
+    let user = ctx
+        .run("find_user", retry::with_retry("find_refresh_user", || users::Entity::find_by_id(uid).one(&state.db)))
+        .await?
         .ok_or(AppError::AuthError("User not found".to_string()))?;
 
+    let user = AdminService::lift_expired_ban(state, user).await?;
     if !user.is_active {
         return Err(AppError::Forbidden("User inactive".to_string()));
     }
 
-    // 第四步：将旧令牌标记为已使用，设置宽限期（Grace Period）。
+    if let Some(region) = issued_region
+        && region != state.config.region
+    {
+        metrics::counter!("refresh_cross_region_total", "from" => region.to_string()).increment(1);
+        tracing::info!(target: target::AUTH, "🌐 Refresh token issued in region {} accepted in {}", region, state.config.region);
+    }
+
+    // 第四步：原子地将旧令牌标记为已使用，设置宽限期（Grace Period）。
     // 宽限期机制允许前端在短时间内并发发送的刷新请求使用同一个旧令牌，
     // 避免因网络延迟或前端并发导致的令牌无效错误。宽限期后令牌将完全失效。
-    let used_val = format!("{}{}", REDIS_PREFIX_USED, user_id);
-    let _: () = redis.set_ex(&redis_key_old, used_val, ROTATION_GRACE_PERIOD).await.unwrap_or_default();
+    // 跨区域的令牌同样标记在本区域，防止同一个令牌在本区域被再次使用。
+    // 使用 SET ... GET 读取标记前的值：并发请求中只有一个能完成标记，其余请求回放它的结果。
+    // 从这里开始不再受请求截止时间约束：标记完成后中途放弃会使旧令牌失效而新令牌尚未签发。
+    let used_val = format!("{}{}", REDIS_PREFIX_USED, session);
+    let previous: Option<String> = redis::cmd("SET")
+        .arg(&redis_key_old)
+        .arg(used_val)
+        .arg("EX")
+        .arg(ROTATION_GRACE_PERIOD)
+        .arg("GET")
+        .query_async(&mut redis)
+        .await?;
+    if previous.is_some_and(|value| value.starts_with(REDIS_PREFIX_USED)) {
+        let replayed = replay_rotation(state, ctx, &old_token).await?;
+        if replayed.is_none() {
+            tracing::warn!(target: target::AUTH, "🚨 Refresh token reused! User: {}", user.username);
+            SecurityService::record(
+                state,
+                SecurityEvent::from_context(ctx, SecurityEventKind::RefreshTokenReuse)
+                    .user(&user.id.to_string())
+                    .subject(user.username.clone()),
+            );
+        }
+        return replayed.ok_or(AppError::Conflict("Token reused. Please login again.".to_string()));
+    }
 
     // 第五步：生成新的访问令牌和刷新令牌。新令牌将替换旧令牌，完成令牌轮转。
-    let new_access = generate_access_token(&state.config, user_id, &user.username, user.role)?;
-    let new_refresh = Uuid::new_v4().to_string();
+    // 轮换结果缓存到宽限期结束，供同一个旧令牌的重复刷新回放。
+    let response = issue_token_pair(state, &user).await?;
+    match serde_json::to_string(&response) {
+        Ok(cached) => {
+            let result: Result<(), _> = redis.set_ex(rotated_key(&old_token), cached, ROTATION_GRACE_PERIOD).await;
+            if let Err(e) = result {
+                tracing::warn!(target: target::AUTH, "⚠️ Failed to cache refresh rotation: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!(target: target::AUTH, "⚠️ Failed to serialize refresh rotation: {}", e),
+    }
 
-    // 类型提示：显式指定 Redis 操作返回类型为 ()，与前面的设置操作保持一致。
-    let _: () = redis
-        .set_ex(
-            refresh_key(&new_refresh),
-            user_id,
-            state.config.refresh_token_expiration as u64,
-        )
-        .await?;
+    Ok(response)
+}
 
-    Ok(LoginResponse {
-        access_token: new_access,
-        refresh_token: new_refresh,
-    })
+/// 读取旧令牌在宽限期内首次轮换签发的令牌对。首次轮换可能仍在进行中，
+/// 因此最多等待 `ROTATION_REPLAY_WAIT_MS` 毫秒，且不超过请求的截止时间。
+///
+/// # 返回值
+/// - `Ok(Some(LoginResponse))`: 首次轮换的结果，原样返回给重复的刷新请求
+/// - `Ok(None)`: 宽限期已过或首次轮换未完成，按令牌重复使用处理
+async fn replay_rotation(
+    state: &AppState,
+    ctx: &RequestContext,
+    old_token: &str,
+) -> Result<Option<LoginResponse>, AppError> {
+    let key = rotated_key(old_token);
+    let mut redis = state.redis.clone();
+    // 等待时间不超过请求的截止时间，客户端已经超时的请求不再继续轮询
+    let wait_deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(ROTATION_REPLAY_WAIT_MS);
+    let deadline = ctx.deadline().map_or(wait_deadline, |deadline| deadline.min(wait_deadline));
+
+    loop {
+        let cached: Option<String> = redis.get(&key).await?;
+        if let Some(response) = cached.and_then(|value| serde_json::from_str::<LoginResponse>(&value).ok()) {
+            metrics::counter!("refresh_replayed_total").increment(1);
+            return Ok(Some(response));
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Ok(None);
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(ROTATION_REPLAY_POLL_MS)).await;
+    }
+}
+
+/// 从跨区域复制的 Redis 从库读取会话。未配置从库或读取失败时返回 `None`（按令牌无效处理）。
+async fn lookup_replicated_session(state: &AppState, key: &str) -> Option<String> {
+    let mut replica = state.redis_replica.clone()?;
+    match replica.get::<_, Option<String>>(key).await {
+        Ok(value) => value,
+        Err(e) => {
+            tracing::warn!(target: target::AUTH, "⚠️ Secondary Redis read failed for refresh token: {}", e);
+            None
+        }
+    }
 }
 
 /// 用户登出服务。这个函数处理令牌失效，将有效的 JWT 令牌加入 Redis 黑名单。
-/// 黑名单中的令牌在剩余有效期内无法再用于访问受保护资源，实现即时登出效果。
+/// 黑名单中的令牌在剩余有效期内无法再用于访问受保护资源，实现即时登出效果。同时清除用户的在线状态。
 /// 即使令牌验证失败（如签名错误），函数也会正常返回，避免泄露验证细节。
 ///
 /// # 参数
@@ -286,6 +562,128 @@ pub async fn logout(state: &AppState, token: &str) -> Result<(), AppError> {
             // 类型提示：显式指定 Redis 操作返回类型为 ()，确保类型推断正确。
             let _: () = redis.set_ex(key, "logout", ttl as u64).await?;
         }
+
+        // 主动登出时立即发布离线事件，不等待心跳超时。失败不影响登出
+        if let Ok(user_id) = Uuid::parse_str(&token_data.claims.sub)
+            && let Err(e) = PresenceService::go_offline(state, user_id).await
+        {
+            tracing::warn!(target: target::AUTH, "⚠️ Failed to clear presence on logout for {}: {}", user_id, e);
+        }
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// 强制吊销用户的全部令牌。记录当前时间戳，此前签发的该用户访问令牌都会被
+/// `check_token_revocation` 中间件拒绝。键的有效期与刷新令牌一致，过期后自动清理：
+/// 从备份恢复认证状态时据此跳过该用户被吊销的会话（见 `backup::restore`）。
+///
+/// 同时删除该用户在本区域的全部刷新令牌。只依赖刷新时的账户状态检查不够：
+/// 临时封禁到期后账户恢复可用，封禁前签发的刷新令牌会重新生效。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含Redis客户端和配置信息。
+/// - `user_id`: 需要强制下线的用户ID。
+pub async fn revoke_user_tokens(state: &AppState, user_id: &str) -> Result<(), AppError> {
+    let mut redis = state.redis.clone();
+    let _: () = redis
+        .set_ex(
+            user_revoked_key(user_id),
+            Utc::now().timestamp(),
+            state.config.refresh_token_expiration.as_secs(),
+        )
+        .await?;
+
+    let mut keys: Vec<String> = find_refresh_tokens(state, user_id).await?.into_iter().map(|(key, _)| key).collect();
+    let revoked = keys.len();
+    keys.push(user_sessions_key(user_id));
+    let _: () = redis.del(&keys).await?;
+
+    tracing::info!(target: target::AUTH, "🔒 Revoked all tokens of user {} ({} refresh tokens)", user_id, revoked);
+    Ok(())
+}
+
+/// 清除用户级吊销标记。解封后使用，之后新签发的令牌不再受封禁时记录的吊销时间影响；
+/// 封禁前签发的访问令牌仍因令牌版本落后被拒绝。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含Redis客户端。
+/// - `user_id`: 被解封的用户ID。
+pub async fn clear_user_revocation(state: &AppState, user_id: &str) -> Result<(), AppError> {
+    let mut redis = state.redis.clone();
+    let _: () = redis.del(user_revoked_key(user_id)).await?;
+    Ok(())
+}
+
+/// 递增用户的令牌版本。此前签发的访问令牌会被 `check_token_revocation` 中间件拒绝，
+/// 客户端使用刷新令牌换取携带最新角色的新令牌即可继续访问；被禁用的账户无法刷新。
+///
+/// 版本键不设置过期时间：过期后版本号归零，可能让之前签发的高版本令牌重新生效。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含Redis客户端。
+/// - `user_id`: 角色或状态发生变更的用户ID。
+///
+/// # 返回值
+/// - `Ok(u64)`: 递增后的令牌版本。
+/// - `Err(AppError)`: Redis 操作失败。
+pub async fn bump_token_version(state: &AppState, user_id: &str) -> Result<u64, AppError> {
+    let mut redis = state.redis.clone();
+    let version: u64 = redis.incr(token_version_key(user_id), 1).await?;
+
+    tracing::info!(target: target::AUTH, "🔁 Token version of user {} bumped to {}", user_id, version);
+    Ok(version)
+}
+
+/// 列出用户当前有效的登录会话（未使用的刷新令牌）。按用户的会话索引读取，不扫描全部刷新令牌。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含Redis客户端。
+/// - `user_id`: 用户ID。
+///
+/// # 返回值
+/// - `Ok(Vec<SessionInfo>)`: 会话列表，只包含令牌前缀和剩余有效期。
+/// - `Err(AppError)`: Redis 操作失败。
+pub async fn list_user_sessions(state: &AppState, user_id: &str) -> Result<Vec<SessionInfo>, AppError> {
+    let mut redis = state.redis.clone();
+    let mut sessions = Vec::new();
+    for (key, region) in find_refresh_tokens(state, user_id).await? {
+        let ttl: i64 = redis.ttl(&key).await?;
+        let token = key.trim_start_matches(REDIS_PREFIX_REFRESH);
+        sessions.push(SessionInfo {
+            token_hint: token.chars().take(8).collect(),
+            expires_in: ttl.max(0),
+            region,
+        });
+    }
+
+    Ok(sessions)
+}
+
+/// 查找用户在本区域未使用的刷新令牌，返回 Redis 键和签发区域。
+/// 会话索引中已过期、已使用或已删除的令牌顺便从索引中移除。
+async fn find_refresh_tokens(state: &AppState, user_id: &str) -> Result<Vec<(String, Option<String>)>, AppError> {
+    let mut redis = state.redis.clone();
+    let sessions_key = user_sessions_key(user_id);
+    let members: Vec<String> = redis.smembers(&sessions_key).await?;
+    if members.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let keys: Vec<String> = members.iter().map(|token| refresh_key(token)).collect();
+    let values: Vec<Option<String>> = redis.mget(&keys).await?;
+
+    // 已使用的令牌值带 "USED:" 前缀，解析出的用户ID不匹配，与过期的令牌一样视为失效
+    let mut tokens = Vec::new();
+    let mut stale = Vec::new();
+    for ((member, key), value) in members.iter().zip(keys).zip(values) {
+        match value.as_deref().map(parse_session_value) {
+            Some((owner, region)) if owner == user_id => tokens.push((key, region.map(str::to_string))),
+            _ => stale.push(member),
+        }
+    }
+    if !stale.is_empty() {
+        let _: () = redis.srem(&sessions_key, &stale).await?;
+    }
+
+    Ok(tokens)
+}
@@ -0,0 +1,45 @@
+// src/services/claims.rs
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::core::log::target;
+use crate::{core::config::Config, entity::users};
+
+/// 自定义声明构建钩子。在签发访问令牌时调用，返回的键值对写入 `Claims::ext`。
+///
+/// 基于本模板的项目可以实现该 trait（例如从数据库查询租户和套餐信息），
+/// 再通过 `AppState::with_claims_builder` 注册，无需修改令牌签发逻辑。
+#[async_trait]
+pub trait ClaimsBuilder: Send + Sync {
+    async fn build(&self, user: &users::Model) -> HashMap<String, Value>;
+}
+
+/// 默认的声明构建器：为所有令牌附加配置中 `JWT_STATIC_CLAIMS` 声明的固定键值对
+/// （如 `{"tenant_id":"acme"}`），未配置时不附加任何扩展声明。
+pub struct StaticClaimsBuilder {
+    claims: Map<String, Value>,
+}
+
+impl StaticClaimsBuilder {
+    pub fn from_config(config: &Config) -> Self {
+        let claims = match config.jwt_static_claims.as_deref() {
+            Some(raw) => match serde_json::from_str::<Map<String, Value>>(raw) {
+                Ok(map) => map,
+                Err(e) => {
+                    tracing::warn!(target: target::AUTH, "⚠️ Ignoring invalid JWT_STATIC_CLAIMS: {}", e);
+                    Map::new()
+                }
+            },
+            None => Map::new(),
+        };
+        Self { claims }
+    }
+}
+
+#[async_trait]
+impl ClaimsBuilder for StaticClaimsBuilder {
+    async fn build(&self, _user: &users::Model) -> HashMap<String, Value> {
+        self.claims.clone().into_iter().collect()
+    }
+}
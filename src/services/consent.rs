@@ -0,0 +1,128 @@
+// src/services/consent.rs
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::core::log::target;
+use crate::{
+    core::{
+        constants::{CACHE_EXPIRE_USER_CONSENTS, REDIS_PREFIX_USER_CONSENTS},
+        enums::ConsentPurpose,
+        error::AppError,
+    },
+    dtos::consent::{ConsentStatus, UpdateConsentsRequest},
+    entity::consents,
+    extractors::context::RequestContext,
+    state::AppState,
+    utils::cache,
+};
+
+fn consents_key(user_id: Uuid) -> String {
+    cache::versioned_key::<ConsentStatus>(&format!("{}{}", REDIS_PREFIX_USER_CONSENTS, user_id))
+}
+
+fn parse_user_id(user_id: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(user_id).map_err(|_| AppError::AuthError("Invalid User ID format".to_string()))
+}
+
+/// 查询用户对全部数据处理目的的当前同意状态（带缓存）。
+///
+/// 每个目的取最新的一条同意记录；同意的不是当前版本的隐私政策时，视为未同意并标记为需要重新确认。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `user_id`: 用户ID。
+///
+/// # 返回值
+/// - `Ok(Vec<ConsentStatus>)`: 每个数据处理目的一项。
+/// - `Err(AppError)`: 数据库查询失败。
+pub async fn current(state: &AppState, user_id: Uuid) -> Result<Vec<ConsentStatus>, AppError> {
+    let db = state.db.clone();
+    let policy_version = state.config.consent_policy_version.clone();
+
+    cache::get_or_fetch(&state.redis, &consents_key(user_id), CACHE_EXPIRE_USER_CONSENTS, || async move {
+        let records = consents::Entity::find()
+            .filter(consents::Column::UserId.eq(user_id))
+            .order_by_desc(consents::Column::CreatedAt)
+            .all(&db)
+            .await?;
+
+        Ok(ConsentPurpose::iter()
+            .map(|purpose| {
+                let latest = records.iter().find(|record| record.purpose == purpose);
+                let outdated = latest.is_some_and(|record| record.policy_version != policy_version);
+                ConsentStatus {
+                    purpose,
+                    granted: latest.is_some_and(|record| record.granted) && !outdated,
+                    policy_version: latest.map(|record| record.policy_version.clone()),
+                    outdated,
+                    updated_at: latest.map(|record| record.created_at.to_string()),
+                }
+            })
+            .collect())
+    })
+    .await
+}
+
+/// 用户是否同意了某个数据处理目的。发送营销通知、采集分析数据等子系统在处理用户数据前必须调用，
+/// 查询失败时按未同意处理（宁可少发一条通知，也不在未经同意的情况下处理数据）。
+/// 请求统计据此决定是否按用户计数（见 `stats::record`）。
+pub async fn has_consent(state: &AppState, user_id: Uuid, purpose: ConsentPurpose) -> bool {
+    match current(state, user_id).await {
+        Ok(statuses) => statuses.iter().any(|status| status.purpose == purpose && status.granted),
+        Err(e) => {
+            tracing::warn!(target: target::USER, "⚠️ Consent lookup failed for {}, treating as not granted: {}", user_id, e);
+            false
+        }
+    }
+}
+
+/// 修改当前用户的同意状态。每个发生变化的目的追加一条记录（附当前隐私政策版本和来源IP），
+/// 状态没有变化且已是当前政策版本的目的不重复记录。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `ctx`: 请求上下文，调用方为当前用户，来源IP写入同意记录。
+/// - `req`: 需要修改的目的及是否同意。
+///
+/// # 返回值
+/// - `Ok(Vec<ConsentStatus>)`: 修改后的全部同意状态。
+/// - `Err(AppError)`: 数据库写入失败。
+pub async fn update(state: &AppState, ctx: &RequestContext, req: UpdateConsentsRequest) -> Result<Vec<ConsentStatus>, AppError> {
+    let user_id = parse_user_id(&ctx.actor()?.sub)?;
+    let policy_version = &state.config.consent_policy_version;
+
+    // 第一步：找出实际发生变化的目的（同一目的出现多次时以最后一次为准）
+    let existing = current(state, user_id).await?;
+    let mut changes: Vec<consents::ActiveModel> = Vec::new();
+    for purpose in ConsentPurpose::iter() {
+        let Some(change) = req.consents.iter().rev().find(|change| change.purpose == purpose) else {
+            continue;
+        };
+        let unchanged = existing.iter().any(|status| {
+            status.purpose == purpose
+                && status.policy_version.as_deref() == Some(policy_version.as_str())
+                && status.granted == change.granted
+        });
+        if unchanged {
+            continue;
+        }
+        changes.push(consents::ActiveModel {
+            user_id: Set(user_id),
+            purpose: Set(purpose),
+            granted: Set(change.granted),
+            policy_version: Set(policy_version.clone()),
+            ip: Set(Some(ctx.client_ip.clone())),
+            ..Default::default()
+        });
+    }
+
+    // 第二步：追加同意记录并失效缓存
+    if !changes.is_empty() {
+        let count = changes.len();
+        consents::Entity::insert_many(changes).exec(&state.db).await?;
+        cache::del(&state.redis, &consents_key(user_id)).await;
+        tracing::info!(target: target::USER, "📝 User {} updated {} consent(s)", user_id, count);
+    }
+
+    current(state, user_id).await
+}
@@ -0,0 +1,223 @@
+// src/services/delegation.rs
+use chrono::{Duration, Utc};
+use redis::AsyncCommands;
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::core::log::target;
+use crate::{
+    core::{
+        constants::{DELEGATION_MAX_ACTIVE, REDIS_PREFIX_DELEGATION_REVOKED},
+        enums::Permission,
+        error::AppError,
+    },
+    dtos::{
+        auth::{ActorClaim, Claims},
+        delegation::{CreateDelegationRequest, DelegatedTokenResponse, DelegationItem, DelegationList},
+        pagination::{PageQuery, Paginated},
+    },
+    entity::{delegations, users},
+    services::{admin as AdminService, auth as AuthService},
+    state::AppState,
+    utils::retry,
+};
+
+/// 可以委托给他人的操作范围。账户安全相关的操作（冻结账户、管理委托、导出数据等）不能委托，
+/// 由 `middleware::delegation::enforce_scope` 对委托令牌单独拦截。
+const DELEGABLE_SCOPES: &[Permission] = &[Permission::ReadSelf, Permission::UpdateSelf];
+
+fn revoked_key(grant_id: &str) -> String {
+    format!("{}{}", REDIS_PREFIX_DELEGATION_REVOKED, grant_id)
+}
+
+fn parse_user_id(user_id: &str) -> Result<Uuid, AppError> {
+    Uuid::parse_str(user_id).map_err(|_| AppError::AuthError("Invalid User ID format".to_string()))
+}
+
+/// 尚未撤销且未到期的委托
+fn active_condition() -> Condition {
+    Condition::all()
+        .add(delegations::Column::RevokedAt.is_null())
+        .add(delegations::Column::ExpiresAt.gt(Utc::now()))
+}
+
+/// 授予委托：允许被授权人在有效期内以当前用户的身份执行限定范围的操作。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `grantor_id`: 授权人（当前用户）ID。
+/// - `req`: 被授权人、操作范围和有效期（已通过格式校验）。
+///
+/// # 返回值
+/// - `Ok(DelegationItem)`: 新创建的委托。
+/// - `Err(AppError)`: 委托给自己、范围不可委托、被授权人不存在或已禁用、有效委托数量超限。
+pub async fn grant(state: &AppState, grantor_id: &str, req: CreateDelegationRequest) -> Result<DelegationItem, AppError> {
+    let grantor = parse_user_id(grantor_id)?;
+    if req.grantee_id == grantor {
+        return Err(AppError::BadRequest("Cannot delegate access to yourself".to_string()));
+    }
+    if let Some(scope) = req.scopes.iter().find(|scope| !DELEGABLE_SCOPES.contains(scope)) {
+        return Err(AppError::BadRequest(format!("Scope {} cannot be delegated", scope)));
+    }
+
+    // 第一步：被授权人必须存在且处于启用状态
+    let grantee = users::Entity::find_by_id(req.grantee_id).one(&state.db).await?;
+    if !grantee.is_some_and(|user| user.is_active) {
+        return Err(AppError::NotFound("Grantee not found".to_string()));
+    }
+
+    // 第二步：限制同时有效的委托数量
+    let active = delegations::Entity::find()
+        .filter(active_condition().add(delegations::Column::GrantorId.eq(grantor)))
+        .count(&state.db)
+        .await?;
+    if active >= DELEGATION_MAX_ACTIVE {
+        return Err(AppError::BadRequest(format!(
+            "At most {} active delegations are allowed",
+            DELEGATION_MAX_ACTIVE
+        )));
+    }
+
+    // 第三步：保存委托，范围去重后存储
+    let mut scopes = req.scopes;
+    scopes.sort_by_key(|scope| scope.to_string());
+    scopes.dedup();
+    let expires_at = Utc::now() + Duration::minutes(req.expires_in_minutes as i64);
+    let delegation = delegations::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        grantor_id: Set(grantor),
+        grantee_id: Set(req.grantee_id),
+        scopes: Set(serde_json::json!(scopes)),
+        expires_at: Set(expires_at.into()),
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await?;
+
+    tracing::info!(target: target::AUTH, "🤝 User {} delegated access to {}", grantor, req.grantee_id);
+    Ok(delegation.into())
+}
+
+/// 查询当前用户有效的委托（授予他人的和他人授予自己的）。
+pub async fn list_for_user(state: &AppState, user_id: &str) -> Result<DelegationList, AppError> {
+    let uid = parse_user_id(user_id)?;
+    let delegations = delegations::Entity::find()
+        .filter(
+            active_condition().add(
+                Condition::any()
+                    .add(delegations::Column::GrantorId.eq(uid))
+                    .add(delegations::Column::GranteeId.eq(uid)),
+            ),
+        )
+        .order_by_desc(delegations::Column::CreatedAt)
+        .all(&state.db)
+        .await?;
+
+    let (granted, received): (Vec<_>, Vec<_>) = delegations
+        .into_iter()
+        .partition(|delegation| delegation.grantor_id == uid);
+    Ok(DelegationList {
+        granted: granted.into_iter().map(DelegationItem::from).collect(),
+        received: received.into_iter().map(DelegationItem::from).collect(),
+    })
+}
+
+/// 撤销委托。授权人和被授权人都可以撤销；已签发的委托令牌立即失效。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `user_id`: 当前用户ID，必须是该委托的授权人或被授权人。
+/// - `grant_id`: 委托ID。
+///
+/// # 返回值
+/// - `Ok(DelegationItem)`: 撤销后的委托（已撤销的委托重复撤销同样返回成功）。
+/// - `Err(AppError)`: 委托不存在或与当前用户无关。
+pub async fn revoke(state: &AppState, user_id: &str, grant_id: Uuid) -> Result<DelegationItem, AppError> {
+    let uid = parse_user_id(user_id)?;
+    let delegation = delegations::Entity::find_by_id(grant_id)
+        .one(&state.db)
+        .await?
+        .filter(|delegation| delegation.grantor_id == uid || delegation.grantee_id == uid)
+        .ok_or(AppError::NotFound("Delegation not found".to_string()))?;
+    if delegation.revoked_at.is_some() {
+        return Ok(delegation.into());
+    }
+
+    // 第一步：标记撤销，之后无法再换取新的委托令牌
+    let mut active: delegations::ActiveModel = delegation.into();
+    active.revoked_at = Set(Some(Utc::now().into()));
+    let delegation = active.update(&state.db).await?;
+
+    // 第二步：记录撤销标记，已签发的委托令牌在剩余有效期内被拒绝
+    let mut redis = state.redis.clone();
+    let _: () = redis
+        .set_ex(revoked_key(&grant_id.to_string()), 1, state.config.jwt_expiration.as_secs())
+        .await?;
+
+    tracing::info!(target: target::AUTH, "🤝 Delegation {} revoked by {}", grant_id, uid);
+    Ok(delegation.into())
+}
+
+/// 被授权人使用有效的委托换取委托访问令牌。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `grantee`: 当前用户（被授权人）的令牌声明。
+/// - `grant_id`: 委托ID。
+///
+/// # 返回值
+/// - `Ok(DelegatedTokenResponse)`: 委托访问令牌。
+/// - `Err(AppError)`: 委托不存在、已撤销或已过期，或授权人账户已被禁用。
+pub async fn issue_token(state: &AppState, grantee: &Claims, grant_id: Uuid) -> Result<DelegatedTokenResponse, AppError> {
+    let uid = parse_user_id(&grantee.sub)?;
+    let delegation = delegations::Entity::find_by_id(grant_id)
+        .filter(active_condition().add(delegations::Column::GranteeId.eq(uid)))
+        .one(&state.db)
+        .await?
+        .ok_or(AppError::NotFound("Delegation not found".to_string()))?;
+
+    // 授权人被禁用后委托不再可用
+    let grantor = users::Entity::find_by_id(delegation.grantor_id)
+        .one(&state.db)
+        .await?
+        .ok_or(AppError::NotFound("Delegation not found".to_string()))?;
+    let grantor = AdminService::lift_expired_ban(state, grantor).await?;
+    if !grantor.is_active {
+        return Err(AppError::Forbidden("Grantor account is disabled".to_string()));
+    }
+
+    let actor = ActorClaim {
+        sub: grantee.sub.clone(),
+        username: grantee.username.clone(),
+        grant: grant_id.to_string(),
+        scopes: serde_json::from_value(delegation.scopes).unwrap_or_default(),
+    };
+    let (access_token, expires_in) =
+        AuthService::issue_delegated_token(state, &grantor, actor, delegation.expires_at.into()).await?;
+
+    tracing::info!(target: target::AUTH, "🤝 Issued delegated token for {} acting as {}", grantee.username, grantor.username);
+    Ok(DelegatedTokenResponse { access_token, expires_in })
+}
+
+/// 委托是否已被撤销。供委托令牌校验使用。
+pub async fn is_revoked(state: &AppState, grant_id: &str) -> Result<bool, AppError> {
+    let mut redis = state.redis.clone();
+    Ok(redis.exists(revoked_key(grant_id)).await?)
+}
+
+/// 管理端分页查询全部有效的委托，按创建时间倒序。
+pub async fn list_active(state: &AppState, page: PageQuery) -> Result<Paginated<DelegationItem>, AppError> {
+    let paginator = delegations::Entity::find()
+        .filter(active_condition())
+        .order_by_desc(delegations::Column::CreatedAt)
+        .paginate(&state.db, page.per_page);
+
+    let counts = retry::with_retry("count_delegations", || paginator.num_items_and_pages()).await?;
+    let items = retry::with_retry("list_delegations", || paginator.fetch_page(page.page_index()))
+        .await?
+        .into_iter()
+        .map(DelegationItem::from)
+        .collect();
+
+    Ok(Paginated::new(items, &page, counts.number_of_items, counts.number_of_pages))
+}
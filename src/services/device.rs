@@ -0,0 +1,238 @@
+// src/services/device.rs
+use rand::Rng;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::core::log::target;
+use crate::{
+    core::{
+        constants::{DEVICE_CODE_EXPIRE, DEVICE_POLL_INTERVAL, REDIS_PREFIX_DEVICE_CODE, REDIS_PREFIX_DEVICE_USER_CODE},
+        enums::LoginMethod,
+        error::AppError,
+    },
+    dtos::auth::{DeviceCodeResponse, LoginResponse},
+    entity::users,
+    extractors::context::RequestContext,
+    services::{admin as AdminService, auth as AuthService},
+    state::AppState,
+    utils::nonce,
+};
+
+// 设备授权流程（RFC 8628）：无浏览器或输入不便的客户端（CLI、电视）先申请一对设备码/用户码，
+// 把用户码展示给用户；用户在另一台设备上登录并确认后，客户端凭设备码轮询换取令牌。
+
+/// 用户码字符集：去掉元音和易混淆字符（RFC 8628 第 6.1 节建议），避免拼出单词或输错
+const USER_CODE_CHARSET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+const USER_CODE_LEN: usize = 8;
+
+#[inline]
+fn device_code_key(device_code: &str) -> String {
+    format!("{}{}", REDIS_PREFIX_DEVICE_CODE, device_code)
+}
+#[inline]
+fn user_code_key(user_code: &str) -> String {
+    format!("{}{}", REDIS_PREFIX_DEVICE_USER_CODE, user_code)
+}
+
+/// 授权请求的状态
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+enum DeviceGrant {
+    /// 等待用户确认
+    Pending,
+    /// 用户已同意，记录授权的用户ID
+    Approved { user_id: String },
+    /// 用户已拒绝
+    Denied,
+}
+
+/// 生成用户码（不含分隔符），如 "BCDFGHJK"
+fn generate_user_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..USER_CODE_LEN)
+        .map(|_| USER_CODE_CHARSET[rng.gen_range(0..USER_CODE_CHARSET.len())] as char)
+        .collect()
+}
+
+/// 规范化用户输入的用户码：忽略大小写、空格和连字符
+fn normalize_user_code(input: &str) -> String {
+    input
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// 展示格式：每 4 位插入一个连字符，如 "BCDF-GHJK"
+fn display_user_code(code: &str) -> String {
+    let (head, tail) = code.split_at(code.len() / 2);
+    format!("{}-{}", head, tail)
+}
+
+/// 写入授权状态，保留键原有的过期时间
+async fn save_grant(state: &AppState, device_code: &str, grant: &DeviceGrant) -> Result<(), AppError> {
+    let value = serde_json::to_string(grant)
+        .map_err(|e| AppError::InternalServerError(format!("Serialize device grant failed: {}", e)))?;
+    let mut redis = state.redis.clone();
+    let _: () = redis::cmd("SET")
+        .arg(device_code_key(device_code))
+        .arg(value)
+        .arg("KEEPTTL")
+        .query_async(&mut redis)
+        .await?;
+    Ok(())
+}
+
+/// 读取授权状态，不存在或已过期时返回 `None`
+async fn load_grant(state: &AppState, device_code: &str) -> Result<Option<DeviceGrant>, AppError> {
+    let mut redis = state.redis.clone();
+    let value: Option<String> = redis.get(device_code_key(device_code)).await?;
+    Ok(value.and_then(|value| serde_json::from_str(&value).ok()))
+}
+
+/// 发起设备授权请求。生成设备码和用户码，并在 Redis 中记录待确认的授权状态。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含 Redis 客户端和配置信息。
+///
+/// # 返回值
+/// - `Ok(DeviceCodeResponse)`: 设备码、用户码、确认地址和轮询参数。
+/// - `Err(AppError)`: Redis 写入失败。
+pub async fn request_code(state: &AppState) -> Result<DeviceCodeResponse, AppError> {
+    let device_code = Uuid::new_v4().simple().to_string();
+    let mut redis = state.redis.clone();
+
+    // 用户码空间较小，生成后使用 SET NX 占位，冲突时重新生成
+    let mut user_code = generate_user_code();
+    loop {
+        let created: Option<String> = redis::cmd("SET")
+            .arg(user_code_key(&user_code))
+            .arg(&device_code)
+            .arg("NX")
+            .arg("EX")
+            .arg(DEVICE_CODE_EXPIRE)
+            .query_async(&mut redis)
+            .await?;
+        if created.is_some() {
+            break;
+        }
+        user_code = generate_user_code();
+    }
+
+    let value = serde_json::to_string(&DeviceGrant::Pending)
+        .map_err(|e| AppError::InternalServerError(format!("Serialize device grant failed: {}", e)))?;
+    let _: () = redis.set_ex(device_code_key(&device_code), value, DEVICE_CODE_EXPIRE).await?;
+
+    let user_code = display_user_code(&user_code);
+    let verification_uri = state.config.device_verification_uri.clone();
+    tracing::info!(target: target::AUTH, "📺 Device authorization requested: {}", user_code);
+
+    Ok(DeviceCodeResponse {
+        device_code,
+        verification_uri_complete: format!("{}?user_code={}", verification_uri, user_code),
+        verification_uri,
+        user_code,
+        expires_in: DEVICE_CODE_EXPIRE,
+        interval: DEVICE_POLL_INTERVAL,
+    })
+}
+
+/// 用户确认（或拒绝）设备授权请求。
+///
+/// 用户码确认后立即删除，同一个用户码只能被处理一次。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `user_id`: 当前登录用户的ID，来自 JWT claims。
+/// - `user_code`: 设备上展示的用户码，大小写和连字符不敏感。
+/// - `approve`: true 表示同意，false 表示拒绝。
+///
+/// # 返回值
+/// - `Ok(())`: 处理成功。
+/// - `Err(AppError)`: 用户码无效或已过期。
+pub async fn confirm(state: &AppState, user_id: &str, user_code: &str, approve: bool) -> Result<(), AppError> {
+    let code = normalize_user_code(user_code);
+    let mut redis = state.redis.clone();
+
+    // GETDEL 原子地取出并删除用户码，防止同一个用户码被并发确认两次
+    let device_code: Option<String> = redis.get_del(user_code_key(&code)).await?;
+    let device_code = device_code.ok_or(AppError::NotFound("Invalid or expired user code".to_string()))?;
+
+    if load_grant(state, &device_code).await? != Some(DeviceGrant::Pending) {
+        return Err(AppError::NotFound("Invalid or expired user code".to_string()));
+    }
+
+    let grant = if approve {
+        DeviceGrant::Approved { user_id: user_id.to_string() }
+    } else {
+        DeviceGrant::Denied
+    };
+    save_grant(state, &device_code, &grant).await?;
+
+    tracing::info!(
+        target: target::AUTH,
+        "📺 Device authorization {} by user {}",
+        if approve { "approved" } else { "denied" },
+        user_id
+    );
+    Ok(())
+}
+
+/// 设备端轮询换取令牌。错误消息使用 RFC 8628 第 3.5 节定义的错误码，便于客户端按标准处理：
+/// - `authorization_pending`: 用户尚未确认，继续轮询
+/// - `slow_down`: 轮询过快，客户端应增大间隔
+/// - `access_denied`: 用户拒绝授权
+/// - `expired_token`: 设备码不存在或已过期
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `ctx`: 请求上下文，设备的来源IP和客户端标识写入登录历史。
+/// - `device_code`: 发起授权时获得的设备码。
+///
+/// # 返回值
+/// - `Ok(LoginResponse)`: 用户已同意，返回访问令牌和刷新令牌（设备码随即失效）。
+/// - `Err(AppError)`: 上述错误码之一，或账户已被禁用。
+pub async fn poll_token(state: &AppState, ctx: &RequestContext, device_code: &str) -> Result<LoginResponse, AppError> {
+    // 第一步：轮询频率控制。在最小间隔内重复轮询时返回 slow_down。
+    if !nonce::remember(&state.redis, "device_poll", device_code, DEVICE_POLL_INTERVAL).await? {
+        return Err(AppError::BadRequest("slow_down".to_string()));
+    }
+
+    // 第二步：检查授权状态
+    let user_id = match load_grant(state, device_code).await? {
+        None => return Err(AppError::BadRequest("expired_token".to_string())),
+        Some(DeviceGrant::Pending) => return Err(AppError::BadRequest("authorization_pending".to_string())),
+        Some(DeviceGrant::Denied) => {
+            let mut redis = state.redis.clone();
+            let _: () = redis.del(device_code_key(device_code)).await?;
+            return Err(AppError::Forbidden("access_denied".to_string()));
+        }
+        Some(DeviceGrant::Approved { user_id }) => user_id,
+    };
+
+    // 第三步：原子地删除设备码，保证同一个设备码只能换取一次令牌
+    let mut redis = state.redis.clone();
+    let removed: usize = redis.del(device_code_key(device_code)).await?;
+    if removed == 0 {
+        return Err(AppError::BadRequest("expired_token".to_string()));
+    }
+
+    // 第四步：再次校验账户状态（确认授权后账户可能已被禁用），然后签发令牌对
+    let uid = Uuid::parse_str(&user_id).map_err(|_| AppError::InternalServerError("ID error".to_string()))?;
+    let user = users::Entity::find_by_id(uid)
+        .one(&state.db)
+        .await?
+        .ok_or(AppError::AuthError("User not found".to_string()))?;
+
+    let user = AdminService::lift_expired_ban(state, user).await?;
+    if !user.is_active {
+        return Err(AuthService::inactive_account_error(&user));
+    }
+
+    tracing::info!(target: target::AUTH, "✅ Device authorized for user {}", user.username);
+    let response = AuthService::issue_token_pair(state, &user).await?;
+    AuthService::record_login(state, ctx, user.id, LoginMethod::Device).await;
+    Ok(response)
+}
@@ -0,0 +1,291 @@
+// src/services/export.rs
+use chrono::Utc;
+use redis::AsyncCommands;
+use sea_orm::*;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::core::log::target;
+use crate::{
+    core::{
+        constants::{EXPORT_EXPIRE, EXPORT_FAILED_EXPIRE, REDIS_PREFIX_EXPORT_DATA, REDIS_PREFIX_EXPORT_JOB},
+        error::AppError,
+    },
+    dtos::{
+        audit::AuditLogItem,
+        consent::ConsentRecord,
+        export::{ExportFormat, ExportJob, ExportStatus},
+        user::{LoginHistoryItem, UserProfile},
+    },
+    entity::{audit_logs, consents, login_history, username_history, users},
+    services::auth as AuthService,
+    state::AppState,
+};
+
+// 个人数据导出（GDPR 数据可携带权）：在后台汇总用户的资料、设置、用户名历史、登录会话、同意记录和相关审计日志，
+// 生成 JSON 或 CSV 文件暂存在 Redis 中，用户轮询状态后下载。导出文件包含个人数据，
+// 因此不写入公开的文件存储。
+
+#[inline]
+fn job_key(user_id: &str) -> String {
+    format!("{}{}", REDIS_PREFIX_EXPORT_JOB, user_id)
+}
+#[inline]
+fn data_key(user_id: &str) -> String {
+    format!("{}{}", REDIS_PREFIX_EXPORT_DATA, user_id)
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, AppError> {
+    serde_json::to_string(value)
+        .map_err(|e| AppError::InternalServerError(format!("Serialize export failed: {}", e)))
+}
+
+/// 查询当前的导出任务
+///
+/// # 返回值
+/// - `Ok(Some(ExportJob))`: 存在未过期的导出任务
+/// - `Ok(None)`: 没有导出任务或已过期
+pub async fn get_job(state: &AppState, user_id: &str) -> Result<Option<ExportJob>, AppError> {
+    let mut redis = state.redis.clone();
+    let raw: Option<String> = redis.get(job_key(user_id)).await?;
+    Ok(raw.and_then(|raw| serde_json::from_str(&raw).ok()))
+}
+
+/// 申请导出个人数据。已有未过期的导出任务时直接返回该任务，否则创建新任务并在后台生成文件。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `user_id`: 用户ID。
+/// - `format`: 导出格式。
+///
+/// # 返回值
+/// - `Ok(ExportJob)`: 导出任务状态。
+/// - `Err(AppError)`: Redis 操作失败。
+pub async fn request_export(state: &AppState, user_id: &str, format: ExportFormat) -> Result<ExportJob, AppError> {
+    let job = ExportJob {
+        status: ExportStatus::Pending,
+        format,
+        requested_at: Utc::now().to_rfc3339(),
+        completed_at: None,
+        error: None,
+    };
+
+    // SET NX：同一时间每个用户只有一个导出任务，并发请求都会拿到同一个任务
+    let mut redis = state.redis.clone();
+    let created: Option<String> = redis::cmd("SET")
+        .arg(job_key(user_id))
+        .arg(to_json(&job)?)
+        .arg("NX")
+        .arg("EX")
+        .arg(EXPORT_EXPIRE)
+        .query_async(&mut redis)
+        .await?;
+
+    if created.is_none() {
+        return get_job(state, user_id)
+            .await?
+            .ok_or(AppError::Conflict("Export job state changed, please retry".to_string()));
+    }
+
+    tracing::info!(target: target::USER, "📦 Data export requested by user {}", user_id);
+    let task_state = state.clone();
+    let task_user = user_id.to_string();
+    let task_job = job.clone();
+    tokio::spawn(async move {
+        run(task_state, task_user, task_job).await;
+    });
+
+    Ok(job)
+}
+
+/// 读取已生成的导出文件
+///
+/// # 返回值
+/// - `Ok((ExportFormat, Vec<u8>))`: 文件格式和内容
+/// - `Err(AppError)`: 没有导出任务、任务未完成或已过期
+pub async fn download(state: &AppState, user_id: &str) -> Result<(ExportFormat, Vec<u8>), AppError> {
+    let job = get_job(state, user_id)
+        .await?
+        .ok_or(AppError::NotFound("No data export found".to_string()))?;
+    if job.status != ExportStatus::Ready {
+        return Err(AppError::Conflict("Data export is not ready".to_string()));
+    }
+
+    let mut redis = state.redis.clone();
+    let data: Option<Vec<u8>> = redis.get(data_key(user_id)).await?;
+    let data = data.ok_or(AppError::NotFound("Data export has expired".to_string()))?;
+    Ok((job.format, data))
+}
+
+/// 后台生成导出文件，并更新任务状态
+async fn run(state: AppState, user_id: String, mut job: ExportJob) {
+    let result = async {
+        let archive = collect(&state, &user_id).await?;
+        let bytes = match job.format {
+            ExportFormat::Json => serde_json::to_vec_pretty(&archive)
+                .map_err(|e| AppError::InternalServerError(format!("Serialize export failed: {}", e)))?,
+            ExportFormat::Csv => to_csv(&archive)?,
+        };
+
+        // 导出文件与任务状态使用相同的有效期
+        let mut redis = state.redis.clone();
+        let ttl: i64 = redis.ttl(job_key(&user_id)).await?;
+        let ttl = if ttl > 0 { ttl as u64 } else { EXPORT_EXPIRE };
+        let _: () = redis.set_ex(data_key(&user_id), bytes, ttl).await?;
+        Ok::<(), AppError>(())
+    }
+    .await;
+
+    job.completed_at = Some(Utc::now().to_rfc3339());
+    match result {
+        Ok(()) => {
+            job.status = ExportStatus::Ready;
+            tracing::info!(target: target::USER, "✅ Data export ready for user {}", user_id);
+        }
+        Err(e) => {
+            job.status = ExportStatus::Failed;
+            job.error = Some("Export generation failed".to_string());
+            tracing::error!(target: target::USER, "❌ Data export failed for user {}: {}", user_id, e);
+        }
+    }
+
+    // 成功时保留任务创建时设置的过期时间（KEEPTTL）；失败时只短暂保留，
+    // 避免一次临时故障让用户在 `EXPORT_EXPIRE` 内都无法重新申请导出
+    let saved = async {
+        let mut redis = state.redis.clone();
+        let mut cmd = redis::cmd("SET");
+        cmd.arg(job_key(&user_id)).arg(to_json(&job)?);
+        match job.status {
+            ExportStatus::Failed => cmd.arg("EX").arg(EXPORT_FAILED_EXPIRE),
+            _ => cmd.arg("KEEPTTL"),
+        };
+        let _: () = cmd.query_async(&mut redis).await?;
+        Ok::<(), AppError>(())
+    }
+    .await;
+    if let Err(e) = saved {
+        tracing::error!(target: target::USER, "❌ Failed to save export job for user {}: {}", user_id, e);
+    }
+}
+
+/// 汇总用户的个人数据
+async fn collect(state: &AppState, user_id: &str) -> Result<Value, AppError> {
+    let uid = Uuid::parse_str(user_id)
+        .map_err(|_| AppError::AuthError("Invalid User ID format".to_string()))?;
+
+    // 第一步：用户资料（导出给本人，包含全部字段）
+    let user = users::Entity::find_by_id(uid)
+        .one(&state.db)
+        .await?
+        .ok_or(AppError::NotFound("User not found".to_string()))?;
+    let settings = user.settings.clone();
+    let profile = UserProfile::from(user);
+
+    // 第二步：用户名变更历史
+    let username_history: Vec<Value> = username_history::Entity::find()
+        .filter(username_history::Column::UserId.eq(uid))
+        .order_by_asc(username_history::Column::ChangedAt)
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(|entry| {
+            json!({
+                "old_username": entry.old_username,
+                "new_username": entry.new_username,
+                "changed_at": entry.changed_at.to_string(),
+            })
+        })
+        .collect();
+
+    // 第三步：当前有效的登录会话和登录历史
+    let sessions = AuthService::list_user_sessions(state, user_id).await?;
+    let login_history: Vec<LoginHistoryItem> = login_history::Entity::find()
+        .filter(login_history::Column::UserId.eq(uid))
+        .order_by_asc(login_history::Column::CreatedAt)
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(LoginHistoryItem::from)
+        .collect();
+
+    // 第四步：数据处理同意的完整变更记录
+    let consents: Vec<ConsentRecord> = consents::Entity::find()
+        .filter(consents::Column::UserId.eq(uid))
+        .order_by_asc(consents::Column::CreatedAt)
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(ConsentRecord::from)
+        .collect();
+
+    // 第五步：与用户相关的审计日志（本人执行的操作和针对本人的操作）。
+    // 导出内容只能包含该用户本人的数据：本人对其他用户执行的操作只保留操作类型和时间，
+    // 去掉目标用户和变更内容；其他人（如管理员）对本人执行的操作去掉操作者的ID和IP，
+    // 执行操作的员工账号属于内部数据
+    let audit_logs: Vec<AuditLogItem> = audit_logs::Entity::find()
+        .filter(
+            Condition::any()
+                .add(audit_logs::Column::ActorId.eq(uid))
+                .add(audit_logs::Column::TargetId.eq(uid)),
+        )
+        .order_by_asc(audit_logs::Column::CreatedAt)
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(|log| {
+            let about_subject = log.target_id.is_none_or(|target| target == uid);
+            let by_subject = log.actor_id == Some(uid);
+            let mut item = AuditLogItem::from(log);
+            if !about_subject {
+                item.target_id = None;
+                item.diff = None;
+            }
+            if !by_subject {
+                item.actor_id = None;
+                item.ip = None;
+            }
+            item
+        })
+        .collect();
+
+    Ok(json!({
+        "exported_at": Utc::now().to_rfc3339(),
+        "profile": profile,
+        "settings": settings,
+        "username_history": username_history,
+        "sessions": sessions,
+        "login_history": login_history,
+        "consents": consents,
+        "audit_logs": audit_logs,
+    }))
+}
+
+/// 把 JSON 扁平化为 "field,value" 两列的 CSV，字段名为 JSON 路径（如 "audit_logs.0.action"）
+fn to_csv(archive: &Value) -> Result<Vec<u8>, AppError> {
+    fn flatten(prefix: &str, value: &Value, rows: &mut Vec<(String, String)>) {
+        let path = |key: &str| if prefix.is_empty() { key.to_string() } else { format!("{}.{}", prefix, key) };
+        match value {
+            Value::Object(map) => map.iter().for_each(|(key, value)| flatten(&path(key), value, rows)),
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .for_each(|(index, value)| flatten(&path(&index.to_string()), value, rows)),
+            Value::Null => rows.push((prefix.to_string(), String::new())),
+            Value::String(s) => rows.push((prefix.to_string(), s.clone())),
+            other => rows.push((prefix.to_string(), other.to_string())),
+        }
+    }
+
+    let mut rows = Vec::new();
+    flatten("", archive, &mut rows);
+
+    let invalid = |e: csv::Error| AppError::InternalServerError(format!("Write export CSV failed: {}", e));
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(["field", "value"]).map_err(invalid)?;
+    for (field, value) in rows {
+        writer.write_record([field, value]).map_err(invalid)?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| AppError::InternalServerError(format!("Write export CSV failed: {}", e)))
+}
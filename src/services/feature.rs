@@ -0,0 +1,166 @@
+// src/services/feature.rs
+use std::collections::BTreeMap;
+
+use sea_orm::{sea_query::OnConflict, *};
+
+use crate::core::log::target;
+use crate::{
+    core::{
+        constants::{CACHE_EXPIRE_FEATURE_FLAGS, REDIS_KEY_FEATURE_FLAGS},
+        error::AppError,
+    },
+    dtos::{
+        auth::Claims,
+        feature::{FeatureFlag, UpsertFeatureFlagRequest, FEATURE_KEY_REGEX},
+    },
+    entity::feature_flags,
+    state::AppState,
+    utils::cache,
+};
+
+/// 功能开关列表的缓存键，包含 `FeatureFlag` 的结构版本
+fn flags_key() -> String {
+    cache::versioned_key::<Vec<FeatureFlag>>(REDIS_KEY_FEATURE_FLAGS)
+}
+
+/// 获取全部功能开关定义（按键名排序）。
+///
+/// 开关数量少、读取频繁（每次评估都需要全部定义），整体缓存为一个 Redis 键，
+/// 管理端修改后立即失效；缓存过期时间很短，失效通知丢失时最迟在过期后生效。
+/// 所有用户共享这个键，每次过期时由单飞锁保证只有一个请求查询数据库。
+///
+/// # 返回值
+/// - `Ok(Vec<FeatureFlag>)`: 全部功能开关定义
+/// - `Err(AppError)`: 数据库查询失败
+pub async fn list_flags(state: &AppState) -> Result<Vec<FeatureFlag>, AppError> {
+    let db = state.db.clone();
+    cache::get_or_fetch_locked(&state.redis, &flags_key(), CACHE_EXPIRE_FEATURE_FLAGS, || async move {
+        let flags = feature_flags::Entity::find()
+            .order_by_asc(feature_flags::Column::Key)
+            .all(&db)
+            .await?;
+        Ok(flags.into_iter().map(FeatureFlag::from).collect())
+    })
+    .await
+}
+
+/// 创建或整体覆盖一个功能开关，并失效开关缓存。
+///
+/// # 参数
+/// - `state`: 应用程序状态
+/// - `key`: 开关键名
+/// - `req`: 开关定义
+///
+/// # 返回值
+/// - `Ok(FeatureFlag)`: 保存后的开关定义
+/// - `Err(AppError)`: 键名不合法或数据库写入失败
+pub async fn upsert_flag(
+    state: &AppState,
+    key: &str,
+    req: UpsertFeatureFlagRequest,
+) -> Result<FeatureFlag, AppError> {
+    validate_key(key)?;
+
+    let model = feature_flags::ActiveModel {
+        key: Set(key.to_string()),
+        description: Set(req.description),
+        enabled: Set(req.enabled),
+        rollout_percentage: Set(req.rollout_percentage as i16),
+        target_users: Set(serde_json::json!(req.target_users)),
+        target_tenants: Set(serde_json::json!(req.target_tenants)),
+        updated_at: Set(chrono::Utc::now().into()),
+    };
+    let saved = feature_flags::Entity::insert(model)
+        .on_conflict(
+            OnConflict::column(feature_flags::Column::Key)
+                .update_columns([
+                    feature_flags::Column::Description,
+                    feature_flags::Column::Enabled,
+                    feature_flags::Column::RolloutPercentage,
+                    feature_flags::Column::TargetUsers,
+                    feature_flags::Column::TargetTenants,
+                    feature_flags::Column::UpdatedAt,
+                ])
+                .to_owned(),
+        )
+        .exec_with_returning(&state.db)
+        .await?;
+
+    cache::del(&state.redis, &flags_key()).await;
+    tracing::info!(target: target::ADMIN, "🚩 Feature flag updated: {}", key);
+
+    Ok(FeatureFlag::from(saved))
+}
+
+/// 删除一个功能开关，并失效开关缓存。删除后该开关对所有用户评估为关闭。
+///
+/// # 返回值
+/// - `Ok(())`: 删除成功
+/// - `Err(AppError)`: 开关不存在或数据库操作失败
+pub async fn delete_flag(state: &AppState, key: &str) -> Result<(), AppError> {
+    let result = feature_flags::Entity::delete_by_id(key.to_string()).exec(&state.db).await?;
+    if result.rows_affected == 0 {
+        return Err(AppError::NotFound(format!("Feature flag '{}' not found", key)));
+    }
+
+    cache::del(&state.redis, &flags_key()).await;
+    tracing::info!(target: target::ADMIN, "🚩 Feature flag deleted: {}", key);
+    Ok(())
+}
+
+/// 为当前用户评估全部功能开关，前端一次请求即可拿到完整的开关集合。
+///
+/// # 返回值
+/// - `Ok(BTreeMap<String, bool>)`: 开关键名到是否开启的映射
+/// - `Err(AppError)`: 读取开关定义失败
+pub async fn evaluate_all(state: &AppState, claims: &Claims) -> Result<BTreeMap<String, bool>, AppError> {
+    let flags = list_flags(state).await?;
+    Ok(flags
+        .iter()
+        .map(|flag| (flag.key.clone(), is_enabled(flag, claims)))
+        .collect())
+}
+
+/// 评估单个功能开关，规则按顺序：
+/// 1. 总开关关闭时一律关闭
+/// 2. 用户在定向用户列表中、所属租户在定向租户列表中，或令牌的 `features` 声明授予了该功能时开启
+/// 3. 否则按用户ID稳定分桶（0-99），桶号小于灰度比例时开启。同一用户对同一开关的结果始终一致，
+///    提高灰度比例时已开启的用户保持开启
+pub fn is_enabled(flag: &FeatureFlag, claims: &Claims) -> bool {
+    if !flag.enabled {
+        return false;
+    }
+
+    let targeted = flag.target_users.contains(&claims.sub)
+        || claims
+            .tenant_id()
+            .is_some_and(|tenant| flag.target_tenants.contains(&tenant))
+        || claims.has_feature(&flag.key);
+    if targeted {
+        return true;
+    }
+
+    bucket(&flag.key, &claims.sub) < u32::from(flag.rollout_percentage)
+}
+
+/// 用户在某个开关上的灰度桶号（0-99）。
+///
+/// 使用 FNV-1a 而不是标准库的 `DefaultHasher`：后者的算法不保证跨版本稳定，
+/// 升级编译器后用户可能被重新分桶。键名参与哈希，使不同开关的灰度人群相互独立。
+fn bucket(key: &str, user_id: &str) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in key.bytes().chain([b':']).chain(user_id.bytes()) {
+        hash ^= u32::from(byte);
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash % 100
+}
+
+fn validate_key(key: &str) -> Result<(), AppError> {
+    if !FEATURE_KEY_REGEX.is_match(key) {
+        return Err(AppError::BadRequest(
+            "Feature flag key must be 1-64 lowercase letters, digits, '.', '_' or '-'".to_string(),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,80 @@
+// src/services/importer/csv.rs
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tokio::sync::OnceCell;
+
+use super::{import_file, parse_options, ImportRecord, UserImporter};
+use crate::core::{config::Config, error::AppError};
+
+#[derive(Deserialize)]
+struct CsvOptions {
+    /// `IMPORT_DIR` 下的 CSV 文件名，第一行为表头
+    file: String,
+    /// 分隔符，默认逗号
+    #[serde(default = "default_delimiter")]
+    delimiter: char,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+/// CSV 文件导入器。首次读取时把整个文件解析到内存，之后按偏移量分页返回。
+pub struct CsvImporter {
+    path: PathBuf,
+    delimiter: u8,
+    records: OnceCell<Vec<ImportRecord>>,
+}
+
+impl CsvImporter {
+    pub fn from_options(config: &Config, options: &serde_json::Value) -> Result<Box<dyn UserImporter>, AppError> {
+        let options: CsvOptions = parse_options(options)?;
+        if !options.delimiter.is_ascii() {
+            return Err(AppError::BadRequest("CSV delimiter must be an ASCII character".to_string()));
+        }
+        let path = import_file(config, &options.file)?;
+        Ok(Box::new(Self { path, delimiter: options.delimiter as u8, records: OnceCell::new() }))
+    }
+
+    fn load(path: &Path, delimiter: u8) -> Result<Vec<ImportRecord>, AppError> {
+        let invalid = |e: ::csv::Error| AppError::BadRequest(format!("Invalid CSV file: {}", e));
+        let mut reader = ::csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .trim(::csv::Trim::All)
+            .from_path(path)
+            .map_err(invalid)?;
+
+        let headers = reader.headers().map_err(invalid)?.clone();
+        reader
+            .records()
+            .map(|row| {
+                let row = row.map_err(invalid)?;
+                Ok(headers
+                    .iter()
+                    .zip(row.iter())
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect())
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl UserImporter for CsvImporter {
+    async fn fetch(&self, offset: usize, limit: usize) -> Result<Vec<ImportRecord>, AppError> {
+        let records = self
+            .records
+            .get_or_try_init(|| async {
+                let path = self.path.clone();
+                let delimiter = self.delimiter;
+                tokio::task::spawn_blocking(move || Self::load(&path, delimiter))
+                    .await
+                    .map_err(|e| AppError::InternalServerError(format!("CSV loader panicked: {}", e)))?
+            })
+            .await?;
+
+        Ok(records.iter().skip(offset).take(limit).cloned().collect())
+    }
+}
@@ -0,0 +1,78 @@
+// src/services/importer/database.rs
+use async_trait::async_trait;
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, QueryResult, Statement};
+use serde::Deserialize;
+use tokio::sync::OnceCell;
+
+use super::{parse_options, ImportRecord, UserImporter};
+use crate::core::{
+    config::{Config, ImportDatabase},
+    error::AppError,
+};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DatabaseOptions {
+    /// `IMPORT_DATABASES` 中配置的来源名称。连接地址和查询语句只能来自配置，不接受请求中的原始地址或 SQL
+    connection: String,
+}
+
+/// 外部数据库导入器。把查询结果按 LIMIT/OFFSET 分页读取，每列转换为字符串。
+pub struct DatabaseImporter {
+    source: ImportDatabase,
+    conn: OnceCell<DatabaseConnection>,
+}
+
+impl DatabaseImporter {
+    pub fn from_options(config: &Config, options: &serde_json::Value) -> Result<Box<dyn UserImporter>, AppError> {
+        let options: DatabaseOptions = parse_options(options)?;
+        let source = config
+            .import_database(&options.connection)
+            .ok_or_else(|| AppError::BadRequest(format!("Unknown import connection: {}", options.connection)))?;
+        Ok(Box::new(Self { source, conn: OnceCell::new() }))
+    }
+
+    /// 读取一列并转换为字符串。依次尝试文本、整数、布尔类型，都不匹配时忽略该列。
+    fn column_to_string(row: &QueryResult, column: &str) -> Option<String> {
+        if let Ok(value) = row.try_get::<Option<String>>("", column) {
+            return value;
+        }
+        if let Ok(value) = row.try_get::<Option<i64>>("", column) {
+            return value.map(|v| v.to_string());
+        }
+        if let Ok(value) = row.try_get::<Option<bool>>("", column) {
+            return value.map(|v| v.to_string());
+        }
+        None
+    }
+}
+
+#[async_trait]
+impl UserImporter for DatabaseImporter {
+    async fn fetch(&self, offset: usize, limit: usize) -> Result<Vec<ImportRecord>, AppError> {
+        let conn = self
+            .conn
+            .get_or_try_init(|| Database::connect(&self.source.url))
+            .await?;
+
+        let sql = format!(
+            "SELECT * FROM ({}) AS import_source LIMIT {} OFFSET {}",
+            self.source.query.trim().trim_end_matches(';'),
+            limit,
+            offset
+        );
+        let rows = conn
+            .query_all(Statement::from_string(conn.get_database_backend(), sql))
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| {
+                row.column_names()
+                    .into_iter()
+                    .filter_map(|column| Self::column_to_string(row, &column).map(|value| (column, value)))
+                    .collect()
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,88 @@
+// src/services/importer/firebase.rs
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::OnceCell;
+
+use super::{import_file, parse_options, ImportRecord, UserImporter};
+use crate::{
+    core::{config::Config, error::AppError},
+    dtos::import::FieldMapping,
+};
+
+#[derive(Deserialize)]
+struct FirebaseOptions {
+    /// `firebase auth:export users.json --format=JSON` 导出的文件，`IMPORT_DIR` 下的文件名
+    file: String,
+}
+
+/// 导出文件的结构：`{"users": [{"localId": "...", "email": "...", ...}]}`
+#[derive(Deserialize)]
+struct FirebaseExport {
+    users: Vec<serde_json::Map<String, Value>>,
+}
+
+/// Firebase Authentication 导出文件导入器。
+///
+/// Firebase 使用修改版 scrypt 哈希密码，无法直接校验，因此导入的用户都需要重置密码。
+/// 记录中的 `disabled` 字段会被转换为 `is_active`（取反）。
+pub struct FirebaseImporter {
+    path: PathBuf,
+    records: OnceCell<Vec<ImportRecord>>,
+}
+
+impl FirebaseImporter {
+    pub fn from_options(config: &Config, options: &serde_json::Value) -> Result<Box<dyn UserImporter>, AppError> {
+        let options: FirebaseOptions = parse_options(options)?;
+        let path = import_file(config, &options.file)?;
+        Ok(Box::new(Self { path, records: OnceCell::new() }))
+    }
+
+    /// 把每个用户对象展开为 字段名 -> 字符串值，忽略嵌套字段（如 providerUserInfo）
+    fn flatten(user: serde_json::Map<String, Value>) -> ImportRecord {
+        let mut record: ImportRecord = user
+            .into_iter()
+            .filter_map(|(name, value)| match value {
+                Value::String(s) => Some((name, s)),
+                Value::Bool(b) => Some((name, b.to_string())),
+                Value::Number(n) => Some((name, n.to_string())),
+                _ => None,
+            })
+            .collect();
+
+        let disabled = record.get("disabled").is_some_and(|value| value == "true");
+        record.insert("is_active".to_string(), (!disabled).to_string());
+        record
+    }
+}
+
+#[async_trait]
+impl UserImporter for FirebaseImporter {
+    async fn fetch(&self, offset: usize, limit: usize) -> Result<Vec<ImportRecord>, AppError> {
+        let records = self
+            .records
+            .get_or_try_init(|| async {
+                let raw = tokio::fs::read(&self.path)
+                    .await
+                    .map_err(|e| AppError::BadRequest(format!("Cannot read Firebase export: {}", e)))?;
+                let export: FirebaseExport = serde_json::from_slice(&raw)
+                    .map_err(|e| AppError::BadRequest(format!("Invalid Firebase export: {}", e)))?;
+                Ok::<_, AppError>(export.users.into_iter().map(Self::flatten).collect())
+            })
+            .await?;
+
+        Ok(records.iter().skip(offset).take(limit).cloned().collect())
+    }
+
+    fn default_mapping(&self) -> FieldMapping {
+        FieldMapping {
+            username: "email".to_string(),
+            phone: Some("phoneNumber".to_string()),
+            password_hash: None,
+            role: None,
+            is_active: Some("is_active".to_string()),
+        }
+    }
+}
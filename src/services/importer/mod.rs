@@ -0,0 +1,423 @@
+// src/services/importer/mod.rs
+use argon2::{password_hash::SaltString, PasswordHash};
+use async_trait::async_trait;
+use chrono::Utc;
+use rand::rngs::OsRng;
+use redis::AsyncCommands;
+use sea_orm::{sea_query::OnConflict, *};
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+};
+use uuid::Uuid;
+
+use crate::core::log::target;
+use crate::{
+    core::{
+        config::Config,
+        constants::{IMPORT_JOB_EXPIRE, IMPORT_LOCK_EXPIRE, REDIS_PREFIX_IMPORT_JOB, REDIS_PREFIX_IMPORT_LOCK},
+        enums::{AuditAction, UserRole},
+        error::AppError,
+    },
+    dtos::{
+        import::{FieldMapping, ImportProgress, ImportRequest, ImportStatus, PasswordMode},
+        PHONE_REGEX,
+    },
+    entity::users,
+    services::audit::{self as AuditService, AuditEntry},
+    state::AppState,
+    utils::public_id,
+};
+
+mod csv;
+mod database;
+mod firebase;
+
+// 用户导入框架：从外部系统（CSV 文件、其他数据库、Firebase 导出文件等）迁移用户。
+// 每种来源实现 `UserImporter`，按名称注册到 `ImporterRegistry`；导入任务按批读取、写入，
+// 每批提交后把进度保存到 Redis，进程中断后可以使用相同的任务ID从断点继续。
+
+/// 来源系统中的一条用户记录：字段名 -> 字段值（统一为字符串）。
+pub type ImportRecord = HashMap<String, String>;
+
+/// 用户数据来源。实现方只负责按偏移量分页读取原始记录，字段映射、校验和写入由导入框架处理。
+#[async_trait]
+pub trait UserImporter: Send + Sync {
+    /// 从第 `offset` 条记录开始读取最多 `limit` 条。返回空列表表示已读取完毕。
+    /// 同一偏移量必须返回相同的记录，以保证断点续传的正确性。
+    async fn fetch(&self, offset: usize, limit: usize) -> Result<Vec<ImportRecord>, AppError>;
+
+    /// 未在请求中指定字段映射时使用的默认映射。
+    fn default_mapping(&self) -> FieldMapping {
+        FieldMapping {
+            username: "username".to_string(),
+            phone: Some("phone".to_string()),
+            password_hash: Some("password_hash".to_string()),
+            role: Some("role".to_string()),
+            is_active: Some("is_active".to_string()),
+        }
+    }
+}
+
+/// 导入器工厂：根据请求中的 `options` 创建导入器，参数不合法时返回错误。
+/// 来源位置（文件目录、数据库连接）只能来自配置，`options` 中只能按名称引用。
+pub type ImporterFactory = fn(&Config, &serde_json::Value) -> Result<Box<dyn UserImporter>, AppError>;
+
+/// 导入器注册表。默认注册 "csv"、"firebase"、"database" 三种来源，
+/// 部署时可以注册自定义来源，再通过 `AppState::with_importers` 替换。
+pub struct ImporterRegistry {
+    factories: HashMap<&'static str, ImporterFactory>,
+}
+
+impl ImporterRegistry {
+    /// 创建空的注册表
+    pub fn empty() -> Self {
+        Self { factories: HashMap::new() }
+    }
+
+    /// 注册（或覆盖）一种数据来源
+    pub fn register(mut self, name: &'static str, factory: ImporterFactory) -> Self {
+        self.factories.insert(name, factory);
+        self
+    }
+
+    fn build(&self, config: &Config, source: &str, options: &serde_json::Value) -> Result<Box<dyn UserImporter>, AppError> {
+        let factory = self
+            .factories
+            .get(source)
+            .ok_or_else(|| AppError::BadRequest(format!("Unknown import source: {}", source)))?;
+        factory(config, options)
+    }
+}
+
+impl Default for ImporterRegistry {
+    fn default() -> Self {
+        Self::empty()
+            .register("csv", csv::CsvImporter::from_options)
+            .register("firebase", firebase::FirebaseImporter::from_options)
+            .register("database", database::DatabaseImporter::from_options)
+    }
+}
+
+/// 解析导入器参数，参数不合法时返回 400 错误
+fn parse_options<T: serde::de::DeserializeOwned>(options: &serde_json::Value) -> Result<T, AppError> {
+    serde_json::from_value(options.clone())
+        .map_err(|e| AppError::BadRequest(format!("Invalid import options: {}", e)))
+}
+
+/// 解析导入文件名：只接受 `IMPORT_DIR` 下的相对路径，拒绝绝对路径和 `..`，
+/// 并确认解析符号链接后仍位于该目录中。错误信息中不包含服务器上的路径。
+fn import_file(config: &Config, name: &str) -> Result<PathBuf, AppError> {
+    let relative = Path::new(name);
+    let plain = relative.components().all(|component| matches!(component, Component::Normal(_)));
+    if name.is_empty() || !plain {
+        return Err(AppError::BadRequest("Import file must be a file name inside the import directory".to_string()));
+    }
+
+    let not_found = || AppError::BadRequest(format!("Import file not found: {}", name));
+    let dir = Path::new(&config.import_dir).canonicalize().map_err(|_| not_found())?;
+    let path = dir.join(relative).canonicalize().map_err(|_| not_found())?;
+    if !path.starts_with(&dir) {
+        return Err(not_found());
+    }
+    Ok(path)
+}
+
+#[inline]
+fn job_key(job_id: &str) -> String {
+    format!("{}{}", REDIS_PREFIX_IMPORT_JOB, job_id)
+}
+#[inline]
+fn lock_key(job_id: &str) -> String {
+    format!("{}{}", REDIS_PREFIX_IMPORT_LOCK, job_id)
+}
+
+/// 查询导入任务进度
+///
+/// # 返回值
+/// - `Ok(ImportProgress)`: 任务进度
+/// - `Err(AppError)`: 任务不存在或已过期
+pub async fn get_progress(state: &AppState, job_id: &str) -> Result<ImportProgress, AppError> {
+    let mut redis = state.redis.clone();
+    let raw: Option<String> = redis.get(job_key(job_id)).await?;
+    raw.and_then(|raw| serde_json::from_str(&raw).ok())
+        .ok_or(AppError::NotFound("Import job not found".to_string()))
+}
+
+async fn save_progress(state: &AppState, progress: &mut ImportProgress) -> Result<(), AppError> {
+    progress.updated_at = Utc::now().to_rfc3339();
+    let raw = serde_json::to_string(progress)
+        .map_err(|e| AppError::InternalServerError(format!("Serialize import progress failed: {}", e)))?;
+    let mut redis = state.redis.clone();
+    let _: () = redis.set_ex(job_key(&progress.job_id), raw, IMPORT_JOB_EXPIRE).await?;
+    Ok(())
+}
+
+/// 启动（或续传）导入任务。校验参数、获取任务锁后在后台执行，立即返回当前进度。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `actor_id`: 发起导入的管理员ID（写入审计日志）。
+/// - `req`: 导入请求。
+///
+/// # 返回值
+/// - `Ok(ImportProgress)`: 任务已启动，返回初始进度（续传时为上次中断时的进度）。
+/// - `Err(AppError)`: 来源未注册、参数不合法、任务已完成或正在执行。
+pub async fn start(state: &AppState, actor_id: &str, req: ImportRequest) -> Result<ImportProgress, AppError> {
+    // 第一步：创建导入器，提前暴露来源和参数错误
+    let importer = state.importers.build(&state.config, &req.source, &req.options)?;
+    let mapping = req.mapping.clone().unwrap_or_else(|| importer.default_mapping());
+
+    // 第二步：确定任务进度。指定了已存在的任务ID时从断点继续。
+    let job_id = req.job_id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut progress = match get_progress(state, &job_id).await {
+        Ok(progress) if progress.status == ImportStatus::Completed => {
+            return Err(AppError::Conflict("Import job already completed".to_string()));
+        }
+        Ok(progress) => progress,
+        Err(_) => ImportProgress {
+            job_id: job_id.clone(),
+            source: req.source.clone(),
+            status: ImportStatus::Running,
+            cursor: 0,
+            imported: 0,
+            skipped: 0,
+            failed: 0,
+            password_resets: 0,
+            error: None,
+            updated_at: String::new(),
+        },
+    };
+
+    // 第三步：获取任务锁，防止同一任务被重复启动
+    let mut redis = state.redis.clone();
+    let locked: Option<String> = redis::cmd("SET")
+        .arg(lock_key(&job_id))
+        .arg(actor_id)
+        .arg("NX")
+        .arg("EX")
+        .arg(IMPORT_LOCK_EXPIRE)
+        .query_async(&mut redis)
+        .await?;
+    if locked.is_none() {
+        return Err(AppError::Conflict("Import job is already running".to_string()));
+    }
+
+    progress.status = ImportStatus::Running;
+    progress.error = None;
+    save_progress(state, &mut progress).await?;
+    tracing::info!(target: target::ADMIN, "📥 Import job {} started from {} at cursor {}", job_id, req.source, progress.cursor);
+
+    // 第四步：后台执行导入
+    let task_state = state.clone();
+    let actor_id = actor_id.to_string();
+    let initial = progress.clone();
+    tokio::spawn(async move {
+        run(task_state, actor_id, importer, mapping, req.password_mode, req.batch_size, progress).await;
+    });
+
+    Ok(initial)
+}
+
+/// 按批执行导入，直到来源数据读取完毕或发生错误
+async fn run(
+    state: AppState,
+    actor_id: String,
+    importer: Box<dyn UserImporter>,
+    mapping: FieldMapping,
+    password_mode: PasswordMode,
+    batch_size: usize,
+    mut progress: ImportProgress,
+) {
+    let result = async {
+        loop {
+            let records = importer.fetch(progress.cursor, batch_size).await?;
+            if records.is_empty() {
+                return Ok::<(), AppError>(());
+            }
+
+            import_batch(&state, &records, &mapping, password_mode, &mut progress).await?;
+            progress.cursor += records.len();
+            save_progress(&state, &mut progress).await?;
+
+            // 续期任务锁
+            let mut redis = state.redis.clone();
+            let _: () = redis.expire(lock_key(&progress.job_id), IMPORT_LOCK_EXPIRE as i64).await?;
+        }
+    }
+    .await;
+
+    match result {
+        Ok(()) => {
+            progress.status = ImportStatus::Completed;
+            tracing::info!(
+                target: target::ADMIN,
+                "✅ Import job {} completed: {} imported, {} skipped, {} failed",
+                progress.job_id, progress.imported, progress.skipped, progress.failed
+            );
+        }
+        Err(e) => {
+            progress.status = ImportStatus::Failed;
+            progress.error = Some(e.to_string());
+            tracing::error!(target: target::ADMIN, "❌ Import job {} failed at cursor {}: {}", progress.job_id, progress.cursor, e);
+        }
+    }
+
+    if let Err(e) = save_progress(&state, &mut progress).await {
+        tracing::error!(target: target::ADMIN, "❌ Failed to save import progress {}: {}", progress.job_id, e);
+    }
+    let mut redis = state.redis.clone();
+    let _: () = redis.del(lock_key(&progress.job_id)).await.unwrap_or_default();
+
+    AuditService::record(
+        &state,
+        AuditEntry::new(&actor_id, AuditAction::UserImport).diff(serde_json::json!({
+            "job_id": progress.job_id,
+            "source": progress.source,
+            "status": progress.status,
+            "imported": progress.imported,
+            "skipped": progress.skipped,
+            "failed": progress.failed,
+        })),
+    )
+    .await;
+}
+
+/// 在一个事务中写入一批记录。用户名或手机号已存在的记录跳过（因此重复执行同一批是安全的）。
+/// 计数先记在本批内，事务提交成功后才累加到任务进度，提交失败时进度保持不变，续传时重新处理本批。
+async fn import_batch(
+    state: &AppState,
+    records: &[ImportRecord],
+    mapping: &FieldMapping,
+    password_mode: PasswordMode,
+    progress: &mut ImportProgress,
+) -> Result<(), AppError> {
+    let txn = state.db.begin().await?;
+    let (mut imported, mut skipped, mut failed, mut password_resets) = (0, 0, 0, 0);
+
+    for record in records {
+        let (user, password_reset) = match map_record(record, mapping, password_mode) {
+            Ok(mapped) => mapped,
+            Err(reason) => {
+                tracing::warn!(target: target::ADMIN, "⚠️ Import job {}: skipping invalid record: {}", progress.job_id, reason);
+                failed += 1;
+                continue;
+            }
+        };
+
+        let inserted = users::Entity::insert(user)
+            .on_conflict(OnConflict::new().do_nothing().to_owned())
+            .exec_without_returning(&txn)
+            .await?;
+
+        if inserted == 0 {
+            skipped += 1;
+        } else {
+            imported += 1;
+            if password_reset {
+                password_resets += 1;
+            }
+        }
+    }
+
+    txn.commit().await?;
+
+    progress.imported += imported;
+    progress.skipped += skipped;
+    progress.failed += failed;
+    progress.password_resets += password_resets;
+    Ok(())
+}
+
+/// 按字段映射把来源记录转换为用户模型。返回的布尔值表示密码是否需要重置。
+fn map_record(
+    record: &ImportRecord,
+    mapping: &FieldMapping,
+    password_mode: PasswordMode,
+) -> Result<(users::ActiveModel, bool), String> {
+    let field = |source: &Option<String>| {
+        source
+            .as_ref()
+            .and_then(|name| record.get(name))
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+
+    let username = field(&Some(mapping.username.clone()))
+        .ok_or_else(|| format!("missing username field `{}`", mapping.username))?;
+    if username.chars().count() < 3 {
+        return Err(format!("username `{}` is too short", username));
+    }
+
+    let phone = field(&mapping.phone);
+    if let Some(phone) = phone.filter(|phone| !PHONE_REGEX.is_match(phone)) {
+        return Err(format!("invalid phone `{}` for user `{}`", phone, username));
+    }
+
+    // 只导入普通用户和管理员，来源数据不能直接创建超级管理员
+    let role = field(&mapping.role)
+        .and_then(|role| UserRole::from_str(&role.to_lowercase()).ok())
+        .filter(|role| *role != UserRole::SuperAdmin)
+        .unwrap_or(UserRole::User);
+
+    let is_active = !matches!(
+        field(&mapping.is_active).map(str::to_lowercase).as_deref(),
+        Some("false" | "0" | "no")
+    );
+
+    // 沿用密码时只接受登录能够校验的 argon2 哈希（见 `is_verifiable_hash`）；其他格式统一按重置处理
+    let passthrough = match password_mode {
+        PasswordMode::Passthrough => field(&mapping.password_hash)
+            .filter(|hash| is_verifiable_hash(hash))
+            .map(str::to_string),
+        PasswordMode::Reset => None,
+    };
+    let password_reset = passthrough.is_none();
+    let password_hash = passthrough.unwrap_or_else(unusable_password_hash);
+
+    let user = users::ActiveModel {
+        public_id: Set(public_id::generate()),
+        username: Set(username.to_string()),
+        password_hash: Set(password_hash),
+        phone: Set(phone.map(str::to_string)),
+        role: Set(role),
+        is_active: Set(is_active),
+        ..Default::default()
+    };
+    Ok((user, password_reset))
+}
+
+/// 登录只用 argon2 校验密码（见 `AuthService::login`），scrypt、pbkdf2 等其他 PHC 格式的哈希
+/// 虽然能解析，导入后用户却永远无法登录，因此只沿用 argon2id / argon2i / argon2d 哈希。
+fn is_verifiable_hash(hash: &str) -> bool {
+    PasswordHash::new(hash).is_ok_and(|parsed| matches!(parsed.algorithm.as_str(), "argon2id" | "argon2i" | "argon2d"))
+}
+
+/// 生成一个格式合法但不对应任何密码的 argon2 哈希（随机盐 + 随机输出）。
+/// 这样需要重置密码的用户登录时得到的是普通的 "凭证无效"，而不是哈希解析错误。
+/// 管理员通过 `POST /admin/users/{id}/password` 为这些用户设置密码（见 `AdminService::reset_password`）。
+fn unusable_password_hash() -> String {
+    format!(
+        "$argon2id$v=19$m=19456,t=2,p=1${}${}",
+        SaltString::generate(&mut OsRng).as_str(),
+        SaltString::generate(&mut OsRng).as_str(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_argon2_hashes_pass_through() {
+        let unusable = unusable_password_hash();
+        assert!(is_verifiable_hash(&unusable));
+        assert!(is_verifiable_hash(&unusable.replacen("argon2id", "argon2i", 1)));
+        assert!(is_verifiable_hash(&unusable.replacen("argon2id", "argon2d", 1)));
+
+        assert!(!is_verifiable_hash("$scrypt$ln=16,r=8,p=1$aM15713r3Xsvxbi31lqr1Q$nFNh2CVHVjNldFVKDHDlm4CbdRSCdEBsjjJxD+iCs5E"));
+        assert!(!is_verifiable_hash("$pbkdf2-sha256$i=1000$c2FsdHNhbHQ$aGFzaGhhc2hoYXNoaGFzaA"));
+        assert!(!is_verifiable_hash("5f4dcc3b5aa765d61d8327deb882cf99"));
+    }
+}
@@ -1,2 +1,18 @@
+pub mod access_log;
+pub mod admin;
+pub mod analytics;
+pub mod audit;
 pub mod auth;
+pub mod consent;
+pub mod delegation;
+pub mod claims;
+pub mod device;
+pub mod export;
+pub mod feature;
+pub mod importer;
+pub mod permission;
+pub mod presence;
+pub mod security;
+pub mod stats;
+pub mod storage;
 pub mod user;
\ No newline at end of file
@@ -0,0 +1,86 @@
+// src/services/permission.rs
+use sea_orm::*;
+use uuid::Uuid;
+use crate::core::log::target;
+use crate::{
+    core::{
+        constants::{CACHE_EXPIRE_USER_PERMISSIONS, REDIS_PREFIX_USER_PERMISSIONS},
+        enums::{Permission, SecurityEventKind},
+        error::AppError,
+    },
+    entity::users,
+    services::security::{self as SecurityService, SecurityEvent},
+    state::AppState,
+    utils::{cache, retry},
+};
+
+#[inline]
+fn permissions_key(user_id: &str) -> String {
+    cache::versioned_key::<Permission>(&format!("{}{}", REDIS_PREFIX_USER_PERMISSIONS, user_id))
+}
+
+/// 获取用户的权限集合。权限根据数据库中的最新角色计算，并缓存到Redis中，
+/// 这样受保护的请求不需要每次都查询数据库，同时角色变更后可以通过失效缓存立即生效
+/// （而不是等到访问令牌过期）。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接和Redis客户端。
+/// - `user_id`: 用户ID字符串，通常来自JWT claims中的sub字段。
+///
+/// # 返回值
+/// - `Ok(Vec<Permission>)`: 用户拥有的权限。已禁用的账户返回空集合。
+/// - `Err(AppError)`: 用户不存在或数据库查询失败。
+pub async fn get_user_permissions(state: &AppState, user_id: &str) -> Result<Vec<Permission>, AppError> {
+    let key = permissions_key(user_id);
+    let db = state.db.clone();
+    let uid_str = user_id.to_string();
+
+    cache::get_or_fetch(
+        &state.redis,
+        &key,
+        CACHE_EXPIRE_USER_PERMISSIONS,
+        || async move {
+            let uid = Uuid::parse_str(&uid_str)
+                .map_err(|_| AppError::AuthError("Invalid User ID format".to_string()))?;
+
+            let user = retry::with_retry("find_user_permissions", || users::Entity::find_by_id(uid).one(&db))
+                .await?
+                .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+            // 已禁用的账户不具备任何权限
+            if !user.is_active {
+                return Ok(Vec::new());
+            }
+
+            Ok(user.role.permissions())
+        },
+    )
+    .await
+}
+
+/// 校验用户是否拥有指定权限。权限不足时返回403 Forbidden错误。
+pub async fn ensure_permission(state: &AppState, user_id: &str, permission: Permission) -> Result<(), AppError> {
+    let permissions = get_user_permissions(state, user_id).await?;
+    if !permissions.contains(&permission) {
+        tracing::warn!(target: target::ADMIN, "🚫 Permission denied: user {} lacks {}", user_id, permission);
+        SecurityService::record(
+            state,
+            SecurityEvent::new(SecurityEventKind::AccessDenied)
+                .user(user_id)
+                .detail(format!("missing permission {}", permission)),
+        );
+        return Err(AppError::Forbidden(format!("Missing permission: {}", permission)));
+    }
+    Ok(())
+}
+
+/// 失效单个用户的权限缓存。在用户角色变更、账户禁用等场景下调用。
+pub async fn invalidate_user_permissions(state: &AppState, user_id: &str) {
+    cache::del(&state.redis, &permissions_key(user_id)).await;
+}
+
+/// 失效所有用户的权限缓存。在角色与权限的映射关系发生变化时调用。
+#[allow(dead_code)]
+pub async fn invalidate_all_permissions(state: &AppState) -> usize {
+    cache::del_by_pattern(&state.redis, &format!("{}*", REDIS_PREFIX_USER_PERMISSIONS)).await
+}
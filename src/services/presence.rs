@@ -0,0 +1,194 @@
+// src/services/presence.rs
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use redis::{aio::ConnectionManager, AsyncCommands, Script};
+use uuid::Uuid;
+
+use crate::core::log::target;
+use crate::{
+    core::{
+        constants::{
+            PRESENCE_LAST_SEEN_EXPIRE, PRESENCE_SWEEP_BATCH, PRESENCE_SWEEP_INTERVAL, PRESENCE_TTL,
+            REDIS_CHANNEL_PRESENCE, REDIS_KEY_PRESENCE_DEADLINES, REDIS_PREFIX_PRESENCE_LAST_SEEN,
+            REDIS_PREFIX_PRESENCE_ONLINE,
+        },
+        enums::Permission,
+        error::AppError,
+    },
+    dtos::{
+        auth::Claims,
+        presence::{PresenceEvent, PresenceStatus},
+    },
+    services::{permission as PermissionService, user as UserService},
+    state::AppState,
+};
+
+fn online_key(user_id: Uuid) -> String {
+    format!("{}{}", REDIS_PREFIX_PRESENCE_ONLINE, user_id)
+}
+
+fn last_seen_key(user_id: Uuid) -> String {
+    format!("{}{}", REDIS_PREFIX_PRESENCE_LAST_SEEN, user_id)
+}
+
+/// 发布在线状态变更事件。发布失败只记录日志，不影响心跳和登出本身。
+async fn publish(redis: &mut ConnectionManager, user_id: &str, online: bool, at: i64) {
+    let event = PresenceEvent { user_id: user_id.to_string(), online, at };
+    if let Ok(message) = serde_json::to_string(&event)
+        && let Err(e) = redis.publish::<_, _, ()>(REDIS_CHANNEL_PRESENCE, message).await
+    {
+        tracing::warn!(target: target::USER, "⚠️ Presence event publish failed for {}: {}", user_id, e);
+    }
+}
+
+fn format_timestamp(timestamp: Option<i64>) -> Option<String> {
+    timestamp
+        .and_then(|ts| DateTime::<Utc>::from_timestamp(ts, 0))
+        .map(|at| at.to_rfc3339())
+}
+
+/// 记录一次在线心跳：刷新在线状态的过期时间和最后在线时间。
+///
+/// 在线状态基于客户端定期调用的 HTTP 心跳接口，项目没有 WebSocket/SSE 长连接。上下线事件发布到
+/// `REDIS_CHANNEL_PRESENCE` 频道，由订阅方自行转发：用户从离线变为在线时发布上线事件；
+/// 心跳停止 `PRESENCE_TTL` 秒后由 `spawn_sweeper` 发布离线事件，主动登出时由 `go_offline` 立即发布。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `user_id`: 发送心跳的用户ID。
+///
+/// # 返回值
+/// - `Ok(PresenceStatus)`: 心跳后的在线状态。
+/// - `Err(AppError)`: Redis 操作失败。
+pub async fn heartbeat(state: &AppState, user_id: Uuid) -> Result<PresenceStatus, AppError> {
+    let now = Utc::now().timestamp();
+    let mut redis = state.redis.clone();
+
+    // 离线期限集合决定上下线事件：ZADD 新增成员（返回 1）说明本次心跳之前处于离线状态。
+    // 在线状态键只用于查询，与离线期限同时过期
+    let (added,): (i64,) = redis::pipe()
+        .set_ex(online_key(user_id), now, PRESENCE_TTL)
+        .ignore()
+        .set_ex(last_seen_key(user_id), now, PRESENCE_LAST_SEEN_EXPIRE)
+        .ignore()
+        .zadd(REDIS_KEY_PRESENCE_DEADLINES, user_id.to_string(), now + PRESENCE_TTL as i64)
+        .query_async(&mut redis)
+        .await?;
+
+    if added == 1 {
+        tracing::debug!(target: target::USER, "🟢 User came online: {}", user_id);
+        publish(&mut redis, &user_id.to_string(), true, now).await;
+    }
+
+    Ok(PresenceStatus { online: true, last_seen_at: format_timestamp(Some(now)) })
+}
+
+/// 查询用户的在线状态。
+///
+/// 用户本人和拥有 `ViewUsers` 权限的管理员总是可以查看；其他用户只有在对方的设置中
+/// 开启了 `show_presence` 时才能查看。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `claims`: 查看者的JWT Claims。
+/// - `user_id`: 被查看的用户ID。
+///
+/// # 返回值
+/// - `Ok(PresenceStatus)`: 在线状态和最后在线时间。
+/// - `Err(AppError)`: 对方未公开在线状态（403）、用户不存在或查询失败。
+pub async fn get(state: &AppState, claims: &Claims, user_id: Uuid) -> Result<PresenceStatus, AppError> {
+    let target_id = user_id.to_string();
+
+    // 第一步：隐私检查
+    if claims.sub != target_id {
+        // 委托令牌只代表授权人访问其本人的资源，不继承授权人的管理权限
+        let can_view_all = claims.act.is_none()
+            && PermissionService::get_user_permissions(state, &claims.sub)
+                .await?
+                .contains(&Permission::ViewUsers);
+        if !can_view_all {
+            let settings = UserService::get_settings(state, &target_id).await?;
+            if settings.show_presence != Some(true) {
+                return Err(AppError::Forbidden("This user does not share their presence".to_string()));
+            }
+        }
+    }
+
+    // 第二步：读取在线状态和最后在线时间
+    let mut redis = state.redis.clone();
+    let (online, last_seen): (Option<i64>, Option<i64>) = redis::cmd("MGET")
+        .arg(online_key(user_id))
+        .arg(last_seen_key(user_id))
+        .query_async(&mut redis)
+        .await?;
+
+    Ok(PresenceStatus { online: online.is_some(), last_seen_at: format_timestamp(last_seen) })
+}
+
+/// 主动下线（登出时调用）：立即清除在线状态并发布离线事件。本来就不在线时不发布。
+///
+/// 同一用户的其他设备仍在发送心跳时，下一次心跳会重新上线。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `user_id`: 登出的用户ID。
+///
+/// # 返回值
+/// - `Ok(())`: 已下线或本来就不在线。
+/// - `Err(AppError)`: Redis 操作失败。
+pub async fn go_offline(state: &AppState, user_id: Uuid) -> Result<(), AppError> {
+    let mut redis = state.redis.clone();
+    let (removed,): (i64,) = redis::pipe()
+        .zrem(REDIS_KEY_PRESENCE_DEADLINES, user_id.to_string())
+        .del(online_key(user_id))
+        .ignore()
+        .query_async(&mut redis)
+        .await?;
+
+    if removed == 1 {
+        tracing::debug!(target: target::USER, "⚪ User went offline: {}", user_id);
+        publish(&mut redis, &user_id.to_string(), false, Utc::now().timestamp()).await;
+    }
+    Ok(())
+}
+
+/// 启动离线检查任务：定期取出心跳已超时的用户，发布离线事件，事件时间为离线期限。
+///
+/// 取出和删除在同一个脚本中原子执行，多实例部署时每个用户的离线事件只会由一个实例发布，不需要额外加锁。
+pub fn spawn_sweeper(redis: ConnectionManager) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(PRESENCE_SWEEP_INTERVAL));
+        let script = Script::new(r#"
+            local expired = redis.call("ZRANGEBYSCORE", KEYS[1], "-inf", ARGV[1], "WITHSCORES", "LIMIT", 0, ARGV[2])
+            for i = 1, #expired, 2 do
+                redis.call("ZREM", KEYS[1], expired[i])
+            end
+            return expired
+        "#);
+
+        loop {
+            ticker.tick().await;
+
+            let mut redis = redis.clone();
+            let expired: Vec<(String, i64)> = match script
+                .key(REDIS_KEY_PRESENCE_DEADLINES)
+                .arg(Utc::now().timestamp())
+                .arg(PRESENCE_SWEEP_BATCH)
+                .invoke_async(&mut redis)
+                .await
+            {
+                Ok(expired) => expired,
+                Err(e) => {
+                    tracing::warn!(target: target::USER, "⚠️ Presence sweep failed: {}", e);
+                    continue;
+                }
+            };
+
+            for (user_id, deadline) in expired {
+                tracing::debug!(target: target::USER, "⚪ User went offline: {}", user_id);
+                publish(&mut redis, &user_id, false, deadline).await;
+            }
+        }
+    });
+}
@@ -0,0 +1,147 @@
+// src/services/security.rs
+use axum::http::Extensions;
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::core::log::target;
+use crate::{
+    core::{enums::SecurityEventKind, error::AppError},
+    dtos::{
+        pagination::{PageQuery, Paginated},
+        security::{SecurityEventFilter, SecurityEventItem},
+    },
+    entity::security_events,
+    extractors::context::RequestContext,
+    state::AppState,
+    utils::retry,
+};
+
+/// 一条待记录的安全事件。
+pub struct SecurityEvent {
+    pub kind: SecurityEventKind,
+    /// 事件涉及的用户ID，登录失败等无法确定用户时为空
+    pub user_id: Option<Uuid>,
+    /// 事件涉及的账号（用户名或提交的登录账号）
+    pub subject: Option<String>,
+    pub ip: Option<String>,
+    pub request_id: Option<String>,
+    /// 补充说明，如所需的角色或权限
+    pub detail: Option<String>,
+}
+
+impl SecurityEvent {
+    pub fn new(kind: SecurityEventKind) -> Self {
+        Self {
+            kind,
+            user_id: None,
+            subject: None,
+            ip: None,
+            request_id: None,
+            detail: None,
+        }
+    }
+
+    /// 以请求上下文构建安全事件：来源IP、请求ID，以及携带有效令牌时的用户ID和用户名。
+    pub fn from_context(ctx: &RequestContext, kind: SecurityEventKind) -> Self {
+        let mut event = Self::new(kind);
+        event.ip = Some(ctx.client_ip.clone());
+        event.request_id = ctx.request_id.clone();
+        if let Some(claims) = &ctx.claims {
+            event = event.user(&claims.sub).subject(claims.username.clone());
+        }
+        event
+    }
+
+    /// 在中间件中构建安全事件：请求扩展中有请求上下文时使用它，否则只记录事件类型。
+    pub fn from_extensions(extensions: &Extensions, kind: SecurityEventKind) -> Self {
+        match extensions.get::<RequestContext>() {
+            Some(ctx) => Self::from_context(ctx, kind),
+            None => Self::new(kind),
+        }
+    }
+
+    pub fn user(mut self, user_id: &str) -> Self {
+        self.user_id = Uuid::parse_str(user_id).ok();
+        self
+    }
+
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// 记录一条安全事件。
+///
+/// 安全事件发生在请求被拒绝的路径上，写入放到后台任务中，不增加拒绝响应的延迟；
+/// 写入失败只记录错误日志（与审计日志的 Soft Fail 策略一致）。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接。
+/// - `event`: 待记录的安全事件。
+pub fn record(state: &AppState, event: SecurityEvent) {
+    metrics::counter!("security_events_total", "kind" => event.kind.to_string()).increment(1);
+
+    let db = state.db.clone();
+    tokio::spawn(async move {
+        let kind = event.kind.clone();
+        let model = security_events::ActiveModel {
+            kind: Set(event.kind),
+            user_id: Set(event.user_id),
+            subject: Set(event.subject),
+            ip: Set(event.ip),
+            request_id: Set(event.request_id),
+            detail: Set(event.detail),
+            ..Default::default()
+        };
+        if let Err(e) = security_events::Entity::insert(model).exec(&db).await {
+            tracing::error!(target: target::AUTH, "❌ Failed to record security event {}: {}", kind, e);
+        }
+    });
+}
+
+/// 分页查询安全事件，按时间倒序返回。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接。
+/// - `page`: 分页参数。
+/// - `filter`: 过滤条件（事件类型、用户、账号）。
+///
+/// # 返回值
+/// - `Ok(Paginated<SecurityEventItem>)`: 当前页的安全事件及分页信息。
+/// - `Err(AppError)`: 数据库查询失败。
+pub async fn list(
+    state: &AppState,
+    page: PageQuery,
+    filter: SecurityEventFilter,
+) -> Result<Paginated<SecurityEventItem>, AppError> {
+    let mut condition = Condition::all();
+    if let Some(kind) = filter.kind {
+        condition = condition.add(security_events::Column::Kind.eq(kind));
+    }
+    if let Some(user_id) = filter.user_id {
+        condition = condition.add(security_events::Column::UserId.eq(user_id));
+    }
+    if let Some(subject) = filter.subject {
+        condition = condition.add(security_events::Column::Subject.eq(subject));
+    }
+
+    let paginator = security_events::Entity::find()
+        .filter(condition)
+        .order_by_desc(security_events::Column::CreatedAt)
+        .paginate(&state.db, page.per_page);
+
+    let counts = retry::with_retry("count_security_events", || paginator.num_items_and_pages()).await?;
+    let items = retry::with_retry("list_security_events", || paginator.fetch_page(page.page_index()))
+        .await?
+        .into_iter()
+        .map(SecurityEventItem::from)
+        .collect();
+
+    Ok(Paginated::new(items, &page, counts.number_of_items, counts.number_of_pages))
+}
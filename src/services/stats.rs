@@ -0,0 +1,164 @@
+// src/services/stats.rs
+use std::collections::HashMap;
+
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::core::log::target;
+use crate::{
+    core::{
+        constants::{REDIS_PREFIX_STATS, STATS_RETENTION_MINUTES, STATS_TOP_USERS},
+        enums::ConsentPurpose,
+        error::AppError,
+    },
+    dtos::admin::{RouteStats, StatsOverview, UserStats},
+    services::{analytics as AnalyticsService, consent as ConsentService},
+    state::AppState,
+};
+
+/// 一个请求的统计信息，由 `stats::count_requests` 中间件在响应后生成。
+pub struct RequestSample {
+    /// "方法 路由模板"，如 "GET /users/me"
+    pub route: String,
+    /// 已登录用户的ID，匿名请求为空
+    pub user_id: Option<String>,
+    /// 来源IP，只用于匿名使用统计中区分匿名调用方
+    pub client_ip: String,
+    pub status: u16,
+}
+
+/// 某一分钟、某一类统计的 Redis 键
+fn bucket_key(kind: &str, minute: i64) -> String {
+    format!("{}{}:{}", REDIS_PREFIX_STATS, kind, minute)
+}
+
+fn current_minute() -> i64 {
+    Utc::now().timestamp() / 60
+}
+
+/// 记录一个请求。计数按分钟分桶写入 Redis，所有实例共享同一份统计：
+///
+/// - `stats:requests:{minute}` / `stats:errors:{minute}`：按路由计数的哈希，错误只统计 5xx
+/// - `stats:users:{minute}`：按用户ID计数的哈希
+/// - `stats:active:{minute}`：已登录用户的 HyperLogLog，用于估算活跃用户数
+///
+/// 同一管道中还会累加匿名使用统计的当天原始计数（见 `analytics::append`）。
+///
+/// 按用户的计数只在用户同意使用行为分析（`ConsentPurpose::Analytics`）时写入，否则按匿名请求统计。
+///
+/// 写入在后台任务中通过一次管道完成，不增加请求延迟；写入失败只影响统计，不影响请求。
+pub fn record(state: &AppState, sample: RequestSample) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        let consented = match sample.user_id.as_deref().and_then(|user_id| Uuid::parse_str(user_id).ok()) {
+            Some(user_id) => ConsentService::has_consent(&state, user_id, ConsentPurpose::Analytics).await,
+            None => false,
+        };
+
+        let mut redis = state.redis.clone();
+        let minute = current_minute();
+        // 多保留一分钟，避免查询最早的分桶时刚好过期
+        let expire = ((STATS_RETENTION_MINUTES + 1) * 60) as i64;
+
+        let mut pipe = redis::pipe();
+        let requests_key = bucket_key("requests", minute);
+        pipe.hincr(&requests_key, &sample.route, 1).ignore();
+        pipe.expire(&requests_key, expire).ignore();
+        if sample.status >= 500 {
+            let errors_key = bucket_key("errors", minute);
+            pipe.hincr(&errors_key, &sample.route, 1).ignore();
+            pipe.expire(&errors_key, expire).ignore();
+        }
+        if let Some(user_id) = sample.user_id.as_ref().filter(|_| consented) {
+            let users_key = bucket_key("users", minute);
+            let active_key = bucket_key("active", minute);
+            pipe.hincr(&users_key, user_id, 1).ignore();
+            pipe.expire(&users_key, expire).ignore();
+            pipe.pfadd(&active_key, user_id).ignore();
+            pipe.expire(&active_key, expire).ignore();
+        }
+        AnalyticsService::append(&mut pipe, &sample, consented);
+
+        if let Err(e) = pipe.query_async::<()>(&mut redis).await {
+            tracing::debug!(target: target::CACHE, "⚠️ Failed to record request stats: {}", e);
+        }
+    });
+}
+
+/// 汇总最近若干分钟的请求统计：总请求数、5xx 错误率、活跃用户数、各路由的请求数和请求最多的用户。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含Redis客户端。
+/// - `minutes`: 统计最近多少分钟（包含当前这一分钟）。
+///
+/// # 返回值
+/// - `Ok(StatsOverview)`: 统计汇总。
+/// - `Err(AppError)`: Redis 读取失败。
+pub async fn overview(state: &AppState, minutes: u64) -> Result<StatsOverview, AppError> {
+    let now = current_minute();
+    let buckets: Vec<i64> = (0..minutes as i64).map(|offset| now - offset).collect();
+
+    // 第一步：一次管道读取所有分桶的计数，再读取活跃用户数
+    let mut pipe = redis::pipe();
+    for kind in ["requests", "errors", "users"] {
+        for minute in &buckets {
+            pipe.hgetall(bucket_key(kind, *minute));
+        }
+    }
+    let mut redis = state.redis.clone();
+    let counts: Vec<HashMap<String, u64>> = pipe.query_async(&mut redis).await?;
+
+    // PFCOUNT 传入多个键时返回并集的基数，同一用户在多个分钟内活跃只计一次
+    let active_users: u64 = redis::cmd("PFCOUNT")
+        .arg(buckets.iter().map(|minute| bucket_key("active", *minute)).collect::<Vec<_>>())
+        .query_async(&mut redis)
+        .await?;
+
+    // 第二步：按路由和用户累加
+    let mut chunks = counts.chunks(buckets.len());
+    let sum = |chunk: Option<&[HashMap<String, u64>]>| {
+        let mut total: HashMap<String, u64> = HashMap::new();
+        for bucket in chunk.unwrap_or_default() {
+            for (key, count) in bucket {
+                *total.entry(key.clone()).or_default() += count;
+            }
+        }
+        total
+    };
+    let requests = sum(chunks.next());
+    let errors = sum(chunks.next());
+    let users = sum(chunks.next());
+
+    let mut routes: Vec<RouteStats> = requests
+        .into_iter()
+        .map(|(route, requests)| {
+            let errors = errors.get(&route).copied().unwrap_or(0);
+            RouteStats { route, requests, errors, error_rate: ratio(errors, requests) }
+        })
+        .collect();
+    routes.sort_by(|a, b| b.requests.cmp(&a.requests).then_with(|| a.route.cmp(&b.route)));
+
+    let mut top_users: Vec<UserStats> = users
+        .into_iter()
+        .map(|(user_id, requests)| UserStats { user_id, requests })
+        .collect();
+    top_users.sort_by(|a, b| b.requests.cmp(&a.requests).then_with(|| a.user_id.cmp(&b.user_id)));
+    top_users.truncate(STATS_TOP_USERS);
+
+    let total_requests = routes.iter().map(|route| route.requests).sum();
+    let total_errors = routes.iter().map(|route| route.errors).sum();
+
+    Ok(StatsOverview {
+        minutes,
+        requests: total_requests,
+        errors: total_errors,
+        error_rate: ratio(total_errors, total_requests),
+        active_users,
+        routes,
+        top_users,
+    })
+}
+
+fn ratio(part: u64, total: u64) -> f64 {
+    if total == 0 { 0.0 } else { part as f64 / total as f64 }
+}
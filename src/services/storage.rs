@@ -0,0 +1,72 @@
+// src/services/storage.rs
+use async_trait::async_trait;
+use std::path::{Component, Path, PathBuf};
+
+use crate::core::{config::Config, error::AppError};
+
+/// 文件存储后端。上传的文件（如用户头像）通过该 trait 读写，业务代码不关心文件实际存放在哪里。
+///
+/// 默认使用本地磁盘（`LocalStorage`）；需要对象存储（S3、OSS 等）的部署可以实现该 trait，
+/// 再通过 `AppState::with_storage` 注册。
+#[async_trait]
+pub trait ObjectStorage: Send + Sync {
+    /// 保存文件，返回可公开访问的URL。`key` 为相对路径，如 "avatars/{user_id}/{file}.png"。
+    async fn put(&self, key: &str, bytes: &[u8], content_type: &str) -> Result<String, AppError>;
+
+    /// 根据 `put` 返回的URL删除文件。文件不存在时视为成功。
+    async fn delete(&self, url: &str) -> Result<(), AppError>;
+}
+
+/// 本地磁盘存储：文件写入 `STORAGE_LOCAL_DIR` 目录，由 `/uploads` 静态路由对外提供访问。
+pub struct LocalStorage {
+    root: PathBuf,
+    public_url: String,
+}
+
+impl LocalStorage {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            root: PathBuf::from(&config.storage_local_dir),
+            public_url: config.storage_public_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// 把相对路径映射到存储目录下的绝对路径，拒绝包含 ".." 或绝对路径的键，防止目录穿越。
+    fn resolve(&self, key: &str) -> Result<PathBuf, AppError> {
+        let relative = Path::new(key);
+        if !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(AppError::BadRequest(format!("Invalid storage key: {}", key)));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for LocalStorage {
+    async fn put(&self, key: &str, bytes: &[u8], _content_type: &str) -> Result<String, AppError> {
+        let path = self.resolve(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| AppError::InternalServerError(format!("Create storage dir failed: {}", e)))?;
+        }
+        tokio::fs::write(&path, bytes)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Write file failed: {}", e)))?;
+
+        Ok(format!("{}/{}", self.public_url, key))
+    }
+
+    async fn delete(&self, url: &str) -> Result<(), AppError> {
+        // 只处理本后端签发的URL，其他来源的地址直接忽略
+        let Some(key) = url.strip_prefix(&format!("{}/", self.public_url)) else {
+            return Ok(());
+        };
+
+        match tokio::fs::remove_file(self.resolve(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::InternalServerError(format!("Delete file failed: {}", e))),
+        }
+    }
+}
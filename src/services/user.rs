@@ -1,17 +1,34 @@
 // src/services/user.rs
-use sea_orm::*;
+use redis::AsyncCommands;
+use sea_orm::{sea_query::Expr, *};
 use uuid::Uuid;
+use crate::core::log::target;
 use crate::{
     core::{
         error::AppError, 
-        constants::{REDIS_PREFIX_USER_PROFILE, CACHE_EXPIRE_USER_PROFILE}
+        constants::{
+            REDIS_PREFIX_USER_PROFILE, REDIS_PREFIX_USERNAME_COOLDOWN, CACHE_EXPIRE_USER_PROFILE,
+            USER_SETTINGS_MAX_BYTES, ACCOUNT_FROZEN_REASON,
+        }
+    },
+    dtos::{
+        pagination::{CursorPage, CursorQuery, PageQuery, Paginated},
+        user::{
+            LoginHistoryItem, UserListFilter, UserProfile, UserSearchQuery, UserSettings, UserSort, UpdateUserRequest,
+        },
     },
-    dtos::user::{UserProfile, UpdateUserRequest},
-    entity::users,
+    entity::{login_history, username_history, users},
+    services::{auth as AuthService, permission as PermissionService},
     state::AppState,
-    utils::cache, // 引入缓存模块，用于后续的缓存操作（如获取或设置用户资料缓存）
+    utils::{cache, pagination, retry}, // 引入缓存模块，用于后续的缓存操作（如获取或设置用户资料缓存）
 };
 
+/// 用户资料的缓存键，包含 `UserProfile` 的结构版本
+#[inline]
+fn profile_key(user_id: &str) -> String {
+    cache::versioned_key::<UserProfile>(&format!("{}{}", REDIS_PREFIX_USER_PROFILE, user_id))
+}
+
 /// 获取用户资料信息。这个函数实现了缓存优先的逻辑：首先尝试从Redis缓存中读取用户资料，
 /// 如果缓存命中则直接返回缓存数据；如果缓存未命中，则从数据库中查询用户信息，
 /// 并将查询结果存入Redis缓存，以便后续快速访问。
@@ -24,26 +41,28 @@ use crate::{
 /// - `Ok(UserProfile)`: 成功时返回用户资料数据。
 /// - `Err(AppError)`: 失败时返回相应的错误类型，如用户不存在、数据库查询失败等。
 pub async fn get_user_profile(state: &AppState, user_id: &str) -> Result<UserProfile, AppError> {
-    // 根据Redis键前缀和用户ID拼接出完整的Redis缓存键。这是缓存策略的一部分，确保每个用户有独立的缓存键。
+    // 根据Redis键前缀、用户ID和资料结构版本拼接出完整的Redis缓存键。这是缓存策略的一部分，确保每个用户有独立的缓存键，
+    // 并且 UserProfile 结构变更后不会读到旧结构的缓存。
     // user_id 参数是从Handler传递过来的，来源于JWT claims中的sub字段（即用户标识）
-    let key = format!("{}{}", REDIS_PREFIX_USER_PROFILE, user_id);
+    let key = profile_key(user_id);
     
     // 为了在闭包中使用，需要克隆一下变量。因为闭包可能在不同的线程中执行，需要获取变量的所有权。
     let db = state.db.clone();
     let uid_str = user_id.to_string();
 
     // 调用通用缓存逻辑：首先尝试从Redis缓存中获取数据，如果缓存未命中，则执行闭包中的数据库查询逻辑。
-    cache::get_or_fetch(
+    // 用户资料读取频繁且查询代价低，Redis 响应缓慢时启用对冲读取（由配置控制）。
+    cache::get_or_fetch_hedged(
         &state.redis, 
         &key, 
         CACHE_EXPIRE_USER_PROFILE, 
+        state.config.cache_hedge_after,
         || async move {
             // 只有缓存未命中时才会执行这里的代码。这部分代码负责从数据库中查询用户信息。
             let uid = Uuid::parse_str(&uid_str)
                 .map_err(|_| AppError::AuthError("Invalid User ID format".to_string()))?;
             
-            let user = users::Entity::find_by_id(uid)
-                .one(&db)
+            let user = retry::with_retry("find_user_profile", || users::Entity::find_by_id(uid).one(&db))
                 .await?
                 .ok_or(AppError::NotFound("User not found".to_string()))?;
 
@@ -87,8 +106,585 @@ pub async fn update_user_profile(
     let profile: UserProfile = updated_user.into();
 
     // 第二步：同步更新Redis缓存（Write Through策略）。确保缓存与数据库的数据一致性，避免脏读。
-    let key = format!("{}{}", REDIS_PREFIX_USER_PROFILE, user_id);
+    let key = profile_key(user_id);
+    cache::set(&state.redis, &key, &profile, CACHE_EXPIRE_USER_PROFILE).await;
+
+    Ok(profile)
+}
+
+/// 按 JSON Merge Patch（RFC 7396）规则合并：对象逐键递归合并，值为 null 的键被删除，其他值直接覆盖。
+fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = serde_json::Value::Object(serde_json::Map::new());
+    }
+    if let serde_json::Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge_patch(target.entry(key.clone()).or_insert(serde_json::Value::Null), value);
+            }
+        }
+    }
+}
+
+/// 获取用户设置
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接。
+/// - `user_id`: 用户ID字符串。
+///
+/// # 返回值
+/// - `Ok(UserSettings)`: 用户设置，未设置过时为空对象。
+/// - `Err(AppError)`: 用户不存在或数据库查询失败。
+pub async fn get_settings(state: &AppState, user_id: &str) -> Result<UserSettings, AppError> {
+    let uid = Uuid::parse_str(user_id)
+        .map_err(|_| AppError::AuthError("Invalid User ID format".to_string()))?;
+
+    let user = users::Entity::find_by_id(uid)
+        .one(&state.db)
+        .await?
+        .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+    // 列中的历史数据不符合当前结构时返回空设置，而不是让整个接口失败
+    Ok(serde_json::from_value(user.settings).unwrap_or_default())
+}
+
+/// 部分更新用户设置（JSON Merge Patch 语义）：只修改请求中出现的键，值为 null 的键被删除。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接。
+/// - `user_id`: 用户ID字符串。
+/// - `patch`: 需要合并的设置（必须是 JSON 对象）。
+///
+/// # 返回值
+/// - `Ok(UserSettings)`: 合并后的完整设置。
+/// - `Err(AppError)`: 请求不是对象、合并结果校验失败、超过大小限制或数据库错误。
+pub async fn update_settings(
+    state: &AppState,
+    user_id: &str,
+    patch: serde_json::Value,
+) -> Result<UserSettings, AppError> {
+    if !patch.is_object() {
+        return Err(AppError::BadRequest("Settings patch must be a JSON object".to_string()));
+    }
+
+    let uid = Uuid::parse_str(user_id)
+        .map_err(|_| AppError::AuthError("Invalid User ID format".to_string()))?;
+
+    // 在事务中锁定用户行后再读取、合并、写回，并发的 PATCH 依次执行，不会互相覆盖对方的修改
+    state
+        .db
+        .transaction::<_, UserSettings, AppError>(|txn| {
+            Box::pin(async move {
+                let user = users::Entity::find_by_id(uid)
+                    .lock_exclusive()
+                    .one(txn)
+                    .await?
+                    .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+                // 第一步：合并，并按 UserSettings 校验内置设置项的类型和取值
+                let mut merged = user.settings.clone();
+                merge_patch(&mut merged, &patch);
+                let settings: UserSettings = serde_json::from_value(merged)
+                    .map_err(|e| AppError::BadRequest(format!("Invalid settings: {}", e)))?;
+                validator::Validate::validate(&settings)?;
+
+                let value = serde_json::to_value(&settings)
+                    .map_err(|e| AppError::InternalServerError(format!("Serialize settings failed: {}", e)))?;
+                if value.to_string().len() > USER_SETTINGS_MAX_BYTES {
+                    return Err(AppError::BadRequest(format!(
+                        "Settings must not exceed {} bytes",
+                        USER_SETTINGS_MAX_BYTES
+                    )));
+                }
+
+                // 第二步：写回数据库
+                let mut user_active: users::ActiveModel = user.into();
+                user_active.settings = Set(value);
+                user_active.update(txn).await?;
+
+                Ok(settings)
+            })
+        })
+        .await
+        .map_err(|e| match e {
+            TransactionError::Connection(e) => AppError::DatabaseError(e),
+            TransactionError::Transaction(e) => e,
+        })
+}
+
+/// 修改用户名。新用户名必须未被占用，两次修改之间有冷却时间（`USERNAME_CHANGE_COOLDOWN`），
+/// 旧用户名写入变更历史表。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接、Redis客户端和配置信息。
+/// - `user_id`: 用户ID字符串。
+/// - `new_username`: 新的用户名（已通过格式校验）。
+///
+/// # 返回值
+/// - `Ok(UserProfile)`: 成功时返回更新后的用户资料数据。
+/// - `Err(AppError)`: 用户名未变化、已被占用、冷却期内或数据库错误。
+pub async fn change_username(state: &AppState, user_id: &str, new_username: String) -> Result<UserProfile, AppError> {
+    let uid = Uuid::parse_str(user_id)
+        .map_err(|_| AppError::AuthError("Invalid User ID format".to_string()))?;
+
+    let user = users::Entity::find_by_id(uid)
+        .one(&state.db)
+        .await?
+        .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+    // 第一步：唯一性检查。数据库的唯一索引是最终保障，这里提前检查以返回友好的错误信息。
+    if user.username == new_username {
+        return Err(AppError::BadRequest("New username must be different from the current one".to_string()));
+    }
+    let taken = users::Entity::find()
+        .filter(users::Column::Username.eq(&new_username))
+        .count(&state.db)
+        .await?;
+    if taken > 0 {
+        return Err(AppError::Conflict("Username already exists".to_string()));
+    }
+
+    // 第二步：冷却检查。使用 SET NX EX 原子地占用冷却期，并发的修改请求只有一个能通过。
+    let cooldown_key = format!("{}{}", REDIS_PREFIX_USERNAME_COOLDOWN, user_id);
+    let mut redis = state.redis.clone();
+    let acquired: Option<String> = redis::cmd("SET")
+        .arg(&cooldown_key)
+        .arg(chrono::Utc::now().timestamp())
+        .arg("NX")
+        .arg("EX")
+        .arg(state.config.username_change_cooldown.as_secs())
+        .query_async(&mut redis)
+        .await?;
+    if acquired.is_none() {
+        let remaining: i64 = redis.ttl(&cooldown_key).await?;
+        return Err(AppError::RateLimitExceeded(format!(
+            "Username can be changed again in {} seconds",
+            remaining.max(0)
+        )));
+    }
+
+    // 第三步：在事务中更新用户名并记录历史，遇到序列化冲突等瞬时错误时整个事务重试。
+    // COMMIT 时连接中断的事务可能已经提交，重试时先锁定并重新读取用户：用户名已经是新值时说明上一次尝试已生效，
+    // 直接返回，不再重复记录历史。失败时释放冷却期，允许用户立即重试。
+    let result = retry::with_retry("change_username", || {
+        state.db.transaction::<_, users::Model, DbErr>(|txn| {
+            let new_username = new_username.clone();
+            Box::pin(async move {
+                let current = users::Entity::find_by_id(uid)
+                    .lock_exclusive()
+                    .one(txn)
+                    .await?
+                    .ok_or_else(|| DbErr::RecordNotFound("User not found".to_string()))?;
+                if current.username == new_username {
+                    return Ok(current);
+                }
+
+                let old_username = current.username.clone();
+                let mut user_active: users::ActiveModel = current.into();
+                user_active.username = Set(new_username.clone());
+                let updated = user_active.update(txn).await?;
+
+                username_history::ActiveModel {
+                    user_id: Set(uid),
+                    old_username: Set(old_username),
+                    new_username: Set(new_username),
+                    ..Default::default()
+                }
+                .insert(txn)
+                .await?;
+
+                Ok(updated)
+            })
+        })
+    })
+    .await;
+
+    let updated_user = match result {
+        Ok(user) => user,
+        Err(e) => {
+            let _: () = redis.del(&cooldown_key).await.unwrap_or_default();
+            return Err(match e {
+                TransactionError::Transaction(e) if e.to_string().contains("duplicate key") => {
+                    AppError::Conflict("Username already exists".to_string())
+                }
+                TransactionError::Connection(e) | TransactionError::Transaction(e) => AppError::DatabaseError(e),
+            });
+        }
+    };
+
+    // 第四步：同步更新Redis缓存（Write Through策略）
+    let profile: UserProfile = updated_user.into();
+    let key = profile_key(user_id);
+    cache::set(&state.redis, &key, &profile, CACHE_EXPIRE_USER_PROFILE).await;
+
+    tracing::info!(target: target::USER, "✏️ User {} renamed to {}", user_id, profile.username);
+    Ok(profile)
+}
+
+/// 支持的头像格式：(MIME类型, 文件扩展名, 文件头魔数)
+const AVATAR_FORMATS: &[(&str, &str, &[u8])] = &[
+    ("image/png", "png", b"\x89PNG\r\n\x1a\n"),
+    ("image/jpeg", "jpg", b"\xff\xd8\xff"),
+    ("image/gif", "gif", b"GIF8"),
+];
+
+/// 根据文件头识别图片格式，返回 (MIME类型, 扩展名)。不信任客户端声明的 Content-Type。
+fn detect_image_type(bytes: &[u8]) -> Option<(&'static str, &'static str)> {
+    // WebP: "RIFF" + 4字节长度 + "WEBP"
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return Some(("image/webp", "webp"));
+    }
+    AVATAR_FORMATS
+        .iter()
+        .find(|(_, _, magic)| bytes.starts_with(magic))
+        .map(|(mime, ext, _)| (*mime, *ext))
+}
+
+/// 上传并更新用户头像。校验文件大小和图片格式后写入存储后端，更新 `avatar_url` 字段，
+/// 并删除旧的头像文件。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接、存储后端和配置信息。
+/// - `user_id`: 用户ID字符串。
+/// - `bytes`: 头像文件内容。
+/// - `declared_type`: 客户端声明的 Content-Type（可选），必须与实际文件格式一致。
+///
+/// # 返回值
+/// - `Ok(UserProfile)`: 成功时返回更新后的用户资料数据。
+/// - `Err(AppError)`: 文件过大、格式不支持、用户不存在或存储失败。
+pub async fn update_avatar(
+    state: &AppState,
+    user_id: &str,
+    bytes: &[u8],
+    declared_type: Option<&str>,
+) -> Result<UserProfile, AppError> {
+    // 第一步：校验文件大小和格式
+    if bytes.is_empty() {
+        return Err(AppError::BadRequest("Avatar file is empty".to_string()));
+    }
+    if bytes.len() > state.config.avatar_max_bytes {
+        return Err(AppError::BadRequest(format!(
+            "Avatar must not exceed {} bytes",
+            state.config.avatar_max_bytes
+        )));
+    }
+    let (mime, ext) = detect_image_type(bytes).ok_or(AppError::BadRequest(
+        "Unsupported avatar format, expected PNG, JPEG, GIF or WebP".to_string(),
+    ))?;
+    if declared_type.is_some_and(|declared| declared != mime) {
+        return Err(AppError::BadRequest("Avatar content does not match its declared type".to_string()));
+    }
+
+    let uid = Uuid::parse_str(user_id)
+        .map_err(|_| AppError::AuthError("Invalid User ID format".to_string()))?;
+    let user = users::Entity::find_by_id(uid)
+        .one(&state.db)
+        .await?
+        .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+    // 第二步：写入存储后端。每次上传使用新文件名，避免浏览器和CDN缓存旧头像。
+    let key = format!("avatars/{}/{}.{}", user_id, Uuid::new_v4().simple(), ext);
+    let url = state.storage.put(&key, bytes, mime).await?;
+
+    // 第三步：更新数据库，失败时清理刚写入的文件
+    let old_url = user.avatar_url.clone();
+    let mut user_active: users::ActiveModel = user.into();
+    user_active.avatar_url = Set(Some(url.clone()));
+    let updated_user = match user_active.update(&state.db).await {
+        Ok(user) => user,
+        Err(e) => {
+            let _ = state.storage.delete(&url).await;
+            return Err(e.into());
+        }
+    };
+
+    // 第四步：删除旧头像（Soft Fail：失败只记录日志，不影响本次上传结果）
+    if let Some(old_url) = old_url
+        && let Err(e) = state.storage.delete(&old_url).await
+    {
+        tracing::warn!(target: target::USER, "⚠️ Failed to delete old avatar {}: {}", old_url, e);
+    }
+
+    // 第五步：同步更新Redis缓存（Write Through策略）
+    let profile: UserProfile = updated_user.into();
+    let key = profile_key(user_id);
     cache::set(&state.redis, &key, &profile, CACHE_EXPIRE_USER_PROFILE).await;
 
+    tracing::info!(target: target::USER, "🖼️ Avatar updated for user {}", user_id);
     Ok(profile)
-}
\ No newline at end of file
+}
+
+/// 用户自助冻结账户，用于怀疑凭证被盗的场景。
+///
+/// 冻结立即生效：账户被禁用，已签发的访问令牌全部吊销，刷新令牌因账户被禁用无法再换取新令牌。
+/// 冻结不能由用户自己解除（持有被盗凭证的攻击者同样可以调用），需要通过账户恢复流程
+/// （目前由管理员核实身份后解封）恢复访问。
+///
+/// # 参数
+/// - `state`: 应用程序状态。
+/// - `user_id`: 当前用户ID。
+///
+/// # 返回值
+/// - `Ok(())`: 冻结成功（账户已处于冻结状态时同样返回成功）。
+/// - `Err(AppError)`: 用户不存在、数据库或 Redis 操作失败。
+pub async fn freeze_account(state: &AppState, user_id: &str) -> Result<(), AppError> {
+    let uid = Uuid::parse_str(user_id)
+        .map_err(|_| AppError::AuthError("Invalid User ID format".to_string()))?;
+
+    let user = users::Entity::find_by_id(uid)
+        .one(&state.db)
+        .await?
+        .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+    // 第一步：禁用账户。冻结是永久的（没有自动解封时间），原因字段用于与管理员封禁区分
+    if user.is_active || user.ban_reason.as_deref() != Some(ACCOUNT_FROZEN_REASON) {
+        let mut active: users::ActiveModel = user.into();
+        active.is_active = Set(false);
+        active.ban_reason = Set(Some(ACCOUNT_FROZEN_REASON.to_string()));
+        active.banned_until = Set(None);
+        active.update(&state.db).await?;
+    }
+
+    // 第二步：吊销全部令牌并清除缓存，使冻结在下一次请求时生效
+    AuthService::revoke_user_tokens(state, user_id).await?;
+    AuthService::bump_token_version(state, user_id).await?;
+    purge_profile_cache(state, user_id).await;
+    PermissionService::invalidate_user_permissions(state, user_id).await;
+
+    tracing::warn!(target: target::USER, "🧊 Account {} frozen by its owner", user_id);
+    Ok(())
+}
+
+/// 清除用户资料缓存。用于管理员修改用户状态等不返回新资料的场景，下次读取时从数据库重新加载。
+pub async fn purge_profile_cache(state: &AppState, user_id: &str) {
+    let key = profile_key(user_id);
+    cache::del(&state.redis, &key).await;
+}
+
+/// 管理端分页查询用户列表，支持按角色、账户状态过滤，按用户名/手机号模糊搜索，以及排序。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接。
+/// - `page`: 分页参数。
+/// - `filter`: 过滤、搜索和排序条件。
+///
+/// # 返回值
+/// - `Ok(Paginated<UserProfile>)`: 当前页的用户资料及分页信息。
+/// - `Err(AppError)`: 数据库查询失败。
+pub async fn list_users(
+    state: &AppState,
+    page: PageQuery,
+    filter: UserListFilter,
+) -> Result<Paginated<UserProfile>, AppError> {
+    let query = users::Entity::find().filter(user_list_condition(&filter));
+    let query = match filter.sort {
+        UserSort::CreatedAtAsc => query.order_by_asc(users::Column::CreatedAt),
+        UserSort::CreatedAtDesc => query.order_by_desc(users::Column::CreatedAt),
+        UserSort::UsernameAsc => query.order_by_asc(users::Column::Username),
+        UserSort::UsernameDesc => query.order_by_desc(users::Column::Username),
+    };
+
+    let paginator = query.paginate(&state.db, page.per_page);
+    let counts = retry::with_retry("count_users", || paginator.num_items_and_pages()).await?;
+    let items = retry::with_retry("list_users", || paginator.fetch_page(page.page_index()))
+        .await?
+        .into_iter()
+        .map(UserProfile::from)
+        .collect();
+
+    Ok(Paginated::new(items, &page, counts.number_of_items, counts.number_of_pages))
+}
+
+/// 管理端按游标分页查询用户列表，过滤和排序条件与 `list_users` 相同。
+/// 排序键为排序字段加用户ID，游标只能在相同的排序方式下使用。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接。
+/// - `query`: 游标和每页条数。
+/// - `filter`: 过滤、搜索和排序条件。
+///
+/// # 返回值
+/// - `Ok(CursorPage<UserProfile>)`: 当前页的用户资料及下一页的游标。
+/// - `Err(AppError)`: 游标无效或数据库查询失败。
+pub async fn list_users_by_cursor(
+    state: &AppState,
+    query: CursorQuery,
+    filter: UserListFilter,
+) -> Result<CursorPage<UserProfile>, AppError> {
+    let select = users::Entity::find().filter(user_list_condition(&filter));
+    let by_created_at = (users::Column::CreatedAt, users::Column::Id);
+    let by_username = (users::Column::Username, users::Column::Id);
+    let page = match filter.sort {
+        UserSort::CreatedAtAsc => {
+            pagination::fetch_cursor_page(&state.db, select, by_created_at, Order::Asc, "users:created_at", &query, |user| {
+                (user.created_at, user.id)
+            })
+            .await?
+        }
+        UserSort::CreatedAtDesc => {
+            pagination::fetch_cursor_page(&state.db, select, by_created_at, Order::Desc, "users:-created_at", &query, |user| {
+                (user.created_at, user.id)
+            })
+            .await?
+        }
+        UserSort::UsernameAsc => {
+            pagination::fetch_cursor_page(&state.db, select, by_username, Order::Asc, "users:username", &query, |user| {
+                (user.username.clone(), user.id)
+            })
+            .await?
+        }
+        UserSort::UsernameDesc => {
+            pagination::fetch_cursor_page(&state.db, select, by_username, Order::Desc, "users:-username", &query, |user| {
+                (user.username.clone(), user.id)
+            })
+            .await?
+        }
+    };
+
+    Ok(page.map(UserProfile::from))
+}
+
+/// 用户列表的过滤条件：按角色、账户状态过滤，按用户名/手机号模糊搜索
+fn user_list_condition(filter: &UserListFilter) -> Condition {
+    let mut condition = Condition::all();
+    if let Some(role) = filter.role.clone() {
+        condition = condition.add(users::Column::Role.eq(role));
+    }
+    if let Some(is_active) = filter.is_active {
+        condition = condition.add(users::Column::IsActive.eq(is_active));
+    }
+    if let Some(q) = filter.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+        // 模糊搜索：用户名或手机号包含关键字即可匹配
+        let pattern = contains_pattern(q);
+        condition = condition.add(
+            Condition::any()
+                .add(users::Column::Username.like(pattern.clone()))
+                .add(users::Column::Phone.like(pattern)),
+        );
+    }
+    condition
+}
+
+/// 构造"包含关键字"的 LIKE 模式。转义 LIKE 通配符，避免用户输入的 % 和 _ 被当作模式字符
+/// （Postgres 的 LIKE 默认以反斜杠作为转义字符）。
+fn contains_pattern(keyword: &str) -> String {
+    let escaped = keyword
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// 分页查询用户本人的登录历史，按时间倒序返回。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接。
+/// - `user_id`: 用户ID（来自 `claims.sub`）。
+/// - `page`: 分页参数。
+///
+/// # 返回值
+/// - `Ok(Paginated<LoginHistoryItem>)`: 当前页的登录记录及分页信息。
+/// - `Err(AppError)`: 用户ID格式错误或数据库查询失败。
+pub async fn list_logins(state: &AppState, user_id: &str, page: PageQuery) -> Result<Paginated<LoginHistoryItem>, AppError> {
+    let uid = Uuid::parse_str(user_id)
+        .map_err(|_| AppError::AuthError("Invalid User ID format".to_string()))?;
+
+    let paginator = login_history::Entity::find()
+        .filter(login_history::Column::UserId.eq(uid))
+        .order_by_desc(login_history::Column::CreatedAt)
+        .paginate(&state.db, page.per_page);
+
+    let counts = paginator.num_items_and_pages().await?;
+    let items = paginator
+        .fetch_page(page.page_index())
+        .await?
+        .into_iter()
+        .map(LoginHistoryItem::from)
+        .collect();
+
+    Ok(Paginated::new(items, &page, counts.number_of_items, counts.number_of_pages))
+}
+
+/// 按用户名或手机号的部分内容搜索用户。使用 `ILIKE` 匹配并按 pg_trgm 相似度排序，
+/// 配合三元组 GIN 索引，即使用户表很大也能快速返回结果。
+///
+/// # 参数
+/// - `state`: 应用程序状态，包含数据库连接。
+/// - `query`: 搜索关键字和返回条数。
+///
+/// # 返回值
+/// - `Ok(Vec<UserProfile>)`: 按相似度从高到低排序的用户资料。
+/// - `Err(AppError)`: 数据库查询失败。
+pub async fn search_users(state: &AppState, query: UserSearchQuery) -> Result<Vec<UserProfile>, AppError> {
+    let keyword = query.q.trim();
+
+    let pattern = contains_pattern(keyword);
+
+    let select = users::Entity::find()
+        .filter(Expr::cust_with_values(
+            "(username ILIKE $1 OR phone ILIKE $1)",
+            [pattern],
+        ))
+        .order_by(
+            Expr::cust_with_values(
+                "GREATEST(similarity(username, $1), similarity(COALESCE(phone, ''), $1))",
+                [keyword.to_string()],
+            ),
+            Order::Desc,
+        )
+        .limit(query.limit);
+    let users = retry::with_retry("search_users", || select.clone().all(&state.db)).await?;
+
+    Ok(users.into_iter().map(UserProfile::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn merged(target: serde_json::Value, patch: serde_json::Value) -> serde_json::Value {
+        let mut target = target;
+        merge_patch(&mut target, &patch);
+        target
+    }
+
+    #[test]
+    fn null_removes_key() {
+        assert_eq!(merged(json!({"a": 1, "b": 2}), json!({"a": null})), json!({"b": 2}));
+        // 删除不存在的键不影响其他键
+        assert_eq!(merged(json!({"a": 1}), json!({"c": null})), json!({"a": 1}));
+    }
+
+    #[test]
+    fn nested_objects_merge_recursively() {
+        assert_eq!(
+            merged(
+                json!({"theme": "dark", "notify": {"email": true, "sms": false}}),
+                json!({"notify": {"sms": true, "email": null, "push": true}}),
+            ),
+            json!({"theme": "dark", "notify": {"sms": true, "push": true}}),
+        );
+    }
+
+    #[test]
+    fn non_object_values_replace() {
+        assert_eq!(merged(json!({"tags": [1, 2]}), json!({"tags": [3]})), json!({"tags": [3]}));
+        assert_eq!(merged(json!({"a": {"b": 1}}), json!({"a": "x"})), json!({"a": "x"}));
+        assert_eq!(merged(json!({"a": "x"}), json!({"a": {"b": 1}})), json!({"a": {"b": 1}}));
+        // 补丁对象中的 null 不会出现在新建的嵌套对象里
+        assert_eq!(merged(json!({}), json!({"a": {"b": null}})), json!({"a": {}}));
+    }
+
+    #[test]
+    fn non_object_target_becomes_object() {
+        assert_eq!(merged(json!(null), json!({"a": 1})), json!({"a": 1}));
+        assert_eq!(merged(json!([1]), json!({"a": 1})), json!({"a": 1}));
+    }
+}
@@ -1,14 +1,23 @@
 // src/start.rs
-use std::{net::SocketAddr, time::Duration};
-use sea_orm::{Database, ConnectOptions};
+use std::{net::SocketAddr, sync::Arc};
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{
+    sqlx::{self, postgres::{PgConnection, PgPoolOptions}, Connection},
+    ConnectOptions, Database, DatabaseConnection, SqlxPostgresConnector,
+};
 use secrecy::ExposeSecret;
-use tokio::net::TcpListener;
+#[cfg(unix)]
+use std::os::fd::{AsRawFd, RawFd};
 use tokio::signal;
 
+use crate::core::log::target;
 use crate::{
-    core::{config::Config, log},
+    core::{banner, breaker, config::Config, constants::MIGRATION_LOCK_KEY, error, flags, log, maintenance, metrics, reporting, secrets, standby::Standby, upgrade},
+    extractors::client_ip,
     routes,
+    services::{access_log::AccessLogger, analytics as AnalyticsService, presence as PresenceService},
     state::AppState,
+    utils::{allowlist, cache, json_case},
 };
 
 /// 启动并运行应用程序。这是应用程序的入口点，负责初始化所有必要的组件，
@@ -24,25 +33,75 @@ use crate::{
 /// 7. 监听系统信号以实现优雅关闭
 pub async fn run() {
     // 第一步：加载应用程序配置。配置从环境变量中读取，包括数据库URL、Redis URL、JWT密钥等。
-    let config = Config::new();
+    let config = Config::new().await;
 
     // 第二步：初始化日志系统。返回的 guard 用于在作用域结束时保持日志系统的活跃状态。
-    let _guard = log::init(&config.rust_log);
-    tracing::info!("🔍 Config loaded successfully.");
+    let _guard = log::init(config.dynamic().log_filter(), config.log_format);
+    tracing::info!(target: target::SYSTEM, "🔍 Config loaded successfully.");
+
+    // panic 写入日志文件而不只是标准错误；配置了 SENTRY_DSN 时同时上报（Sentry 的钩子串联在日志钩子之前执行）
+    install_panic_hook();
+    let _reporting_guard = reporting::init(&config);
+
+    // 初始化全局 JSON 命名风格，供响应转换层和请求规范化中间件使用
+    json_case::init(config.json_case);
+
+    // 初始化可信反向代理名单，来源IP提取器据此决定是否读取转发头
+    client_ip::init(config.trusted_proxies());
+
+    // 5xx 响应是否返回内部错误详情，默认只在开发环境返回
+    error::expose_details(config.expose_error_details());
 
     // 第三步：配置并建立数据库连接池。
     // ConnectOptions 允许我们精细控制连接池的行为，如最大/最小连接数、连接超时等。
     let mut opt = ConnectOptions::new(config.database_url.expose_secret());
-    opt.max_connections(100)      // 最大连接数：连接池中最多保持100个连接
-        .min_connections(5)       // 最小连接数：连接池中至少保持5个连接
-        .connect_timeout(Duration::from_secs(10))  // 连接超时：10秒内必须建立连接
-        .sqlx_logging(false);     // 禁用SQLx的日志，避免日志过于冗长
+    opt.max_connections(config.database_max_connections)      // 最大连接数：连接池中最多保持的连接数
+        .min_connections(config.database_min_connections)       // 最小连接数：连接池中至少保持的连接数
+        .connect_timeout(config.database_connect_timeout)  // 连接超时：超时未建立连接视为失败
+        .acquire_timeout(config.database_acquire_timeout)  // 获取超时：连接池耗尽时最多等待的时间
+        .sqlx_logging(config.database_log_statements);     // 默认禁用SQLx的日志，避免日志过于冗长，慢查询由指标回调单独记录
+    if !config.database_idle_timeout.is_zero() {
+        opt.idle_timeout(config.database_idle_timeout);   // 空闲超时：多余的空闲连接在此之后关闭
+    }
+    if !config.database_max_lifetime.is_zero() {
+        opt.max_lifetime(config.database_max_lifetime);   // 最长存活：到期的连接关闭重建
+    }
+    if config.database_log_statements {
+        opt.sqlx_logging_level(::log::LevelFilter::Debug).sqlx_slow_statements_logging_settings(
+            ::log::LevelFilter::Warn,
+            config.slow_query_threshold,
+        );
+    }
+    // 语句超时：作为连接参数下发，对连接池中的每个连接生效，失控的查询不会一直占用连接和锁
+    if !config.database_statement_timeout.is_zero() {
+        let statement_timeout = config.database_statement_timeout.as_millis().to_string();
+        opt.map_sqlx_postgres_opts(move |pg| pg.options([("statement_timeout", statement_timeout.as_str())]));
+    }
 
     // 建立数据库连接。如果连接失败，程序会直接panic（在生产环境中应该使用更优雅的错误处理）。
-    let db = Database::connect(opt)
+    let mut db = Database::connect(opt)
         .await
         .expect("❌ Failed to connect to Database");
-    tracing::info!("✅ Database connected.");
+    // 语句日志已关闭，每条语句的耗时记入指标，超过阈值的慢查询输出警告
+    metrics::watch_queries(&mut db, config.slow_query_threshold);
+    tracing::info!(
+        target: target::SYSTEM,
+        max_connections = config.database_max_connections,
+        min_connections = config.database_min_connections,
+        connect_timeout_secs = config.database_connect_timeout.as_secs(),
+        acquire_timeout_secs = config.database_acquire_timeout.as_secs(),
+        idle_timeout_secs = config.database_idle_timeout.as_secs(),
+        max_lifetime_secs = config.database_max_lifetime.as_secs(),
+        statement_timeout_ms = config.database_statement_timeout.as_millis() as u64,
+        slow_query_ms = config.slow_query_threshold.as_millis() as u64,
+        log_statements = config.database_log_statements,
+        "✅ Database connected."
+    );
+
+    // 可选：在开始接收请求之前执行未完成的数据库迁移
+    if config.run_migrations {
+        run_migrations(&db).await.expect("❌ Failed to run database migrations");
+    }
 
     // 第四步：建立Redis连接。这里使用连接管理器（ConnectionManager），
     // 它提供了自动重连等高级功能，适合在异步环境中使用。
@@ -51,40 +110,219 @@ pub async fn run() {
     let redis_manager = client.get_connection_manager()
         .await
         .expect("❌ Failed to connect to Redis");
-    tracing::info!("✅ Redis connected.");
+    tracing::info!(target: target::SYSTEM, "✅ Redis connected.");
+
+    // 可选：启用 Redis 之前的进程内缓存，并订阅其他实例发出的失效通知
+    cache::init_local(config.local_cache_capacity, config.local_cache_ttl);
+    cache::spawn_invalidation_listener(client.clone());
 
     // 第五步：创建应用程序状态。这个状态对象会在所有请求处理器之间共享，
-    // 包含数据库连接池、Redis客户端和配置信息。
-    let state = AppState::new(db, redis_manager, config.clone());
+    // 包含数据库连接池、Redis客户端、配置信息和指标导出句柄。
+    let metrics_handle = metrics::init();
+    let mut state = AppState::new(db, redis_manager, config.clone(), metrics_handle);
+
+    // 可选：连接跨区域复制的 Redis 从库。连接失败不影响启动，只是无法读取其他区域的会话。
+    if let Some(url) = &config.redis_secondary_url {
+        match connect_redis_replica(url.expose_secret()).await {
+            Ok(replica) => {
+                state = state.with_redis_replica(replica);
+                tracing::info!(target: target::SYSTEM, "✅ Secondary Redis connected (region: {}).", config.region);
+            }
+            Err(e) => tracing::warn!(target: target::SYSTEM, "⚠️ Secondary Redis unavailable, cross-region sessions disabled: {}", e),
+        }
+    }
+
+    // 可选：启用访问日志，由后台任务写入文件或数据库
+    if let Some(logger) = AccessLogger::spawn(&config, state.db.clone()) {
+        state = state.with_access_log(logger);
+    }
+
+    // 可选：主备部署，与对端实例通过 Redis 锁协调主节点
+    if let Some(standby) = Standby::from_config(&config) {
+        standby.clone().spawn(state.redis.clone());
+        state = state.with_standby(standby);
+        tracing::info!(target: target::SYSTEM, "✅ Standby coordination enabled (peer: {}).", config.standby_peer_url.as_deref().unwrap_or_default());
+    }
+
+    // 启动依赖服务健康探测，驱动数据库和Redis的熔断器
+    breaker::spawn_probe(state.breakers.clone(), state.db.clone(), state.redis.clone());
+    // 采集数据库连接池的使用量和获取连接耗时，连接池接近耗尽时输出警告
+    metrics::spawn_pool_monitor(
+        state.db.clone(),
+        config.database_max_connections,
+        config.database_slow_acquire_threshold,
+        config.database_acquire_timeout,
+    );
+
+    // 同步维护模式开关，管理员在任一实例上切换后所有实例都会生效
+    maintenance::spawn_watcher(state.maintenance.clone(), state.redis.clone());
+
+    // 同步系统开关的运行时覆盖
+    flags::spawn_watcher(state.flags.clone(), state.redis.clone());
+
+    // 同步限流豁免名单：配置中的名单立即生效，Redis 中的名单定期刷新
+    allowlist::spawn_refresh(state.redis.clone(), state.config.clone());
+
+    // 配置了密钥管理时定期重新读取数据库凭据，轮换后的凭据用于之后新建的连接
+    secrets::spawn_rotation(state.config.clone(), state.db.clone());
+
+    // 收到 SIGHUP 时重新加载配置文件中可热加载的配置项（限额、豁免名单、日志级别）
+    #[cfg(unix)]
+    spawn_reload_on_sighup(state.config.clone());
+
+    // 定期把当天的原始计数聚合为匿名使用统计日报
+    AnalyticsService::spawn_aggregator(state.clone());
+
+    // 定期为心跳超时的用户发布离线事件
+    PresenceService::spawn_sweeper(state.redis.clone());
 
     // 第六步：配置服务器监听地址。从配置中读取主机和端口，解析为SocketAddr。
     let addr_str = format!("{}:{}", config.host, config.port);
     let addr: SocketAddr = addr_str.parse().expect("❌ Invalid address configuration");
 
-    tracing::info!("🚀 Server listening on http://{}", addr);
+    // 创建TCP监听器，用于接受传入的连接请求。平滑升级启动时直接使用旧进程交接的监听套接字。
+    let listener = upgrade::bind(addr).await.expect("❌ Failed to bind listener");
+    #[cfg(unix)]
+    let listener_fd = listener.as_raw_fd();
 
-    // 创建TCP监听器，用于接受传入的连接请求。
-    let listener = TcpListener::bind(addr).await.unwrap();
+    // 打印启动摘要：版本、环境、功能开关、依赖服务版本、迁移版本和监听地址
+    banner::print(&state, &addr).await;
     // 创建路由器，配置所有的HTTP端点。
     let app = routes::create_router(state);
 
     // 第七步：启动HTTP服务器，并配置优雅关闭。
     // with_graceful_shutdown 允许在接收到关闭信号时完成正在处理的请求，
     // 然后再关闭服务器，避免中断正在处理的请求。
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
+    // 使用 into_make_service_with_connect_info 注入对端地址，供 ClientIp 提取器在没有代理头时使用。
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown_signal(
+            #[cfg(unix)]
+            listener_fd,
+        ))
         .await
         .unwrap();
 }
 
+/// 安装记录日志的 panic 钩子。处理器中的 panic 只会中止当前连接，默认钩子只打印到标准错误，
+/// 这里把 panic 的位置和消息写入日志（带当前请求的 span），再交给原有的钩子处理。
+fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic payload".to_string());
+        let location = info
+            .location()
+            .map(|location| format!("{}:{}", location.file(), location.line()))
+            .unwrap_or_default();
+        ::metrics::counter!("panics_total").increment(1);
+        tracing::error!(target: target::SYSTEM, location = %location, "💥 Panic: {}", message);
+        previous(info);
+    }));
+}
+
+/// 执行未完成的数据库迁移（`RUN_MIGRATIONS=true`）。
+///
+/// 多个实例同时启动时，通过 PostgreSQL 会话级咨询锁串行执行：持有锁的实例执行迁移，
+/// 其他实例等到锁释放后发现没有待执行的迁移，直接继续启动。锁由一条独立于连接池的连接持有，
+/// 连接池很小时也不会与迁移本身争用连接；实例中途退出时连接断开，锁随之释放。
+/// 迁移本身在另一条关闭了 `statement_timeout` 的连接上执行（见 `migration_connection`）。
+async fn run_migrations(db: &DatabaseConnection) -> Result<(), String> {
+    // 第一步：建立持有锁的独立连接，等待其他实例的迁移期间不受 statement_timeout 限制
+    let options = db.get_postgres_connection_pool().connect_options();
+    let mut lock_conn = PgConnection::connect_with(&options).await.map_err(|e| e.to_string())?;
+    sqlx::query("SET statement_timeout = 0")
+        .execute(&mut lock_conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    tracing::info!(target: target::SYSTEM, "🔒 Waiting for the migration lock...");
+    sqlx::query("SELECT pg_advisory_lock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut lock_conn)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // 第二步：持有锁期间执行迁移，其他实例可能已经执行完毕
+    let result = async {
+        let db = &migration_connection(db).await?;
+        let pending = Migrator::get_pending_migrations(db).await?;
+        if pending.is_empty() {
+            tracing::info!(target: target::SYSTEM, "✅ Database schema is up to date, no migrations to run.");
+            return Ok(());
+        }
+        let names: Vec<&str> = pending.iter().map(|migration| migration.name()).collect();
+        tracing::info!(target: target::SYSTEM, "🚚 Running {} migration(s): {}", names.len(), names.join(", "));
+        Migrator::up(db, None).await?;
+        tracing::info!(target: target::SYSTEM, "✅ Migrations applied.");
+        Ok(())
+    }
+    .await
+    .map_err(|e: sea_orm::DbErr| e.to_string());
+
+    // 第三步：释放锁。关闭连接同样会释放锁，这里显式释放以便等待中的实例尽快继续
+    if let Err(e) = sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(MIGRATION_LOCK_KEY)
+        .execute(&mut lock_conn)
+        .await
+    {
+        tracing::warn!(target: target::SYSTEM, "⚠️ Failed to release the migration lock: {}", e);
+    }
+    let _ = lock_conn.close().await;
+    result
+}
+
+/// 建立执行迁移用的单连接数据库连接。应用连接池的每个连接都带有 `statement_timeout`（见 `database_statement_timeout`），
+/// 建索引、回填数据等耗时较长的迁移会被数据库取消，留下执行了一半的迁移，因此这里的连接关闭语句超时。
+async fn migration_connection(db: &DatabaseConnection) -> Result<DatabaseConnection, sea_orm::DbErr> {
+    let options = db.get_postgres_connection_pool().connect_options();
+    let pool = PgPoolOptions::new()
+        .max_connections(1)
+        .after_connect(|conn, _| {
+            Box::pin(async move { sqlx::query("SET statement_timeout = 0").execute(conn).await.map(|_| ()) })
+        })
+        .connect_with((*options).clone())
+        .await
+        .map_err(|e| sea_orm::DbErr::Conn(sea_orm::RuntimeErr::SqlxError(e)))?;
+    Ok(SqlxPostgresConnector::from_sqlx_postgres_pool(pool))
+}
+
+/// 连接跨区域复制的 Redis 从库。
+async fn connect_redis_replica(url: &str) -> redis::RedisResult<redis::aio::ConnectionManager> {
+    redis::Client::open(url)?.get_connection_manager().await
+}
+
+/// 监听 SIGHUP 信号并重新加载配置（见 `Config::reload`）。新配置有问题时保持原配置并记录全部问题。
+#[cfg(unix)]
+fn spawn_reload_on_sighup(config: Arc<Config>) {
+    tokio::spawn(async move {
+        let mut signal = signal::unix::signal(signal::unix::SignalKind::hangup())
+            .expect("failed to install reload signal handler");
+
+        while signal.recv().await.is_some() {
+            tracing::info!(target: target::SYSTEM, "🔄 SIGHUP received, reloading configuration");
+            if let Err(problems) = config.reload().await {
+                tracing::error!(
+                    target: target::SYSTEM,
+                    "❌ Configuration reload rejected, keeping current settings: {}",
+                    problems.join("; ")
+                );
+            }
+        }
+    });
+}
+
 /// 监听系统关闭信号。这个函数会阻塞当前任务，直到接收到关闭信号为止。
 /// 支持的信号包括：
 /// - Ctrl+C（SIGINT）：在终端中按下 Ctrl+C
 /// - SIGTERM：Unix系统中的终止信号（如 kill 命令）
+/// - SIGUSR2：平滑升级，新进程接管监听套接字后旧进程退出（仅Unix）
 ///
 /// # 返回值
 /// 当接收到任一关闭信号时，函数返回，触发优雅关闭流程。
-async fn shutdown_signal() {
+async fn shutdown_signal(#[cfg(unix)] listener_fd: RawFd) {
     // 监听 Ctrl+C 信号（SIGINT）。这是用户在终端中手动中断程序的常用方式。
     let ctrl_c = async {
         signal::ctrl_c()
@@ -101,16 +339,32 @@ async fn shutdown_signal() {
             .await;
     };
 
+    // 监听 SIGUSR2 信号：启动新版本进程并交接监听套接字。升级失败时继续服务，等待下一次信号。
+    #[cfg(unix)]
+    let upgrade = async {
+        let mut signal = signal::unix::signal(signal::unix::SignalKind::user_defined2())
+            .expect("failed to install upgrade signal handler");
+        loop {
+            signal.recv().await;
+            if upgrade::hand_over(listener_fd).await {
+                break;
+            }
+        }
+    };
+
     // 在非Unix系统（如Windows）上，使用一个永不完成的future作为占位符。
     #[cfg(not(unix))]
     let terminate = std::future::pending::<()>();
+    #[cfg(not(unix))]
+    let upgrade = std::future::pending::<()>();
 
     // 使用 tokio::select! 宏同时等待多个异步操作。
     // 只要其中任一操作完成，就会立即取消并清理其他操作。
     tokio::select! {
         _ = ctrl_c => {},   // Ctrl+C 被按下
         _ = terminate => {}, // SIGTERM 信号被接收
+        _ = upgrade => {},   // 新进程已接管监听套接字
     }
 
-    tracing::info!("🛑 Signal received, starting graceful shutdown...");
+    tracing::info!(target: target::SYSTEM, "🛑 Signal received, starting graceful shutdown...");
 }
\ No newline at end of file
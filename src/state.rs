@@ -1,22 +1,116 @@
 use sea_orm::DatabaseConnection;
 use redis::aio::ConnectionManager;
+use metrics_exporter_prometheus::PrometheusHandle;
 use std::sync::Arc;
-use crate::core::config::Config;
+use crate::core::{
+    breaker::DependencyBreakers, config::Config, flags::SystemFlags, lanes::PriorityLanes, maintenance::MaintenanceMode,
+    scripting::ScriptHooks, standby::Standby,
+};
+use crate::services::{
+    access_log::AccessLogger,
+    claims::{ClaimsBuilder, StaticClaimsBuilder},
+    importer::ImporterRegistry,
+    storage::{LocalStorage, ObjectStorage},
+};
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: DatabaseConnection,
     pub redis: ConnectionManager,
+    /// 跨区域复制的 Redis 从库（可选），只用于读取其他区域签发的会话
+    pub redis_replica: Option<ConnectionManager>,
     /// 全局配置，使用 Arc 包装以实现廉价克隆
     pub config: Arc<Config>,
+    /// 指标导出句柄，用于 `/metrics` 端点渲染 Prometheus 文本
+    pub metrics: PrometheusHandle,
+    /// 自定义声明构建钩子，签发访问令牌时填充扩展声明
+    pub claims_builder: Arc<dyn ClaimsBuilder>,
+    /// 文件存储后端，保存用户上传的头像等文件
+    pub storage: Arc<dyn ObjectStorage>,
+    /// 用户导入器注册表，按名称查找外部数据来源
+    pub importers: Arc<ImporterRegistry>,
+    /// 数据库和Redis的熔断器，由后台探测任务更新，熔断期间相关路由快速返回 503
+    pub breakers: Arc<DependencyBreakers>,
+    /// 请求优先级通道的并发预算
+    pub lanes: Arc<PriorityLanes>,
+    /// 维护模式开关，由后台任务从 Redis 同步，开启期间除健康检查和开关端点外的请求返回 503
+    pub maintenance: Arc<MaintenanceMode>,
+    /// 系统开关的运行时覆盖，由后台任务从 Redis 同步
+    pub flags: Arc<SystemFlags>,
+    /// 访问日志记录器，未启用时为 `None`
+    pub access_log: Option<AccessLogger>,
+    /// 路由脚本钩子，启动时按配置编译，未配置时为空
+    pub script_hooks: Arc<ScriptHooks>,
+    /// 主备协调器，未配置对端实例时为 `None`（实例总是按主节点运行）
+    pub standby: Option<Arc<Standby>>,
 }
 
 impl AppState {
-    pub fn new(db: DatabaseConnection, redis: ConnectionManager, config: Config) -> Self {
+    pub fn new(
+        db: DatabaseConnection,
+        redis: ConnectionManager,
+        config: Config,
+        metrics: PrometheusHandle,
+    ) -> Self {
+        let claims_builder = Arc::new(StaticClaimsBuilder::from_config(&config));
+        let storage = Arc::new(LocalStorage::from_config(&config));
+        let lanes = Arc::new(PriorityLanes::from_config(&config));
+        let script_hooks = Arc::new(ScriptHooks::from_config(&config));
         Self {
             db,
             redis,
+            redis_replica: None,
             config: Arc::new(config),
+            metrics,
+            claims_builder,
+            storage,
+            importers: Arc::new(ImporterRegistry::default()),
+            breakers: Arc::new(DependencyBreakers::default()),
+            lanes,
+            maintenance: Arc::new(MaintenanceMode::default()),
+            flags: Arc::new(SystemFlags::default()),
+            access_log: None,
+            script_hooks,
+            standby: None,
         }
     }
+
+    /// 设置跨区域复制的 Redis 从库连接。
+    pub fn with_redis_replica(mut self, replica: ConnectionManager) -> Self {
+        self.redis_replica = Some(replica);
+        self
+    }
+
+    /// 启用访问日志记录。
+    pub fn with_access_log(mut self, logger: AccessLogger) -> Self {
+        self.access_log = Some(logger);
+        self
+    }
+
+    /// 启用主备协调。
+    pub fn with_standby(mut self, standby: Arc<Standby>) -> Self {
+        self.standby = Some(standby);
+        self
+    }
+
+    /// 替换默认的声明构建器，用于部署时注入自定义的扩展声明（租户、套餐、功能授权等）。
+    #[allow(dead_code)]
+    pub fn with_claims_builder(mut self, builder: Arc<dyn ClaimsBuilder>) -> Self {
+        self.claims_builder = builder;
+        self
+    }
+
+    /// 替换默认的本地磁盘存储，用于部署时接入对象存储（S3、OSS 等）。
+    #[allow(dead_code)]
+    pub fn with_storage(mut self, storage: Arc<dyn ObjectStorage>) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// 替换默认的导入器注册表，用于注册自定义的用户数据来源。
+    #[allow(dead_code)]
+    pub fn with_importers(mut self, importers: ImporterRegistry) -> Self {
+        self.importers = Arc::new(importers);
+        self
+    }
 }
\ No newline at end of file
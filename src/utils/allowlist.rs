@@ -0,0 +1,131 @@
+use std::{
+    collections::HashSet,
+    net::IpAddr,
+    sync::{Arc, LazyLock, RwLock},
+    time::Duration,
+};
+
+use ipnet::IpNet;
+use redis::{AsyncCommands, aio::ConnectionManager};
+
+use crate::core::log::target;
+use crate::core::config::Config;
+use crate::core::constants::{RATE_LIMIT_ALLOWLIST_REFRESH_INTERVAL, REDIS_KEY_RATE_LIMIT_ALLOWLIST};
+
+// 限流豁免名单：内部健康检查、可信合作方等调用方不受限流器约束。
+// 名单来自 `RATE_LIMIT_ALLOWLIST` 配置和 Redis 集合 `rate_limit:allowlist`，二者合并后保存在进程内，
+// 限流器每次检查只读内存，不增加 Redis 往返。Redis 中的名单修改后最多 `RATE_LIMIT_ALLOWLIST_REFRESH_INTERVAL` 秒生效。
+//
+// IP 或 CIDR 名单项只匹配请求的来源IP（见 `ClientIp`，经过可信代理校验），不与限流对象比较：
+// 限流对象可能是请求方填写的任意字符串（如登录账号），写成IP即可冒充豁免地址。
+// 其余名单项与限流对象（`rate_limit!` 的第三个参数）按字符串精确匹配，如用户ID。
+
+/// 解析后的豁免名单
+#[derive(Debug, Default)]
+struct Allowlist {
+    exact: HashSet<String>,
+    networks: Vec<IpNet>,
+}
+
+impl Allowlist {
+    fn parse<'a>(entries: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut allowlist = Self::default();
+        for entry in entries.into_iter().map(str::trim).filter(|entry| !entry.is_empty()) {
+            if let Ok(network) = entry.parse::<IpNet>() {
+                allowlist.networks.push(network);
+            } else if let Ok(ip) = entry.parse::<IpAddr>() {
+                allowlist.networks.push(IpNet::from(ip));
+            } else {
+                allowlist.exact.insert(entry.to_string());
+            }
+        }
+        allowlist
+    }
+
+    fn contains(&self, subject: &str, client_ip: Option<IpAddr>) -> bool {
+        self.exact.contains(subject)
+            || client_ip.is_some_and(|ip| self.networks.iter().any(|network| network.contains(&ip)))
+    }
+}
+
+static ALLOWLIST: LazyLock<RwLock<Allowlist>> = LazyLock::new(|| RwLock::new(Allowlist::default()));
+
+/// 限流对象或来源IP是否在豁免名单中
+///
+/// # 参数
+/// - `subject`: 限流对象，与名单中的非IP项精确匹配
+/// - `client_ip`: 请求的来源IP，与名单中的 IP/CIDR 匹配，不在请求中时为 `None`
+pub fn is_exempt(subject: &str, client_ip: Option<IpAddr>) -> bool {
+    ALLOWLIST.read().map(|allowlist| allowlist.contains(subject, client_ip)).unwrap_or(false)
+}
+
+fn replace(allowlist: Allowlist) {
+    if let Ok(mut current) = ALLOWLIST.write() {
+        *current = allowlist;
+    }
+}
+
+/// 加载配置中的豁免名单，并启动后台任务定期合并 Redis 中的名单。
+/// 每次刷新都重新读取配置中的名单（见 `Config::rate_limit_allowlist`），配置重新加载后随下一次刷新生效。
+/// Redis 读取失败时保留上一次的名单。
+pub fn spawn_refresh(redis: ConnectionManager, config: Arc<Config>) {
+    replace(Allowlist::parse(config.rate_limit_allowlist().iter().map(String::as_str)));
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(Duration::from_secs(RATE_LIMIT_ALLOWLIST_REFRESH_INTERVAL));
+
+        loop {
+            ticker.tick().await;
+
+            let configured = config.rate_limit_allowlist();
+            let mut conn = redis.clone();
+            match conn.smembers::<_, Vec<String>>(REDIS_KEY_RATE_LIMIT_ALLOWLIST).await {
+                Ok(dynamic) => {
+                    replace(Allowlist::parse(configured.iter().chain(dynamic.iter()).map(String::as_str)));
+                }
+                Err(e) => {
+                    tracing::warn!(target: target::LIMITER, "⚠️ Failed to refresh rate limit allowlist: {}", e);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowlist() -> Allowlist {
+        Allowlist::parse(["10.0.0.0/8", " 203.0.113.7 ", "partner", ""])
+    }
+
+    #[test]
+    fn parse_splits_networks_and_exact_entries() {
+        let allowlist = allowlist();
+        assert_eq!(allowlist.networks.len(), 2);
+        assert_eq!(allowlist.exact, HashSet::from(["partner".to_string()]));
+    }
+
+    #[test]
+    fn networks_match_client_ip() {
+        let allowlist = allowlist();
+        assert!(allowlist.contains("user", Some("10.1.2.3".parse().unwrap())));
+        assert!(allowlist.contains("user", Some("203.0.113.7".parse().unwrap())));
+        assert!(!allowlist.contains("user", Some("203.0.113.8".parse().unwrap())));
+        assert!(!allowlist.contains("user", None));
+    }
+
+    #[test]
+    fn ip_shaped_subject_does_not_match_networks() {
+        let allowlist = allowlist();
+        assert!(!allowlist.contains("10.1.2.3", None));
+        assert!(!allowlist.contains("203.0.113.7", Some("198.51.100.1".parse().unwrap())));
+    }
+
+    #[test]
+    fn exact_entries_match_subject() {
+        let allowlist = allowlist();
+        assert!(allowlist.contains("partner", None));
+        assert!(!allowlist.contains("Partner", None));
+    }
+}
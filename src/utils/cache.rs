@@ -1,7 +1,140 @@
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use futures_util::StreamExt;
+use moka::sync::Cache;
 use redis::{aio::ConnectionManager, AsyncCommands};
 use serde::{de::DeserializeOwned, Serialize};
-use std::future::Future;
+use std::{
+    future::Future,
+    io::{Read, Write},
+    sync::OnceLock,
+    time::Duration,
+};
+use uuid::Uuid;
+use crate::core::log::target;
 use crate::core::error::AppError;
+use crate::core::constants::{
+    CACHE_COMPRESS_THRESHOLD_BYTES, CACHE_LOCK_EXPIRE_MS, CACHE_LOCK_POLL_MS, REDIS_CHANNEL_CACHE_INVALIDATE,
+    REDIS_PREFIX_CACHE_LOCK,
+};
+
+/// 压缩格式标记：压缩后的缓存值以该字节开头，后接 gzip 数据。
+/// 未压缩的缓存值是 JSON 文本，不会以该字节开头，因此新旧格式可以共存。
+const MARKER_GZIP: u8 = 0x01;
+
+/// 缓存值的结构版本。修改缓存类型的结构（增删字段、改变字段类型）时递增 `CACHE_VERSION`，
+/// 新代码使用新的缓存键，不会读到旧结构的缓存数据，旧键随过期时间自动清理。
+pub trait CacheSchema {
+    const CACHE_VERSION: u32;
+}
+
+impl<T: CacheSchema> CacheSchema for Vec<T> {
+    const CACHE_VERSION: u32 = T::CACHE_VERSION;
+}
+
+/// 构建带结构版本的缓存键：`{base}:v{版本}`。同一类数据的读取、写入和删除都必须通过该函数构建键。
+///
+/// # 参数
+/// - `base`: 不带版本的缓存键，如 `cache:user:profile:{user_id}`
+pub fn versioned_key<T: CacheSchema>(base: &str) -> String {
+    format!("{}:v{}", base, T::CACHE_VERSION)
+}
+
+/// 进程内缓存（一级缓存）：位于 Redis 之前，命中时不产生 Redis 往返。
+/// 条目有效期很短，本实例修改缓存时通过 Redis 发布/订阅通知其他实例失效对应的条目。
+struct LocalCache {
+    entries: Cache<String, String>,
+    /// 本实例的标识，用于忽略自己发出的失效通知
+    instance_id: String,
+}
+
+/// 全局进程内缓存，启动时根据配置初始化一次；未初始化时所有缓存函数只使用 Redis。
+static LOCAL_CACHE: OnceLock<LocalCache> = OnceLock::new();
+
+/// 初始化进程内缓存。`capacity` 为零时不启用；重复调用时保留第一次的设置。
+///
+/// # 参数
+/// - `capacity`: 最大条目数，超出时按最近最少使用淘汰
+/// - `ttl`: 条目有效期
+pub fn init_local(capacity: u64, ttl: Duration) {
+    if capacity == 0 {
+        return;
+    }
+    let local = LocalCache {
+        entries: Cache::builder().max_capacity(capacity).time_to_live(ttl).build(),
+        instance_id: Uuid::new_v4().simple().to_string(),
+    };
+    if LOCAL_CACHE.set(local).is_ok() {
+        tracing::info!(target: target::CACHE, "🧠 Local cache enabled: {} entries, ttl {:?}", capacity, ttl);
+    }
+}
+
+/// 启动进程内缓存的失效通知订阅任务。未启用进程内缓存时不启动。
+///
+/// 订阅连接断开期间可能错过通知，因此每次（重新）订阅成功后清空本地缓存，之后的读取回到 Redis。
+///
+/// # 参数
+/// - `client`: Redis 客户端，发布/订阅需要独占一条连接，不能使用连接管理器
+pub fn spawn_invalidation_listener(client: redis::Client) {
+    let Some(local) = LOCAL_CACHE.get() else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        loop {
+            match client.get_async_pubsub().await {
+                Ok(mut pubsub) => match pubsub.subscribe(REDIS_CHANNEL_CACHE_INVALIDATE).await {
+                    Ok(()) => {
+                        local.entries.invalidate_all();
+                        let mut messages = pubsub.into_on_message();
+                        while let Some(message) = messages.next().await {
+                            let Ok(payload) = message.get_payload::<String>() else {
+                                continue;
+                            };
+                            if let Some((sender, key)) = payload.split_once(' ')
+                                && sender != local.instance_id
+                            {
+                                invalidate_local(local, key);
+                            }
+                        }
+                        tracing::warn!(target: target::CACHE, "⚠️ Cache invalidation subscription closed, reconnecting");
+                    }
+                    Err(e) => tracing::warn!(target: target::CACHE, "⚠️ Cache invalidation subscribe failed: {}", e),
+                },
+                Err(e) => tracing::warn!(target: target::CACHE, "⚠️ Cache invalidation connection failed: {}", e),
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    });
+}
+
+/// 失效本地缓存中的一个键或一类键。模式只支持 `prefix*` 形式，其他模式清空全部本地缓存。
+fn invalidate_local(local: &LocalCache, key: &str) {
+    match key.strip_suffix('*') {
+        Some(prefix) if !prefix.contains(['*', '?', '[']) => {
+            for (cached_key, _) in local.entries.iter() {
+                if cached_key.starts_with(prefix) {
+                    local.entries.invalidate(cached_key.as_str());
+                }
+            }
+        }
+        Some(_) => local.entries.invalidate_all(),
+        None => local.entries.invalidate(key),
+    }
+}
+
+/// 修改缓存后失效本实例的本地条目，并通知其他实例。通知失败只记录日志，其他实例的条目最迟在过期后更新。
+async fn broadcast_invalidation(manager: &ConnectionManager, key: &str) {
+    let Some(local) = LOCAL_CACHE.get() else {
+        return;
+    };
+    invalidate_local(local, key);
+
+    let mut redis = manager.clone();
+    let message = format!("{} {}", local.instance_id, key);
+    if let Err(e) = redis.publish::<_, _, ()>(REDIS_CHANNEL_CACHE_INVALIDATE, message).await {
+        tracing::warn!(target: target::CACHE, "⚠️ Cache invalidation publish failed for {}: {}", key, e);
+    }
+}
 
 /// 通用缓存获取函数（Cache-Aside 模式）：优先从缓存读取，缓存未命中时从数据库获取并回填缓存。
 ///
@@ -24,42 +157,242 @@ where
     F: FnOnce() -> Fut + Send,
     Fut: Future<Output = Result<T, AppError>> + Send,
 {
+    // 第一步：尝试从 Redis 读取缓存数据。如果读取成功且数据有效，则直接返回缓存数据。
+    if let Some(data) = read_cached(manager, key).await {
+        return Ok(data);
+    }
+
+    // 第二步：缓存未命中（或 Redis 故障），执行 fetcher 查询数据库。这是缓存旁路模式的核心：当缓存不可用时，直接从数据源获取数据。
+    tracing::debug!(target: target::CACHE, "🔍 Cache miss, fetching from DB: {}", key);
+    let data = fetcher().await?;
+
+    // 第三步：将查询结果回填到 Redis 缓存中。这样后续请求就可以直接从缓存中获取数据，提高性能。
+    if let Some(json_str) = serialize(&data) {
+        fill(manager.clone(), key.to_string(), json_str, ttl_seconds).await;
+    }
+
+    Ok(data)
+}
+
+/// 带对冲读取的缓存获取函数：在 `get_or_fetch` 的基础上，如果 Redis 在 `hedge_after` 内没有响应，
+/// 就并行发起数据库查询，取先完成的一方，用于平滑 Redis 抖动时的尾延迟。
+///
+/// 对冲会增加数据库负载，只适合读取代价低、访问频繁的数据（如用户资料）。
+/// `hedge_after` 为零时不启用对冲，行为与 `get_or_fetch` 完全一致。
+///
+/// # 参数
+/// - `hedge_after`: 等待 Redis 的延迟预算，超过后启动数据库查询
+/// - 其余参数与 `get_or_fetch` 相同
+pub async fn get_or_fetch_hedged<T, F, Fut>(
+    manager: &ConnectionManager,
+    key: &str,
+    ttl_seconds: u64,
+    hedge_after: Duration,
+    fetcher: F,
+) -> Result<T, AppError>
+where
+    T: Serialize + DeserializeOwned + Send + Sync,
+    F: FnOnce() -> Fut + Send,
+    Fut: Future<Output = Result<T, AppError>> + Send,
+{
+    if hedge_after.is_zero() {
+        return get_or_fetch(manager, key, ttl_seconds, fetcher).await;
+    }
+
+    // 第一步：在延迟预算内等待 Redis，正常情况下直接命中或确认未命中
+    let cached = read_cached::<T>(manager, key);
+    tokio::pin!(cached);
+
+    let data = match tokio::time::timeout(hedge_after, &mut cached).await {
+        Ok(Some(data)) => return Ok(data),
+        Ok(None) => {
+            tracing::debug!(target: target::CACHE, "🔍 Cache miss, fetching from DB: {}", key);
+            fetcher().await?
+        }
+        // 第二步：Redis 超出预算仍未响应，并行查询数据库，取先完成的结果
+        Err(_) => {
+            metrics::counter!("cache_hedged_reads_total").increment(1);
+            tracing::debug!(target: target::CACHE, "⏱️ Redis slow, hedging with DB fetch: {}", key);
+
+            let fetch = fetcher();
+            tokio::pin!(fetch);
+            tokio::select! {
+                cached = &mut cached => match cached {
+                    Some(data) => return Ok(data),
+                    None => fetch.await?,
+                },
+                fetched = &mut fetch => {
+                    metrics::counter!("cache_hedge_wins_total").increment(1);
+                    fetched?
+                }
+            }
+        }
+    };
+
+    // 第三步：回填缓存。Redis 可能仍然缓慢，放到后台执行，不拖慢本次请求
+    if let Some(json_str) = serialize(&data) {
+        tokio::spawn(fill(manager.clone(), key.to_string(), json_str, ttl_seconds));
+    }
+
+    Ok(data)
+}
+
+/// 带单飞锁的缓存获取函数：在 `get_or_fetch` 的基础上，缓存未命中时先用 `SET NX` 抢占一个短期锁，
+/// 只有抢到锁的请求查询数据库并回填缓存，其余请求轮询等待缓存出现，避免热点键过期瞬间
+/// 大量请求同时打到数据库（缓存击穿）。
+///
+/// 锁只是保护措施而非正确性保证：等待超过锁的有效期、或 Redis 故障无法加锁时，降级为直接查询数据库，
+/// 不会让请求失败。只适合所有调用方共享的热点键（如全局配置、功能开关），按用户区分的键没有击穿问题。
+///
+/// # 参数
+/// - 与 `get_or_fetch` 相同
+pub async fn get_or_fetch_locked<T, F, Fut>(
+    manager: &ConnectionManager,
+    key: &str,
+    ttl_seconds: u64,
+    fetcher: F,
+) -> Result<T, AppError>
+where
+    T: Serialize + DeserializeOwned + Send + Sync,
+    F: FnOnce() -> Fut + Send,
+    Fut: Future<Output = Result<T, AppError>> + Send,
+{
+    // 第一步：尝试从 Redis 读取缓存数据，命中时直接返回
+    if let Some(data) = read_cached(manager, key).await {
+        return Ok(data);
+    }
+
+    // 第二步：抢占重建锁。Redis 故障时视为抢到锁，直接查询数据库
+    let lock_key = format!("{}{}", REDIS_PREFIX_CACHE_LOCK, key);
     let mut redis = manager.clone();
+    let acquired = redis::cmd("SET")
+        .arg(&lock_key)
+        .arg(1)
+        .arg("NX")
+        .arg("PX")
+        .arg(CACHE_LOCK_EXPIRE_MS)
+        .query_async::<Option<String>>(&mut redis)
+        .await
+        .map(|reply| reply.is_some())
+        .unwrap_or(true);
 
-    // 第一步：尝试从 Redis 读取缓存数据。如果读取成功且数据有效，则直接返回缓存数据。
-    // 使用 match 处理错误，Redis 故障不应阻断业务（Soft Fail 策略）。即使 Redis 出现故障，应用程序也应继续运行，降级为直接查询数据库。
-    match redis.get::<_, String>(key).await {
-        Ok(json_str) if !json_str.is_empty() => {
+    // 第三步：没有抢到锁，说明其他请求正在重建缓存，在锁的有效期内等待缓存出现
+    if !acquired {
+        metrics::counter!("cache_lock_waits_total").increment(1);
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(CACHE_LOCK_EXPIRE_MS);
+        while tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(CACHE_LOCK_POLL_MS)).await;
+            if let Some(data) = read_cached(manager, key).await {
+                return Ok(data);
+            }
+        }
+        // 重建方可能已经失败或进程崩溃，不再等待
+        metrics::counter!("cache_lock_timeouts_total").increment(1);
+        tracing::warn!(target: target::CACHE, "⏱️ Timed out waiting for cache rebuild, fetching from DB: {}", key);
+        return fetcher().await;
+    }
+
+    // 第四步：持有锁，查询数据库并回填缓存。无论查询成功与否都释放锁，让等待方尽快重试或降级
+    tracing::debug!(target: target::CACHE, "🔒 Cache miss, rebuilding under lock: {}", key);
+    let result = fetcher().await;
+    if let Ok(data) = &result
+        && let Some(json_str) = serialize(data)
+    {
+        fill(manager.clone(), key.to_string(), json_str, ttl_seconds).await;
+    }
+    let _: () = redis.del(&lock_key).await.unwrap_or_default();
+
+    result
+}
+
+/// 从 Redis 读取并反序列化缓存数据。
+/// Redis 故障不应阻断业务（Soft Fail 策略），读取失败或数据损坏都视为未命中，降级为直接查询数据库。
+async fn read_cached<T: DeserializeOwned>(manager: &ConnectionManager, key: &str) -> Option<T> {
+    // 先查进程内缓存，命中时不访问 Redis
+    let local = LOCAL_CACHE.get();
+    if let Some(json_str) = local.and_then(|local| local.entries.get(key))
+        && let Ok(data) = serde_json::from_str::<T>(&json_str)
+    {
+        metrics::counter!("cache_local_hits_total").increment(1);
+        return Some(data);
+    }
+
+    let mut redis = manager.clone();
+    match redis.get::<_, Option<Vec<u8>>>(key).await {
+        Ok(Some(bytes)) if !bytes.is_empty() => {
+            let Some(json_str) = decode(bytes) else {
+                tracing::warn!(target: target::CACHE, "⚠️ Cache decode failed for {}", key);
+                return None;
+            };
             match serde_json::from_str::<T>(&json_str) {
                 Ok(data) => {
-                    tracing::debug!("✅ Cache hit: {}", key);
-                    return Ok(data);
+                    tracing::debug!(target: target::CACHE, "✅ Cache hit: {}", key);
+                    if let Some(local) = local {
+                        local.entries.insert(key.to_string(), json_str);
+                    }
+                    Some(data)
+                }
+                Err(e) => {
+                    tracing::warn!(target: target::CACHE, "⚠️ Cache deserialize failed for {}: {}", key, e);
+                    None
                 }
-                Err(e) => tracing::warn!("⚠️ Cache deserialize failed for {}: {}", key, e),
             }
         }
-        Err(e) => tracing::warn!("⚠️ Redis get failed for {}: {}", key, e),
-        _ => {} // Key 不存在，继续向下执行。这种情况属于正常的缓存未命中，需要执行数据库查询。
-    };
+        Err(e) => {
+            tracing::warn!(target: target::CACHE, "⚠️ Redis get failed for {}: {}", key, e);
+            None
+        }
+        _ => None, // Key 不存在，属于正常的缓存未命中
+    }
+}
 
-    // 第二步：缓存未命中（或 Redis 故障），执行 fetcher 查询数据库。这是缓存旁路模式的核心：当缓存不可用时，直接从数据源获取数据。
-    tracing::debug!("🔍 Cache miss, fetching from DB: {}", key);
-    let data = fetcher().await?;
+/// 编码写入 Redis 的缓存值：超过 `CACHE_COMPRESS_THRESHOLD_BYTES` 时压缩，压缩失败时按原文写入。
+fn encode(json_str: String) -> Vec<u8> {
+    if json_str.len() <= CACHE_COMPRESS_THRESHOLD_BYTES {
+        return json_str.into_bytes();
+    }
 
-    // 第三步：将查询结果回填到 Redis 缓存中。这样后续请求就可以直接从缓存中获取数据，提高性能。
-    // 同样，写入失败不报错，只记录日志。这是 Soft Fail 策略的一部分，确保缓存故障不影响主要业务流程。
-    match serde_json::to_string(&data) {
-        Ok(json_str) => {
-            if let Err(e) = redis.set_ex::<_, _, ()>(key, json_str, ttl_seconds).await {
-                tracing::warn!("⚠️ Redis set failed for {}: {}", key, e);
-            } else {
-                tracing::debug!("💾 Cache set: {}", key);
-            }
+    let mut encoder = GzEncoder::new(vec![MARKER_GZIP], Compression::fast());
+    match encoder.write_all(json_str.as_bytes()).and_then(|()| encoder.finish()) {
+        Ok(compressed) => {
+            metrics::counter!("cache_compressed_writes_total").increment(1);
+            compressed
+        }
+        Err(e) => {
+            tracing::warn!(target: target::CACHE, "⚠️ Cache compression failed: {}", e);
+            json_str.into_bytes()
         }
-        Err(e) => tracing::error!("❌ Data serialization failed: {}", e),
     }
+}
 
-    Ok(data)
+/// 解码从 Redis 读取的缓存值，根据首字节判断是否为压缩格式。数据损坏时返回 `None`。
+fn decode(bytes: Vec<u8>) -> Option<String> {
+    match bytes.split_first() {
+        Some((&MARKER_GZIP, compressed)) => {
+            let mut json_str = String::new();
+            GzDecoder::new(compressed).read_to_string(&mut json_str).ok()?;
+            Some(json_str)
+        }
+        _ => String::from_utf8(bytes).ok(),
+    }
+}
+
+fn serialize<T: Serialize>(data: &T) -> Option<String> {
+    serde_json::to_string(data)
+        .inspect_err(|e| tracing::error!(target: target::CACHE, "❌ Data serialization failed: {}", e))
+        .ok()
+}
+
+/// 回填缓存。写入失败不报错，只记录日志，确保缓存故障不影响主要业务流程。
+async fn fill(mut redis: ConnectionManager, key: String, json_str: String, ttl_seconds: u64) {
+    if let Some(local) = LOCAL_CACHE.get() {
+        local.entries.insert(key.clone(), json_str.clone());
+    }
+    if let Err(e) = redis.set_ex::<_, _, ()>(&key, encode(json_str), ttl_seconds).await {
+        tracing::warn!(target: target::CACHE, "⚠️ Redis set failed for {}: {}", key, e);
+    } else {
+        tracing::debug!(target: target::CACHE, "💾 Cache set: {}", key);
+    }
 }
 
 /// 通用缓存更新函数（直接覆盖）：将数据直接写入 Redis 缓存，覆盖已存在的键值。
@@ -70,23 +403,73 @@ where
     let mut redis = manager.clone();
     match serde_json::to_string(data) {
         Ok(json_str) => {
-            if let Err(e) = redis.set_ex::<_, _, ()>(key, json_str, ttl_seconds).await {
-                tracing::warn!("⚠️ Redis set failed for {}: {}", key, e);
+            if let Err(e) = redis.set_ex::<_, _, ()>(key, encode(json_str), ttl_seconds).await {
+                tracing::warn!(target: target::CACHE, "⚠️ Redis set failed for {}: {}", key, e);
             } else {
-                tracing::debug!("🔄 Cache updated: {}", key);
+                tracing::debug!(target: target::CACHE, "🔄 Cache updated: {}", key);
             }
+            broadcast_invalidation(manager, key).await;
         }
-        Err(e) => tracing::error!("❌ Serialization failed: {}", e),
+        Err(e) => tracing::error!(target: target::CACHE, "❌ Serialization failed: {}", e),
     }
 }
 
 /// 通用缓存删除函数：从 Redis 缓存中删除指定的键。用于缓存失效或数据更新时的清理操作。
-#[allow(dead_code)]
 pub async fn del(manager: &ConnectionManager, key: &str) {
     let mut redis = manager.clone();
     if let Err(e) = redis.del::<_, ()>(key).await {
-        tracing::warn!("⚠️ Redis delete failed for {}: {}", key, e);
+        tracing::warn!(target: target::CACHE, "⚠️ Redis delete failed for {}: {}", key, e);
     } else {
-        tracing::debug!("🗑️ Cache deleted: {}", key);
+        tracing::debug!(target: target::CACHE, "🗑️ Cache deleted: {}", key);
     }
-}
\ No newline at end of file
+    broadcast_invalidation(manager, key).await;
+}
+/// 按模式批量删除缓存：使用 SCAN 增量遍历匹配 `pattern` 的键并逐批删除，避免 KEYS 命令阻塞 Redis。
+/// 适用于需要一次性失效某一类缓存的场景（如角色权限定义变更后清空所有用户的权限缓存）。
+///
+/// # 参数
+/// - `pattern`: Redis glob 模式，如 `cache:user:permissions:*`。
+///
+/// # 返回值
+/// - 实际删除的键数量。与其他缓存函数一致，Redis 故障只记录日志，不阻断业务。
+pub async fn del_by_pattern(manager: &ConnectionManager, pattern: &str) -> usize {
+    broadcast_invalidation(manager, pattern).await;
+    let mut scan_conn = manager.clone();
+
+    // 第一步：先收集所有匹配的键。迭代器持有连接的可变借用，因此删除操作放在遍历结束之后。
+    let mut keys: Vec<String> = Vec::new();
+    match scan_conn.scan_match::<_, String>(pattern).await {
+        Ok(mut iter) => {
+            while let Some(item) = iter.next_item().await {
+                match item {
+                    Ok(key) => keys.push(key),
+                    Err(e) => {
+                        tracing::warn!(target: target::CACHE, "⚠️ Redis scan failed for {}: {}", pattern, e);
+                        break;
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            tracing::warn!(target: target::CACHE, "⚠️ Redis scan failed for {}: {}", pattern, e);
+            return 0;
+        }
+    }
+
+    if keys.is_empty() {
+        return 0;
+    }
+
+    // 第二步：分批删除，避免单条 DEL 命令携带过多参数。
+    let mut redis = manager.clone();
+    let mut deleted = 0;
+    for chunk in keys.chunks(500) {
+        match redis.del::<_, usize>(chunk).await {
+            Ok(n) => deleted += n,
+            Err(e) => tracing::warn!(target: target::CACHE, "⚠️ Redis delete failed for {}: {}", pattern, e),
+        }
+    }
+
+    tracing::debug!(target: target::CACHE, "🗑️ Cache deleted by pattern {}: {} keys", pattern, deleted);
+    deleted
+}
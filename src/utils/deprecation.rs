@@ -0,0 +1,45 @@
+use std::{cell::RefCell, future::Future};
+
+// API 弃用标记工具：端点或请求字段被标记为弃用后，每次使用都会被记录下来，
+// 由弃用追踪中间件转换为 `Deprecation` / `Sunset` / `Warning` 响应头，并按客户端统计使用次数，
+// 用实际数据决定何时可以移除旧接口。
+
+/// 一个被弃用的端点或字段
+#[derive(Debug)]
+pub struct Deprecation {
+    /// 弃用项的稳定名称，用作指标标签，如 "POST /v1/orders"、"bulk_users.action=disable"
+    pub feature: &'static str,
+    /// 开始弃用的版本
+    pub since: &'static str,
+    /// 替代方案，如 "POST /v2/orders"
+    pub replacement: Option<&'static str>,
+    /// 计划移除的时间（HTTP-date 格式，如 "Wed, 01 Jul 2026 00:00:00 GMT"）
+    pub sunset: Option<&'static str>,
+}
+
+tokio::task_local! {
+    /// 当前请求中使用到的弃用项
+    static USED: RefCell<Vec<&'static Deprecation>>;
+}
+
+/// 在弃用追踪作用域内执行 future，返回其结果以及期间记录的弃用项。
+pub async fn scope<F: Future>(fut: F) -> (F::Output, Vec<&'static Deprecation>) {
+    USED.scope(RefCell::new(Vec::new()), async {
+        let output = fut.await;
+        let used = USED.with(|used| used.take());
+        (output, used)
+    })
+    .await
+}
+
+/// 记录当前请求使用了某个弃用项。处理器在检测到弃用字段时调用；
+/// 不在请求作用域内（如后台任务）时忽略。
+#[allow(dead_code)]
+pub fn record(deprecation: &'static Deprecation) {
+    let _ = USED.try_with(|used| {
+        let mut used = used.borrow_mut();
+        if !used.iter().any(|item| std::ptr::eq(*item, deprecation)) {
+            used.push(deprecation);
+        }
+    });
+}
@@ -0,0 +1,115 @@
+use serde_json::{Map, Value};
+use std::sync::OnceLock;
+use crate::core::log::target;
+use crate::core::enums::JsonCase;
+
+// JSON 字段命名风格转换工具。DTO 在代码中统一使用 snake_case 定义，
+// 当配置要求 camelCase 时，在 HTTP 边界处统一转换，避免为每个 DTO 单独添加 serde 属性。
+
+/// 全局命名风格，启动时根据配置初始化一次。
+static JSON_CASE: OnceLock<JsonCase> = OnceLock::new();
+
+/// 初始化全局命名风格。重复调用时保留第一次设置的值。
+pub fn init(case: JsonCase) {
+    if JSON_CASE.set(case).is_err() {
+        tracing::warn!(target: target::HTTP, "⚠️ JSON case already initialized, ignoring: {}", case);
+    }
+}
+
+/// 当前生效的命名风格。未初始化时默认为 snake_case。
+pub fn current() -> JsonCase {
+    JSON_CASE.get().copied().unwrap_or_default()
+}
+
+/// snake_case -> camelCase，如 "access_token" -> "accessToken"
+pub fn snake_to_camel(key: &str) -> String {
+    let mut result = String::with_capacity(key.len());
+    let mut upper_next = false;
+    for ch in key.chars() {
+        if ch == '_' && !result.is_empty() {
+            upper_next = true;
+        } else if upper_next {
+            result.extend(ch.to_uppercase());
+            upper_next = false;
+        } else {
+            result.push(ch);
+        }
+    }
+    result
+}
+
+/// camelCase -> snake_case，如 "perPage" -> "per_page"
+pub fn camel_to_snake(key: &str) -> String {
+    let mut result = String::with_capacity(key.len() + 4);
+    for ch in key.chars() {
+        if ch.is_uppercase() {
+            if !result.is_empty() {
+                result.push('_');
+            }
+            result.extend(ch.to_lowercase());
+        } else {
+            result.push(ch);
+        }
+    }
+    result
+}
+
+/// 值为自由格式 JSON 的字段（snake_case 名称）：审计差异、令牌扩展声明、导入器参数、
+/// 用户设置及其自定义设置项等。这些字段的键名由调用方或业务数据决定，原样保留，只转换字段名本身。
+const OPAQUE_FIELDS: &[&str] = &["diff", "from", "to", "ext", "options", "settings", "extra"];
+
+/// 递归转换 JSON 对象的键名（只转换键，不修改值）。`OPAQUE_FIELDS` 中字段的值不再向下转换。
+pub fn convert_keys(value: Value, convert: fn(&str) -> String) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let converted = convert(&key);
+                    let opaque = [key.as_str(), converted.as_str()].iter().any(|name| OPAQUE_FIELDS.contains(name));
+                    let value = if opaque { value } else { convert_keys(value, convert) };
+                    (converted, value)
+                })
+                .collect::<Map<String, Value>>(),
+        ),
+        Value::Array(items) => Value::Array(
+            items.into_iter().map(|item| convert_keys(item, convert)).collect(),
+        ),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn converts_between_cases() {
+        assert_eq!(snake_to_camel("access_token"), "accessToken");
+        assert_eq!(camel_to_snake("perPage"), "per_page");
+        assert_eq!(camel_to_snake(&snake_to_camel("show_presence")), "show_presence");
+    }
+
+    #[test]
+    fn converts_nested_keys() {
+        let value = json!({"userId": 1, "items": [{"createdAt": 2}]});
+        assert_eq!(
+            convert_keys(value, camel_to_snake),
+            json!({"user_id": 1, "items": [{"created_at": 2}]}),
+        );
+    }
+
+    #[test]
+    fn keeps_opaque_values() {
+        let value = json!({
+            "settings": {"my_key": 1, "myKey": 2},
+            "extra": {"custom_flag": true},
+            "diff": {"old_value": 1},
+        });
+        assert_eq!(convert_keys(value.clone(), snake_to_camel), value);
+        assert_eq!(
+            convert_keys(json!({"exportedAt": 1, "settings": {"darkMode": true}}), camel_to_snake),
+            json!({"exported_at": 1, "settings": {"darkMode": true}}),
+        );
+    }
+}
@@ -1,16 +1,333 @@
+use std::{cell::Cell, future::Future, net::IpAddr};
+
 use redis::Script;
 use redis::aio::ConnectionManager;
-use crate::core::error::AppError;
+use serde::{Deserialize, Serialize};
+use crate::core::log::target;
+use crate::core::{
+    constants::{
+        REDIS_PREFIX_RATE_LIMIT_BAN, REDIS_PREFIX_RATE_LIMIT_STRIKES, RATE_LIMIT_PENALTY_STEPS, RATE_LIMIT_STRIKE_TTL,
+        RATE_LIMIT_UNPENALIZED_ACTIONS,
+    },
+    error::AppError,
+};
+use crate::utils::allowlist;
+
+/// 限流模式。在 `RATE_LIMITS` 配置中由 `mode` 字段区分：`{"mode":"fixed_window","limit":5,"window":60}` 为固定窗口，
+/// `{"mode":"token_bucket","burst":20,"refill_per_sec":1.0}` 为令牌桶。显式的模式让解析错误指明是哪种模式的哪个字段有误。
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case", deny_unknown_fields)]
+pub enum RateLimitMode {
+    /// 固定窗口：窗口内最多 `limit` 次，窗口从第一次请求开始计时
+    FixedWindow { limit: usize, window: u64 },
+    /// 令牌桶：桶容量为 `burst`，每秒补充 `refill_per_sec` 个令牌。
+    /// 允许短时间内连续请求（最多 `burst` 次），持续吞吐量不超过补充速率
+    TokenBucket { burst: u64, refill_per_sec: f64 },
+}
+
+impl RateLimitMode {
+    /// 窗口内允许的最大次数（令牌桶为桶容量）
+    pub fn limit(&self) -> u64 {
+        match *self {
+            RateLimitMode::FixedWindow { limit, .. } => limit as u64,
+            RateLimitMode::TokenBucket { burst, .. } => burst,
+        }
+    }
+
+    /// 参数是否有效（次数、窗口、桶容量和补充速率都必须大于零）
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            RateLimitMode::FixedWindow { limit, window } => limit > 0 && window > 0,
+            RateLimitMode::TokenBucket { burst, refill_per_sec } => burst > 0 && refill_per_sec > 0.0,
+        }
+    }
+}
+
+/// 一次限流检查后的额度信息，由限流头中间件转换为 `X-RateLimit-*` / `Retry-After` 响应头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitInfo {
+    /// 窗口内允许的最大次数（令牌桶为桶容量）
+    pub limit: u64,
+    /// 剩余可用次数
+    pub remaining: u64,
+    /// 额度完全恢复前的秒数（固定窗口为窗口剩余时间，令牌桶为补满所需时间）
+    pub reset: u64,
+    /// 被限流时建议的重试等待秒数，未限流时为 `None`
+    pub retry_after: Option<u64>,
+}
+
+impl RateLimitInfo {
+    /// 剩余额度是否低于 `threshold`（占上限的比例），被限流时总是返回 `true`
+    pub fn is_near_limit(&self, threshold: f64) -> bool {
+        self.retry_after.is_some() || (self.remaining as f64) < self.limit as f64 * threshold
+    }
+}
+
+/// 一个请求的限流作用域
+struct Scope {
+    /// 当前请求中最紧张的一次限流检查结果
+    info: Cell<Option<RateLimitInfo>>,
+    /// 请求的来源IP（见 `ClientIp`），用于匹配豁免名单中的 IP/CIDR
+    client_ip: Option<IpAddr>,
+}
+
+tokio::task_local! {
+    static CURRENT: Scope;
+}
+
+/// 在限流信息收集作用域内执行 future，返回其结果以及期间最紧张的一次限流检查结果。
+///
+/// # 参数
+/// - `client_ip`: 请求的来源IP，只有经过可信代理校验的地址（见 `ClientIp`）才能传入
+pub async fn scope<F: Future>(client_ip: Option<IpAddr>, fut: F) -> (F::Output, Option<RateLimitInfo>) {
+    CURRENT
+        .scope(Scope { info: Cell::new(None), client_ip }, async {
+            let output = fut.await;
+            let info = CURRENT.with(|current| current.info.take());
+            (output, info)
+        })
+        .await
+}
+
+/// 当前请求的来源IP，不在请求作用域内时返回 `None`
+fn client_ip() -> Option<IpAddr> {
+    CURRENT.try_with(|current| current.client_ip).ok().flatten()
+}
+
+/// 记录一次限流检查结果。同一请求经过多个限流器（路由组、处理器）时，
+/// 保留被限流的那一次，都未限流时保留剩余次数最少的一次。不在请求作用域内时忽略。
+pub fn record(info: RateLimitInfo) {
+    let _ = CURRENT.try_with(|Scope { info: current, .. }| {
+        let tighter = match current.get() {
+            None => true,
+            Some(existing) => match (existing.retry_after, info.retry_after) {
+                (Some(_), _) => false,
+                (None, Some(_)) => true,
+                (None, None) => info.remaining < existing.remaining,
+            },
+        };
+        if tighter {
+            current.set(Some(info));
+        }
+    });
+}
 
 /// Lua 脚本实现滑动窗口限流或固定窗口限流
+#[allow(dead_code)]
 pub async fn check_rate_limit(
     redis_manager: &ConnectionManager,
     action_key: &str,
     user_id: &str,
     limit: usize,
     window: u64,
-) -> Result<(), AppError> {
-    let redis_key = format!("rate_limit:{}:{}", action_key, user_id);
+) -> Result<RateLimitInfo, AppError> {
+    check_rate_limit_mode(redis_manager, action_key, user_id, RateLimitMode::FixedWindow { limit, window }).await
+}
+
+/// 按指定模式检查请求频率。
+///
+/// # 参数
+/// - `action_key`: 操作名称，如 "read_me"
+/// - `user_id`: 限流对象，如用户ID或客户端IP
+/// - `mode`: 固定窗口或令牌桶
+///
+/// 检查结果同时记录到当前请求的限流信息中（见 `scope`），由限流头中间件写入响应头。
+///
+/// 豁免名单中的限流对象或来源IP（见 `allowlist`）直接放行，不计数。
+/// 每次超出限制都会记为一次违规，并进入冷却期（见 `RATE_LIMIT_PENALTY_STEPS`）：冷却期间的请求直接被拒绝，
+/// 不再计数；近期反复违规时冷却时长逐级升级（1 分钟 → 10 分钟 → 1 小时）。
+/// 限流对象由请求方填写的操作（见 `RATE_LIMIT_UNPENALIZED_ACTIONS`）不记违规，只按窗口拒绝。
+///
+/// # 返回值
+/// - `Ok(RateLimitInfo)`: 未超出限制，附带剩余次数和重置时间
+/// - `Err(AppError::RateLimitExceeded)`: 超出限制，错误消息中带有冷却时间
+/// - `Err(AppError::TemporarilyBanned)`: 仍处于之前违规的冷却期
+pub async fn check_rate_limit_mode(
+    redis_manager: &ConnectionManager,
+    action_key: &str,
+    user_id: &str,
+    mode: RateLimitMode,
+) -> Result<RateLimitInfo, AppError> {
+    // 第一步：豁免名单中的对象和来源IP不计数，也不受冷却期限制
+    if allowlist::is_exempt(user_id, client_ip()) {
+        metrics::counter!(
+            "rate_limit_decisions_total",
+            "action" => action_key.to_string(),
+            "outcome" => "exempt",
+        )
+        .increment(1);
+        return Ok(RateLimitInfo { limit: mode.limit(), remaining: mode.limit(), reset: 0, retry_after: None });
+    }
+
+    // 第二步：冷却期内直接拒绝
+    if let Some(remaining) = active_ban(redis_manager, action_key, user_id).await? {
+        record(RateLimitInfo { limit: mode.limit(), remaining: 0, reset: remaining, retry_after: Some(remaining) });
+        metrics::counter!(
+            "rate_limit_decisions_total",
+            "action" => action_key.to_string(),
+            "outcome" => "banned",
+        )
+        .increment(1);
+        return Err(AppError::TemporarilyBanned(format!(
+            "Too many requests. Try again in {} seconds.",
+            remaining
+        )));
+    }
+
+    // 第三步：按模式计数
+    let (allowed, utilization, mut info) = match mode {
+        RateLimitMode::FixedWindow { limit, window } => {
+            let (count, ttl_ms) = fixed_window(redis_manager, action_key, user_id, window).await?;
+            // 键没有过期时间（-1）时按完整窗口计算
+            let reset = if ttl_ms > 0 { (ttl_ms as u64).div_ceil(1000) } else { window };
+            let allowed = count <= limit;
+            let info = RateLimitInfo {
+                limit: limit as u64,
+                remaining: limit.saturating_sub(count) as u64,
+                reset,
+                retry_after: (!allowed).then_some(reset.max(1)),
+            };
+            (allowed, count as f64 / limit.max(1) as f64, info)
+        }
+        RateLimitMode::TokenBucket { burst, refill_per_sec } => {
+            let (allowed, remaining, retry_ms, full_ms) =
+                token_bucket(redis_manager, action_key, user_id, burst, refill_per_sec).await?;
+            let info = RateLimitInfo {
+                limit: burst,
+                remaining,
+                reset: full_ms.div_ceil(1000),
+                retry_after: (!allowed).then_some(retry_ms.div_ceil(1000).max(1)),
+            };
+            (allowed, 1.0 - remaining as f64 / burst.max(1) as f64, info)
+        }
+    };
+
+    // 第四步：超出限制时记一次违规并进入冷却期，冷却时长不短于限流器自身的重试等待时间
+    if let Some(retry_after) = info.retry_after
+        && is_penalized(action_key)
+    {
+        let cooldown = escalate(redis_manager, action_key, user_id).await?;
+        info.retry_after = Some(retry_after.max(cooldown));
+        info.reset = info.reset.max(cooldown);
+    }
+    record(info);
+
+    // 记录限流决策指标：按操作和结果（allowed/limited）计数，并记录当前窗口的利用率
+    // （令牌桶为已消耗的令牌占比），便于根据实际流量调整限额。
+    let outcome = if allowed { "allowed" } else { "limited" };
+    metrics::counter!(
+        "rate_limit_decisions_total",
+        "action" => action_key.to_string(),
+        "outcome" => outcome,
+    )
+    .increment(1);
+    metrics::histogram!("rate_limit_window_utilization", "action" => action_key.to_string())
+        .record(utilization);
+
+    if let Some(retry_after) = info.retry_after {
+        tracing::warn!(target: target::LIMITER, "⛔ Rate limit exceeded: User {} on {} ({:?})", user_id, action_key, mode);
+        return Err(AppError::RateLimitExceeded(
+            format!("Rate limit exceeded. Try again in {} seconds.", retry_after)
+        ));
+    }
+
+    Ok(info)
+}
+
+/// 超出限制时是否记违规并升级冷却
+fn is_penalized(action_key: &str) -> bool {
+    !RATE_LIMIT_UNPENALIZED_ACTIONS.contains(&action_key)
+}
+
+/// 第 `strikes` 次违规的冷却时长（秒），超过级数后保持最后一级
+fn penalty(strikes: u64) -> u64 {
+    let step = (strikes.max(1) as usize).min(RATE_LIMIT_PENALTY_STEPS.len()) - 1;
+    RATE_LIMIT_PENALTY_STEPS[step]
+}
+
+/// 查询冷却期的剩余秒数，不在冷却期时返回 `None`
+async fn active_ban(
+    redis_manager: &ConnectionManager,
+    action_key: &str,
+    user_id: &str,
+) -> Result<Option<u64>, AppError> {
+    let mut conn = redis_manager.clone();
+    let ttl_ms: i64 = redis::cmd("PTTL")
+        .arg(format!("{}{}:{}", REDIS_PREFIX_RATE_LIMIT_BAN, action_key, user_id))
+        .query_async(&mut conn)
+        .await?;
+    Ok((ttl_ms > 0).then(|| (ttl_ms as u64).div_ceil(1000)))
+}
+
+/// 记录一次违规并设置冷却期，返回冷却时长（秒）。
+/// 违规次数在最后一次违规后保留 `RATE_LIMIT_STRIKE_TTL` 秒，次数越多冷却越长。
+async fn escalate(redis_manager: &ConnectionManager, action_key: &str, user_id: &str) -> Result<u64, AppError> {
+    let mut conn = redis_manager.clone();
+    let script = Script::new(r#"
+        local strikes = redis.call("INCR", KEYS[1])
+        redis.call("EXPIRE", KEYS[1], ARGV[1])
+        return strikes
+    "#);
+
+    let strikes: u64 = script
+        .key(format!("{}{}:{}", REDIS_PREFIX_RATE_LIMIT_STRIKES, action_key, user_id))
+        .arg(RATE_LIMIT_STRIKE_TTL)
+        .invoke_async(&mut conn)
+        .await?;
+
+    let cooldown = penalty(strikes);
+    let _: () = redis::cmd("SET")
+        .arg(format!("{}{}:{}", REDIS_PREFIX_RATE_LIMIT_BAN, action_key, user_id))
+        .arg(strikes)
+        .arg("EX")
+        .arg(cooldown)
+        .query_async(&mut conn)
+        .await?;
+
+    metrics::counter!("rate_limit_bans_total", "action" => action_key.to_string()).increment(1);
+    tracing::warn!(
+        target: target::LIMITER,
+        "🚫 {} banned from {} for {}s (strike {})",
+        user_id,
+        action_key,
+        cooldown,
+        strikes
+    );
+    Ok(cooldown)
+}
+
+/// 解除限流对象在某个操作上的冷却期，并清空违规次数（下一次违规从第一级冷却开始）。
+///
+/// # 返回值
+/// - `Ok(true)`: 已清除冷却期或违规记录
+/// - `Ok(false)`: 该对象没有冷却期和违规记录
+pub async fn clear_ban(redis_manager: &ConnectionManager, action_key: &str, user_id: &str) -> Result<bool, AppError> {
+    let mut conn = redis_manager.clone();
+    let removed: u64 = redis::cmd("DEL")
+        .arg(format!("{}{}:{}", REDIS_PREFIX_RATE_LIMIT_BAN, action_key, user_id))
+        .arg(format!("{}{}:{}", REDIS_PREFIX_RATE_LIMIT_STRIKES, action_key, user_id))
+        .query_async(&mut conn)
+        .await?;
+    Ok(removed > 0)
+}
+
+/// 固定窗口计数，返回窗口内的请求次数（包含本次）和窗口剩余的毫秒数
+async fn fixed_window(
+    redis_manager: &ConnectionManager,
+    action_key: &str,
+    user_id: &str,
+    window: u64,
+) -> Result<(usize, i64), AppError> {
+    increment(redis_manager, &format!("rate_limit:{}:{}", action_key, user_id), window).await
+}
+
+/// 计数键自增一次，第一次写入时设置过期时间，返回自增后的计数和键剩余的毫秒数。
+/// 固定窗口限流和长周期配额（见 `quota::consume`）共用。
+pub(crate) async fn increment(
+    redis_manager: &ConnectionManager,
+    redis_key: &str,
+    ttl_seconds: u64,
+) -> Result<(usize, i64), AppError> {
     let mut conn = redis_manager.clone();
 
     // 原子操作：自增并设置过期时间（如果是第一次）
@@ -19,21 +336,122 @@ pub async fn check_rate_limit(
         if count == 1 then
             redis.call("EXPIRE", KEYS[1], ARGV[1])
         end
-        return count
+        return {count, redis.call("PTTL", KEYS[1])}
     "#);
 
-    let count: usize = script
-        .key(&redis_key)
-        .arg(window)
+    let (count, ttl_ms): (usize, i64) = script
+        .key(redis_key)
+        .arg(ttl_seconds)
         .invoke_async(&mut conn)
         .await?; // thiserror 自动处理错误
 
-    if count > limit {
-        tracing::warn!("⛔ Rate limit exceeded: User {} on {} ({}/{})", user_id, action_key, count, limit);
-        return Err(AppError::RateLimitExceeded(
-            format!("Rate limit exceeded. Try again in {} seconds.", window)
-        ));
+    Ok((count, ttl_ms))
+}
+
+/// 令牌桶取令牌，返回（是否允许、剩余令牌数、令牌不足时下一个令牌的等待毫秒数、桶补满所需的毫秒数）
+async fn token_bucket(
+    redis_manager: &ConnectionManager,
+    action_key: &str,
+    user_id: &str,
+    burst: u64,
+    refill_per_sec: f64,
+) -> Result<(bool, u64, u64, u64), AppError> {
+    // 与固定窗口的计数键类型不同（哈希 vs 字符串），使用独立的前缀
+    let redis_key = format!("rate_limit:bucket:{}:{}", action_key, user_id);
+    let mut conn = redis_manager.clone();
+
+    // 原子操作：按上次取令牌以来经过的时间补充令牌（不超过桶容量），再尝试取出一个。
+    // 时间取 Redis 服务器时间，避免多个实例之间的时钟偏差。令牌数是小数，以字符串保存，
+    // 桶补满后键自动过期，长时间不活跃的调用方不占用内存。
+    let script = Script::new(r#"
+        local burst = tonumber(ARGV[1])
+        local rate = tonumber(ARGV[2])
+        local time = redis.call("TIME")
+        local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+        local bucket = redis.call("HMGET", KEYS[1], "tokens", "ts")
+        local tokens = tonumber(bucket[1]) or burst
+        local ts = tonumber(bucket[2]) or now
+        tokens = math.min(burst, tokens + math.max(0, now - ts) * rate / 1000)
+
+        local allowed = 0
+        local retry_ms = 0
+        if tokens >= 1 then
+            tokens = tokens - 1
+            allowed = 1
+        else
+            retry_ms = math.ceil((1 - tokens) * 1000 / rate)
+        end
+
+        redis.call("HSET", KEYS[1], "tokens", tostring(tokens), "ts", now)
+        redis.call("PEXPIRE", KEYS[1], math.ceil(burst * 1000 / rate) + 1000)
+        return {allowed, math.floor(tokens), retry_ms, math.ceil((burst - tokens) * 1000 / rate)}
+    "#);
+
+    let (allowed, remaining, retry_ms, full_ms): (u8, u64, u64, u64) = script
+        .key(&redis_key)
+        .arg(burst)
+        .arg(refill_per_sec)
+        .invoke_async(&mut conn)
+        .await?;
+
+    Ok((allowed == 1, remaining, retry_ms, full_ms))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn penalty_escalates_and_caps_at_last_step() {
+        assert_eq!(penalty(0), 60);
+        assert_eq!(penalty(1), 60);
+        assert_eq!(penalty(2), 600);
+        assert_eq!(penalty(3), 3600);
+        assert_eq!(penalty(100), 3600);
     }
 
-    Ok(())
-}
\ No newline at end of file
+    #[test]
+    fn account_keyed_actions_are_not_penalized() {
+        assert!(!is_penalized("login"));
+        assert!(!is_penalized("register"));
+        assert!(is_penalized("group:auth"));
+        assert!(is_penalized("read_me"));
+    }
+
+    #[test]
+    fn mode_is_tagged() {
+        let fixed: RateLimitMode = serde_json::from_str(r#"{"mode":"fixed_window","limit":5,"window":60}"#).unwrap();
+        assert_eq!(fixed, RateLimitMode::FixedWindow { limit: 5, window: 60 });
+
+        let bucket: RateLimitMode =
+            serde_json::from_str(r#"{"mode":"token_bucket","burst":20,"refill_per_sec":1.0}"#).unwrap();
+        assert_eq!(bucket, RateLimitMode::TokenBucket { burst: 20, refill_per_sec: 1.0 });
+
+        assert_eq!(
+            serde_json::to_value(fixed).unwrap(),
+            serde_json::json!({"mode": "fixed_window", "limit": 5, "window": 60})
+        );
+    }
+
+    #[test]
+    fn mode_errors_name_the_field() {
+        let missing = serde_json::from_str::<RateLimitMode>(r#"{"mode":"fixed_window","limit":5}"#).unwrap_err();
+        assert!(missing.to_string().contains("window"), "{missing}");
+
+        let unknown =
+            serde_json::from_str::<RateLimitMode>(r#"{"mode":"token_bucket","burst":5,"refill_per_sec":1.0,"window":60}"#)
+                .unwrap_err();
+        assert!(unknown.to_string().contains("window"), "{unknown}");
+
+        assert!(serde_json::from_str::<RateLimitMode>(r#"{"limit":5,"window":60}"#).is_err());
+    }
+
+    #[test]
+    fn near_limit_threshold() {
+        let info = RateLimitInfo { limit: 10, remaining: 1, reset: 30, retry_after: None };
+        assert!(info.is_near_limit(0.2));
+        assert!(!info.is_near_limit(0.1));
+        assert!(RateLimitInfo { remaining: 10, retry_after: Some(5), ..info }.is_near_limit(0.0));
+    }
+}
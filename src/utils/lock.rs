@@ -0,0 +1,109 @@
+use std::time::Duration;
+
+use redis::Script;
+use redis::aio::ConnectionManager;
+use uuid::Uuid;
+
+use crate::core::error::AppError;
+
+/// 基于 Redis 的分布式锁。每个锁实例持有唯一令牌，续期和释放时校验令牌，
+/// 不会误续或误删其他实例持有的锁。锁在 TTL 到期后自动释放，持有者崩溃时不会永久占用。
+pub struct DistributedLock {
+    key: String,
+    token: String,
+    ttl: Duration,
+}
+
+impl DistributedLock {
+    /// 创建锁实例（不会访问 Redis）。
+    ///
+    /// # 参数
+    /// - `key`: 锁的 Redis 键
+    /// - `ttl`: 锁的有效期，持有者需要在到期前续期
+    pub fn new(key: impl Into<String>, ttl: Duration) -> Self {
+        Self {
+            key: key.into(),
+            token: Uuid::new_v4().to_string(),
+            ttl,
+        }
+    }
+
+    /// 持有者令牌，用于日志和排查
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// 尝试获取锁。已经由当前实例持有时同样返回 `true` 并刷新有效期。
+    ///
+    /// # 返回值
+    /// - `Ok(true)`: 获取成功
+    /// - `Ok(false)`: 锁由其他实例持有
+    pub async fn try_acquire(&self, redis: &ConnectionManager) -> Result<bool, AppError> {
+        let script = Script::new(r#"
+            if redis.call("SET", KEYS[1], ARGV[1], "NX", "PX", ARGV[2]) then
+                return 1
+            end
+            if redis.call("GET", KEYS[1]) == ARGV[1] then
+                redis.call("PEXPIRE", KEYS[1], ARGV[2])
+                return 1
+            end
+            return 0
+        "#);
+        self.invoke(&script, redis).await
+    }
+
+    /// 续期。只有仍由当前实例持有时才会延长有效期。
+    ///
+    /// # 返回值
+    /// - `Ok(true)`: 续期成功
+    /// - `Ok(false)`: 锁已过期或已被其他实例取得
+    pub async fn renew(&self, redis: &ConnectionManager) -> Result<bool, AppError> {
+        let script = Script::new(r#"
+            if redis.call("GET", KEYS[1]) == ARGV[1] then
+                redis.call("PEXPIRE", KEYS[1], ARGV[2])
+                return 1
+            end
+            return 0
+        "#);
+        self.invoke(&script, redis).await
+    }
+
+    /// 强制取得锁，覆盖当前持有者。只用于确认持有者已不可用时的接管，
+    /// 原持有者下一次续期时会发现锁已丢失。
+    pub async fn take_over(&self, redis: &ConnectionManager) -> Result<(), AppError> {
+        let mut conn = redis.clone();
+        let _: () = redis::cmd("SET")
+            .arg(&self.key)
+            .arg(&self.token)
+            .arg("PX")
+            .arg(self.ttl.as_millis() as u64)
+            .query_async(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    /// 释放锁。只删除由当前实例持有的锁。
+    #[allow(dead_code)]
+    pub async fn release(&self, redis: &ConnectionManager) -> Result<(), AppError> {
+        let script = Script::new(r#"
+            if redis.call("GET", KEYS[1]) == ARGV[1] then
+                return redis.call("DEL", KEYS[1])
+            end
+            return 0
+        "#);
+        let mut conn = redis.clone();
+        let _: i64 = script.key(&self.key).arg(&self.token).invoke_async(&mut conn).await?;
+        Ok(())
+    }
+
+    async fn invoke(&self, script: &Script, redis: &ConnectionManager) -> Result<bool, AppError> {
+        let mut conn = redis.clone();
+        let result: i64 = script
+            .key(&self.key)
+            .arg(&self.token)
+            .arg(self.ttl.as_millis() as u64)
+            .invoke_async(&mut conn)
+            .await?;
+        Ok(result == 1)
+    }
+}
@@ -1,14 +1,43 @@
+pub mod allowlist; // 限流豁免名单：合并配置和 Redis 中的名单，按限流对象或来源IP匹配。
 pub mod limiter;
+pub mod lock; // 基于 Redis 的分布式锁：获取、续期、接管和释放。
 pub mod cache; // 新增模块注册：缓存模块，提供通用的缓存操作功能。
+pub mod deprecation; // API 弃用标记：记录弃用端点和字段的使用情况。
+pub mod json_case; // JSON 字段命名风格转换（snake_case / camelCase）。
+pub mod pagination; // 游标分页：游标的编码解析和按游标查询。
+pub mod nonce; // 一次性标识模块：原子占用，防止重放（设备轮询、幂等键）。
+pub mod public_id; // 用户公开短ID：生成，以及从短ID或UUID解析内部ID。
+pub mod quota; // 长周期（按天）配额模块，补充分钟级限流器。
+pub mod retry; // 数据库瞬时错误重试：识别可重试的错误，按带抖动的指数退避重试。
+pub mod request_id; // 请求ID：生成、校验并在请求处理期间传递。
 
 /// 限流宏：提供便捷的速率限制检查功能，防止 API 滥用。
 /// 用法: rate_limit!(&state.redis, "action_name", &user_id, max_count, window_seconds); 其中参数依次为：Redis 连接、操作名称、用户标识、最大请求次数、时间窗口（秒）。
+/// 也可以传入限流模式，如令牌桶: rate_limit!(&state.redis, "action_name", &user_id, RateLimitMode::TokenBucket { burst: 20, refill_per_sec: 1.0 });
+/// 推荐只传入状态、操作名称和用户标识: rate_limit!(state, "action_name", &user_id); 限额从配置中读取（见 `Config::rate_limit`）。
 #[macro_export]
 macro_rules! rate_limit {
+    ($state:expr, $action:expr, $key:expr) => {
+        if let Err(e) = $crate::utils::limiter::check_rate_limit_mode(
+            &$state.redis,
+            $action,
+            $key,
+            $state.config.rate_limit($action),
+        )
+        .await
+        {
+            return Err(e.into());
+        }
+    };
     ($redis:expr, $action:expr, $key:expr, $limit:expr, $window:expr) => {
         if let Err(e) = $crate::utils::limiter::check_rate_limit($redis, $action, $key, $limit, $window).await {
             // 将限流器的错误转换为 AppError 类型，保持错误处理的一致性。
             return Err(e.into());
         }
     };
+    ($redis:expr, $action:expr, $key:expr, $mode:expr) => {
+        if let Err(e) = $crate::utils::limiter::check_rate_limit_mode($redis, $action, $key, $mode).await {
+            return Err(e.into());
+        }
+    };
 }
\ No newline at end of file
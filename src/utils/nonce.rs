@@ -0,0 +1,57 @@
+use redis::aio::ConnectionManager;
+use crate::core::{constants::REDIS_PREFIX_NONCE, error::AppError};
+
+// 一次性标识（Nonce）工具：同一个标识在有效期内只能被占用一次，用于设备授权轮询限频、幂等键等防重放场景。
+// 所有键按命名空间隔离，格式为 "nonce:{namespace}:{nonce}"，并设置 TTL 自动过期。
+
+/// 标识对应的 Redis 键。调用方需要读取或更新占用时写入的数据时使用（如幂等键保存首次响应）。
+#[inline]
+pub fn key(namespace: &str, nonce: &str) -> String {
+    format!("{}{}:{}", REDIS_PREFIX_NONCE, namespace, nonce)
+}
+
+/// 占用一个标识，并写入关联的数据。使用 `SET NX EX` 原子写入，并发请求中只有一个能占用成功。
+///
+/// # 参数
+/// - `namespace`: 命名空间，如 "device_poll"、"idempotency"
+/// - `nonce`: 由调用方提供的标识，如设备码、幂等键
+/// - `payload`: 与标识关联的数据，可通过 `key` 读取
+/// - `ttl_seconds`: 有效期（秒），过期后可以再次占用
+///
+/// # 返回值
+/// - `Ok(true)`: 首次出现，请求可以继续处理
+/// - `Ok(false)`: 在有效期内已经被占用，应视为重放请求
+pub async fn claim(
+    redis_manager: &ConnectionManager,
+    namespace: &str,
+    nonce: &str,
+    payload: &str,
+    ttl_seconds: u64,
+) -> Result<bool, AppError> {
+    let mut conn = redis_manager.clone();
+    let result: Option<String> = redis::cmd("SET")
+        .arg(key(namespace, nonce))
+        .arg(payload)
+        .arg("NX")
+        .arg("EX")
+        .arg(ttl_seconds)
+        .query_async(&mut conn)
+        .await?;
+
+    Ok(result.is_some())
+}
+
+/// 记录一个由外部提供的随机数（如轮询请求中携带的设备码），用于防重放检查。
+/// 只有第一次出现时返回 `true`（见 `claim`）。
+///
+/// # 返回值
+/// - `Ok(true)`: 首次出现，请求可以继续处理
+/// - `Ok(false)`: 在有效期内已经出现过，应视为重放请求并拒绝
+pub async fn remember(
+    redis_manager: &ConnectionManager,
+    namespace: &str,
+    nonce: &str,
+    ttl_seconds: u64,
+) -> Result<bool, AppError> {
+    claim(redis_manager, namespace, nonce, "1", ttl_seconds).await
+}
@@ -0,0 +1,136 @@
+// src/utils/pagination.rs
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sea_orm::{
+    sea_query::IntoValueTuple, ConnectionTrait, EntityTrait, FromQueryResult, IntoIdentity, Order, Select,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    core::error::AppError,
+    dtos::pagination::{CursorPage, CursorQuery},
+    utils::retry,
+};
+
+/// 游标的内容：排序范围和上一页最后一条记录的排序键。
+/// 排序范围（如 `users:-created_at`）用于拒绝在其他端点或其他排序方式下使用的游标。
+#[derive(Serialize, Deserialize)]
+struct CursorPayload<K> {
+    #[serde(rename = "s")]
+    scope: String,
+    #[serde(rename = "k")]
+    key: K,
+}
+
+/// 把排序键编码为不透明的游标（JSON 后做 URL 安全的 Base64 编码，可以直接放在查询参数中）。
+pub fn encode_cursor<K: Serialize>(scope: &str, key: K) -> String {
+    let payload = CursorPayload { scope: scope.to_string(), key };
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(&payload).unwrap_or_default())
+}
+
+/// 解析游标中的排序键。游标格式错误或不属于当前排序范围时返回 400。
+pub fn decode_cursor<K: DeserializeOwned>(scope: &str, cursor: &str) -> Result<K, AppError> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|raw| serde_json::from_slice::<CursorPayload<K>>(&raw).ok())
+        .filter(|payload| payload.scope == scope)
+        .map(|payload| payload.key)
+        .ok_or_else(|| AppError::BadRequest("Invalid cursor".to_string()))
+}
+
+/// 按游标分页查询。排序列最后一列必须唯一（通常是主键），保证排序稳定、翻页时不重复也不遗漏。
+///
+/// 多查询一条记录来判断是否还有下一页，不需要额外的 `COUNT(*)`。查询遇到瞬时错误时自动重试（见 `retry::with_retry`）。
+///
+/// # 参数
+/// - `db`: 数据库连接
+/// - `select`: 已经添加过滤条件的查询，原有的排序会被替换
+/// - `order_columns`: 排序列，如 `(users::Column::CreatedAt, users::Column::Id)`
+/// - `order`: 排序方向，所有排序列使用同一方向
+/// - `scope`: 排序范围，写入游标，不同端点和排序方式使用不同的值
+/// - `query`: 游标和每页条数
+/// - `key`: 从记录中取出排序键，与 `order_columns` 一一对应
+///
+/// # 返回值
+/// - `Ok(CursorPage)`: 当前页的记录和下一页的游标
+/// - `Err(AppError)`: 游标无效或数据库查询失败
+pub async fn fetch_cursor_page<E, C, K, D>(
+    db: &D,
+    select: Select<E>,
+    order_columns: C,
+    order: Order,
+    scope: &str,
+    query: &CursorQuery,
+    key: impl Fn(&E::Model) -> K,
+) -> Result<CursorPage<E::Model>, AppError>
+where
+    E: EntityTrait + Clone,
+    E::Model: FromQueryResult + Sync,
+    C: IntoIdentity + Clone,
+    K: Serialize + DeserializeOwned + IntoValueTuple,
+    D: ConnectionTrait,
+{
+    let limit = query.limit();
+    let after = query
+        .cursor
+        .as_deref()
+        .map(|raw| decode_cursor::<K>(scope, raw))
+        .transpose()?
+        .map(IntoValueTuple::into_value_tuple);
+
+    // 游标查询只读，遇到瞬时错误时重新构建游标并重试
+    let mut items = retry::with_retry("fetch_cursor_page", || {
+        let mut cursor = select.clone().cursor_by(order_columns.clone());
+        match order {
+            Order::Desc => cursor.desc(),
+            _ => cursor.asc(),
+        };
+        if let Some(after) = after.clone() {
+            cursor.after(after);
+        }
+        async move { cursor.first(limit + 1).all(db).await }
+    })
+    .await?;
+    let has_more = items.len() as u64 > limit;
+    items.truncate(limit as usize);
+    let next_cursor = if has_more {
+        items.last().map(|last| encode_cursor(scope, key(last)))
+    } else {
+        None
+    };
+
+    Ok(CursorPage { items, next_cursor, has_more })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips() {
+        let key = ("2024-01-01T00:00:00Z".to_string(), 42i64);
+        let cursor = encode_cursor("users:created_at", &key);
+        assert!(!cursor.contains(['+', '/', '=']), "{cursor}");
+
+        let decoded: (String, i64) = decode_cursor("users:created_at", &cursor).unwrap();
+        assert_eq!(decoded, key);
+    }
+
+    #[test]
+    fn cursor_from_another_scope_is_rejected() {
+        let cursor = encode_cursor("users:created_at", 42i64);
+        assert!(matches!(decode_cursor::<i64>("users:username", &cursor), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn malformed_cursor_is_rejected() {
+        let wrong_key_type = encode_cursor("users:created_at", "not a number");
+        let not_json = URL_SAFE_NO_PAD.encode("garbage");
+        for cursor in ["", "!!!", "not base64 at all", not_json.as_str(), wrong_key_type.as_str()] {
+            assert!(
+                matches!(decode_cursor::<i64>("users:created_at", cursor), Err(AppError::BadRequest(_))),
+                "{cursor}"
+            );
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use sea_orm::*;
+use uuid::Uuid;
+
+use crate::{core::error::AppError, entity::users};
+
+// 公开短ID：对外的URL和响应使用短ID而不是内部UUID，数据库主键和内部关联仍然使用UUID。
+// 只使用字母和数字，便于复制、搜索，也不需要URL转义。
+
+/// 短ID字符表（62个字母和数字）
+const ALPHABET: [char; 62] = [
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k',
+    'l', 'm', 'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z', 'A', 'B', 'C', 'D', 'E', 'F',
+    'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
+];
+
+/// 短ID长度：12位约71比特的随机性，在千万级用户规模下冲突概率可以忽略，冲突时由唯一索引兜底
+const LENGTH: usize = 12;
+
+/// 生成一个新的公开短ID
+pub fn generate() -> String {
+    nanoid::nanoid!(LENGTH, &ALPHABET)
+}
+
+/// 把路径中的用户标识解析为内部UUID。同时接受公开短ID和UUID，兼容仍在使用UUID的旧客户端。
+///
+/// # 参数
+/// - `db`: 数据库连接（可以是事务）
+/// - `id`: 公开短ID或UUID字符串
+///
+/// # 返回值
+/// - `Ok(Uuid)`: 用户的内部ID
+/// - `Err(AppError)`: 找不到对应的用户（404）或数据库错误
+pub async fn resolve_user<C: ConnectionTrait>(db: &C, id: &str) -> Result<Uuid, AppError> {
+    if let Ok(uuid) = Uuid::parse_str(id) {
+        return Ok(uuid);
+    }
+
+    users::Entity::find()
+        .select_only()
+        .column(users::Column::Id)
+        .filter(users::Column::PublicId.eq(id))
+        .into_tuple::<Uuid>()
+        .one(db)
+        .await?
+        .ok_or(AppError::NotFound("User not found".to_string()))
+}
@@ -0,0 +1,174 @@
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc};
+use redis::Script;
+use redis::aio::ConnectionManager;
+use crate::core::log::target;
+use crate::core::{enums::QuotaPeriod, error::AppError};
+use crate::utils::limiter::{self, RateLimitInfo};
+
+/// 长周期配额（按自然日或自然月计数）。与 `limiter::check_rate_limit` 的分钟级窗口不同，
+/// 这里的 Redis 键带有周期后缀（如 `quota:register:admin:{user}:20251229`、`quota:upload_avatar:{user}:202512`），
+/// 每个周期自动切换到新的计数键，用于限制注册等低频但需要防批量滥用的操作，以及用户的长期使用量。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    /// 操作名称，如 "register:admin"
+    pub action: &'static str,
+    pub period: QuotaPeriod,
+    /// 每个周期允许的最大次数
+    pub limit: usize,
+}
+
+/// 配额的当前使用情况
+#[derive(Debug, Clone, Copy)]
+pub struct QuotaUsage {
+    pub used: usize,
+    pub remaining: usize,
+    /// 下一次重置的时间（UTC 零点）
+    pub resets_at: DateTime<Utc>,
+}
+
+impl Quota {
+    fn key(&self, subject: &str, now: DateTime<Utc>) -> String {
+        let suffix = match self.period {
+            QuotaPeriod::Day => now.format("%Y%m%d"),
+            QuotaPeriod::Month => now.format("%Y%m"),
+        };
+        format!("quota:{}:{}:{}", self.action, subject, suffix)
+    }
+
+    /// 当前周期结束（下一次重置）的时间
+    fn resets_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let today = now.date_naive();
+        let next = match self.period {
+            QuotaPeriod::Day => today + Days::new(1),
+            QuotaPeriod::Month => {
+                NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap_or(today) + Months::new(1)
+            }
+        };
+        next.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+    }
+
+    /// 计数键的过期时间（秒）：周期长度再加一天，确保跨时区边界时键仍然有效
+    fn key_ttl(&self) -> u64 {
+        match self.period {
+            QuotaPeriod::Day => 60 * 60 * 48,
+            QuotaPeriod::Month => 60 * 60 * 24 * 32,
+        }
+    }
+
+    fn usage(&self, used: usize, now: DateTime<Utc>) -> QuotaUsage {
+        QuotaUsage { used, remaining: self.limit.saturating_sub(used), resets_at: self.resets_at(now) }
+    }
+}
+
+/// 消耗一次配额。超出的尝试同样计数。
+///
+/// # 参数
+/// - `quota`: 配额定义
+/// - `subject`: 计数对象，如用户ID、客户端IP或手机号前缀
+///
+/// # 返回值
+/// - `Ok(QuotaUsage)`: 未超出配额，附带本次消耗后的使用情况
+/// - `Err(AppError::RateLimitExceeded)`: 本周期的配额已用完
+pub async fn consume(redis_manager: &ConnectionManager, quota: &Quota, subject: &str) -> Result<QuotaUsage, AppError> {
+    let now = Utc::now();
+    let (count, _) = limiter::increment(redis_manager, &quota.key(subject, now), quota.key_ttl()).await?;
+
+    let usage = quota.usage(count, now);
+    let exceeded = count > quota.limit;
+    let reset = (usage.resets_at - now).num_seconds().max(1) as u64;
+    limiter::record(RateLimitInfo {
+        limit: quota.limit as u64,
+        remaining: usage.remaining as u64,
+        reset,
+        retry_after: exceeded.then_some(reset),
+    });
+
+    if exceeded {
+        tracing::warn!(
+            target: target::LIMITER,
+            "⛔ {} quota exceeded: {} on {} ({}/{})",
+            quota.period,
+            subject,
+            quota.action,
+            count,
+            quota.limit
+        );
+        let message = match quota.period {
+            QuotaPeriod::Day => "Daily quota exceeded. Please try again tomorrow.",
+            QuotaPeriod::Month => "Monthly quota exceeded. Please try again next month.",
+        };
+        return Err(AppError::RateLimitExceeded(message.to_string()));
+    }
+
+    Ok(usage)
+}
+
+/// 退还一次配额，用于消耗配额后操作本身失败的情况（如注册时用户名已存在）。
+/// 计数键不存在或已为0时不做处理；Redis 出错时只记录日志，不影响请求结果。
+pub async fn refund(redis_manager: &ConnectionManager, quota: &Quota, subject: &str) {
+    let mut conn = redis_manager.clone();
+
+    // 原子操作：计数大于0时才自减，避免周期切换后写出负数
+    let script = Script::new(r#"
+        local count = tonumber(redis.call("GET", KEYS[1]) or "0")
+        if count > 0 then
+            return redis.call("DECR", KEYS[1])
+        end
+        return 0
+    "#);
+
+    let result: Result<i64, _> = script.key(quota.key(subject, Utc::now())).invoke_async(&mut conn).await;
+    if let Err(e) = result {
+        tracing::warn!(target: target::LIMITER, "⚠️ Failed to refund {} quota for {}: {}", quota.action, subject, e);
+    }
+}
+
+/// 批量查询配额的当前使用情况（不消耗配额），结果与 `quotas` 一一对应。
+pub async fn usages(
+    redis_manager: &ConnectionManager,
+    quotas: &[Quota],
+    subject: &str,
+) -> Result<Vec<QuotaUsage>, AppError> {
+    if quotas.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let now = Utc::now();
+    let mut conn = redis_manager.clone();
+    let mut cmd = redis::cmd("MGET");
+    for quota in quotas {
+        cmd.arg(quota.key(subject, now));
+    }
+    let counts: Vec<Option<usize>> = cmd.query_async(&mut conn).await?;
+
+    Ok(quotas
+        .iter()
+        .zip(counts)
+        .map(|(quota, count)| quota.usage(count.unwrap_or(0), now))
+        .collect())
+}
+
+/// 按自然日检查配额，见 `consume`。
+///
+/// # 参数
+/// - `action_key`: 操作名称，如 "register:admin"
+/// - `subject`: 计数对象，如管理员ID或手机号前缀
+/// - `limit`: 每天允许的最大次数
+pub async fn check_daily_quota(
+    redis_manager: &ConnectionManager,
+    action_key: &'static str,
+    subject: &str,
+    limit: usize,
+) -> Result<(), AppError> {
+    consume(redis_manager, &daily(action_key, limit), subject).await?;
+    Ok(())
+}
+
+/// 退还一次按自然日计数的配额，见 `refund`。
+pub async fn refund_daily_quota(redis_manager: &ConnectionManager, action_key: &'static str, subject: &str) {
+    refund(redis_manager, &daily(action_key, 0), subject).await;
+}
+
+fn daily(action: &'static str, limit: usize) -> Quota {
+    Quota { action, period: QuotaPeriod::Day, limit }
+}
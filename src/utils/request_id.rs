@@ -0,0 +1,40 @@
+use uuid::Uuid;
+
+// 请求ID工具：每个请求在入口处获得一个ID（沿用客户端传入的 `X-Request-Id` 或新生成），
+// 写入追踪 span、响应头和 `ApiResponse` 响应体，便于客户端反馈问题时与服务端日志对应。
+
+/// 请求ID所使用的HTTP头
+pub const HEADER: &str = "x-request-id";
+
+/// 客户端传入的请求ID最大长度，超出或包含非法字符时重新生成
+const MAX_LEN: usize = 128;
+
+/// 当前请求的ID，由请求ID中间件存入请求扩展，处理器可通过 `Extension<RequestId>` 读取。
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+tokio::task_local! {
+    /// 当前请求处理任务中的请求ID，供 `ApiResponse` 在序列化时读取
+    static CURRENT: String;
+}
+
+/// 校验客户端传入的请求ID，只接受长度合理的字母、数字和 `-_.:`，
+/// 防止把任意内容写入日志；不合法时生成新的ID。
+pub fn accept_or_generate(incoming: Option<&str>) -> String {
+    incoming
+        .map(str::trim)
+        .filter(|id| !id.is_empty() && id.len() <= MAX_LEN)
+        .filter(|id| id.chars().all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.' | ':')))
+        .map(ToString::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().simple().to_string())
+}
+
+/// 在请求ID作用域内执行 future，期间 `current()` 返回该ID。
+pub async fn scope<F: Future>(id: String, fut: F) -> F::Output {
+    CURRENT.scope(id, fut).await
+}
+
+/// 当前请求的ID。在请求处理任务之外（如后台任务）调用时返回 `None`。
+pub fn current() -> Option<String> {
+    CURRENT.try_with(Clone::clone).ok()
+}
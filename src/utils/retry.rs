@@ -0,0 +1,166 @@
+// src/utils/retry.rs
+use std::{fmt::Display, future::Future, time::Duration};
+
+use rand::Rng;
+use sea_orm::{sqlx, ConnAcquireErr, DbErr, RuntimeErr, TransactionError};
+
+use crate::core::{
+    constants::{DB_RETRY_BASE_DELAY_MS, DB_RETRY_MAX_ATTEMPTS, DB_RETRY_MAX_DELAY_MS},
+    error::AppError,
+    log::target,
+};
+
+/// 可以重试的 PostgreSQL 错误码（SQLSTATE）：序列化冲突、死锁，以及数据库重启期间的连接中断。
+/// `08` 开头的连接异常单独判断。
+const TRANSIENT_SQLSTATES: &[&str] = &["40001", "40P01", "57P01", "57P02", "57P03"];
+
+/// 区分瞬时错误和其他错误。瞬时错误重新执行通常就能成功，不应直接以 500 返回给客户端。
+pub trait Transient {
+    fn is_transient(&self) -> bool;
+}
+
+impl Transient for DbErr {
+    fn is_transient(&self) -> bool {
+        match self {
+            // 连接池中的连接已被关闭；获取连接超时说明连接池耗尽，重试只会更久，不重试
+            DbErr::ConnectionAcquire(ConnAcquireErr::ConnectionClosed) => true,
+            DbErr::Conn(RuntimeErr::SqlxError(e))
+            | DbErr::Exec(RuntimeErr::SqlxError(e))
+            | DbErr::Query(RuntimeErr::SqlxError(e)) => match e {
+                // 连接在执行过程中断开
+                sqlx::Error::Io(_) => true,
+                sqlx::Error::Database(e) => e
+                    .code()
+                    .is_some_and(|code| code.starts_with("08") || TRANSIENT_SQLSTATES.contains(&code.as_ref())),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+impl Transient for TransactionError<DbErr> {
+    fn is_transient(&self) -> bool {
+        match self {
+            TransactionError::Connection(e) | TransactionError::Transaction(e) => e.is_transient(),
+        }
+    }
+}
+
+impl Transient for AppError {
+    fn is_transient(&self) -> bool {
+        matches!(self, AppError::DatabaseError(e) if e.is_transient())
+    }
+}
+
+/// 执行数据库操作，遇到瞬时错误时按带随机抖动的指数退避重试，最多执行 `DB_RETRY_MAX_ATTEMPTS` 次。
+/// 其他错误和最后一次重试的错误直接返回。
+///
+/// 只用于可以安全重复执行的操作：只读查询，或能识别自身已经生效的整个事务。
+/// 事务在执行过程中失败时已经回滚，但 COMMIT 时连接断开返回的 IO 错误无法区分事务是否已经提交，
+/// 重试的事务需要先检查目标状态（如重新读取并锁定要修改的行），已经生效时不再重复写入。
+/// 单条写入语句同理，在连接断开时可能已经提交，不应直接重试。
+///
+/// ```ignore
+/// let user = retry::with_retry("find_user", || users::Entity::find_by_id(uid).one(&state.db)).await?;
+/// ```
+///
+/// # 参数
+/// - `operation`: 操作名称，用于日志和 `db_retries_total` 指标的标签
+/// - `f`: 每次尝试时调用，返回新的数据库操作
+pub async fn with_retry<T, E, F, Fut>(operation: &'static str, mut f: F) -> Result<T, E>
+where
+    E: Transient + Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Err(e) if attempt < DB_RETRY_MAX_ATTEMPTS && e.is_transient() => {
+                let delay = backoff(attempt);
+                metrics::counter!("db_retries_total", "operation" => operation).increment(1);
+                tracing::warn!(
+                    target: target::SYSTEM,
+                    operation,
+                    attempt,
+                    delay_ms = delay.as_millis() as u64,
+                    "🔁 Transient database error, retrying: {}",
+                    e
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// 第 `attempt` 次失败后的等待时间：在 0 到指数退避上限之间随机取值（full jitter），
+/// 避免多个请求同时冲突后又同时重试。
+fn backoff(attempt: u32) -> Duration {
+    let ceiling = DB_RETRY_BASE_DELAY_MS
+        .saturating_mul(1 << (attempt - 1).min(16))
+        .min(DB_RETRY_MAX_DELAY_MS);
+    Duration::from_millis(rand::thread_rng().gen_range(0..=ceiling))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use super::*;
+
+    fn io_error() -> DbErr {
+        DbErr::Exec(RuntimeErr::SqlxError(sqlx::Error::Io(io::Error::new(io::ErrorKind::ConnectionReset, "reset"))))
+    }
+
+    #[test]
+    fn backoff_stays_within_exponential_ceiling() {
+        for attempt in 1..=40 {
+            let ceiling = (DB_RETRY_BASE_DELAY_MS << (attempt - 1).min(16)).min(DB_RETRY_MAX_DELAY_MS);
+            for _ in 0..100 {
+                assert!(backoff(attempt) <= Duration::from_millis(ceiling), "attempt {attempt}");
+            }
+        }
+    }
+
+    #[test]
+    fn connection_failures_are_transient() {
+        assert!(io_error().is_transient());
+        assert!(DbErr::ConnectionAcquire(ConnAcquireErr::ConnectionClosed).is_transient());
+        assert!(TransactionError::Connection(io_error()).is_transient());
+        assert!(TransactionError::Transaction(io_error()).is_transient());
+        assert!(AppError::DatabaseError(io_error()).is_transient());
+    }
+
+    #[test]
+    fn other_failures_are_not_transient() {
+        assert!(!DbErr::ConnectionAcquire(ConnAcquireErr::Timeout).is_transient());
+        assert!(!DbErr::Query(RuntimeErr::SqlxError(sqlx::Error::RowNotFound)).is_transient());
+        assert!(!DbErr::RecordNotFound("user".to_string()).is_transient());
+        assert!(!DbErr::Custom("boom".to_string()).is_transient());
+        assert!(!AppError::BadRequest("bad".to_string()).is_transient());
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors_up_to_the_limit() {
+        let mut attempts = 0;
+        let result: Result<(), DbErr> = with_retry("test", || {
+            attempts += 1;
+            async { Err(io_error()) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts, DB_RETRY_MAX_ATTEMPTS);
+
+        let mut attempts = 0;
+        let result: Result<(), DbErr> = with_retry("test", || {
+            attempts += 1;
+            async { Err(DbErr::Custom("boom".to_string())) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+}